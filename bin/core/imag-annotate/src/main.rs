@@ -54,7 +54,7 @@ use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::IntoStoreId;
 
@@ -62,10 +62,11 @@ mod ui;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-annotation",
-                                    &version,
-                                    "Add annotations to entries",
-                                    ui::build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-annotation",
+                                                     &version,
+                                                     "Add annotations to entries",
+                                                     &["annotations"],
+                                                     ui::build_ui);
 
     rt.cli()
         .subcommand_name()
@@ -94,15 +95,19 @@ fn add(rt: &Runtime) {
         .map(|pb| pb.into_storeid().map_err_trace_exit_unwrap(1))
         .unwrap(); // safed by clap
 
-    let _ = rt.store()
+    let mut entry = rt.store()
         .get(entry_name)
         .map_err_trace_exit_unwrap(1)
         .ok_or(AE::from("Entry does not exist".to_owned()))
-        .map_err_trace_exit_unwrap(1)
+        .map_err_trace_exit_unwrap(1);
+
+    let _ = entry
         .annotate(rt.store(), annotation_name)
         .map_err_trace_exit_unwrap(1)
         .edit_content(&rt)
         .map_err_trace_exit_unwrap(1);
+
+    let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
 }
 
 fn remove(rt: &Runtime) {
@@ -137,16 +142,18 @@ fn remove(rt: &Runtime) {
     } else {
         debug!("Not deleting annotation object");
     }
+
+    let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
 }
 
 fn list(rt: &Runtime) {
     let scmd        = rt.cli().subcommand_matches("list").unwrap(); // safed by clap
     let with_text   = scmd.is_present("list-with-text");
-    match scmd.value_of("entry").map(PathBuf::from) {
-        Some(pb) => {
+    if scmd.is_present("entry") || rt.ids_from_pipe() {
+        for id in rt.ids(scmd, "entry", None).map_err_trace_exit_unwrap(1) {
             let _ = rt
                 .store()
-                .get(pb.into_storeid().map_err_trace_exit_unwrap(1))
+                .get(id)
                 .map_err_trace_exit_unwrap(1)
                 .ok_or(AE::from("Entry does not exist".to_owned()))
                 .map_err_trace_exit_unwrap(1)
@@ -158,19 +165,17 @@ fn list(rt: &Runtime) {
                 })
                 .collect::<Vec<_>>();
         }
-
-        None => {
-            // show them all
-            let _ = rt
-                .store()
-                .all_annotations()
-                .map_err_trace_exit_unwrap(1)
-                .enumerate()
-                .map(|(i, a)| {
-                    list_annotation(&rt, i, a.map_err_trace_exit_unwrap(1), with_text)
-                })
-                .collect::<Vec<_>>();
-        }
+    } else {
+        // show them all
+        let _ = rt
+            .store()
+            .all_annotations()
+            .map_err_trace_exit_unwrap(1)
+            .enumerate()
+            .map(|(i, a)| {
+                list_annotation(&rt, i, a.map_err_trace_exit_unwrap(1), with_text)
+            })
+            .collect::<Vec<_>>();
     }
 }
 
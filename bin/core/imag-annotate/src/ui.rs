@@ -74,7 +74,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .takes_value(true)
                          .required(false)
                          .multiple(false)
-                         .help("The entry to list annotations for (all annotations if not passed)")
+                         .help("The entry to list annotations for (read from stdin if stdin is a pipe, all annotations if not passed)")
                          .value_name("ENTRY"))
                     .arg(Arg::with_name("list-with-text")
                          .long("text")
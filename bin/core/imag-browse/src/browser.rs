@@ -0,0 +1,462 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The state of the browser, independent of the terminal
+//!
+//! The browser shows the store as a tree of collections and entries. Everything in here works on
+//! a `Store`, no matter which backend it uses.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+use libimagentrytag::tagable::Tagable;
+use libimagentrytag::tag::is_tag_str;
+use libimagentrylink::internal::InternalLinker;
+use libimagentrylink::external::Link as ExternalLink;
+use libimagentrylink::external::is_external_link_storeid;
+use libimagentrylink::external::iter::OnlyInternalLinks;
+use libimagentryannotation::annotateable::Annotateable;
+
+use toml_query::read::TomlValueReadTypeExt;
+
+use error::BrowseError as BE;
+use error::BrowseErrorKind as BEK;
+use error::Result;
+
+/// Whether a row in the tree is a collection or an entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RowKind {
+    Collection { expanded: bool },
+    Entry,
+}
+
+/// A visible row in the tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub depth: usize,
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: RowKind,
+}
+
+/// Everything which is shown about the selected entry
+#[derive(Debug)]
+pub struct Details {
+    pub id: StoreId,
+    pub header: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub links: Vec<StoreId>,
+    pub urls: Vec<String>,
+    pub annotations: Vec<String>,
+}
+
+pub struct Browser<'a> {
+    store: &'a Store,
+    ids: Vec<PathBuf>,
+    expanded: BTreeSet<PathBuf>,
+    selected: usize,
+}
+
+impl<'a> Browser<'a> {
+
+    pub fn new(store: &'a Store) -> Result<Browser<'a>> {
+        let mut browser = Browser {
+            store: store,
+            ids: vec![],
+            expanded: BTreeSet::new(),
+            selected: 0,
+        };
+
+        let _ = browser.refresh()?;
+        Ok(browser)
+    }
+
+    /// Read the ids from the store again
+    pub fn refresh(&mut self) -> Result<()> {
+        let mut ids = vec![];
+        for id in self.store.entries()? {
+            ids.push(id?.without_base().local().clone());
+        }
+
+        ids.sort();
+        self.ids = ids;
+        self.clamp_selection();
+        Ok(())
+    }
+
+    /// The visible rows of the tree
+    ///
+    /// Collections are shown before the entries they contain, the contents of collapsed
+    /// collections are not shown.
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        let mut seen = BTreeSet::new();
+
+        for id in self.ids.iter() {
+            let components = id
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<String>>();
+
+            let mut path = PathBuf::new();
+            for (depth, name) in components.iter().enumerate() {
+                path.push(name);
+
+                if depth + 1 == components.len() {
+                    rows.push(Row {
+                        depth: depth,
+                        name: name.clone(),
+                        path: path.clone(),
+                        kind: RowKind::Entry,
+                    });
+                } else {
+                    let expanded = self.expanded.contains(&path);
+
+                    if seen.insert(path.clone()) {
+                        rows.push(Row {
+                            depth: depth,
+                            name: name.clone(),
+                            path: path.clone(),
+                            kind: RowKind::Collection { expanded: expanded },
+                        });
+                    }
+
+                    if !expanded {
+                        break;
+                    }
+                }
+            }
+        }
+
+        rows
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_row(&self) -> Option<Row> {
+        self.rows().into_iter().nth(self.selected)
+    }
+
+    /// The id of the selected entry, if an entry (and not a collection) is selected
+    pub fn selected_id(&self) -> Option<StoreId> {
+        self.selected_row()
+            .and_then(|row| match row.kind {
+                RowKind::Entry => StoreId::new_baseless(row.path).ok(),
+                _              => None,
+            })
+    }
+
+    pub fn up(&mut self, n: usize) {
+        self.selected = self.selected.saturating_sub(n);
+    }
+
+    pub fn down(&mut self, n: usize) {
+        self.selected = self.selected.saturating_add(n);
+        self.clamp_selection();
+    }
+
+    /// Expand or collapse the selected collection
+    pub fn toggle(&mut self) {
+        if let Some(row) = self.selected_row() {
+            if let RowKind::Collection { expanded } = row.kind {
+                if expanded {
+                    let _ = self.expanded.remove(&row.path);
+                } else {
+                    let _ = self.expanded.insert(row.path);
+                }
+            }
+        }
+    }
+
+    /// Collapse the selected collection, or select the collection of the selected entry
+    pub fn collapse(&mut self) {
+        let row = match self.selected_row() {
+            Some(row) => row,
+            None      => return,
+        };
+
+        if row.kind == (RowKind::Collection { expanded: true }) {
+            let _ = self.expanded.remove(&row.path);
+            return;
+        }
+
+        if let Some(parent) = row.path.parent() {
+            if let Some(i) = self.rows().iter().position(|r| r.path == parent) {
+                self.selected = i;
+            }
+        }
+    }
+
+    /// Select the entry `id`, expanding all collections it is in
+    pub fn select(&mut self, id: &StoreId) -> Result<()> {
+        let path = id.clone().without_base().local().clone();
+        if !self.ids.contains(&path) {
+            return Err(BE::from_kind(BEK::EntryNotFound(format!("{}", path.display()))));
+        }
+
+        let mut ancestor = path.parent();
+        while let Some(a) = ancestor {
+            if a.components().next().is_some() {
+                let _ = self.expanded.insert(a.to_path_buf());
+            }
+            ancestor = a.parent();
+        }
+
+        if let Some(i) = self.rows().iter().position(|r| r.path == path) {
+            self.selected = i;
+        }
+
+        Ok(())
+    }
+
+    fn clamp_selection(&mut self) {
+        let n = self.rows().len();
+        if self.selected >= n {
+            self.selected = n.saturating_sub(1);
+        }
+    }
+
+    fn selected_id_or_err(&self) -> Result<StoreId> {
+        self.selected_id().ok_or_else(|| BE::from_kind(BEK::NoEntrySelected))
+    }
+
+    fn get(&self, id: StoreId) -> Result<::libimagstore::store::FileLockEntry<'a>> {
+        let name = format!("{}", id);
+        self.store.get(id)?.ok_or_else(|| BE::from_kind(BEK::EntryNotFound(name)))
+    }
+
+    /// Read a copy of the entry `id`, which is not written back to the store
+    ///
+    /// Used for everything which is only displayed, so redrawing the browser does not touch the
+    /// entries on disk.
+    fn read(&self, id: StoreId) -> Result<Entry> {
+        let path = id.clone().without_base().local().clone();
+        if !self.ids.contains(&path) {
+            return Err(BE::from_kind(BEK::EntryNotFound(format!("{}", id))));
+        }
+
+        self.store.get_copy(id).map_err(BE::from)
+    }
+
+    /// Everything which is shown about the selected entry, if an entry is selected
+    pub fn details(&self) -> Result<Option<Details>> {
+        let id = match self.selected_id() {
+            Some(id) => id,
+            None     => return Ok(None),
+        };
+
+        let entry  = self.read(id.clone())?;
+        let header = ::toml::ser::to_string_pretty(entry.get_header())?;
+        let tags   = entry.get_tags()?;
+
+        let links = entry
+            .get_internal_links()?
+            .only_internal_links()
+            .map(|link| link.get_store_id().clone().without_base())
+            .collect::<Vec<_>>();
+
+        let mut urls        = vec![];
+        let mut annotations = vec![];
+        for link in entry.get_internal_links()? {
+            let linked = self.read(link.get_store_id().clone())?;
+
+            if is_external_link_storeid(&link) {
+                urls.extend(linked.get_link_uri_from_filelockentry()?.map(|url| url.into_string()));
+            } else if linked.is_annotation()? {
+                annotations.extend(linked.get_header().read_string("annotation.name")?);
+            }
+        }
+
+        Ok(Some(Details {
+            id: id,
+            header: header,
+            content: entry.get_content().clone(),
+            tags: tags,
+            links: links,
+            urls: urls,
+            annotations: annotations,
+        }))
+    }
+
+    pub fn add_tag(&self, tag: &str) -> Result<()> {
+        let tag = String::from(tag);
+        if let Err(_) = is_tag_str(&tag) {
+            return Err(BE::from_kind(BEK::InvalidTag(tag)));
+        }
+
+        let mut entry = self.get(self.selected_id_or_err()?)?;
+        entry.add_tag(tag).map_err(BE::from)
+    }
+
+    pub fn remove_tag(&self, tag: &str) -> Result<()> {
+        let mut entry = self.get(self.selected_id_or_err()?)?;
+        entry.remove_tag(String::from(tag)).map_err(BE::from)
+    }
+
+    /// Link the selected entry with the entry `other`
+    pub fn link(&self, other: &str) -> Result<()> {
+        let other = StoreId::new_baseless(PathBuf::from(other))?;
+        let id    = self.selected_id_or_err()?;
+
+        let mut entry = self.get(id)?;
+        let mut other = self.get(other)?;
+        entry.add_internal_link(&mut other).map_err(BE::from)
+    }
+
+    /// Remove the selected entry from the store, after removing all links to it
+    pub fn delete(&mut self) -> Result<()> {
+        let id = self.selected_id_or_err()?;
+
+        {
+            let mut entry = self.get(id.clone())?;
+            let _ = entry.unlink(self.store)?;
+        }
+
+        let _ = self.store.delete(id)?;
+        self.refresh()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use libimagstore::store::Store;
+    use libimagstore::store::InMemoryFileAbstraction;
+    use libimagstore::storeid::StoreId;
+
+    use super::*;
+
+    fn get_store() -> Store {
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    fn create(store: &Store, ids: &[&str]) {
+        for id in ids {
+            let _ = store.create(PathBuf::from(*id)).unwrap();
+        }
+    }
+
+    fn names(browser: &Browser) -> Vec<String> {
+        browser
+            .rows()
+            .into_iter()
+            .map(|r| format!("{}{}", "  ".repeat(r.depth), r.name))
+            .collect()
+    }
+
+    fn id(s: &str) -> StoreId {
+        StoreId::new_baseless(PathBuf::from(s)).unwrap()
+    }
+
+    #[test]
+    fn test_collapsed_tree() {
+        let store = get_store();
+        create(&store, &["notes/b", "notes/a", "diary/work/1", "todo"]);
+
+        let browser = Browser::new(&store).unwrap();
+        assert_eq!(names(&browser), vec!["diary", "notes", "todo"]);
+        assert_eq!(browser.selected_id(), None);
+    }
+
+    #[test]
+    fn test_expand_and_collapse() {
+        let store = get_store();
+        create(&store, &["notes/b", "notes/a", "diary/work/1"]);
+
+        let mut browser = Browser::new(&store).unwrap();
+        browser.toggle(); // diary
+        assert_eq!(names(&browser), vec!["diary", "  work", "notes"]);
+
+        browser.down(1);
+        browser.toggle(); // work
+        browser.down(1);
+        assert_eq!(names(&browser), vec!["diary", "  work", "    1", "notes"]);
+        assert_eq!(browser.selected_id(), Some(id("diary/work/1")));
+
+        browser.collapse();
+        assert_eq!(browser.selected(), 1);
+        browser.collapse();
+        assert_eq!(names(&browser), vec!["diary", "  work", "notes"]);
+
+        browser.down(10);
+        assert_eq!(browser.selected(), 2);
+    }
+
+    #[test]
+    fn test_select() {
+        let store = get_store();
+        create(&store, &["notes/b", "notes/a", "diary/work/1"]);
+
+        let mut browser = Browser::new(&store).unwrap();
+        browser.select(&id("notes/b")).unwrap();
+        assert_eq!(names(&browser), vec!["diary", "notes", "  a", "  b"]);
+        assert_eq!(browser.selected_id(), Some(id("notes/b")));
+
+        assert!(browser.select(&id("notes/c")).is_err());
+    }
+
+    #[test]
+    fn test_tag_and_link() {
+        let store = get_store();
+        create(&store, &["notes/a", "notes/b"]);
+
+        let mut browser = Browser::new(&store).unwrap();
+        browser.select(&id("notes/a")).unwrap();
+
+        browser.add_tag("shopping").unwrap();
+        assert!(browser.add_tag("not a tag").is_err());
+        browser.link("notes/b").unwrap();
+
+        let details = browser.details().unwrap().unwrap();
+        assert_eq!(details.tags, vec!["shopping"]);
+        assert_eq!(details.links, vec![id("notes/b")]);
+        assert!(details.header.contains("shopping"));
+
+        browser.remove_tag("shopping").unwrap();
+        assert!(browser.details().unwrap().unwrap().tags.is_empty());
+
+        browser.select(&id("notes/b")).unwrap();
+        assert_eq!(browser.details().unwrap().unwrap().links, vec![id("notes/a")]);
+    }
+
+    #[test]
+    fn test_delete() {
+        let store = get_store();
+        create(&store, &["notes/a", "notes/b"]);
+
+        let mut browser = Browser::new(&store).unwrap();
+        browser.select(&id("notes/b")).unwrap();
+        browser.link("notes/a").unwrap();
+        browser.delete().unwrap();
+
+        assert_eq!(names(&browser), vec!["notes", "  a"]);
+        assert_eq!(browser.selected_id(), Some(id("notes/a")));
+        assert!(browser.details().unwrap().unwrap().links.is_empty());
+    }
+}
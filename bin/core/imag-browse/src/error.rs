@@ -0,0 +1,55 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+error_chain! {
+    types {
+        BrowseError, BrowseErrorKind, ResultExt, Result;
+    }
+
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+        TagError(::libimagentrytag::error::TagError, ::libimagentrytag::error::TagErrorKind);
+        LinkError(::libimagentrylink::error::LinkError, ::libimagentrylink::error::LinkErrorKind);
+        AnnotationError(::libimagentryannotation::error::AnnotationError, ::libimagentryannotation::error::AnnotationErrorKind);
+        EditError(::libimagentryedit::error::EditError, ::libimagentryedit::error::EditErrorKind);
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+        TomlSerError(::toml::ser::Error);
+        TomlQueryError(::toml_query::error::Error);
+    }
+
+    errors {
+        NoEntrySelected {
+            description("No entry selected")
+            display("No entry selected")
+        }
+
+        EntryNotFound(id: String) {
+            description("Entry not found")
+            display("Entry not found: '{}'", id)
+        }
+
+        InvalidTag(tag: String) {
+            description("Invalid tag")
+            display("Invalid tag: '{}'", tag)
+        }
+    }
+}
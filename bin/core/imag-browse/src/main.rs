@@ -0,0 +1,74 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+extern crate toml;
+extern crate toml_query;
+extern crate termion;
+#[macro_use] extern crate log;
+#[macro_use] extern crate error_chain;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagstore;
+extern crate libimagentrytag;
+extern crate libimagentrylink;
+extern crate libimagentryannotation;
+extern crate libimagentryedit;
+
+use libimagrt::setup::generate_runtime_setup;
+use libimagerror::trace::MapErrTrace;
+
+mod browser;
+mod error;
+mod tui;
+mod ui;
+
+use tui::Tui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-browse",
+                                    &version,
+                                    "Browse the store in a terminal user interface",
+                                    ui::build_ui);
+
+    let mut tui = Tui::new(&rt).map_err_trace_exit_unwrap(1);
+    let result  = tui.run();
+
+    // Restore the terminal before reporting errors
+    drop(tui);
+    let _ = result.map_err_trace_exit_unwrap(1);
+    debug!("Done");
+}
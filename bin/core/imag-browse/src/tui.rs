@@ -0,0 +1,320 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The terminal frontend of the browser
+
+use std::io::Stdin;
+use std::io::Stdout;
+use std::io::Write;
+use std::io::stdin;
+use std::io::stdout;
+
+use termion::clear;
+use termion::cursor;
+use termion::event::Key;
+use termion::input::Keys;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::raw::RawTerminal;
+use termion::screen::ToAlternateScreen;
+use termion::screen::ToMainScreen;
+use termion::style;
+use termion::terminal_size;
+
+use libimagrt::runtime::Runtime;
+use libimagentryedit::edit::Edit;
+
+use browser::Browser;
+use browser::Details;
+use browser::RowKind;
+use error::BrowseError as BE;
+use error::BrowseErrorKind as BEK;
+use error::Result;
+
+const HELP: &str = "j/k: move  enter: open  h: close  e: edit  t/T: tag/untag  L: link  f: follow  d: delete  r: refresh  q: quit";
+
+pub struct Tui<'a> {
+    rt: &'a Runtime<'a>,
+    browser: Browser<'a>,
+    out: RawTerminal<Stdout>,
+    keys: Keys<Stdin>,
+    offset: usize,
+    status: String,
+}
+
+impl<'a> Tui<'a> {
+
+    pub fn new(rt: &'a Runtime<'a>) -> Result<Tui<'a>> {
+        let browser = Browser::new(rt.store())?;
+        let mut out = stdout().into_raw_mode()?;
+        let _       = write!(out, "{}{}", ToAlternateScreen, cursor::Hide)?;
+
+        Ok(Tui {
+            rt: rt,
+            browser: browser,
+            out: out,
+            keys: stdin().keys(),
+            offset: 0,
+            status: String::from(HELP),
+        })
+    }
+
+    /// Run until the user quits
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            let _ = self.draw()?;
+
+            let key = match self.keys.next() {
+                Some(key) => key?,
+                None      => return Ok(()),
+            };
+
+            let result = match key {
+                Key::Char('q') | Key::Esc | Key::Ctrl('c')    => return Ok(()),
+                Key::Char('j') | Key::Down                    => { self.browser.down(1); Ok(()) },
+                Key::Char('k') | Key::Up                      => { self.browser.up(1); Ok(()) },
+                Key::PageDown                                 => { self.browser.down(page_height()); Ok(()) },
+                Key::PageUp                                   => { self.browser.up(page_height()); Ok(()) },
+                Key::Char('\n') | Key::Char('l') | Key::Right => { self.browser.toggle(); Ok(()) },
+                Key::Char('h') | Key::Left                    => { self.browser.collapse(); Ok(()) },
+                Key::Char('r')                                => self.browser.refresh(),
+                Key::Char('e')                                => self.edit(),
+                Key::Char('t')                                => self.tag(),
+                Key::Char('T')                                => self.untag(),
+                Key::Char('L')                                => self.link(),
+                Key::Char('f')                                => self.follow(),
+                Key::Char('d')                                => self.delete(),
+                _                                             => Ok(()),
+            };
+
+            self.status = match result {
+                Ok(())  => String::from(HELP),
+                Err(e)  => format!("Error: {}", e),
+            };
+        }
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let (width, height) = terminal_size()?;
+        let (width, height) = (width as usize, height as usize);
+        let tree_width      = width / 3;
+        let pane_height     = height.saturating_sub(1);
+
+        // Keep the selected row visible
+        let selected = self.browser.selected();
+        if selected < self.offset {
+            self.offset = selected;
+        } else if pane_height > 0 && selected >= self.offset + pane_height {
+            self.offset = selected + 1 - pane_height;
+        }
+
+        let rows = self.browser
+            .rows()
+            .into_iter()
+            .skip(self.offset)
+            .take(pane_height)
+            .enumerate()
+            .map(|(i, row)| {
+                let marker = match row.kind {
+                    RowKind::Collection { expanded: true }  => "- ",
+                    RowKind::Collection { expanded: false } => "+ ",
+                    RowKind::Entry                          => "  ",
+                };
+                let text = format!("{}{}{}", "  ".repeat(row.depth), marker, row.name);
+                (i + self.offset == selected, truncate(&text, tree_width.saturating_sub(1)))
+            })
+            .collect::<Vec<_>>();
+
+        let details = match self.browser.details() {
+            Ok(Some(details)) => details_lines(&details),
+            Ok(None)          => vec![],
+            Err(e)            => vec![format!("Error: {}", e)],
+        };
+
+        let _ = write!(self.out, "{}", clear::All)?;
+
+        for (i, &(is_selected, ref text)) in rows.iter().enumerate() {
+            let _ = write!(self.out, "{}", cursor::Goto(1, i as u16 + 1))?;
+            if is_selected {
+                let _ = write!(self.out, "{}{}{}", style::Invert, text, style::Reset)?;
+            } else {
+                let _ = write!(self.out, "{}", text)?;
+            }
+        }
+
+        let details_width = width.saturating_sub(tree_width + 2);
+        for (i, line) in details.iter().take(pane_height).enumerate() {
+            let _ = write!(self.out, "{}| {}",
+                           cursor::Goto(tree_width as u16 + 1, i as u16 + 1),
+                           truncate(line, details_width))?;
+        }
+
+        let _ = write!(self.out, "{}{}",
+                       cursor::Goto(1, height as u16),
+                       truncate(&self.status, width))?;
+
+        self.out.flush().map_err(BE::from)
+    }
+
+    /// Ask the user for a line of input on the status line, `None` if the user cancelled
+    fn prompt(&mut self, question: &str) -> Result<Option<String>> {
+        let mut answer = String::new();
+        let (_, height) = terminal_size()?;
+
+        loop {
+            let _ = write!(self.out, "{}{}{}{}",
+                           cursor::Goto(1, height),
+                           clear::CurrentLine,
+                           question,
+                           answer)?;
+            let _ = self.out.flush()?;
+
+            match self.keys.next() {
+                Some(key) => match key? {
+                    Key::Char('\n')           => return Ok(Some(answer)),
+                    Key::Char(c)              => answer.push(c),
+                    Key::Backspace            => { let _ = answer.pop(); },
+                    Key::Esc | Key::Ctrl('c') => return Ok(None),
+                    _                         => {},
+                },
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Edit the content of the selected entry in the configured editor
+    fn edit(&mut self) -> Result<()> {
+        let id = self.browser
+            .selected_id()
+            .ok_or_else(|| BE::from_kind(BEK::NoEntrySelected))?;
+
+        let _ = write!(self.out, "{}{}", ToMainScreen, cursor::Show)?;
+        let _ = self.out.flush()?;
+        let _ = self.out.suspend_raw_mode()?;
+
+        let result = match self.rt.store().get(id.clone()) {
+            Ok(Some(mut entry)) => entry.edit_content(self.rt).map_err(BE::from),
+            Ok(None)            => Err(BE::from_kind(BEK::EntryNotFound(format!("{}", id)))),
+            Err(e)              => Err(BE::from(e)),
+        };
+
+        let _ = self.out.activate_raw_mode()?;
+        let _ = write!(self.out, "{}{}", ToAlternateScreen, cursor::Hide)?;
+        result
+    }
+
+    fn tag(&mut self) -> Result<()> {
+        match self.prompt("Add tag: ")? {
+            Some(tag) => self.browser.add_tag(tag.trim()),
+            None      => Ok(()),
+        }
+    }
+
+    fn untag(&mut self) -> Result<()> {
+        match self.prompt("Remove tag: ")? {
+            Some(tag) => self.browser.remove_tag(tag.trim()),
+            None      => Ok(()),
+        }
+    }
+
+    fn link(&mut self) -> Result<()> {
+        match self.prompt("Link to: ")? {
+            Some(id) => self.browser.link(id.trim()),
+            None     => Ok(()),
+        }
+    }
+
+    /// Select one of the entries the selected entry links to
+    fn follow(&mut self) -> Result<()> {
+        let links = match self.browser.details()? {
+            Some(details) => details.links,
+            None          => return Err(BE::from_kind(BEK::NoEntrySelected)),
+        };
+
+        let answer = match self.prompt("Follow link number: ")? {
+            Some(answer) => answer,
+            None         => return Ok(()),
+        };
+
+        let link = answer
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| links.get(i))
+            .ok_or_else(|| BE::from_kind(BEK::EntryNotFound(answer.clone())))?;
+
+        self.browser.select(link)
+    }
+
+    fn delete(&mut self) -> Result<()> {
+        let id = self.browser
+            .selected_id()
+            .ok_or_else(|| BE::from_kind(BEK::NoEntrySelected))?;
+
+        match self.prompt(&format!("Delete {}? [y/N] ", id))? {
+            Some(ref answer) if answer.trim() == "y" => self.browser.delete(),
+            _                                        => Ok(()),
+        }
+    }
+
+}
+
+impl<'a> Drop for Tui<'a> {
+    fn drop(&mut self) {
+        let _ = write!(self.out, "{}{}", ToMainScreen, cursor::Show);
+        let _ = self.out.flush();
+    }
+}
+
+fn details_lines(details: &Details) -> Vec<String> {
+    let mut lines = vec![format!("{}", details.id), String::new()];
+
+    lines.push(format!("Tags: {}", details.tags.join(", ")));
+
+    lines.push(String::from("Links:"));
+    for (i, link) in details.links.iter().enumerate() {
+        lines.push(format!("  [{}] {}", i + 1, link));
+    }
+
+    lines.push(String::from("URLs:"));
+    for url in details.urls.iter() {
+        lines.push(format!("  {}", url));
+    }
+
+    lines.push(String::from("Annotations:"));
+    for annotation in details.annotations.iter() {
+        lines.push(format!("  {}", annotation));
+    }
+
+    lines.push(String::new());
+    lines.extend(details.header.lines().map(String::from));
+    lines.push(String::from("---"));
+    lines.extend(details.content.lines().map(String::from));
+    lines
+}
+
+/// The number of tree rows which fit on the screen
+fn page_height() -> usize {
+    terminal_size().map(|(_, h)| h.saturating_sub(1) as usize).unwrap_or(1)
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    s.chars().take(width).collect()
+}
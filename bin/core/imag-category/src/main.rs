@@ -46,13 +46,12 @@ use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagstore::storeid::IntoStoreId;
 
 mod ui;
 
 use std::io::Write;
-use std::io::Read;
 use std::path::PathBuf;
 
 use libimagentrycategory::store::CategoryStore;
@@ -64,10 +63,11 @@ use libimagentrycategory::category::Category;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-category",
-                                    &version,
-                                    "Add a category to entries and manage categories",
-                                    ui::build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-category",
+                                                     &version,
+                                                     "Add a category to entries and manage categories",
+                                                     &["category"],
+                                                     ui::build_ui);
 
     rt.cli()
         .subcommand_name()
@@ -93,26 +93,15 @@ fn main() {
 fn set(rt: &Runtime) {
     let scmd = rt.cli().subcommand_matches("set").unwrap(); // safed by main()
     let name = scmd.value_of("set-name").map(String::from).unwrap(); // safed by clap
-    let sids = match scmd.value_of("set-ids") {
-        Some(path) => vec![PathBuf::from(path).into_storeid().map_err_trace_exit_unwrap(1)],
-        None => if rt.cli().is_present("entries-from-stdin") {
-            let stdin = rt.stdin().unwrap_or_else(|| {
-                error!("Cannot get handle to stdin");
-                ::std::process::exit(1)
-            });
-
-            let mut buf = String::new();
-            let _ = stdin.lock().read_to_string(&mut buf).unwrap_or_else(|_| {
-                error!("Failed to read from stdin");
-                ::std::process::exit(1)
-            });
-
-            buf.lines()
-                .map(PathBuf::from)
-                .map(|p| p.into_storeid().map_err_trace_exit_unwrap(1))
-                .collect()
+    let sids = match scmd.values_of("set-ids") {
+        Some(ids) => ids
+            .map(PathBuf::from)
+            .map(|p| p.into_storeid().map_err_trace_exit_unwrap(1))
+            .collect(),
+        None => if scmd.is_present("entries-from-stdin") || rt.ids_from_pipe() {
+            rt.ids_from_stdin().map_err_trace_exit_unwrap(1)
         } else {
-            error!("Something weird happened. I was not able to find the path of the entries to edit");
+            error!("No ids passed, neither on the commandline nor on stdin");
             ::std::process::exit(1)
         }
     };
@@ -128,31 +117,22 @@ fn set(rt: &Runtime) {
             let _ = entry
                 .set_category_checked(rt.store(), &name)
                 .map_err_trace_exit_unwrap(1);
+
+            let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
         })
 }
 
 fn get(rt: &Runtime) {
     let scmd = rt.cli().subcommand_matches("get").unwrap(); // safed by main()
-    let sids = match scmd.value_of("get-ids") {
-        Some(path) => vec![PathBuf::from(path).into_storeid().map_err_trace_exit_unwrap(1)],
-        None => if rt.cli().is_present("entries-from-stdin") {
-            let stdin = rt.stdin().unwrap_or_else(|| {
-                error!("Cannot get handle to stdin");
-                ::std::process::exit(1)
-            });
-
-            let mut buf = String::new();
-            let _ = stdin.lock().read_to_string(&mut buf).unwrap_or_else(|_| {
-                error!("Failed to read from stdin");
-                ::std::process::exit(1)
-            });
-
-            buf.lines()
-                .map(PathBuf::from)
-                .map(|p| p.into_storeid().map_err_trace_exit_unwrap(1))
-                .collect()
+    let sids = match scmd.values_of("get-ids") {
+        Some(ids) => ids
+            .map(PathBuf::from)
+            .map(|p| p.into_storeid().map_err_trace_exit_unwrap(1))
+            .collect(),
+        None => if scmd.is_present("entries-from-stdin") || rt.ids_from_pipe() {
+            rt.ids_from_stdin().map_err_trace_exit_unwrap(1)
         } else {
-            error!("Something weird happened. I was not able to find the path of the entries to edit");
+            error!("No ids passed, neither on the commandline nor on stdin");
             ::std::process::exit(1)
         }
     };
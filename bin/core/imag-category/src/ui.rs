@@ -88,8 +88,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .help("Read the ids for the entries from stdin"))
 
                     .group(ArgGroup::with_name("input-method")
-                           .args(&["set-ids", "entries-from-stdin"])
-                           .required(true))
+                           .args(&["set-ids", "entries-from-stdin"]))
                    )
 
         .subcommand(SubCommand::with_name("get")
@@ -111,8 +110,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .help("Read the ids for the entries from stdin"))
 
                     .group(ArgGroup::with_name("input-method")
-                           .args(&["get-ids", "entries-from-stdin"])
-                           .required(true))
+                           .args(&["get-ids", "entries-from-stdin"]))
                    )
 }
 
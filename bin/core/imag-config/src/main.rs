@@ -0,0 +1,213 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+extern crate toml;
+extern crate toml_query;
+#[macro_use] extern crate log;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagstore;
+
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::exit;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+use libimagrt::configuration::Layer;
+use libimagrt::configuration::LayeredConfig;
+use libimagrt::configuration::PROJECT_CONFIG_FILE_NAME;
+use libimagrt::configuration::SYSTEM_CONFIG_PATH;
+use libimagrt::configuration::set_in_file;
+use libimagrt::error::RuntimeError;
+use libimagrt::runtime::Runtime;
+use libimagrt::runtime::get_layered_config;
+use libimagrt::schema::discover_keys;
+use libimagrt::setup::generate_runtime_setup;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::exit::ExitUnwrap;
+use libimagerror::io::ToExitCode;
+use libimagstore::store::STORE_CONFIG_FILE_NAME;
+
+mod ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-config",
+                                    &version,
+                                    "Show and change the configuration",
+                                    ui::build_ui);
+
+    rt.cli()
+        .subcommand_name()
+        .map(|name| {
+            match name {
+                "show"     => show(&rt),
+                "get"      => get(&rt),
+                "set"      => set(&rt),
+                "schema"   => schema(&rt),
+                "validate" => validate(&rt),
+                other      => {
+                    debug!("Unknown command");
+                    let _ = rt.handle_unknown_subcommand("imag-config", other, rt.cli())
+                        .map_err_trace_exit_unwrap(1)
+                        .code()
+                        .map(::std::process::exit);
+                }
+            }
+        });
+}
+
+fn show(rt: &Runtime) {
+    let scmd     = rt.cli().subcommand_matches("show").unwrap(); // safed by main()
+    let config   = load(rt);
+    let out      = rt.stdout();
+    let mut lock = out.lock();
+
+    if scmd.is_present("origin") {
+        for (path, origin) in config.origins_below("") {
+            let value = read(&config, path);
+            let _ = writeln!(lock, "{} = {}    # {}", path, value, origin)
+                .to_exit_code()
+                .unwrap_or_exit();
+        }
+    } else {
+        let _ = write!(lock, "{}", config.value())
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+}
+
+fn get(rt: &Runtime) {
+    let scmd     = rt.cli().subcommand_matches("get").unwrap(); // safed by main()
+    let key      = scmd.value_of("key").unwrap(); // safed by clap
+    let config   = load(rt);
+    let out      = rt.stdout();
+    let mut lock = out.lock();
+
+    let _ = match *read(&config, key) {
+        Value::String(ref s) => writeln!(lock, "{}", s),
+        ref other            => writeln!(lock, "{}", other),
+    }.to_exit_code().unwrap_or_exit();
+
+    if scmd.is_present("origin") {
+        for (path, origin) in config.origins_below(key) {
+            let _ = writeln!(lock, "{}: {}", path, origin)
+                .to_exit_code()
+                .unwrap_or_exit();
+        }
+    }
+}
+
+fn set(rt: &Runtime) {
+    let scmd  = rt.cli().subcommand_matches("set").unwrap(); // safed by main()
+    let key   = scmd.value_of("key").unwrap(); // safed by clap
+    let value = scmd.value_of("value").unwrap(); // safed by clap
+    let layer = scmd
+        .value_of("layer")
+        .and_then(Layer::from_name)
+        .unwrap_or(Layer::User); // safed by clap
+    let file  = config_file(rt, layer);
+
+    set_in_file(&file, key, value).map_err_trace_exit_unwrap(1);
+    info!("Set '{}' in {}", key, file.display());
+}
+
+fn schema(rt: &Runtime) {
+    let keys = discover_keys(rt.rtp());
+
+    rt.output(&keys, |out, key| {
+        match key.default {
+            Some(ref default) => writeln!(out, "{} ({}, default: {})", key.path, key.kind, default)?,
+            None              => writeln!(out, "{} ({})", key.path, key.kind)?,
+        }
+        writeln!(out, "    {}", key.doc)
+    }).map_err_trace_exit_unwrap(1);
+}
+
+fn validate(rt: &Runtime) {
+    let config = load(rt);
+    let issues = ::libimagrt::schema::validate(config.value(), &discover_keys(rt.rtp()));
+
+    if issues.is_empty() {
+        info!("Configuration is valid");
+        return
+    }
+
+    rt.output(&issues, |out, issue| writeln!(out, "{}", issue))
+        .map_err_trace_exit_unwrap(1);
+    exit(1)
+}
+
+fn load(rt: &Runtime) -> LayeredConfig {
+    get_layered_config(rt.cli())
+        .map_err_trace_exit_unwrap(1)
+        .unwrap_or_else(|| {
+            error!("No configuration file found");
+            exit(1)
+        })
+}
+
+fn read<'a>(config: &'a LayeredConfig, key: &str) -> &'a Value {
+    config
+        .value()
+        .read(key)
+        .map_err(RuntimeError::from)
+        .map_err_trace_exit_unwrap(1)
+        .unwrap_or_else(|| {
+            error!("No value for '{}'", key);
+            exit(1)
+        })
+}
+
+/// Get the file of `layer` which was loaded, or where it would be loaded from
+fn config_file(rt: &Runtime, layer: Layer) -> PathBuf {
+    let loaded = get_layered_config(rt.cli())
+        .map_err_trace_exit_unwrap(1)
+        .and_then(|config| config.files().get(&layer).cloned());
+
+    loaded.unwrap_or_else(|| match layer {
+        Layer::System  => PathBuf::from(SYSTEM_CONFIG_PATH),
+        Layer::Store   => rt.store().path().join(STORE_CONFIG_FILE_NAME),
+        Layer::Project => env::current_dir()
+            .map_err(RuntimeError::from)
+            .map_err_trace_exit_unwrap(1)
+            .join(PROJECT_CONFIG_FILE_NAME),
+        _              => rt.rtp().join("imagrc.toml"),
+    })
+}
@@ -0,0 +1,91 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use clap::{Arg, App, SubCommand};
+
+pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app
+        .subcommand(SubCommand::with_name("show")
+                    .about("Show the merged configuration")
+                    .version("0.1")
+                    .arg(Arg::with_name("origin")
+                         .long("origin")
+                         .takes_value(false)
+                         .required(false)
+                         .multiple(false)
+                         .help("Show every value with the layer and file it came from"))
+                    )
+
+        .subcommand(SubCommand::with_name("get")
+                    .about("Get a value from the merged configuration")
+                    .version("0.1")
+                    .arg(Arg::with_name("key")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("The path of the value, e.g. 'store.implicit-create'")
+                         .value_name("KEY"))
+                    .arg(Arg::with_name("origin")
+                         .long("origin")
+                         .takes_value(false)
+                         .required(false)
+                         .multiple(false)
+                         .help("Also show the layer and file the value came from"))
+                    )
+
+        .subcommand(SubCommand::with_name("set")
+                    .about("Set a value in one of the configuration files")
+                    .version("0.1")
+                    .arg(Arg::with_name("key")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("The path of the value, e.g. 'store.implicit-create'")
+                         .value_name("KEY"))
+                    .arg(Arg::with_name("value")
+                         .index(2)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("The value. It is parsed as TOML value, if that fails it is used as string")
+                         .value_name("VALUE"))
+                    .arg(Arg::with_name("layer")
+                         .long("layer")
+                         .short("l")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .possible_values(&["system", "user", "store", "project"])
+                         .default_value("user")
+                         .help("The configuration file to write to")
+                         .value_name("LAYER"))
+                    )
+
+        .subcommand(SubCommand::with_name("schema")
+                    .about("Print all configuration keys imag commands read, with type, default and documentation")
+                    .version("0.1")
+                    )
+
+        .subcommand(SubCommand::with_name("validate")
+                    .about("Check the merged configuration for unknown keys and values of the wrong type")
+                    .version("0.1")
+                    )
+}
@@ -0,0 +1,162 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::exit;
+
+use clap::ArgMatches;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use log::Level;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::error::RuntimeError;
+use libimagrt::logger::JsonRecord;
+use libimagrt::logger::configured_log_files;
+use libimagrt::logger::rotated_files;
+use libimagerror::trace::MapErrTrace;
+
+/// Filters for the records of the log files
+struct Filter<'a> {
+    level   : Option<Level>,
+    module  : Option<&'a str>,
+    id      : Option<&'a str>,
+    command : Option<&'a str>,
+    since   : Option<i64>,
+}
+
+impl<'a> Filter<'a> {
+
+    fn matches(&self, record: &JsonRecord) -> bool {
+        self.level.map(|l| record.level().map(|r| r <= l).unwrap_or(false)).unwrap_or(true) &&
+            self.module.map(|m| record.module.starts_with(m)).unwrap_or(true) &&
+            self.id.map(|i| record.store_id.as_ref().map(|r| r == i).unwrap_or(false)).unwrap_or(true) &&
+            self.command.map(|c| record.command == c).unwrap_or(true) &&
+            self.since.map(|s| timestamp(record).map(|t| t >= s).unwrap_or(false)).unwrap_or(true)
+    }
+
+}
+
+pub fn logs(rt: &Runtime, scmd: &ArgMatches) {
+    let files = match scmd.values_of("file") {
+        Some(files) => files.map(PathBuf::from).collect::<Vec<_>>(),
+        None        => rt.config().map(configured_log_files).unwrap_or_default(),
+    };
+
+    if files.is_empty() {
+        error!("No log files configured, pass one with --file");
+        exit(1)
+    }
+
+    let files = if scmd.is_present("rotated") {
+        files
+            .into_iter()
+            .flat_map(|file| {
+                let mut all = rotated_files(&file);
+                all.push(file);
+                all
+            })
+            .collect()
+    } else {
+        files
+    };
+
+    let filter = Filter {
+        level   : scmd.value_of("level").and_then(|l| l.parse().ok()), // safed by clap
+        module  : scmd.value_of("module"),
+        id      : scmd.value_of("id"),
+        command : scmd.value_of("command"),
+        since   : scmd.value_of("since").map(|s| parse_since(s).unwrap_or_else(|| {
+            error!("Cannot parse '{}' as datetime or date", s);
+            exit(1)
+        })),
+    };
+
+    let mut records = vec![];
+    let mut skipped = 0;
+
+    for file in files {
+        if !file.exists() {
+            debug!("Log file {} does not exist", file.display());
+            continue;
+        }
+
+        let reader = File::open(&file)
+            .map(BufReader::new)
+            .map_err(RuntimeError::from)
+            .map_err_trace_exit_unwrap(1);
+
+        for line in reader.lines() {
+            let line = line.map_err(RuntimeError::from).map_err_trace_exit_unwrap(1);
+
+            match JsonRecord::parse(&line) {
+                Some(record) => if filter.matches(&record) {
+                    records.push(record);
+                },
+                None => skipped += 1,
+            }
+        }
+    }
+
+    if skipped != 0 {
+        warn!("Skipped {} lines which are not JSON log records", skipped);
+    }
+
+    // The records of several files are interleaved
+    records.sort_by_key(timestamp);
+
+    rt.output(&records, |out, record| {
+        write!(out, "{} {:<5} [{} {}] {}: {}",
+               record.timestamp,
+               record.level,
+               record.command,
+               record.pid,
+               record.module,
+               record.message)?;
+
+        match record.store_id {
+            Some(ref id) => writeln!(out, " ({})", id),
+            None         => writeln!(out),
+        }
+    }).map_err_trace_exit_unwrap(1);
+}
+
+fn timestamp(record: &JsonRecord) -> Option<i64> {
+    DateTime::parse_from_rfc3339(&record.timestamp)
+        .ok()
+        .map(|dt| dt.timestamp())
+}
+
+fn parse_since(s: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.timestamp())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| Local.from_local_datetime(&date.and_hms(0, 0, 0)).earliest())
+                .map(|dt| dt.timestamp())
+        })
+}
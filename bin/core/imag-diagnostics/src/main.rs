@@ -33,6 +33,7 @@
 )]
 
 extern crate clap;
+extern crate chrono;
 extern crate toml;
 extern crate toml_query;
 #[macro_use] extern crate log;
@@ -45,6 +46,7 @@ extern crate libimagstore;
 use std::io::Write;
 
 use libimagrt::setup::generate_runtime_setup;
+use libimagrt::schema;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::io::ToExitCode;
 use libimagerror::exit::ExitUnwrap;
@@ -58,6 +60,7 @@ use toml_query::read::TomlValueReadExt;
 
 use std::collections::BTreeMap;
 
+mod logs;
 mod ui;
 
 #[derive(Debug)]
@@ -118,6 +121,11 @@ fn main() {
                                     "Print diagnostics about imag and the imag store",
                                     ui::build_ui);
 
+    if let Some(scmd) = rt.cli().subcommand_matches("logs") {
+        logs::logs(&rt, scmd);
+        return
+    }
+
     let mut entries_counter = 0;
     let diags = rt.store()
         .entries()
@@ -230,5 +238,15 @@ fn main() {
         do_write!(out, "{} verified entries", verified_count);
         do_write!(out, "{} unverified entries", unverified_count);
     }
+
+    if let Some(config) = rt.config() {
+        let issues = schema::validate(config, &schema::discover_keys(rt.rtp()));
+
+        do_write!(out, "");
+        do_write!(out, "{} configuration issues", issues.len());
+        for issue in issues {
+            do_write!(out, "{}", issue);
+        }
+    }
 }
 
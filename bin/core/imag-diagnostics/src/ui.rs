@@ -17,9 +17,68 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use clap::App;
+use clap::{Arg, App, SubCommand};
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
+        .subcommand(SubCommand::with_name("logs")
+                    .about("Show the records of JSON log files (see 'imag.logging.file-format')")
+                    .version("0.1")
+                    .arg(Arg::with_name("file")
+                         .long("file")
+                         .short("f")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(true)
+                         .help("The log file to read. Defaults to all log files in the configuration")
+                         .value_name("FILE"))
+                    .arg(Arg::with_name("rotated")
+                         .long("rotated")
+                         .short("r")
+                         .takes_value(false)
+                         .required(false)
+                         .multiple(false)
+                         .help("Also read the rotated log files"))
+                    .arg(Arg::with_name("level")
+                         .long("level")
+                         .short("l")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .possible_values(&["trace", "debug", "info", "warn", "error"])
+                         .help("Only show records of this level or more severe ones")
+                         .value_name("LEVEL"))
+                    .arg(Arg::with_name("module")
+                         .long("module")
+                         .short("m")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .help("Only show records of modules starting with MODULE, e.g. 'libimagstore'")
+                         .value_name("MODULE"))
+                    .arg(Arg::with_name("id")
+                         .long("id")
+                         .short("i")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .help("Only show records about this entry")
+                         .value_name("ID"))
+                    .arg(Arg::with_name("command")
+                         .long("command")
+                         .short("c")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .help("Only show records of this command, e.g. 'imag-diary'")
+                         .value_name("COMMAND"))
+                    .arg(Arg::with_name("since")
+                         .long("since")
+                         .short("s")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .help("Only show records since this time, as RFC 3339 datetime or as date 'YYYY-MM-DD'")
+                         .value_name("DATETIME"))
+                    )
 }
-
@@ -42,7 +42,6 @@ extern crate libimagstore;
 extern crate libimagutil;
 
 use std::path::PathBuf;
-use std::io::Read;
 
 use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
@@ -62,26 +61,15 @@ fn main() {
                                     "Edit store entries with $EDITOR",
                                     ui::build_ui);
 
-    let sids = match rt.cli().value_of("entry") {
-        Some(path) => vec![PathBuf::from(path).into_storeid().map_err_trace_exit_unwrap(1)],
-        None => if rt.cli().is_present("entries-from-stdin") {
-            let stdin = rt.stdin().unwrap_or_else(|| {
-                error!("Cannot get handle to stdin");
-                ::std::process::exit(1)
-            });
-
-            let mut buf = String::new();
-            let _ = stdin.lock().read_to_string(&mut buf).unwrap_or_else(|_| {
-                error!("Failed to read from stdin");
-                ::std::process::exit(1)
-            });
-
-            buf.lines()
-                .map(PathBuf::from)
-                .map(|p| p.into_storeid().map_err_trace_exit_unwrap(1))
-                .collect()
+    let sids = match rt.cli().values_of("entry") {
+        Some(paths) => paths
+            .map(PathBuf::from)
+            .map(|p| p.into_storeid().map_err_trace_exit_unwrap(1))
+            .collect(),
+        None => if rt.cli().is_present("entries-from-stdin") || rt.ids_from_pipe() {
+            rt.ids_from_stdin().map_err_trace_exit_unwrap(1)
         } else {
-            error!("Something weird happened. I was not able to find the path of the entries to edit");
+            error!("No ids passed, neither on the commandline nor on stdin");
             ::std::process::exit(1)
         }
     };
@@ -110,6 +98,8 @@ fn main() {
                     .edit_content(&rt)
                     .map_err_trace_exit_unwrap(1);
             }
+
+            let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
         });
 }
 
@@ -36,8 +36,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .multiple(false)
              .help("The entry/entries are piped in via stdin"))
         .group(ArgGroup::with_name("input-method")
-               .args(&["entry", "entries-from-stdin"])
-               .required(true))
+               .args(&["entry", "entries-from-stdin"]))
 
         .arg(Arg::with_name("edit-header")
              .long("header")
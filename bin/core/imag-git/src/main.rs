@@ -39,6 +39,7 @@ extern crate toml_query;
 
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagutil;
 
 use std::io::Write;
 use std::io::ErrorKind;
@@ -49,16 +50,30 @@ use toml_query::read::TomlValueReadExt;
 
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 
 mod ui;
 
+/// The configuration keys imag-git reads
+const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "git.execute_in_store",
+        kind: ValueType::Boolean,
+        default: None,
+        doc: "Whether git is called in the store directory instead of the runtimepath",
+    },
+];
+
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-git",
-                                    &version,
-                                    "Helper to call git in the store",
-                                    ui::build_ui);
+    let rt = generate_runtime_setup_with_config("imag-git",
+                                                &version,
+                                                "Helper to call git in the store",
+                                                &[],
+                                                &[CONFIG_KEYS],
+                                                ui::build_ui);
 
     let execute_in_store = rt
         .config()
@@ -33,6 +33,8 @@
 )]
 
 extern crate clap;
+extern crate chrono;
+extern crate filters;
 #[macro_use]
 extern crate log;
 
@@ -41,7 +43,10 @@ extern crate libimagentrygps;
 extern crate libimagutil;
 extern crate libimagerror;
 extern crate libimagstore;
+extern crate libimagdiary;
+extern crate libimaglog;
 
+use std::fs::File;
 use std::io::Write;
 use std::process::exit;
 use std::path::PathBuf;
@@ -51,12 +56,16 @@ use libimagentrygps::error::GPSError as GE;
 use libimagentrygps::error::GPSErrorKind as GEK;
 use libimagentrygps::types::*;
 use libimagentrygps::entry::*;
+use libimagentrygps::filter::WithinRadius;
+use libimagentrygps::filter::parse_distance;
+use libimagentrygps::filter::parse_point;
+use libimagentrygps::import::read_gpx;
+use libimagentrygps::import::read_kml;
 use libimagrt::setup::generate_runtime_setup;
 use libimagrt::runtime::Runtime;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
-use libimagstore::storeid::IntoStoreId;
 
 mod ui;
 
@@ -74,6 +83,8 @@ fn main() {
                 "add"    => add(&rt),
                 "remove" => remove(&rt),
                 "get"    => get(&rt),
+                "find"   => find(&rt),
+                "import" => import(&rt),
                 other    => {
                     debug!("Unknown command");
                     let _ = rt.handle_unknown_subcommand("imag-gps", other, rt.cli())
@@ -88,12 +99,20 @@ fn main() {
 fn add(rt: &Runtime) {
     let scmd = rt.cli().subcommand_matches("add").unwrap(); // safed by main()
 
-    let entry_name  = scmd.value_of("entry").unwrap(); // safed by clap
-    let sid         = PathBuf::from(entry_name)
-        .into_storeid()
-        .map_err_trace_exit_unwrap(1);
+    let ids = rt.ids(scmd, "entry", None).map_err_trace_exit_unwrap(1);
 
-    let c = {
+    let c = if scmd.is_present("decimal") {
+        let parse = |value: &str| -> f64 {
+            FromStr::from_str(value)
+                .or_else(|_| Err(GE::from(GEK::NumberConversionError)))
+                .map_err_trace_exit_unwrap(1)
+        };
+
+        let long = parse(scmd.value_of("longitude").unwrap()); // unwrap safed by clap
+        let lati = parse(scmd.value_of("latitude").unwrap()); // unwrap safed by clap
+
+        Position::new(long, lati)
+    } else {
         let parse = |value: &str| -> (i64, i64, i64) {
             debug!("Parsing '{}' into degree, minute and second", value);
             let ary = value.split(".")
@@ -124,73 +143,171 @@ fn add(rt: &Runtime) {
         let long = GPSValue::new(long.0, long.1, long.2);
         let lati = GPSValue::new(lati.0, lati.1, lati.2);
 
-        Coordinates::new(long, lati)
+        Position::from(Coordinates::new(long, lati))
     };
 
-    rt.store()
-        .get(sid)
-        .map_err_trace_exit_unwrap(1)
-        .map(|mut entry| {
-            let _ = entry.set_coordinates(c).map_err_trace_exit_unwrap(1);
+    let parse_meters = |name: &str| -> Option<f64> {
+        scmd.value_of(name).map(|value| {
+            parse_distance(value).map_err_trace_exit_unwrap(1)
         })
-        .unwrap_or_else(|| {
-            error!("No such entry: {}", entry_name);
-            exit(1)
-        });
+    };
+
+    let c = match parse_meters("altitude") {
+        Some(alt) => c.with_altitude(alt),
+        None      => c,
+    };
+
+    let c = match parse_meters("accuracy") {
+        Some(acc) => c.with_accuracy(acc),
+        None      => c,
+    };
+
+    for id in ids {
+        let mut entry = rt.store()
+            .get(id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .unwrap_or_else(|| {
+                error!("No such entry: {}", id);
+                exit(1)
+            });
+
+        let _ = entry.set_position(c.clone()).map_err_trace_exit_unwrap(1);
+        let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
+    }
 }
 
 fn remove(rt: &Runtime) {
-    let scmd = rt.cli().subcommand_matches("remove").unwrap(); // safed by main()
+    let scmd          = rt.cli().subcommand_matches("remove").unwrap(); // safed by main()
+    let print_removed = scmd.is_present("print-removed");
 
-    let entry_name  = scmd.value_of("entry").unwrap(); // safed by clap
-    let sid         = PathBuf::from(entry_name)
-        .into_storeid()
-        .map_err_trace_exit_unwrap(1);
+    for id in rt.ids(scmd, "entry", None).map_err_trace_exit_unwrap(1) {
+        let mut entry = rt
+            .store()
+            .get(id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .unwrap_or_else(|| { // if we have Ok(None)
+                error!("No such entry: {}", id);
+                exit(1)
+            });
 
-    let removed_value = rt
-        .store()
-        .get(sid)
-        .map_err_trace_exit_unwrap(1)
-        .unwrap_or_else(|| { // if we have Ok(None)
-            error!("No such entry: {}", entry_name);
-            exit(1)
-        })
-        .remove_coordinates()
-        .map_err_trace_exit_unwrap(1) // The delete action failed
-        .unwrap_or_else(|| { // if we have Ok(None)
-            error!("Entry had no coordinates: {}", entry_name);
-            exit(1)
-        })
-        .map_err_trace_exit_unwrap(1); // The parsing of the deleted values failed
+        let removed_value = entry
+            .remove_coordinates()
+            .map_err_trace_exit_unwrap(1) // The delete action failed
+            .unwrap_or_else(|| { // if we have Ok(None)
+                error!("Entry had no coordinates: {}", id);
+                exit(1)
+            })
+            .map_err_trace_exit_unwrap(1); // The parsing of the deleted values failed
 
-    if scmd.is_present("print-removed") {
-        let _ = writeln!(rt.stdout(), "{}", removed_value).to_exit_code().unwrap_or_exit();
+        if print_removed {
+            let _ = writeln!(rt.stdout(), "{}", removed_value).to_exit_code().unwrap_or_exit();
+        } else {
+            let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
+        }
     }
 }
 
 fn get(rt: &Runtime) {
     let scmd = rt.cli().subcommand_matches("get").unwrap(); // safed by main()
 
-    let entry_name  = scmd.value_of("entry").unwrap(); // safed by clap
-    let sid         = PathBuf::from(entry_name)
-        .into_storeid()
-        .map_err_trace_exit_unwrap(1);
+    for id in rt.ids(scmd, "entry", None).map_err_trace_exit_unwrap(1) {
+        let value = rt
+            .store()
+            .get(id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .unwrap_or_else(|| { // if we have Ok(None)
+                error!("No such entry: {}", id);
+                exit(1)
+            })
+            .get_coordinates()
+            .map_err_trace_exit_unwrap(1) // The get action failed
+            .unwrap_or_else(|| { // if we have Ok(None)
+                error!("Entry has no coordinates: {}", id);
+                exit(1)
+            });
+
+        let _ = writeln!(rt.stdout(), "{}", value).to_exit_code().unwrap_or_exit();
+    }
+}
+
+fn find(rt: &Runtime) {
+    use filters::failable::filter::FailableFilter;
 
-    let value = rt
-        .store()
-        .get(sid)
+    let scmd   = rt.cli().subcommand_matches("find").unwrap(); // safed by main()
+    let center = parse_point(scmd.value_of("near").unwrap()).map_err_trace_exit_unwrap(1); // safed by clap
+    let radius = parse_distance(scmd.value_of("radius").unwrap()).map_err_trace_exit_unwrap(1); // safed by clap
+    let filter = WithinRadius::new(center.clone(), radius);
+
+    let out         = rt.stdout();
+    let mut outlock = out.lock();
+
+    rt.store()
+        .entries()
         .map_err_trace_exit_unwrap(1)
-        .unwrap_or_else(|| { // if we have Ok(None)
-            error!("No such entry: {}", entry_name);
-            exit(1)
-        })
-        .get_coordinates()
-        .map_err_trace_exit_unwrap(1) // The get action failed
-        .unwrap_or_else(|| { // if we have Ok(None)
-            error!("Entry has no coordinates: {}", entry_name);
-            exit(1)
-        });
+        .into_get_iter()
+        .filter_map(|res| res.map_err_trace_exit_unwrap(1))
+        .filter(|entry| filter.filter(entry).map_err_trace_exit_unwrap(1))
+        .for_each(|entry| {
+            let distance = entry
+                .get_position()
+                .map_err_trace_exit_unwrap(1)
+                .map(|p| p.distance_to(&center))
+                .unwrap_or(0.0); // safe, as the filter only lets entries with coordinates pass
 
-    let _       = writeln!(rt.stdout(), "{}", value).to_exit_code().unwrap_or_exit();
+            let _ = writeln!(outlock, "{} ({:.0}m)", entry.get_location(), distance)
+                .to_exit_code()
+                .unwrap_or_exit();
+        });
 }
 
+fn import(rt: &Runtime) {
+    use chrono::Local;
+    use libimagdiary::diary::Diary;
+    use libimaglog::log::Log;
+
+    let scmd      = rt.cli().subcommand_matches("import").unwrap(); // safed by main()
+    let path      = PathBuf::from(scmd.value_of("file").unwrap()); // safed by clap
+    let diaryname = scmd.value_of("diary").unwrap(); // safed by clap
+    let as_log    = scmd.is_present("log");
+
+    let file = File::open(&path).unwrap_or_else(|e| {
+        error!("Cannot open {}: {}", path.display(), e);
+        exit(1)
+    });
+
+    let is_kml = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase() == "kml")
+        .unwrap_or(false);
+
+    let points = if is_kml {
+        read_kml(file)
+    } else {
+        read_gpx(file)
+    }.map_err_trace_exit_unwrap(1);
+
+    for point in points {
+        let time = match point.time {
+            Some(t) => t.with_timezone(&Local).naive_local(),
+            None    => {
+                warn!("Ignoring point without time: {},{}", point.position.latitude, point.position.longitude);
+                continue
+            },
+        };
+
+        let mut entry = rt
+            .store()
+            .new_entry_at(diaryname, &time)
+            .map_err_trace_exit_unwrap(1);
+
+        let _ = entry.set_position(point.position).map_err_trace_exit_unwrap(1);
+
+        if as_log {
+            let _ = entry.make_log_entry().map_err_trace_exit_unwrap(1);
+        }
+
+        info!("Imported {}", entry.get_location());
+        let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
+    }
+}
@@ -38,13 +38,33 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .multiple(false)
                          .help("Set the latitude. Format: <degrees>.<minutes>.<seconds>")
                          .value_name("LATITUDE"))
+                    .arg(Arg::with_name("decimal")
+                         .long("decimal")
+                         .takes_value(false)
+                         .required(false)
+                         .multiple(false)
+                         .help("Longitude and latitude are passed in decimal degrees, e.g. '48.137154'"))
+                    .arg(Arg::with_name("altitude")
+                         .long("alt")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .help("Set the altitude, e.g. '519m'")
+                         .value_name("ALTITUDE"))
+                    .arg(Arg::with_name("accuracy")
+                         .long("accuracy")
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(false)
+                         .help("Set the accuracy of the position, e.g. '10m'")
+                         .value_name("ACCURACY"))
                     .arg(Arg::with_name("entry")
                          .index(1)
                          .takes_value(true)
-                         .required(true)
-                         .multiple(false)
-                         .help("The entry to add the latitude/longitude to")
-                         .value_name("ENTRY"))
+                         .required(false)
+                         .multiple(true)
+                         .help("The entries to add the latitude/longitude to. Read from stdin if not passed and stdin is a pipe")
+                         .value_name("ENTRIES"))
                     )
 
         .subcommand(SubCommand::with_name("remove")
@@ -59,10 +79,10 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                 .arg(Arg::with_name("entry")
                      .index(1)
                      .takes_value(true)
-                     .required(true)
-                     .multiple(false)
-                     .help("The entry to remove the latitude/longitude from")
-                     .value_name("ENTRY"))
+                     .required(false)
+                     .multiple(true)
+                     .help("The entries to remove the latitude/longitude from. Read from stdin if not passed and stdin is a pipe")
+                     .value_name("ENTRIES"))
                 )
 
         .subcommand(SubCommand::with_name("get")
@@ -71,10 +91,10 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                 .arg(Arg::with_name("entry")
                      .index(1)
                      .takes_value(true)
-                     .required(true)
-                     .multiple(false)
-                     .help("The entry to get the latitude/longitude from")
-                     .value_name("ENTRY"))
+                     .required(false)
+                     .multiple(true)
+                     .help("The entries to get the latitude/longitude from. Read from stdin if not passed and stdin is a pipe")
+                     .value_name("ENTRIES"))
                 .arg(Arg::with_name("format-json")
                      .long("json")
                      .takes_value(false)
@@ -88,4 +108,50 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                      .multiple(false)
                      .help("Print as <key>=<value> pairs (2 lines, default)"))
                 )
+
+        .subcommand(SubCommand::with_name("find")
+                .about("Find entries with coordinates near a point")
+                .version("0.1")
+                .arg(Arg::with_name("near")
+                     .long("near")
+                     .takes_value(true)
+                     .required(true)
+                     .multiple(false)
+                     .help("The point to search around, in decimal degrees")
+                     .value_name("LATITUDE,LONGITUDE"))
+                .arg(Arg::with_name("radius")
+                     .long("radius")
+                     .takes_value(true)
+                     .required(false)
+                     .multiple(false)
+                     .default_value("1km")
+                     .help("The radius to search in, e.g. '5km' or '500m'")
+                     .value_name("RADIUS"))
+                )
+
+        .subcommand(SubCommand::with_name("import")
+                .about("Import the points of a GPX or KML file as diary entries")
+                .version("0.1")
+                .arg(Arg::with_name("file")
+                     .index(1)
+                     .takes_value(true)
+                     .required(true)
+                     .multiple(false)
+                     .help("The GPX or KML (*.kml) file to import. Points without time are ignored.")
+                     .value_name("FILE"))
+                .arg(Arg::with_name("diary")
+                     .long("diary")
+                     .short("d")
+                     .takes_value(true)
+                     .required(true)
+                     .multiple(false)
+                     .help("The diary to create the entries in")
+                     .value_name("DIARY"))
+                .arg(Arg::with_name("log")
+                     .long("log")
+                     .takes_value(false)
+                     .required(false)
+                     .multiple(false)
+                     .help("Mark the created entries as log entries (see imag-log)"))
+                )
 }
@@ -35,14 +35,18 @@
 #[macro_use] extern crate log;
 extern crate clap;
 extern crate regex;
+extern crate ansi_term;
 
 extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagsearch;
+extern crate libimagentryfilter;
 
 use std::io::Write;
 
 use regex::Regex;
+use ansi_term::Style;
 
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
@@ -50,12 +54,28 @@ use libimagstore::store::Entry;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
+use libimagsearch::index::Index;
+use libimagsearch::index::default_index_path;
+use libimagsearch::query::Query;
+use libimagsearch::snippet::Snippet;
+use libimagentryfilter::cli::query_from_matches;
+use libimagentryfilter::query::Query as FilterQuery;
 
 mod ui;
 
 struct Options {
     files_with_matches: bool,
     count: bool,
+    filter: Option<FilterQuery>,
+}
+
+impl Options {
+    fn accepts(&self, entry: &Entry) -> bool {
+        self.filter
+            .as_ref()
+            .map(|f| f.matches(entry).map_err_trace_exit_unwrap(1))
+            .unwrap_or(true)
+    }
 }
 
 fn main() {
@@ -68,8 +88,13 @@ fn main() {
     let opts = Options {
         files_with_matches    : rt.cli().is_present("files-with-matches"),
         count                 : rt.cli().is_present("count"),
+        filter                : query_from_matches(rt.store(), rt.cli()).map_err_trace_exit_unwrap(1),
     };
 
+    if rt.cli().is_present("index") {
+        return grep_index(&rt, &opts);
+    }
+
     let mut count : usize = 0;
 
     let pattern = rt
@@ -88,6 +113,7 @@ fn main() {
         .map_err_trace_exit_unwrap(1)
         .into_get_iter()
         .filter_map(|res| res.map_err_trace_exit_unwrap(1))
+        .filter(|entry| opts.accepts(entry))
         .filter(|entry| pattern.is_match(entry.get_content()))
         .map(|entry| show(&rt, &entry, &pattern, &opts, &mut count))
         .count();
@@ -124,3 +150,63 @@ fn show(rt: &Runtime, e: &Entry, re: &Regex, opts: &Options, count: &mut usize)
     }
 }
 
+
+fn grep_index(rt: &Runtime, opts: &Options) {
+    let query = rt
+        .cli()
+        .value_of("pattern")
+        .map(Query::parse)
+        .unwrap() // ensured by clap
+        .map_err_trace_exit_unwrap(1);
+
+    let index = Index::load_refreshed(&default_index_path(rt.rtp()), rt.store())
+        .map_err_trace_exit_unwrap(1);
+
+    let hits : Vec<_> = index
+        .search(&query)
+        .map_err_trace_exit_unwrap(1)
+        .into_iter()
+        .filter(|hit| opts.filter.is_none() || rt
+            .store()
+            .get(hit.id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .map(|entry| opts.accepts(&entry))
+            .unwrap_or(false))
+        .collect();
+
+    if opts.count {
+        let _ = writeln!(rt.stdout(), "{}", hits.len()).to_exit_code().unwrap_or_exit();
+        return;
+    }
+
+    let terms : Vec<String> = query
+        .positive_terms()
+        .into_iter()
+        .filter(|&(ref field, _)| field.is_none())
+        .map(|(_, term)| term)
+        .collect();
+
+    let highlight = Style::new().bold();
+    let colored   = !rt.cli().is_present(Runtime::arg_no_color_output_name());
+
+    for hit in hits {
+        if opts.files_with_matches {
+            let _ = writeln!(rt.stdout(), "{}", hit.id).to_exit_code().unwrap_or_exit();
+            continue;
+        }
+
+        let snippet = rt
+            .store()
+            .get(hit.id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .and_then(|entry| Snippet::new(index.analyzer(), entry.get_content(), &terms, 80))
+            .map(|snippet| if colored {
+                snippet.highlight(&highlight.prefix().to_string(), &highlight.suffix().to_string())
+            } else {
+                snippet.highlight("", "")
+            })
+            .unwrap_or_else(String::new);
+
+        let _ = writeln!(rt.stdout(), "{}:\n {}\n", hit.id, snippet).to_exit_code().unwrap_or_exit();
+    }
+}
@@ -19,6 +19,9 @@
 
 use clap::{Arg, App};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .arg(Arg::with_name("files-with-matches")
@@ -37,11 +40,22 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .multiple(false)
              .help("Count matches"))
 
+        .arg(Arg::with_name("index")
+             .long("index")
+             .short("i")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Use the search index instead of scanning all entries. PATTERN is a search query then (see imag-search)."))
+
+        .arg(where_arg())
+        .arg(saved_arg())
+
         .arg(Arg::with_name("pattern")
              .index(1)
              .takes_value(false)
              .required(true)
              .multiple(false)
              .value_name("PATTERN")
-             .help("Pattern to search for. Regex is supported, multiple patterns are not."))
+             .help("Pattern to search for. Regex is supported, multiple patterns are not. With --index, a search query."))
 }
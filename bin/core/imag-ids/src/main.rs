@@ -34,35 +34,41 @@
 
 extern crate clap;
 extern crate filters;
-#[macro_use] extern crate nom;
 #[macro_use] extern crate log;
-#[macro_use] extern crate is_match;
+extern crate prettytable;
+extern crate serde_json;
 extern crate toml;
 extern crate toml_query;
 
-#[cfg(test)]
-extern crate env_logger;
-
 extern crate libimagerror;
 extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
+extern crate libimagentryfilter;
 
 use std::io::Write;
 use std::process::exit;
 
 use filters::filter::Filter;
 
+use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
+use libimagentryfilter::cli::query_from_matches;
+use libimagentryfilter::saved::SavedQueryStore;
+use libimagentryfilter::saved::SAVED_QUERY_COLLECTION;
+use libimagentryfilter::saved::is_saved_query_id;
 
 mod id_filters;
+mod report;
 mod ui;
 
 use ui::build_ui;
 use id_filters::IsInCollectionsFilter;
+use report::Record;
+use report::Report;
 
 fn main() {
     let version = make_imag_version!();
@@ -71,6 +77,21 @@ fn main() {
                                     "print all ids",
                                     build_ui);
 
+    if rt.cli().is_present("list-saved") {
+        list_saved(&rt);
+        return;
+    }
+
+    if let Some(name) = rt.cli().value_of("delete-saved") {
+        let _ = rt.store().delete_saved_query(name).map_err_trace_exit_unwrap(1);
+        return;
+    }
+
+    if let Some(name) = rt.cli().value_of("save") {
+        let query = rt.cli().value_of("where").unwrap(); // safe by clap
+        let _ = rt.store().save_query(name, query).map_err_trace_exit_unwrap(1);
+    }
+
     let print_storepath = rt.cli().is_present("print-storepath");
 
     let values = rt
@@ -78,16 +99,25 @@ fn main() {
         .values_of("in-collection-filter")
         .map(|v| v.collect::<Vec<&str>>());
 
+    // Saved queries are only listed if their collection is requested explicitly
+    let list_saved_queries = values
+        .as_ref()
+        .map(|v| v.contains(&SAVED_QUERY_COLLECTION))
+        .unwrap_or(false);
+
     let collection_filter = IsInCollectionsFilter::new(values);
-    let query_filter      : Option<id_filters::header_filter_lang::Query> = rt
-        .cli()
-        .subcommand_matches("where")
-        .map(|matches| {
+    let query_filter = match rt.cli().subcommand_matches("where") {
+        Some(matches) => {
             let query = matches.value_of("where-filter").unwrap(); // safe by clap
-            id_filters::header_filter_lang::parse(&query)
-        });
+            Some(::libimagentryfilter::query::Query::parse(query).map_err_trace_exit_unwrap(1))
+        },
+        None => query_from_matches(rt.store(), rt.cli()).map_err_trace_exit_unwrap(1),
+    };
 
-    rt.store()
+    let report = Report::from_matches(rt.cli(), rt.output_format());
+
+    let ids = rt
+        .store()
         .entries()
         .map_err_trace_exit_unwrap(1)
         .trace_unwrap_exit(1)
@@ -100,6 +130,7 @@ fn main() {
             e
         })
         .filter(|id| collection_filter.filter(id))
+        .filter(|id| list_saved_queries || !is_saved_query_id(id))
         .filter(|id| match query_filter.as_ref() {
             None     => true,
             Some(qf) => {
@@ -112,18 +143,44 @@ fn main() {
                         exit(1)
                     });
 
-                qf.filter(&entry)
+                qf.matches(&entry).map_err_trace_exit_unwrap(1)
             }
-        })
-        .map(|id| if print_storepath {
-            id
-        } else {
-            id.without_base()
-        })
-        .for_each(|id| {
-            let _ = writeln!(rt.stdout(), "{}", id.to_str().map_err_trace_exit_unwrap(1))
-                .to_exit_code()
-                .unwrap_or_exit();
-        })
+        });
+
+    if report.is_streamable() {
+        ids.take(report.limit().unwrap_or(::std::usize::MAX))
+            .map(|id| if print_storepath {
+                id
+            } else {
+                id.without_base()
+            })
+            .for_each(|id| {
+                let _ = writeln!(rt.stdout(), "{}", id.to_str().map_err_trace_exit_unwrap(1))
+                    .to_exit_code()
+                    .unwrap_or_exit();
+            })
+    } else {
+        let records = ids
+            .map(|id| Record::new(&rt, id, print_storepath))
+            .collect();
+
+        report.print(&rt, records)
+    }
 }
 
+fn list_saved(rt: &Runtime) {
+    let out      = rt.stdout();
+    let mut lock = out.lock();
+
+    for name in rt.store().all_saved_query_names().map_err_trace_exit_unwrap(1) {
+        let expr = rt
+            .store()
+            .get_saved_query_expression(&name)
+            .map_err_trace_exit_unwrap(1)
+            .unwrap_or_default();
+
+        let _ = writeln!(lock, "{}: {}", name, expr)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+}
@@ -0,0 +1,505 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Sorting, grouping and aggregation of the listed ids
+//!
+//! If any of the reporting arguments is passed, the ids are not streamed to stdout but collected
+//! into `Record`s, which are then sorted, grouped, aggregated and printed in the requested format.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::exit;
+use std::time::SystemTime;
+
+use clap::ArgMatches;
+use prettytable::Table;
+use prettytable::cell::Cell;
+use prettytable::row::Row;
+use serde_json::Map;
+use serde_json::Number;
+use serde_json::Value as JsonValue;
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::output::OutputFormat as RtOutputFormat;
+use libimagrt::output::write_records;
+use libimagstore::storeid::StoreId;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::exit::ExitUnwrap;
+use libimagerror::io::ToExitCode;
+
+/// Key to sort the listed entries by
+pub enum SortKey {
+    Id,
+    Mtime,
+    Header(String),
+}
+
+impl<'a> From<&'a str> for SortKey {
+    fn from(s: &'a str) -> SortKey {
+        match s {
+            "id"    => SortKey::Id,
+            "mtime" => SortKey::Mtime,
+            other   => SortKey::Header(String::from(other)),
+        }
+    }
+}
+
+/// Aggregation function over a group of entries
+pub enum Aggregation {
+    Count,
+    Sum(String),
+    Min(String),
+    Max(String),
+}
+
+impl Aggregation {
+
+    /// Parse "count", "sum:<header.path>", "min:<header.path>" or "max:<header.path>"
+    pub fn parse(s: &str) -> Option<Aggregation> {
+        let mut split = s.splitn(2, ':');
+        match (split.next(), split.next()) {
+            (Some("count"), None)     => Some(Aggregation::Count),
+            (Some("sum"), Some(path)) => Some(Aggregation::Sum(String::from(path))),
+            (Some("min"), Some(path)) => Some(Aggregation::Min(String::from(path))),
+            (Some("max"), Some(path)) => Some(Aggregation::Max(String::from(path))),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match *self {
+            Aggregation::Count       => String::from("count"),
+            Aggregation::Sum(ref p)  => format!("sum({})", p),
+            Aggregation::Min(ref p)  => format!("min({})", p),
+            Aggregation::Max(ref p)  => format!("max({})", p),
+        }
+    }
+
+    /// Apply the aggregation on the records
+    ///
+    /// Header values which are not numeric are ignored.
+    fn apply(&self, records: &[&Record]) -> Option<Value> {
+        let numbers = |path: &str| -> Vec<Value> {
+            records
+                .iter()
+                .filter_map(|r| r.read(path))
+                .filter(|v| is_numeric(v))
+                .collect()
+        };
+
+        match *self {
+            Aggregation::Count => Some(Value::Integer(records.len() as i64)),
+            Aggregation::Sum(ref path) => {
+                let values = numbers(path);
+                if values.iter().all(|v| v.as_integer().is_some()) {
+                    Some(Value::Integer(values.iter().filter_map(Value::as_integer).sum()))
+                } else {
+                    Some(Value::Float(values.iter().filter_map(as_float).sum()))
+                }
+            },
+            Aggregation::Min(ref path) => numbers(path)
+                .into_iter()
+                .min_by(|a, b| compare_values(Some(a), Some(b))),
+            Aggregation::Max(ref path) => numbers(path)
+                .into_iter()
+                .max_by(|a, b| compare_values(Some(a), Some(b))),
+        }
+    }
+
+}
+
+/// The format the report is printed in
+pub enum OutputFormat {
+    Plain,
+    Table,
+    Json,
+    Toml,
+    Csv,
+}
+
+/// An entry which is listed, with everything needed for the report
+pub struct Record {
+    id: String,
+    mtime: Option<SystemTime>,
+    header: Value,
+}
+
+impl Record {
+
+    pub fn new(rt: &Runtime, id: StoreId, print_storepath: bool) -> Record {
+        let mtime = id
+            .clone()
+            .into_pathbuf()
+            .ok()
+            .and_then(|path| path.metadata().ok())
+            .and_then(|meta| meta.modified().ok());
+
+        let header = rt
+            .store()
+            .get(id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .map(|entry| entry.get_header().clone())
+            .unwrap_or_else(|| {
+                error!("Tried to get '{}', but it does not exist!", id);
+                exit(1)
+            });
+
+        let id = if print_storepath { id } else { id.without_base() };
+        let id = id.to_str().map_err_trace_exit_unwrap(1);
+
+        Record { id, mtime, header }
+    }
+
+    fn read(&self, path: &str) -> Option<Value> {
+        self.header
+            .read(path)
+            .map_err_trace_exit_unwrap(1)
+            .cloned()
+    }
+
+}
+
+/// Sorting, grouping, aggregation and output settings, as passed on the commandline
+pub struct Report {
+    sort_by: Option<SortKey>,
+    reverse: bool,
+    group_by: Option<String>,
+    aggregations: Vec<Aggregation>,
+    columns: Vec<String>,
+    limit: Option<usize>,
+    format: OutputFormat,
+}
+
+impl Report {
+
+    /// Create the report settings, `rt_format` is the output format passed to the runtime
+    ///
+    /// `--output-format` takes precedence over the runtime output format.
+    pub fn from_matches(matches: &ArgMatches, rt_format: RtOutputFormat) -> Report {
+        let aggregations = matches
+            .values_of("aggregate")
+            .map(|vals| {
+                vals.map(|v| Aggregation::parse(v).unwrap_or_else(|| {
+                        error!("Invalid aggregation: '{}'", v);
+                        error!("Use one of 'count', 'sum:<path>', 'min:<path>' or 'max:<path>'");
+                        exit(1)
+                    }))
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new);
+
+        let columns = matches
+            .values_of("column")
+            .map(|vals| vals.map(String::from).collect())
+            .unwrap_or_else(Vec::new);
+
+        let limit = matches.value_of("limit").map(|l| l.parse::<usize>().unwrap_or_else(|e| {
+            error!("Cannot parse limit '{}': {}", l, e);
+            exit(1)
+        }));
+
+        let format = match matches.value_of("output-format") {
+            Some("table") => OutputFormat::Table,
+            Some("json")  => OutputFormat::Json,
+            Some("csv")   => OutputFormat::Csv,
+            Some(_)       => OutputFormat::Plain, // safe by clap
+            None          => match rt_format {
+                RtOutputFormat::Json => OutputFormat::Json,
+                RtOutputFormat::Toml => OutputFormat::Toml,
+                RtOutputFormat::Text => {
+                    if columns.is_empty() && aggregations.is_empty() && !matches.is_present("group-by") {
+                        OutputFormat::Plain
+                    } else {
+                        OutputFormat::Table
+                    }
+                },
+            },
+        };
+
+        Report {
+            sort_by: matches.value_of("sort-by").map(SortKey::from),
+            reverse: matches.is_present("reverse"),
+            group_by: matches.value_of("group-by").map(String::from),
+            aggregations,
+            columns,
+            limit,
+            format,
+        }
+    }
+
+    /// Whether the ids can be streamed to stdout directly, without collecting them first
+    pub fn is_streamable(&self) -> bool {
+        match self.format {
+            OutputFormat::Plain => self.sort_by.is_none() &&
+                self.group_by.is_none() &&
+                self.aggregations.is_empty() &&
+                self.columns.is_empty(),
+            _ => false,
+        }
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Sort, group and aggregate the records and print the result
+    pub fn print(&self, rt: &Runtime, mut records: Vec<Record>) {
+        if let Some(ref key) = self.sort_by {
+            records.sort_by(|a, b| match *key {
+                SortKey::Id            => a.id.cmp(&b.id),
+                SortKey::Mtime         => a.mtime.cmp(&b.mtime),
+                SortKey::Header(ref p) => compare_values(a.read(p).as_ref(), b.read(p).as_ref()),
+            });
+        }
+
+        if self.reverse {
+            records.reverse();
+        }
+
+        let (titles, mut rows) = if self.group_by.is_some() || !self.aggregations.is_empty() {
+            self.aggregate(&records)
+        } else {
+            self.list(&records)
+        };
+
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+
+        match self.format {
+            OutputFormat::Plain => print_plain(rt, rows),
+            OutputFormat::Table => print_table(rt, titles, rows),
+            OutputFormat::Json  => print_json(rt, titles, rows),
+            OutputFormat::Toml  => print_toml(rt, titles, rows),
+            OutputFormat::Csv   => print_csv(rt, titles, rows),
+        }
+    }
+
+    fn list(&self, records: &[Record]) -> (Vec<String>, Vec<Vec<Option<Value>>>) {
+        let mut titles = vec![String::from("id")];
+        titles.extend(self.columns.iter().cloned());
+
+        let rows = records
+            .iter()
+            .map(|record| {
+                let mut row = vec![Some(Value::String(record.id.clone()))];
+                row.extend(self.columns.iter().map(|c| record.read(c)));
+                row
+            })
+            .collect();
+
+        (titles, rows)
+    }
+
+    fn aggregate(&self, records: &[Record]) -> (Vec<String>, Vec<Vec<Option<Value>>>) {
+        let count        = [Aggregation::Count];
+        let aggregations = if self.aggregations.is_empty() {
+            &count[..]
+        } else {
+            &self.aggregations[..]
+        };
+
+        let mut titles = Vec::new();
+        if let Some(ref path) = self.group_by {
+            titles.push(path.clone());
+        }
+        titles.extend(aggregations.iter().map(Aggregation::name));
+
+        let aggregate_group = |key: Option<Option<Value>>, group: Vec<&Record>| {
+            let mut row = Vec::new();
+            if let Some(key) = key {
+                row.push(key);
+            }
+            row.extend(aggregations.iter().map(|a| a.apply(&group)));
+            row
+        };
+
+        let rows = match self.group_by {
+            None => vec![aggregate_group(None, records.iter().collect())],
+            Some(ref path) => {
+                // groups are ordered by the rendered group value, entries without the header
+                // value are grouped under the empty string
+                let mut groups : BTreeMap<String, (Option<Value>, Vec<&Record>)> = BTreeMap::new();
+                for record in records {
+                    let value = record.read(path);
+                    let key   = value.as_ref().map(render).unwrap_or_else(String::new);
+                    groups.entry(key).or_insert_with(|| (value, Vec::new())).1.push(record);
+                }
+
+                let mut rows : Vec<_> = groups
+                    .into_iter()
+                    .map(|(_, (value, group))| aggregate_group(Some(value), group))
+                    .collect();
+
+                if self.reverse {
+                    rows.reverse();
+                }
+
+                rows
+            },
+        };
+
+        (titles, rows)
+    }
+
+}
+
+fn is_numeric(v: &Value) -> bool {
+    match *v {
+        Value::Integer(_) | Value::Float(_) => true,
+        _ => false,
+    }
+}
+
+fn as_float(v: &Value) -> Option<f64> {
+    match *v {
+        Value::Integer(i) => Some(i as f64),
+        Value::Float(f)   => Some(f),
+        _ => None,
+    }
+}
+
+/// Compare two header values, missing values are ordered last
+fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => match (a, b) {
+            (&Value::Integer(a), &Value::Integer(b))         => a.cmp(&b),
+            (&Value::Boolean(a), &Value::Boolean(b))         => a.cmp(&b),
+            (&Value::String(ref a), &Value::String(ref b))   => a.cmp(b),
+            _ => match (as_float(a), as_float(b)) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                _                  => render(a).cmp(&render(b)),
+            },
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None)    => Ordering::Equal,
+    }
+}
+
+fn render(v: &Value) -> String {
+    match *v {
+        Value::String(ref s)   => s.clone(),
+        Value::Datetime(ref d) => d.to_string(),
+        Value::Array(ref a)    => a.iter().map(render).collect::<Vec<_>>().join(", "),
+        ref other              => format!("{}", other),
+    }
+}
+
+fn render_cell(v: &Option<Value>) -> String {
+    v.as_ref().map(render).unwrap_or_else(String::new)
+}
+
+fn to_json(v: &Value) -> JsonValue {
+    match *v {
+        Value::String(ref s)   => JsonValue::String(s.clone()),
+        Value::Integer(i)      => JsonValue::Number(Number::from(i)),
+        Value::Float(f)        => Number::from_f64(f).map(JsonValue::Number).unwrap_or(JsonValue::Null),
+        Value::Boolean(b)      => JsonValue::Bool(b),
+        Value::Datetime(ref d) => JsonValue::String(d.to_string()),
+        Value::Array(ref a)    => JsonValue::Array(a.iter().map(to_json).collect()),
+        Value::Table(ref t)    => JsonValue::Object(t.iter().map(|(k, v)| (k.clone(), to_json(v))).collect()),
+    }
+}
+
+fn print_plain(rt: &Runtime, rows: Vec<Vec<Option<Value>>>) {
+    let out      = rt.stdout();
+    let mut lock = out.lock();
+
+    for row in rows {
+        let line = row.iter().map(render_cell).collect::<Vec<_>>().join("\t");
+        let _ = writeln!(lock, "{}", line).to_exit_code().unwrap_or_exit();
+    }
+}
+
+fn print_table(rt: &Runtime, titles: Vec<String>, rows: Vec<Vec<Option<Value>>>) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(titles.iter().map(|s| Cell::new(s)).collect()));
+
+    for row in rows {
+        table.add_row(Row::new(row.iter().map(|c| Cell::new(&render_cell(c))).collect()));
+    }
+
+    let _ = table.print(&mut rt.stdout()).to_exit_code().unwrap_or_exit();
+}
+
+fn print_json(rt: &Runtime, titles: Vec<String>, rows: Vec<Vec<Option<Value>>>) {
+    let objects = rows
+        .into_iter()
+        .map(|row| {
+            let object = titles
+                .iter()
+                .cloned()
+                .zip(row.iter().map(|c| c.as_ref().map(to_json).unwrap_or(JsonValue::Null)))
+                .collect::<Map<String, JsonValue>>();
+
+            JsonValue::Object(object)
+        })
+        .collect();
+
+    let json = ::serde_json::to_string(&JsonValue::Array(objects)).unwrap_or_else(|e| {
+        error!("Error generating JSON: {:?}", e);
+        exit(1)
+    });
+
+    let _ = writeln!(rt.stdout(), "{}", json).to_exit_code().unwrap_or_exit();
+}
+
+fn print_toml(rt: &Runtime, titles: Vec<String>, rows: Vec<Vec<Option<Value>>>) {
+    // TOML has no null, so empty cells are left out
+    let records = rows
+        .into_iter()
+        .map(|row| {
+            titles
+                .iter()
+                .cloned()
+                .zip(row.into_iter())
+                .filter_map(|(title, cell)| cell.map(|c| (title, c)))
+                .collect::<BTreeMap<String, Value>>()
+        })
+        .collect::<Vec<_>>();
+
+    write_records(&mut rt.stdout(), RtOutputFormat::Toml, &records, |_, _| Ok(()))
+        .map_err_trace_exit_unwrap(1);
+}
+
+fn print_csv(rt: &Runtime, titles: Vec<String>, rows: Vec<Vec<Option<Value>>>) {
+    fn csv_line<I: Iterator<Item = String>>(cells: I) -> String {
+        cells
+            .map(|cell| if cell.contains(',') || cell.contains('"') || cell.contains('\n') {
+                format!("\"{}\"", cell.replace("\"", "\"\""))
+            } else {
+                cell
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    let out      = rt.stdout();
+    let mut lock = out.lock();
+
+    let _ = writeln!(lock, "{}", csv_line(titles.into_iter())).to_exit_code().unwrap_or_exit();
+    for row in rows {
+        let line = csv_line(row.iter().map(render_cell));
+        let _ = writeln!(lock, "{}", line).to_exit_code().unwrap_or_exit();
+    }
+}
@@ -19,6 +19,10 @@
 
 use clap::{Arg, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+use libimagentryfilter::query::QUERY_LANGUAGE_DOC;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .arg(Arg::with_name("print-storepath")
@@ -37,7 +41,102 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .value_names(&["COLLECTION"])
              .help("Filter for ids which are only in these collections"))
 
+        .arg(where_arg())
+        .arg(saved_arg())
+
+        .arg(Arg::with_name("save")
+             .long("save")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .requires("where")
+             .value_name("NAME")
+             .help("Save the query passed with --where as NAME, so it can be used with --saved NAME later"))
+
+        .arg(Arg::with_name("list-saved")
+             .long("list-saved")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .conflicts_with_all(&["save", "delete-saved"])
+             .help("List all saved queries and exit"))
+
+        .arg(Arg::with_name("delete-saved")
+             .long("delete-saved")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("NAME")
+             .help("Delete the saved query NAME and exit"))
+
+        .arg(Arg::with_name("sort-by")
+             .long("sort-by")
+             .short("s")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("KEY")
+             .help("Sort by 'id', 'mtime' or a header path, e.g. 'todo.priority'"))
+
+        .arg(Arg::with_name("reverse")
+             .long("reverse")
+             .short("r")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Reverse the order of the output"))
+
+        .arg(Arg::with_name("group-by")
+             .long("group-by")
+             .short("g")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("HEADERPATH")
+             .help("Group by the value of this header path. Prints the number of entries per group unless --aggregate is passed"))
+
+        .arg(Arg::with_name("aggregate")
+             .long("aggregate")
+             .short("a")
+             .takes_value(true)
+             .required(false)
+             .multiple(true)
+             .number_of_values(1)
+             .value_name("AGGREGATION")
+             .help("Aggregate over the entries (per group with --group-by): 'count', 'sum:<path>', 'min:<path>' or 'max:<path>'. Can be passed multiple times"))
+
+        .arg(Arg::with_name("column")
+             .long("column")
+             .short("C")
+             .takes_value(true)
+             .required(false)
+             .multiple(true)
+             .number_of_values(1)
+             .conflicts_with_all(&["group-by", "aggregate"])
+             .value_name("HEADERPATH")
+             .help("Print the value of this header path next to the id. Can be passed multiple times"))
+
+        .arg(Arg::with_name("limit")
+             .long("limit")
+             .short("n")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("N")
+             .help("Print at most N lines"))
+
+        .arg(Arg::with_name("output-format")
+             .long("output-format")
+             .short("o")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .possible_values(&["plain", "table", "json", "csv"])
+             .value_name("FORMAT")
+             .help("Output format. Defaults to 'table' if --column, --group-by or --aggregate is passed, 'plain' otherwise"))
+
         .subcommand(SubCommand::with_name("where")
+                    .about("Filter the ids with a query (same as --where)")
                     .arg(Arg::with_name("where-filter")
                          .index(1)
                          .required(true)
@@ -46,6 +145,6 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .value_names(&["QUERY"])
                          .help("Query the header of the entries and filter them"))
                    )
-        .after_help(include_str!("../static/language-doc.md"))
+        .after_help(QUERY_LANGUAGE_DOC)
 }
 
@@ -33,8 +33,6 @@
 )]
 
 extern crate clap;
-
-#[cfg(test)]
 extern crate toml;
 
 #[macro_use] extern crate libimagrt;
@@ -43,14 +41,21 @@ extern crate libimagerror;
 mod ui;
 
 use std::fs::OpenOptions;
+use std::io::Stdout;
 use std::io::Write;
 use std::path::PathBuf;
+use std::path::Component;
 use std::path::Path;
 use std::process::Command;
 
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagrt::runtime::Runtime;
+use libimagrt::plugin::discover;
+use libimagrt::plugin::plugin_dir;
+use libimagrt::plugin::plugin_info;
+use libimagrt::schema;
+use libimagrt::schema::ConfigKeyInfo;
 
 const CONFIGURATION_STR : &'static str = include_str!("../imagrc.toml");
 
@@ -107,28 +112,40 @@ fn main() {
             .expect("Failed to create directory");
     }
 
+    {
+        let plugin_dir = plugin_dir(&path);
+        println!("Creating {}", plugin_dir.display());
+
+        let _ = ::std::fs::create_dir_all(plugin_dir)
+            .expect("Failed to create directory");
+    }
+
+    let config_keys = check_plugins(&path, &mut out);
+
     let config_path = {
         let mut config_path = path.clone();
         config_path.push("imagrc.toml");
         config_path
     };
 
+    let content = if matches.is_present("devel") {
+        get_config_devel()
+    } else {
+        get_config()
+    };
+
     let _ = OpenOptions::new()
         .write(true)
         .create(true)
         .open(config_path)
         .map(|mut f| {
-            let content = if matches.is_present("devel") {
-                get_config_devel()
-            } else {
-                get_config()
-            };
-
             let _ = f.write_all(content.as_bytes())
                 .expect("Failed to write complete config to file");
         })
         .expect("Failed to open new configuration file");
 
+    check_config(&content, &config_keys, &mut out);
+
     if find_command("git").is_some() && !matches.is_present("nogit") {
         // we initialize a git repository
         let _ = writeln!(out, "Going to initialize a git repository in the imag directory...")
@@ -230,6 +247,66 @@ fn main() {
         .unwrap_or_exit();
 }
 
+/// Ask all imag commands for their plugin info and create the store collections they need
+///
+/// Returns the configuration keys the commands read.
+fn check_plugins(path: &Path, out: &mut Stdout) -> Vec<ConfigKeyInfo> {
+    let commands = discover(path);
+    let mut keys = schema::core_keys();
+
+    for (name, executable) in commands.iter() {
+        match plugin_info(path, name, executable) {
+            Ok(info) => {
+                for collection in info.collections {
+                    if !is_collection_name(&collection) {
+                        let _ = writeln!(out, "Warning: '{}' names an invalid collection: '{}'",
+                                         executable.display(), collection)
+                            .to_exit_code()
+                            .unwrap_or_exit();
+                        continue;
+                    }
+
+                    let _ = ::std::fs::create_dir_all(path.join("store").join(collection))
+                        .expect("Failed to create directory");
+                }
+                keys.extend(info.config);
+            },
+            Err(_) => {
+                let _ = writeln!(out, "Warning: '{}' does not print its plugin info", executable.display())
+                    .to_exit_code()
+                    .unwrap_or_exit();
+            },
+        }
+    }
+
+    let _ = writeln!(out, "Found {} imag commands", commands.len())
+        .to_exit_code()
+        .unwrap_or_exit();
+
+    schema::sorted_keys(keys)
+}
+
+/// Whether `collection` is the name of a single directory in the store, like "diary"
+fn is_collection_name(collection: &str) -> bool {
+    let mut components = Path::new(collection).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => true,
+        _                                  => false,
+    }
+}
+
+/// Check the new configuration against the configuration keys the commands read
+fn check_config(content: &str, keys: &[ConfigKeyInfo], out: &mut Stdout) {
+    let config = ::toml::de::from_str::<::toml::Value>(content)
+        .expect("The default configuration is not valid TOML");
+
+    for issue in schema::validate(&config, keys) {
+        let _ = writeln!(out, "Warning: {}", issue)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+}
+
 fn get_config() -> String {
     get_config_devel()
         .replace(
@@ -262,8 +339,10 @@ fn find_command<P: AsRef<Path>>(exe_name: P) -> Option<PathBuf> {
 mod tests {
     use toml::from_str;
     use toml::Value;
+    use libimagrt::schema;
     use super::get_config;
     use super::get_config_devel;
+    use super::is_collection_name;
 
     #[test]
     fn test_config() {
@@ -271,4 +350,23 @@ mod tests {
         assert!(from_str::<Value>(&get_config_devel()[..]).is_ok());
     }
 
+    #[test]
+    fn test_config_matches_core_keys() {
+        let config = from_str::<Value>(&get_config()[..]).unwrap();
+        assert_eq!(schema::validate(&config, &schema::core_keys()), vec![]);
+    }
+
+    #[test]
+    fn test_is_collection_name() {
+        assert!(is_collection_name("diary"));
+        assert!(is_collection_name("diary/"));
+
+        assert!(!is_collection_name(""));
+        assert!(!is_collection_name("."));
+        assert!(!is_collection_name(".."));
+        assert!(!is_collection_name("../diary"));
+        assert!(!is_collection_name("/etc"));
+        assert!(!is_collection_name("diary/2018"));
+    }
+
 }
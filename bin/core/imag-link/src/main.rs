@@ -36,6 +36,8 @@
 extern crate clap;
 extern crate url;
 #[macro_use] extern crate prettytable;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 #[cfg(test)] extern crate toml;
 #[cfg(test)] extern crate toml_query;
 #[cfg(test)] extern crate env_logger;
@@ -63,7 +65,7 @@ use libimagerror::trace::{MapErrTrace, trace_error};
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagstore::error::StoreError;
 use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::StoreId;
@@ -78,10 +80,11 @@ use ui::build_ui;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-link",
-                                    &version,
-                                    "Link entries",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-link",
+                                                     &version,
+                                                     "Link entries",
+                                                     &["links"],
+                                                     build_ui);
     if rt.cli().is_present("check-consistency") {
         let exit_code = match rt.store().check_link_consistency() {
             Ok(_) => {
@@ -182,6 +185,8 @@ fn link_from_to<'a, I>(rt: &'a Runtime, from: &'a str, to: I)
 
         info!("Ok: {} -> {}", from, entry);
     }
+
+    let _ = rt.report_touched(&from_entry).map_err_trace_exit_unwrap(1);
 }
 
 fn remove_linking(rt: &Runtime) {
@@ -239,29 +244,39 @@ fn remove_linking(rt: &Runtime) {
                 }
             }
         });
+
+    let _ = rt.report_touched(&from).map_err_trace_exit_unwrap(1);
 }
 
 fn unlink(rt: &Runtime) {
     use libimagerror::iter::TraceIterator;
     use libimagstore::iter::get::StoreIdGetIteratorExtension;
 
+    let scmd = rt.cli().subcommand_matches("unlink").unwrap(); // checked in main()
+
     let _ = rt
-        .cli()
-        .subcommand_matches("unlink")
-        .unwrap() // checked in main()
-        .values_of("from")
-        .unwrap() // checked by clap
-        .map(PathBuf::from)
-        .collect::<Vec<PathBuf>>().into_iter() // for lifetime inference
-        .map(StoreId::new_baseless)
+        .ids(scmd, "from", None)
+        .map_err_trace_exit_unwrap(1)
+        .into_iter()
+        .map(Ok::<StoreId, StoreError>)
         .into_get_iter(rt.store())
         .trace_unwrap_exit(1)
         .filter_map(|x| x)
-        .map(|mut entry| entry.unlink(rt.store()))
+        .map(|mut entry| entry.unlink(rt.store()).map(|_| entry))
+        .trace_unwrap_exit(1)
+        .map(|entry| rt.report_touched(&entry))
         .trace_unwrap_exit(1)
         .collect::<Vec<_>>();
 }
 
+/// A link of an entry, as printed by `list` in a structured output format
+#[derive(Serialize)]
+struct LinkRecord {
+    entry: String,
+    link: String,
+    external: bool,
+}
+
 fn list_linkings(rt: &Runtime) {
     let cmd = rt.cli()
         .subcommand_matches("list")
@@ -269,12 +284,17 @@ fn list_linkings(rt: &Runtime) {
 
     let list_externals  = cmd.is_present("list-externals-too");
     let list_plain      = cmd.is_present("list-plain");
+    let structured      = rt.output_format().is_structured();
+    let mut records     = vec![];
 
     let mut tab = ::prettytable::Table::new();
     tab.set_titles(row!["#", "Link"]);
 
-    for entry in cmd.values_of("entries").unwrap() { // safed by clap
-        match rt.store().get(PathBuf::from(entry)) {
+    for id in rt.ids(cmd, "entries", None).map_err_trace_exit_unwrap(1) {
+        let name = id.to_str().map_err_trace_exit_unwrap(1);
+        let name = name.as_str();
+
+        match rt.store().get(id.clone()) {
             Ok(Some(entry)) => {
                 for (i, link) in entry.get_internal_links().map_err_trace_exit_unwrap(1).enumerate() {
                     let link = link
@@ -283,7 +303,13 @@ fn list_linkings(rt: &Runtime) {
                         .ok();
 
                     if let Some(link) = link {
-                        if list_plain {
+                        if structured {
+                            records.push(LinkRecord {
+                                entry: String::from(name),
+                                link: link,
+                                external: false,
+                            });
+                        } else if list_plain {
                             let _ = writeln!(rt.stdout(), "{: <3}: {}", i, link)
                                 .to_exit_code()
                                 .unwrap_or_exit();
@@ -302,7 +328,13 @@ fn list_linkings(rt: &Runtime) {
                                 .map_err_trace_exit_unwrap(1)
                                 .into_string();
 
-                            if list_plain {
+                            if structured {
+                                records.push(LinkRecord {
+                                    entry: String::from(name),
+                                    link: link,
+                                    external: true,
+                                });
+                            } else if list_plain {
                                 let _ = writeln!(rt.stdout(), "{: <3}: {}", i, link)
                                     .to_exit_code()
                                     .unwrap_or_exit();
@@ -312,12 +344,14 @@ fn list_linkings(rt: &Runtime) {
                         })
                 }
             },
-            Ok(None)        => warn!("Not found: {}", entry),
+            Ok(None)        => warn!("Not found: {}", name),
             Err(e)          => trace_error(&e),
         }
     }
 
-    if !list_plain {
+    if structured {
+        rt.output(&records, |_, _| Ok(())).map_err_trace_exit_unwrap(1);
+    } else if !list_plain {
         let out      = rt.stdout();
         let mut lock = out.lock();
         tab.print(&mut lock)
@@ -45,9 +45,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                 .arg(Arg::with_name("from")
                      .index(1)
                      .takes_value(true)
-                     .required(true)
+                     .required(false)
                      .multiple(true)
-                     .help("Remove links from these entries")
+                     .help("Remove links from these entries. Read from stdin if not passed and stdin is a pipe")
                      .value_name("ENTRY"))
                 )
 
@@ -58,8 +58,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                      .index(1)
                      .takes_value(true)
                      .multiple(true)
-                     .required(true)
-                     .help("List these entries, seperate by comma")
+                     .required(false)
+                     .help("List these entries, seperate by comma. Read from stdin if not passed and stdin is a pipe")
                      .value_name("ENTRIES"))
 
                 .arg(Arg::with_name("list-externals-too")
@@ -131,7 +131,11 @@ fn main() {
         .map_err_trace_exit_unwrap(1);
 
     // re-add links to moved entry
-    relink(rt.store(), destname, &mut linked_entries);
+    relink(rt.store(), destname.clone(), &mut linked_entries);
+
+    if let Some(entry) = rt.store().get(destname).map_err_trace_exit_unwrap(1) {
+        let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
+    }
 
     info!("Ok.");
 }
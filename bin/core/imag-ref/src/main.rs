@@ -45,21 +45,20 @@ extern crate libimagutil;
 mod ui;
 use ui::build_ui;
 
-use std::path::PathBuf;
 use std::process::exit;
 
 use libimagerror::trace::MapErrTrace;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagrt::runtime::Runtime;
-use libimagstore::storeid::IntoStoreId;
 use libimagentryref::reference::Ref;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-ref",
-                                    &version,
-                                    "Reference files outside of the store",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-ref",
+                                                     &version,
+                                                     "Reference files outside of the store",
+                                                     &["ref"],
+                                                     build_ui);
     rt.cli()
         .subcommand_name()
         .map(|name| {
@@ -80,29 +79,25 @@ fn main() {
 
 fn deref(rt: &Runtime) {
     let cmd  = rt.cli().subcommand_matches("deref").unwrap();
-    let id   = cmd.value_of("ID")
-        .map(String::from)
-        .map(PathBuf::from)
-        .unwrap() // saved by clap
-        .into_storeid()
-        .map_err_trace_exit_unwrap(1);
 
-    match rt.store().get(id.clone()).map_err_trace_exit_unwrap(1) {
-        Some(entry) => entry
-            .get_path()
-            .map_err_trace_exit_unwrap(1)
-            .to_str()
-            .ok_or_else(|| {
-                error!("Could not transform path into string!");
+    for id in rt.ids(cmd, "ID", None).map_err_trace_exit_unwrap(1) {
+        match rt.store().get(id.clone()).map_err_trace_exit_unwrap(1) {
+            Some(entry) => entry
+                .get_path()
+                .map_err_trace_exit_unwrap(1)
+                .to_str()
+                .ok_or_else(|| {
+                    error!("Could not transform path into string!");
+                    exit(1)
+                })
+                .map(|s| info!("{}", s))
+                .ok(), // safe here because we exited already in the error case
+            None => {
+                error!("No entry for id '{}' found", id);
                 exit(1)
-            })
-            .map(|s| info!("{}", s))
-            .ok(), // safe here because we exited already in the error case
-        None => {
-            error!("No entry for id '{}' found", id);
-            exit(1)
-        },
-    };
+            },
+        };
+    }
 }
 
 fn remove(rt: &Runtime) {
@@ -110,25 +105,28 @@ fn remove(rt: &Runtime) {
 
     let cmd  = rt.cli().subcommand_matches("remove").unwrap();
     let yes  = cmd.is_present("yes");
-    let id   = cmd.value_of("ID")
-        .map(String::from)
-        .map(PathBuf::from)
-        .unwrap() // saved by clap
-        .into_storeid()
-        .map_err_trace_exit_unwrap(1);
+    let ids  = rt.ids(cmd, "ID", None).map_err_trace_exit_unwrap(1);
 
-    match rt.store().get(id.clone()).map_err_trace_exit_unwrap(1) {
-        Some(mut entry) => {
-            if yes || ask_bool(&format!("Delete ref from entry '{}'", id), None) {
-                let _ = entry.remove_ref().map_err_trace_exit_unwrap(1);
-            } else {
-                info!("Aborted");
-            }
-        },
-        None => {
-            error!("No entry for id '{}' found", id);
-            exit(1)
-        },
-    };
-}
+    if !yes && !cmd.is_present("ID") {
+        // stdin is used up by the ids, so there is no way to ask
+        error!("Pass --yes to remove references from entries read from stdin");
+        exit(1)
+    }
 
+    for id in ids {
+        match rt.store().get(id.clone()).map_err_trace_exit_unwrap(1) {
+            Some(mut entry) => {
+                if yes || ask_bool(&format!("Delete ref from entry '{}'", id), None) {
+                    let _ = entry.remove_ref().map_err_trace_exit_unwrap(1);
+                    let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
+                } else {
+                    info!("Aborted");
+                }
+            },
+            None => {
+                error!("No entry for id '{}' found", id);
+                exit(1)
+            },
+        };
+    }
+}
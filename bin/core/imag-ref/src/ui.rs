@@ -27,8 +27,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                     .arg(Arg::with_name("ID")
                          .index(1)
                          .takes_value(true)
-                         .required(true)
-                         .help("The id of the store entry to dereference")
+                         .required(false)
+                         .multiple(true)
+                         .help("The ids of the store entries to dereference. Read from stdin if not passed and stdin is a pipe")
                          .value_name("ID"))
                     )
 
@@ -38,9 +39,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                 .arg(Arg::with_name("ID")
                      .index(1)
                      .takes_value(true)
-                     .required(true)
+                     .required(false)
                      .multiple(true)
-                     .help("Remove the reference from this store entry")
+                     .help("Remove the reference from these store entries. Read from stdin if not passed and stdin is a pipe")
                      .value_name("ENTRIES"))
 
                 .arg(Arg::with_name("yes")
@@ -0,0 +1,141 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+#[macro_use] extern crate log;
+extern crate clap;
+extern crate ansi_term;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagsearch;
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use ansi_term::Style;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::setup::generate_runtime_setup;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::exit::ExitUnwrap;
+use libimagerror::io::ToExitCode;
+use libimagsearch::index::Index;
+use libimagsearch::index::default_index_path;
+use libimagsearch::query::Query;
+use libimagsearch::snippet::Snippet;
+
+mod ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-search",
+                                    &version,
+                                    "Full text search over all entries",
+                                    ui::build_ui);
+
+    let index_path = default_index_path(rt.rtp());
+
+    if rt.cli().is_present("rebuild") {
+        rebuild(&rt, &index_path);
+    }
+
+    let query = match rt.cli().values_of("query") {
+        Some(values) => values.collect::<Vec<_>>().join(" "),
+        None         => return, // only rebuilding
+    };
+
+    let query = Query::parse(&query).map_err_trace_exit_unwrap(1);
+    let index = Index::load_refreshed(&index_path, rt.store()).map_err_trace_exit_unwrap(1);
+    let hits  = index.search(&query).map_err_trace_exit_unwrap(1);
+
+    let limit = rt
+        .cli()
+        .value_of("limit")
+        .map(|l| l.parse::<usize>().unwrap_or_else(|e| {
+            error!("Cannot parse '{}' as number: {}", l, e);
+            ::std::process::exit(1)
+        }))
+        .unwrap_or(hits.len());
+
+    if rt.cli().is_present("count") {
+        let _ = writeln!(rt.stdout(), "{}", hits.len()).to_exit_code().unwrap_or_exit();
+        return;
+    }
+
+    // Only terms from the content can be highlighted in the snippet
+    let terms : Vec<String> = query
+        .positive_terms()
+        .into_iter()
+        .filter(|&(ref field, _)| field.is_none())
+        .map(|(_, term)| term)
+        .collect();
+
+    let style    = Style::new().bold();
+    let colored  = !rt.cli().is_present(Runtime::arg_no_color_output_name());
+    let ids_only = rt.cli().is_present("ids");
+
+    for hit in hits.into_iter().take(limit) {
+        if ids_only {
+            let _ = writeln!(rt.stdout(), "{}", hit.id).to_exit_code().unwrap_or_exit();
+            continue;
+        }
+
+        let _ = writeln!(rt.stdout(), "{} ({:.3})", hit.id, hit.score)
+            .to_exit_code()
+            .unwrap_or_exit();
+
+        let snippet = rt
+            .store()
+            .get(hit.id.clone())
+            .map_err_trace_exit_unwrap(1)
+            .and_then(|entry| Snippet::new(index.analyzer(), entry.get_content(), &terms, 120));
+
+        if let Some(snippet) = snippet {
+            let text = if colored {
+                snippet.highlight(&style.prefix().to_string(), &style.suffix().to_string())
+            } else {
+                snippet.highlight("", "")
+            };
+
+            let _ = writeln!(rt.stdout(), "    {}", text).to_exit_code().unwrap_or_exit();
+        }
+    }
+}
+
+fn rebuild(rt: &Runtime, index_path: &PathBuf) {
+    let mut index = Index::new();
+    let stats     = index.refresh(rt.store()).map_err_trace_exit_unwrap(1);
+    index.save(index_path).map_err_trace_exit_unwrap(1);
+
+    info!("Indexed {} entries", stats.added);
+}
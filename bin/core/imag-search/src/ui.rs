@@ -0,0 +1,62 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+use clap::{Arg, App};
+
+pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app
+        .arg(Arg::with_name("rebuild")
+             .long("rebuild")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Throw away the search index and build it from scratch"))
+
+        .arg(Arg::with_name("ids")
+             .long("ids")
+             .short("l")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Only print the ids of the matching entries"))
+
+        .arg(Arg::with_name("count")
+             .long("count")
+             .short("c")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Only print the number of matching entries"))
+
+        .arg(Arg::with_name("limit")
+             .long("limit")
+             .short("n")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("N")
+             .help("Show only the N best matches"))
+
+        .arg(Arg::with_name("query")
+             .index(1)
+             .takes_value(true)
+             .required_unless("rebuild")
+             .multiple(true)
+             .value_name("QUERY")
+             .help("Search query. Terms are combined with AND, use OR, NOT or -term, (grouping), \"phrases\" and field:term (e.g. tag:work) for more complex queries."))
+}
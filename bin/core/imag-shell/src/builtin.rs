@@ -0,0 +1,191 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Commands which are executed in the shell process, on the store of the session
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use libimagrt::runtime::Runtime;
+use libimagstore::error::Result as StoreResult;
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+use libimagentrytag::tagable::Tagable;
+use libimagerror::trace::trace_error;
+use libimagerror::io::ToExitCode;
+use libimagerror::exit::ExitUnwrap;
+
+use commands::COMMANDS;
+use completion::CandidateCache;
+
+/// Names and help texts of the builtin commands
+pub const BUILTINS : &'static [(&'static str, &'static str)] = &[
+    ("help",        "Show this help"),
+    ("exit",        "Leave the shell (also: quit, CTRL-D)"),
+    ("ids",         "List the ids of all entries, or of the entries in the passed collections"),
+    ("collections", "List all collections"),
+    ("show",        "Print the passed entries"),
+    ("tags",        "List all tags, or the tags of the passed entries"),
+    ("tagged",      "List the ids of the entries which have all of the passed tags"),
+    ("categories",  "List all categories"),
+    ("refresh",     "Forget the cached ids, tags and categories, for example after the store was altered outside of the shell"),
+];
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.iter().any(|&(builtin, _)| builtin == name) || name == "quit"
+}
+
+/// Execute the builtin command `name`
+///
+/// Errors are traced and do not end the session.
+pub fn execute(rt: &Runtime, cache: &CandidateCache, name: &str, args: &[String]) {
+    match name {
+        "help"        => help(rt),
+        "ids"         => ids(rt, cache, args),
+        "collections" => print_all(rt, cache.get().collections.iter()),
+        "show"        => show(rt, args),
+        "tags"        => tags(rt, cache, args),
+        "tagged"      => tagged(rt, cache, args),
+        "categories"  => print_all(rt, cache.get().categories.iter()),
+        "refresh"     => cache.invalidate(),
+        _             => {}, // exit and quit are handled by the session loop
+    }
+}
+
+fn help(rt: &Runtime) {
+    let mut out = rt.stdout();
+    let _ = writeln!(out, "Builtin commands:\n")
+        .to_exit_code()
+        .unwrap_or_exit();
+
+    for &(name, help) in BUILTINS {
+        let _ = writeln!(out, "    {:12} {}", name, help)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+
+    let _ = writeln!(out, "\nCommands which are executed in the shell:\n")
+        .to_exit_code()
+        .unwrap_or_exit();
+
+    for &(name, help) in COMMANDS {
+        let _ = writeln!(out, "    {:12} {}", name, help)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+
+    let _ = writeln!(out, "\nEvery other command is executed as 'imag <command> <args...>'")
+        .to_exit_code()
+        .unwrap_or_exit();
+}
+
+fn print_all<'a, I: Iterator<Item = &'a String>>(rt: &Runtime, iter: I) {
+    let out      = rt.stdout();
+    let mut lock = out.lock();
+
+    for s in iter {
+        let _ = writeln!(lock, "{}", s)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+}
+
+fn ids(rt: &Runtime, cache: &CandidateCache, collections: &[String]) {
+    let candidates = cache.get();
+    let ids        = candidates
+        .ids
+        .iter()
+        .filter(|id| {
+            collections.is_empty() || collections.iter().any(|c| {
+                id.starts_with(c.as_str()) && id[c.len()..].starts_with('/')
+            })
+        });
+
+    print_all(rt, ids)
+}
+
+/// Read the entry `id` without locking it, so it is not written back to the store
+///
+/// Returns None if there is no such entry.
+pub fn read_entry(store: &Store, id: &str) -> StoreResult<Option<Entry>> {
+    let id = StoreId::new(Some(store.path().clone()), PathBuf::from(id))?;
+    if !id.exists()? {
+        return Ok(None)
+    }
+
+    store.get_copy(id).map(Some)
+}
+
+fn show(rt: &Runtime, ids: &[String]) {
+    for id in ids {
+        match read_entry(rt.store(), id) {
+            Ok(Some(entry)) => match entry.to_str() {
+                Ok(s)  => {
+                    let _ = write!(rt.stdout(), "{}", s)
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                },
+                Err(e) => trace_error(&e),
+            },
+            Ok(None) => error!("No entry '{}'", id),
+            Err(e)   => trace_error(&e),
+        }
+    }
+}
+
+fn tags(rt: &Runtime, cache: &CandidateCache, ids: &[String]) {
+    if ids.is_empty() {
+        return print_all(rt, cache.get().tags.iter())
+    }
+
+    for id in ids {
+        match read_entry(rt.store(), id) {
+            Ok(Some(entry)) => match entry.get_tags() {
+                Ok(tags) => {
+                    let _ = writeln!(rt.stdout(), "{}: {}", id, tags.join(", "))
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                },
+                Err(e) => trace_error(&e),
+            },
+            Ok(None) => error!("No entry '{}'", id),
+            Err(e)   => trace_error(&e),
+        }
+    }
+}
+
+fn tagged(rt: &Runtime, cache: &CandidateCache, tags: &[String]) {
+    if tags.is_empty() {
+        error!("No tags passed");
+        return
+    }
+
+    let candidates = cache.get();
+    let out        = rt.stdout();
+    let mut lock   = out.lock();
+
+    for (id, entry_tags) in candidates.entry_tags.iter() {
+        if tags.iter().all(|t| entry_tags.contains(t)) {
+            let _ = writeln!(lock, "{}", id)
+                .to_exit_code()
+                .unwrap_or_exit();
+        }
+    }
+}
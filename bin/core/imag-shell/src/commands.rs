@@ -0,0 +1,368 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! imag commands which are executed in the shell process, on the store of the session
+//!
+//! These work like the imag commands of the same name, but do not have to set up the store again.
+//! Every other command, and every command line which is not implemented here, is executed as
+//! `imag <command>`.
+
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use clap::ErrorKind as ClapErrorKind;
+
+use libimagrt::runtime::Runtime;
+use libimagstore::store::FileLockEntry;
+use libimagstore::storeid::StoreId;
+use libimagentrytag::tag::is_tag;
+use libimagentrytag::tagable::Tagable;
+use libimagentrycategory::entry::EntryCategory;
+use libimagentrylink::internal::InternalLinker;
+use libimagentryedit::edit::Edit;
+use libimagerror::trace::trace_error;
+
+use builtin::read_entry;
+use error::ShellError as SE;
+use error::ShellErrorKind as SEK;
+use error::Result;
+
+/// Names and help texts of the commands
+pub const COMMANDS : &'static [(&'static str, &'static str)] = &[
+    ("tag",      "Add or remove tags of entries"),
+    ("category", "Set or get the category of entries"),
+    ("link",     "Link an entry to other entries"),
+    ("edit",     "Edit entries"),
+];
+
+/// Subcommands of the imag commands which are not implemented in the shell
+const UNIMPLEMENTED_SUBCOMMANDS : &'static [(&'static str, &'static [&'static str])] = &[
+    ("tag",      &["list"]),
+    ("category", &["create-category", "delete-category", "list-categories", "list-category"]),
+    ("link",     &["remove", "unlink", "list"]),
+];
+
+pub fn is_command(name: &str) -> bool {
+    COMMANDS.iter().any(|&(command, _)| command == name)
+}
+
+/// Whether `words` might use a subcommand which is not implemented in the shell
+///
+/// A word which is only the name of such a subcommand by chance does no harm, as the command line
+/// is then executed as `imag <words...>`.
+fn uses_unimplemented_subcommand(words: &[String]) -> bool {
+    let name = match words.first() {
+        Some(name) => name.as_str(),
+        None       => return false,
+    };
+
+    UNIMPLEMENTED_SUBCOMMANDS
+        .iter()
+        .filter(|&&(command, _)| command == name)
+        .any(|&(_, subcommands)| words[1..].iter().any(|word| subcommands.contains(&word.as_str())))
+}
+
+/// Whether `to` is linked externally by `imag link`, which is not implemented in the shell
+fn is_external_link(to: &str) -> bool {
+    to.contains("://") || Path::new(to).exists()
+}
+
+/// Execute the command `words[0]` with the arguments `words[1..]`
+///
+/// Returns false if the command line is not implemented in the shell, e.g. because it uses a
+/// subcommand or an argument the shell does not know. It has to be executed as `imag <words...>`
+/// then, which also reports the errors on the commandline. Other errors are printed and do not
+/// end the session.
+pub fn execute(rt: &Runtime, words: &[String]) -> bool {
+    let name = match words.first() {
+        Some(name) => name.as_str(),
+        None       => return true,
+    };
+
+    if uses_unimplemented_subcommand(words) {
+        debug!("'{}' is not implemented in the shell", words.join(" "));
+        return false
+    }
+
+    let about = COMMANDS
+        .iter()
+        .find(|&&(command, _)| command == name)
+        .map(|&(_, about)| about)
+        .unwrap_or("");
+
+    let app = App::new(name).about(about);
+    let (app, run) : (App, fn(&Runtime, &ArgMatches) -> Result<()>) = match name {
+        "tag"      => (tag_ui(app), tag),
+        "category" => (category_ui(app), category),
+        "link"     => (link_ui(app), link),
+        "edit"     => (edit_ui(app), edit),
+        _          => return false,
+    };
+
+    let matches = match app.get_matches_from_safe(words) {
+        Ok(matches) => matches,
+        Err(e)      => match e.kind {
+            ClapErrorKind::HelpDisplayed | ClapErrorKind::VersionDisplayed => {
+                let _ = writeln!(rt.stdout(), "{}", e.message);
+                return true
+            },
+            _ => {
+                debug!("'{}' is not implemented in the shell: {}", words.join(" "), e.message);
+                return false
+            },
+        },
+    };
+
+    let links_externally = name == "link" &&
+        matches.values_of("to").map(|mut to| to.any(is_external_link)).unwrap_or(false);
+
+    if links_externally {
+        debug!("External links are not implemented in the shell");
+        return false
+    }
+
+    if let Err(e) = run(rt, &matches) {
+        trace_error(&e);
+    }
+
+    true
+}
+
+/// Get the entries with the ids passed in the argument `arg`
+fn entries<'a>(rt: &'a Runtime, matches: &ArgMatches, arg: &str) -> Result<Vec<FileLockEntry<'a>>> {
+    let mut entries = vec![];
+
+    if let Some(ids) = matches.values_of(arg) {
+        for id in ids {
+            let entry = StoreId::new_baseless(PathBuf::from(id))
+                .and_then(|id| rt.store().get(id))?
+                .ok_or_else(|| SE::from_kind(SEK::EntryNotFound(String::from(id))))?;
+
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+fn ids_arg<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name)
+        .takes_value(true)
+        .required(true)
+        .multiple(true)
+        .value_name("ID")
+        .help(help)
+}
+
+fn tags_arg<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name)
+        .index(1)
+        .takes_value(true)
+        .required(true)
+        .multiple(true)
+        .value_name("TAG")
+        .validator(is_tag)
+        .help(help)
+}
+
+fn tag_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app.setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(ids_arg("id", "The entries to tag").index(1))
+        .subcommand(SubCommand::with_name("add")
+                    .about("Add tags")
+                    .arg(tags_arg("add-tags", "Add these tags")))
+        .subcommand(SubCommand::with_name("remove")
+                    .about("Remove tags")
+                    .arg(tags_arg("remove-tags", "Remove these tags")))
+}
+
+fn tag(rt: &Runtime, matches: &ArgMatches) -> Result<()> {
+    let (add, tags) = match matches.subcommand() {
+        ("add", Some(scmd))    => (true, scmd.values_of("add-tags")),
+        ("remove", Some(scmd)) => (false, scmd.values_of("remove-tags")),
+        _                      => return Ok(()), // a subcommand is required by clap
+    };
+    let tags = tags.map(|tags| tags.map(String::from).collect::<Vec<_>>()).unwrap_or_default();
+
+    for mut entry in entries(rt, matches, "id")? {
+        for tag in tags.iter() {
+            if add {
+                entry.add_tag(tag.clone())?;
+            } else {
+                entry.remove_tag(tag.clone())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn category_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app.setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("set")
+                    .about("Set the category of entries")
+                    .arg(Arg::with_name("set-name")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .value_name("NAME")
+                         .help("The name of the category"))
+                    .arg(ids_arg("set-ids", "The entries to set the category for").index(2)))
+        .subcommand(SubCommand::with_name("get")
+                    .about("Get the category of entries")
+                    .arg(ids_arg("get-ids", "The entries to get the category of").index(1)))
+}
+
+fn category(rt: &Runtime, matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        ("set", Some(scmd)) => {
+            let name = scmd.value_of("set-name").unwrap(); // safed by clap
+            for mut entry in entries(rt, scmd, "set-ids")? {
+                entry.set_category_checked(rt.store(), name)?;
+            }
+        },
+
+        ("get", Some(scmd)) => for id in scmd.values_of("get-ids").unwrap() { // safed by clap
+            let entry = read_entry(rt.store(), id)?
+                .ok_or_else(|| SE::from_kind(SEK::EntryNotFound(String::from(id))))?;
+
+            writeln!(rt.stdout(), "{}: {}", id, entry.get_category()?)?;
+        },
+
+        _ => {}, // a subcommand is required by clap
+    }
+
+    Ok(())
+}
+
+fn link_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app.arg(Arg::with_name("from")
+            .index(1)
+            .takes_value(true)
+            .required(true)
+            .value_name("ID")
+            .help("Link from this entry"))
+        .arg(ids_arg("to", "Link to these entries").index(2))
+}
+
+fn link(rt: &Runtime, matches: &ArgMatches) -> Result<()> {
+    let from = matches.value_of("from").unwrap(); // safed by clap
+
+    if let Some(to) = matches.values_of("to").unwrap().find(|&to| to == from) { // safed by clap
+        return Err(SE::from_kind(SEK::SelfLink(String::from(to))))
+    }
+
+    let mut from_entry = StoreId::new_baseless(PathBuf::from(from))
+        .and_then(|id| rt.store().get(id))?
+        .ok_or_else(|| SE::from_kind(SEK::EntryNotFound(String::from(from))))?;
+
+    for mut to_entry in entries(rt, matches, "to")? {
+        from_entry.add_internal_link(&mut to_entry)?;
+    }
+
+    Ok(())
+}
+
+fn edit_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app.arg(ids_arg("entry", "The entries to edit").index(1))
+        .arg(Arg::with_name("edit-header")
+             .long("header")
+             .short("H")
+             .takes_value(false)
+             .required(false)
+             .help("Also edit the header"))
+        .arg(Arg::with_name("edit-header-only")
+             .long("header-only")
+             .takes_value(false)
+             .required(false)
+             .conflicts_with("edit-header")
+             .help("Only edit the header"))
+}
+
+fn edit(rt: &Runtime, matches: &ArgMatches) -> Result<()> {
+    let edit_header      = matches.is_present("edit-header");
+    let edit_header_only = matches.is_present("edit-header-only");
+
+    for mut entry in entries(rt, matches, "entry")? {
+        if edit_header {
+            entry.edit_header_and_content(rt)?;
+        } else if edit_header_only {
+            entry.edit_header(rt)?;
+        } else {
+            entry.edit_content(rt)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_are_no_builtins() {
+        for &(name, _) in COMMANDS {
+            assert!(!::builtin::is_builtin(name), "{} is a builtin", name);
+        }
+    }
+
+    #[test]
+    fn test_command_uis() {
+        let parse = |ui: fn(App<'static, 'static>) -> App<'static, 'static>, args: &[&str]| {
+            ui(App::new(args[0])).get_matches_from_safe(args).is_ok()
+        };
+
+        assert!(parse(tag_ui, &["tag", "notes/a", "notes/b", "add", "foo", "bar"]));
+        assert!(!parse(tag_ui, &["tag", "notes/a", "add", "no tag"]));
+        assert!(parse(category_ui, &["category", "set", "work", "notes/a"]));
+        assert!(parse(category_ui, &["category", "get", "notes/a"]));
+        assert!(parse(link_ui, &["link", "notes/a", "notes/b", "notes/c"]));
+        assert!(!parse(link_ui, &["link", "notes/a"]));
+        assert!(parse(edit_ui, &["edit", "--header", "notes/a"]));
+        assert!(!parse(edit_ui, &["edit", "--header", "--header-only", "notes/a"]));
+        assert!(!parse(edit_ui, &["edit", "--ids-from-stdin"]));
+        assert!(!parse(tag_ui, &["tag", "--where", "tag(\"work\")", "add", "done"]));
+    }
+
+    #[test]
+    fn test_uses_unimplemented_subcommand() {
+        let uses = |words: &[&str]| {
+            let words = words.iter().map(|&w| String::from(w)).collect::<Vec<_>>();
+            uses_unimplemented_subcommand(&words)
+        };
+
+        assert!(uses(&["link", "list", "notes/a"]));
+        assert!(uses(&["link", "remove", "notes/a", "notes/b"]));
+        assert!(uses(&["tag", "notes/a", "list"]));
+        assert!(uses(&["category", "list-categories"]));
+        assert!(!uses(&["link", "notes/a", "notes/b"]));
+        assert!(!uses(&["tag", "notes/a", "add", "foo"]));
+        assert!(!uses(&["category", "get", "notes/a"]));
+        assert!(!uses(&["ids", "list"]));
+    }
+
+    #[test]
+    fn test_is_external_link() {
+        assert!(is_external_link("https://imag-pim.org"));
+        assert!(!is_external_link("notes/a"));
+    }
+}
@@ -0,0 +1,196 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Completion of commands, store ids, tags, categories and collection names
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use libimagstore::store::Store;
+use libimagentrytag::tag::Tag;
+use libimagentrytag::tagable::Tagable;
+use libimagentrycategory::store::CategoryStore;
+use libimaginteraction::readline::Completion;
+use libimagerror::trace::trace_error;
+
+/// The words which can be completed, collected by walking the store once
+#[derive(Default)]
+pub struct Candidates {
+    pub ids: BTreeSet<String>,
+    pub collections: BTreeSet<String>,
+    pub tags: BTreeSet<String>,
+    pub categories: BTreeSet<String>,
+
+    /// The tags of every entry which has tags, by id
+    pub entry_tags: BTreeMap<String, Vec<Tag>>,
+}
+
+impl Candidates {
+
+    /// Walk the store and collect all candidates
+    ///
+    /// The entries are only read, not locked, so walking the store does not write every entry
+    /// back to it. Errors are traced and the entries which caused them skipped, so completion
+    /// still works on a partially broken store.
+    pub fn collect(store: &Store) -> Candidates {
+        let mut candidates = Candidates::default();
+
+        let ids = match store.entries() {
+            Ok(ids) => ids,
+            Err(e)  => {
+                trace_error(&e);
+                return candidates
+            },
+        };
+
+        for id in ids {
+            let id = match id {
+                Ok(id) => id.without_base(),
+                Err(e) => {
+                    trace_error(&e);
+                    continue
+                },
+            };
+
+            if let Some(collection) = id.local().components().next() {
+                let collection = collection.as_os_str().to_string_lossy().into_owned();
+                let _ = candidates.collections.insert(collection);
+            }
+
+            let name = match id.to_str() {
+                Ok(s)  => s,
+                Err(e) => {
+                    trace_error(&e);
+                    continue
+                },
+            };
+
+            match store.get_copy(id) {
+                Ok(entry) => match entry.get_tags() {
+                    Ok(tags) => if !tags.is_empty() {
+                        candidates.tags.extend(tags.iter().cloned());
+                        let _ = candidates.entry_tags.insert(name.clone(), tags);
+                    },
+                    Err(e) => trace_error(&e),
+                },
+                Err(e) => trace_error(&e),
+            }
+
+            let _ = candidates.ids.insert(name);
+        }
+
+        match store.all_category_names() {
+            Ok(names) => for name in names {
+                match name {
+                    Ok(name) => { let _ = candidates.categories.insert(name); },
+                    Err(e)   => trace_error(&e),
+                }
+            },
+            Err(e) => trace_error(&e),
+        }
+
+        candidates
+    }
+
+    fn matching<'a>(&'a self, word: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        self.ids
+            .iter()
+            .chain(self.collections.iter())
+            .chain(self.tags.iter())
+            .chain(self.categories.iter())
+            .filter(move |c| c.starts_with(word))
+    }
+
+}
+
+/// The candidates of the session, collected when they are needed for the first time
+///
+/// The cache is shared between the shell, which invalidates it whenever a command might have
+/// altered the store, and the completion.
+pub struct CandidateCache<'a> {
+    store: &'a Store,
+    candidates: RefCell<Option<Rc<Candidates>>>,
+}
+
+impl<'a> CandidateCache<'a> {
+
+    pub fn new(store: &'a Store) -> CandidateCache<'a> {
+        CandidateCache {
+            store: store,
+            candidates: RefCell::new(None),
+        }
+    }
+
+    pub fn get(&self) -> Rc<Candidates> {
+        let mut candidates = self.candidates.borrow_mut();
+        if candidates.is_none() {
+            *candidates = Some(Rc::new(Candidates::collect(self.store)));
+        }
+
+        candidates.as_ref().cloned().unwrap() // set above
+    }
+
+    pub fn invalidate(&self) {
+        *self.candidates.borrow_mut() = None;
+    }
+
+}
+
+pub struct ShellCompletion<'a> {
+    commands: Vec<String>,
+    cache: Rc<CandidateCache<'a>>,
+}
+
+impl<'a> ShellCompletion<'a> {
+    pub fn new(commands: Vec<String>, cache: Rc<CandidateCache<'a>>) -> ShellCompletion<'a> {
+        ShellCompletion {
+            commands: commands,
+            cache: cache,
+        }
+    }
+}
+
+impl<'a> Completion for ShellCompletion<'a> {
+
+    /// The first word is a command, every other word may be a store id, a collection, a tag or a
+    /// category
+    fn candidates(&self, word: &str, index: usize) -> Vec<String> {
+        if index == 0 {
+            return self.commands
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .cloned()
+                .collect()
+        }
+
+        let candidates = self.cache.get();
+        let mut result = candidates
+            .matching(word)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        result.sort();
+        result.dedup();
+        result
+    }
+
+}
+
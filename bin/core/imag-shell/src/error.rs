@@ -0,0 +1,49 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+error_chain! {
+    types {
+        ShellError, ShellErrorKind, ResultExt, Result;
+    }
+
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+        TagError(::libimagentrytag::error::TagError, ::libimagentrytag::error::TagErrorKind);
+        CategoryError(::libimagentrycategory::error::CategoryError, ::libimagentrycategory::error::CategoryErrorKind);
+        LinkError(::libimagentrylink::error::LinkError, ::libimagentrylink::error::LinkErrorKind);
+        EditError(::libimagentryedit::error::EditError, ::libimagentryedit::error::EditErrorKind);
+    }
+
+    foreign_links {
+        IO(::std::io::Error);
+    }
+
+    errors {
+        EntryNotFound(id: String) {
+            description("Entry not found")
+            display("Entry not found: '{}'", id)
+        }
+
+        SelfLink(id: String) {
+            description("Cannot link an entry with itself")
+            display("Cannot link an entry with itself: '{}'", id)
+        }
+    }
+}
+
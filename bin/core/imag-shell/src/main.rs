@@ -0,0 +1,183 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+extern crate shlex;
+#[macro_use] extern crate log;
+#[macro_use] extern crate error_chain;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagstore;
+extern crate libimaginteraction;
+extern crate libimagentrytag;
+extern crate libimagentrycategory;
+extern crate libimagentrylink;
+extern crate libimagentryedit;
+
+use std::env;
+use std::fs::read_dir;
+use std::process::Command;
+use std::process::Stdio;
+use std::rc::Rc;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::setup::generate_runtime_setup;
+use libimagerror::trace::MapErrTrace;
+use libimaginteraction::readline::Readline;
+
+mod builtin;
+mod commands;
+mod completion;
+mod error;
+mod ui;
+
+use completion::CandidateCache;
+use completion::ShellCompletion;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-shell",
+                                    &version,
+                                    "Interactive shell for imag",
+                                    ui::build_ui);
+
+    let cache        = Rc::new(CandidateCache::new(rt.store()));
+    let completion   = ShellCompletion::new(command_names(), cache.clone());
+    let mut readline = Readline::new(rt.config(), completion).map_err_trace_exit_unwrap(1);
+
+    while let Some(line) = readline.read_line().map_err_trace_exit_unwrap(1) {
+        let words = match ::shlex::split(&line) {
+            Some(words) => words,
+            None        => {
+                error!("Unbalanced quotes in '{}'", line);
+                continue
+            },
+        };
+
+        let (name, args) = match words.split_first() {
+            Some(split) => split,
+            None        => continue,
+        };
+
+        match name.as_str() {
+            "exit" | "quit"                    => break,
+            name if builtin::is_builtin(name)  => builtin::execute(&rt, &cache, name, args),
+            name if commands::is_command(name) => {
+                if !commands::execute(&rt, &words) {
+                    run_command(&rt, &words);
+                    let _ = rt.store().flush_cache().map_err_trace();
+                }
+
+                // The command might have altered the store
+                cache.invalidate();
+            },
+            _                                  => {
+                run_command(&rt, &words);
+
+                // The command might have altered the store
+                let _ = rt.store().flush_cache().map_err_trace();
+                cache.invalidate();
+            },
+        }
+    }
+}
+
+/// All commands which can be completed: the builtins, the commands which are executed in the
+/// shell and the imag-* executables in $PATH
+fn command_names() -> Vec<String> {
+    let mut commands = builtin::BUILTINS
+        .iter()
+        .chain(commands::COMMANDS.iter())
+        .map(|&(name, _)| String::from(name))
+        .collect::<Vec<_>>();
+
+    commands.push(String::from("quit"));
+
+    if let Ok(path) = env::var("PATH") {
+        for dir in path.split(':') {
+            let entries = match read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_)      => continue,
+            };
+
+            for entry in entries.filter_map(Result::ok) {
+                if let Ok(name) = entry.file_name().into_string() {
+                    if name.starts_with("imag-") && name != "imag-shell" {
+                        commands.push(String::from(&name["imag-".len()..]));
+                    }
+                }
+            }
+        }
+    }
+
+    commands.sort();
+    commands.dedup();
+    commands
+}
+
+/// Run `imag <words...>` on the store of the session
+///
+/// This is only done for commands which are not executed in the shell, like the commands of other
+/// imag modules and plugins or command lines the shell does not implement, as it sets up the store
+/// again in the new process.
+fn run_command(rt: &Runtime, words: &[String]) {
+    let mut args = vec![
+        String::from("--rtp"),
+        rt.rtp().display().to_string(),
+        String::from("--store"),
+        rt.store().path().display().to_string(),
+    ];
+
+    if let Some(config) = rt.cli().value_of(Runtime::arg_config_name()) {
+        args.push(String::from("--config"));
+        args.push(String::from(config));
+    }
+
+    args.extend(words.iter().cloned());
+    debug!("Calling 'imag' with args: {:?}", args);
+
+    match Command::new("imag")
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .args(&args)
+        .status()
+    {
+        Ok(status) => if !status.success() {
+            debug!("'imag {}' exited with {:?}", words.join(" "), status);
+        },
+        Err(e) => error!("Failed to call 'imag': {}", e),
+    }
+}
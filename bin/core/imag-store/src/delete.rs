@@ -17,25 +17,21 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use std::path::PathBuf;
-
 use libimagrt::runtime::Runtime;
 use libimagerror::trace::MapErrTrace;
-use libimagstore::storeid::StoreId;
 use libimagutil::warn_result::*;
 
 pub fn delete(rt: &Runtime) {
     let scmd  = rt.cli().subcommand_matches("delete").unwrap();
-    let id    = scmd.value_of("id").unwrap(); // safe by clap
-    let path  = PathBuf::from(id);
-    let store = Some(rt.store().path().clone());
-    let path  = StoreId::new(store, path).map_err_trace_exit_unwrap(1);
-    debug!("Deleting file at {:?}", id);
 
-    let _ = rt.store()
-        .delete(path)
-        .map_warn_err(|e| format!("Error: {:?}", e))
-        .map_err_trace_exit_unwrap(1);
+    for id in rt.ids(scmd, "id", None).map_err_trace_exit_unwrap(1) {
+        debug!("Deleting file at {:?}", id);
+
+        let _ = rt.store()
+            .delete(id)
+            .map_warn_err(|e| format!("Error: {:?}", e))
+            .map_err_trace_exit_unwrap(1);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,78 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+use std::path::PathBuf;
+
+use libimagrt::runtime::Runtime;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::iter::TraceIterator;
+use libimagentryfrontmatter::frontmatter::FrontMatterFormat;
+use libimagentryfrontmatter::export::export_entry_to_dir;
+use libimagentryfrontmatter::import::import_directory;
+use libimagentryfrontmatter::import::import_file;
+
+pub fn import_frontmatter(rt: &Runtime) {
+    let scmd       = rt.cli().subcommand_matches("import-frontmatter").unwrap();
+    let collection = PathBuf::from(scmd.value_of("collection").unwrap()); // safe by clap
+
+    for path in scmd.values_of("path").unwrap().map(PathBuf::from) { // safe by clap
+        if path.is_dir() {
+            for id in import_directory(rt.store(), &collection, &path).map_err_trace_exit_unwrap(1) {
+                info!("Imported {}", id);
+            }
+        } else {
+            let entry = import_file(rt.store(), &collection, &path).map_err_trace_exit_unwrap(1);
+            info!("Imported {}", entry.get_location());
+        }
+    }
+}
+
+pub fn export_frontmatter(rt: &Runtime) {
+    let scmd       = rt.cli().subcommand_matches("export-frontmatter").unwrap();
+    let collection = scmd.value_of("collection").unwrap(); // safe by clap
+    let dir        = PathBuf::from(scmd.value_of("dir").unwrap()); // safe by clap
+    let format     = scmd
+        .value_of("format")
+        .unwrap() // safe by clap, has default value
+        .parse::<FrontMatterFormat>()
+        .unwrap_or_else(|e| {
+            error!("{}", e);
+            ::std::process::exit(1)
+        });
+
+    rt.store()
+        .entries()
+        .map_err_trace_exit_unwrap(1)
+        .trace_unwrap_exit(1)
+        .filter(|id| id.is_in_collection(&[collection]))
+        .for_each(|id| {
+            let entry = rt
+                .store()
+                .get(id.clone())
+                .map_err_trace_exit_unwrap(1)
+                .unwrap_or_else(|| {
+                    error!("Entry {} vanished", id);
+                    ::std::process::exit(1)
+                });
+
+            let path = export_entry_to_dir(&entry, &dir, format).map_err_trace_exit_unwrap(1);
+            info!("Exported {} to {}", id, path.display());
+        });
+}
@@ -41,6 +41,7 @@ extern crate toml;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagerror;
+extern crate libimagentryfrontmatter;
 
 #[cfg(test)]
 #[macro_use]
@@ -55,6 +56,7 @@ use libimagerror::trace::MapErrTrace;
 mod create;
 mod delete;
 mod error;
+mod frontmatter;
 mod get;
 mod retrieve;
 mod ui;
@@ -66,6 +68,8 @@ use std::ops::Deref;
 
 use create::create;
 use delete::delete;
+use frontmatter::export_frontmatter;
+use frontmatter::import_frontmatter;
 use get::get;
 use retrieve::retrieve;
 use ui::build_ui;
@@ -90,6 +94,8 @@ fn main() {
             "retrieve" => retrieve(&rt),
             "update"   => update(&rt),
             "verify"   => verify(&rt),
+            "import-frontmatter" => import_frontmatter(&rt),
+            "export-frontmatter" => export_frontmatter(&rt),
             other      => {
                 debug!("Unknown command");
                 let _ = rt.handle_unknown_subcommand("imag-store", other, rt.cli())
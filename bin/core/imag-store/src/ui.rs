@@ -158,8 +158,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .long("id")
                         .short("i")
                         .takes_value(true)
-                        .required(true)
-                        .help("Update Store Entry with this path. Root (/) is the store itself")
+                        .required(false)
+                        .multiple(true)
+                        .help("Update Store Entry with this path. Root (/) is the store itself. Read from stdin if not passed and stdin is a pipe")
                         .value_name("PATH"))
                    .arg(Arg::with_name("content")
                         .long("content")
@@ -181,8 +182,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("id")
                         .index(1)
                         .takes_value(true)
-                        .required(true)
-                        .help("Remove Store Entry with this path. Root (/) is the store itself")
+                        .required(false)
+                        .multiple(true)
+                        .help("Remove Store Entry with this path. Root (/) is the store itself. Read from stdin if not passed and stdin is a pipe")
                         .value_name("PATH"))
                    )
 
@@ -190,4 +192,50 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .about("Verify the store")
                    .version("0.1")
                    )
+
+       .subcommand(SubCommand::with_name("import-frontmatter")
+                   .about("Import markdown files with YAML or TOML front matter (as used by Jekyll, Hugo, Obsidian) into a collection")
+                   .version("0.1")
+                   .arg(Arg::with_name("collection")
+                        .long("collection")
+                        .short("c")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Import into this collection")
+                        .value_name("COLLECTION"))
+                   .arg(Arg::with_name("path")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(true)
+                        .help("Import these files, or all markdown files in these directories")
+                        .value_name("PATH"))
+                   )
+
+       .subcommand(SubCommand::with_name("export-frontmatter")
+                   .about("Export the entries of a collection to markdown files with front matter")
+                   .version("0.1")
+                   .arg(Arg::with_name("collection")
+                        .long("collection")
+                        .short("c")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Export the entries of this collection")
+                        .value_name("COLLECTION"))
+                   .arg(Arg::with_name("format")
+                        .long("format")
+                        .short("f")
+                        .takes_value(true)
+                        .required(false)
+                        .possible_values(&["yaml", "toml"])
+                        .default_value("yaml")
+                        .help("Format of the front matter")
+                        .value_name("FORMAT"))
+                   .arg(Arg::with_name("dir")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .help("Write the files to this directory")
+                        .value_name("DIR"))
+                   )
 }
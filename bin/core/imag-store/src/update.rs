@@ -18,34 +18,33 @@
 //
 
 use std::ops::DerefMut;
-use std::path::PathBuf;
 
 use libimagrt::runtime::Runtime;
 use libimagerror::trace::MapErrTrace;
-use libimagstore::storeid::StoreId;
 
 use util::build_toml_header;
 
 pub fn update(rt: &Runtime) {
     let scmd  = rt.cli().subcommand_matches("update").unwrap();
-    let id    = scmd.value_of("id").unwrap(); // Safe by clap
-    let path  = PathBuf::from(id);
-    let store = Some(rt.store().path().clone());
-    let path  = StoreId::new(store, path).map_err_trace_exit_unwrap(1);
-
-    let _ = rt.store()
-        .retrieve(path)
-        .map(|mut locked_e| {
-            let e = locked_e.deref_mut();
-
-            scmd.value_of("content")
-                .map(|new_content| {
-                    *e.get_content_mut() = String::from(new_content);
-                    debug!("New content set");
-                });
-
-            *e.get_header_mut() = build_toml_header(scmd, e.get_header().clone());
-            debug!("New header set");
-        });
-}
 
+    for id in rt.ids(scmd, "id", None).map_err_trace_exit_unwrap(1) {
+        let _ = rt.store()
+            .retrieve(id)
+            .map(|mut locked_e| {
+                {
+                    let e = locked_e.deref_mut();
+
+                    scmd.value_of("content")
+                        .map(|new_content| {
+                            *e.get_content_mut() = String::from(new_content);
+                            debug!("New content set");
+                        });
+
+                    *e.get_header_mut() = build_toml_header(scmd, e.get_header().clone());
+                    debug!("New header set");
+                }
+
+                let _ = rt.report_touched(&locked_e).map_err_trace_exit_unwrap(1);
+            });
+    }
+}
@@ -34,6 +34,8 @@
 
 extern crate clap;
 #[macro_use] extern crate log;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 
 #[cfg(test)] extern crate toml;
 
@@ -41,6 +43,7 @@ extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagentrytag;
 extern crate libimagerror;
+extern crate libimagentryfilter;
 
 #[cfg(test)]
 #[macro_use]
@@ -57,10 +60,11 @@ extern crate env_logger;
 
 use std::path::PathBuf;
 use std::io::Write;
-use std::io::Read;
 
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagrt::output::OutputFormat;
+use libimagrt::output::write_records;
 use libimagentrytag::tagable::Tagable;
 use libimagentrytag::tag::Tag;
 use libimagerror::trace::trace_error;
@@ -68,6 +72,7 @@ use libimagerror::trace::MapErrTrace;
 use libimagerror::io::ToExitCode;
 use libimagerror::exit::ExitUnwrap;
 use libimagstore::storeid::StoreId;
+use libimagentryfilter::cli::query_from_matches;
 use libimagutil::warn_exit::warn_exit;
 
 use clap::ArgMatches;
@@ -83,36 +88,43 @@ fn main() {
                                     "Direct interface to the store. Use with great care!",
                                     build_ui);
 
-    let ids : Vec<PathBuf> = rt
-        .cli()
-        .values_of("id")
-        .map(|vals| {
-            vals.map(PathBuf::from).collect()
-        }).unwrap_or_else(|| {
-            if !rt.cli().is_present("ids-from-stdin") {
-                error!("No ids");
-                ::std::process::exit(1)
-            }
-
-            let stdin = rt.stdin().unwrap_or_else(|| {
-                error!("Cannot get handle to stdin");
-                ::std::process::exit(1)
-            });
-
-            let mut buf = String::new();
-            let _ = stdin.lock().read_to_string(&mut buf).unwrap_or_else(|_| {
-                error!("Failed to read from stdin");
-                ::std::process::exit(1)
-            });
+    let query = query_from_matches(rt.store(), rt.cli()).map_err_trace_exit_unwrap(1);
+
+    let ids : Vec<PathBuf> = if let Some(query) = query {
+        query
+            .matching_ids(rt.store())
+            .map_err_trace_exit_unwrap(1)
+            .into_iter()
+            .map(|id| id.local().clone())
+            .collect()
+    } else {
+        rt.cli()
+            .values_of("id")
+            .map(|vals| {
+                vals.map(PathBuf::from).collect()
+            }).unwrap_or_else(|| {
+                if !rt.cli().is_present("ids-from-stdin") && !rt.ids_from_pipe() {
+                    error!("No ids");
+                    ::std::process::exit(1)
+                }
 
-            buf.lines().map(PathBuf::from).collect()
-        });
+                rt.ids_from_stdin()
+                    .map_err_trace_exit_unwrap(1)
+                    .into_iter()
+                    .map(|id| id.local().clone())
+                    .collect()
+            })
+    };
 
     rt.cli()
         .subcommand_name()
         .map(|name| match name {
-            "list" => for id in ids {
-                list(id, &rt)
+            "list" => if rt.output_format().is_structured() || list_json(&rt) {
+                list_structured(ids, &rt)
+            } else {
+                for id in ids {
+                    list(id, &rt)
+                }
             },
             "remove" => for id in ids {
                 let id = PathBuf::from(id);
@@ -172,6 +184,7 @@ fn alter(rt: &Runtime, id: PathBuf, add: Option<Vec<Tag>>, rem: Option<Vec<Tag>>
 
             debug!("Entry header now = {:?}", e.get_header());
 
+            let _ = rt.report_touched(&e).map_err_trace_exit_unwrap(1);
         },
 
         Ok(None) => {
@@ -185,7 +198,14 @@ fn alter(rt: &Runtime, id: PathBuf, add: Option<Vec<Tag>>, rem: Option<Vec<Tag>>
     }
 }
 
-fn list(id: PathBuf, rt: &Runtime) {
+/// The tags of one entry, as printed by `list` in a structured output format
+#[derive(Serialize)]
+struct TagsRecord {
+    id: String,
+    tags: Vec<Tag>,
+}
+
+fn get_tags(id: PathBuf, rt: &Runtime) -> (StoreId, Vec<Tag>) {
     let path = StoreId::new(Some(rt.store().path().clone()), id).map_err_trace_exit_unwrap(1);
     debug!("path = {:?}", path);
 
@@ -194,24 +214,53 @@ fn list(id: PathBuf, rt: &Runtime) {
         None => warn_exit("No entry found.", 1),
     };
 
+    let tags = entry.get_tags().map_err_trace_exit_unwrap(1);
+    (path, tags)
+}
+
+fn list_json(rt: &Runtime) -> bool {
+    rt.cli()
+        .subcommand_matches("list")
+        .map(|scmd| scmd.is_present("json"))
+        .unwrap_or(false)
+}
+
+/// List the tags of all ids at once, `--json` is an alias for `--format json`
+fn list_structured(ids: Vec<PathBuf>, rt: &Runtime) {
+    let format = if rt.output_format().is_structured() {
+        rt.output_format()
+    } else {
+        OutputFormat::Json
+    };
+
+    let records = ids
+        .into_iter()
+        .map(|id| {
+            let (path, tags) = get_tags(id, rt);
+            TagsRecord {
+                id: path.without_base().to_str().map_err_trace_exit_unwrap(1),
+                tags: tags,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    write_records(&mut rt.stdout(), format, &records, |_, _| Ok(())).map_err_trace_exit_unwrap(1);
+}
+
+fn list(id: PathBuf, rt: &Runtime) {
+    let (_, tags) = get_tags(id, rt);
+
     let scmd = rt.cli().subcommand_matches("list").unwrap(); // safe, we checked in main()
 
-    let json_out = scmd.is_present("json");
     let line_out = scmd.is_present("linewise");
     let sepp_out = scmd.is_present("sep");
     let mut comm_out = scmd.is_present("commasep");
 
-    if !vec![json_out, line_out, comm_out, sepp_out].iter().any(|v| *v) {
+    if !vec![line_out, comm_out, sepp_out].iter().any(|v| *v) {
         // None of the flags passed, go to default
         comm_out = true;
     }
 
-    let tags = entry.get_tags().map_err_trace_exit_unwrap(1);
-
-    if json_out {
-        unimplemented!()
-    }
-
     if line_out {
         for tag in &tags {
             let _ = writeln!(rt.stdout(), "{}", tag)
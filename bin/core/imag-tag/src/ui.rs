@@ -20,6 +20,8 @@
 use clap::{Arg, App, ArgGroup, SubCommand};
 
 use libimagentrytag::tag::is_tag;
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app.arg(Arg::with_name("id")
@@ -38,6 +40,13 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                 .multiple(false)
                 .help("Read store ids to tag from stdin"))
 
+        .arg(where_arg()
+                .conflicts_with("id")
+                .conflicts_with("ids-from-stdin"))
+        .arg(saved_arg()
+                .conflicts_with("id")
+                .conflicts_with("ids-from-stdin"))
+
         .subcommand(SubCommand::with_name("add")
                    .about("Add tags")
                    .version("0.1")
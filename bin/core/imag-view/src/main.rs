@@ -40,6 +40,7 @@ extern crate toml;
 extern crate toml_query;
 
 extern crate libimagentryview;
+extern crate libimagentryfilter;
 extern crate libimagerror;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
@@ -48,7 +49,6 @@ extern crate libimagutil;
 use std::str::FromStr;
 use std::collections::BTreeMap;
 use std::io::Write;
-use std::io::Read;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::exit;
@@ -56,7 +56,9 @@ use std::process::exit;
 use handlebars::Handlebars;
 use toml_query::read::TomlValueReadTypeExt;
 
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 use libimagrt::runtime::Runtime;
 use libimagerror::str::ErrFromStr;
 use libimagerror::trace::MapErrTrace;
@@ -67,6 +69,7 @@ use libimagentryview::builtin::stdout::StdoutViewer;
 use libimagentryview::builtin::md::MarkdownViewer;
 use libimagentryview::viewer::Viewer;
 use libimagentryview::error::ViewError as VE;
+use libimagentryfilter::cli::query_from_matches;
 use libimagstore::storeid::IntoStoreId;
 use libimagstore::storeid::StoreIdIterator;
 use libimagstore::error::StoreError;
@@ -76,12 +79,24 @@ use libimagstore::store::FileLockEntry;
 mod ui;
 use ui::build_ui;
 
+/// The configuration keys imag-view reads
+const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "view.viewers.*",
+        kind: ValueType::String,
+        default: None,
+        doc: "The command of the viewer with this name, {{entries}} is replaced with the files",
+    },
+];
+
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup( "imag-view",
-                                     &version,
-                                     "View entries (readonly)",
-                                     build_ui);
+    let rt = generate_runtime_setup_with_config("imag-view",
+                                                &version,
+                                                "View entries (readonly)",
+                                                &[],
+                                                &[CONFIG_KEYS],
+                                                build_ui);
 
     let entry_ids    = entry_ids(&rt);
     let view_header  = rt.cli().is_present("view-header");
@@ -248,6 +263,11 @@ fn main() {
 }
 
 fn entry_ids(rt: &Runtime) -> StoreIdIterator {
+    if let Some(query) = query_from_matches(rt.store(), rt.cli()).map_err_trace_exit_unwrap(1) {
+        let ids = query.matching_ids(rt.store()).map_err_trace_exit_unwrap(1);
+        return StoreIdIterator::new(Box::new(ids.into_iter().map(Ok)));
+    }
+
     match rt.cli().values_of("id") {
         Some(p) => {
             let pathes : Vec<String> = p.map(String::from).collect();
@@ -255,24 +275,11 @@ fn entry_ids(rt: &Runtime) -> StoreIdIterator {
             StoreIdIterator::new(Box::new(iter))
         },
 
-        None => if rt.cli().is_present("entries-from-stdin") {
-            let stdin = rt.stdin().unwrap_or_else(|| {
-                error!("Cannot get handle to stdin");
-                ::std::process::exit(1)
-            });
-
-            let mut buf = String::new();
-            let _ = stdin.lock().read_to_string(&mut buf).unwrap_or_else(|_| {
-                error!("Failed to read from stdin");
-                ::std::process::exit(1)
-            });
-
-            let lines : Vec<String> = buf.lines().map(String::from).collect();
-            let iter  = lines.into_iter().map(PathBuf::from).map(PathBuf::into_storeid);
-
-            StoreIdIterator::new(Box::new(iter))
+        None => if rt.cli().is_present("entries-from-stdin") || rt.ids_from_pipe() {
+            let ids = rt.ids_from_stdin().map_err_trace_exit_unwrap(1);
+            StoreIdIterator::new(Box::new(ids.into_iter().map(Ok)))
         } else {
-            error!("Something weird happened. I was not able to find the path of the entries to edit");
+            error!("No ids passed, neither on the commandline nor on stdin");
             ::std::process::exit(1)
         }
     }
@@ -19,6 +19,9 @@
 
 use clap::{Arg, ArgGroup, App};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .arg(Arg::with_name("entries-from-stdin")
@@ -36,9 +39,11 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .help("View these entries at this store path")
              .value_name("IDs"))
 
+        .arg(where_arg())
+        .arg(saved_arg())
+
         .group(ArgGroup::with_name("input-method")
-               .args(&["id", "entries-from-stdin"])
-               .required(true))
+               .args(&["id", "entries-from-stdin", "where", "saved"]))
 
         .arg(Arg::with_name("autowrap")
             .long("autowrap")
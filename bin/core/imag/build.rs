@@ -21,8 +21,11 @@ extern crate clap;
 #[macro_use]
 extern crate libimagrt;
 extern crate libimagentrytag;
+extern crate libimagentryfilter;
 extern crate libimagutil;
 
+use std::env;
+
 use clap::Shell;
 use libimagrt::runtime::Runtime;
 
@@ -75,6 +78,9 @@ macro_rules! build_subcommand {
 // Actually generates the module.
 gen_mods_buildui!(
     ("../../../bin/core/imag-annotate/src/ui.rs",    imagannotate),
+    ("../../../bin/core/imag-browse/src/ui.rs",      imagbrowse),
+    ("../../../bin/core/imag-category/src/ui.rs",    imagcategory),
+    ("../../../bin/core/imag-config/src/ui.rs",      imagconfig),
     ("../../../bin/core/imag-diagnostics/src/ui.rs", imagdiagnostics),
     ("../../../bin/core/imag-edit/src/ui.rs",        imagedit),
     ("../../../bin/core/imag-git/src/ui.rs",         imaggit),
@@ -85,9 +91,12 @@ gen_mods_buildui!(
     ("../../../bin/core/imag-link/src/ui.rs",        imaglink),
     ("../../../bin/core/imag-mv/src/ui.rs",          imagmv),
     ("../../../bin/core/imag-ref/src/ui.rs",         imagref),
+    ("../../../bin/core/imag-search/src/ui.rs",      imagsearch),
+    ("../../../bin/core/imag-shell/src/ui.rs",       imagshell),
     ("../../../bin/core/imag-store/src/ui.rs",       imagstore),
     ("../../../bin/core/imag-tag/src/ui.rs",         imagtag),
     ("../../../bin/core/imag-view/src/ui.rs",        imagview)
+    ("../../../bin/domain/imag-agenda/src/ui.rs",    imagagenda),
     ("../../../bin/domain/imag-bookmark/src/ui.rs",  imagbookmark),
     ("../../../bin/domain/imag-contact/src/ui.rs",   imagcontact),
     ("../../../bin/domain/imag-diary/src/ui.rs",     imagdiary),
@@ -97,6 +106,7 @@ gen_mods_buildui!(
     ("../../../bin/domain/imag-notes/src/ui.rs",     imagnotes),
     ("../../../bin/domain/imag-timetrack/src/ui.rs", imagtimetrack),
     ("../../../bin/domain/imag-todo/src/ui.rs",      imagtodo),
+    ("../../../bin/domain/imag-wiki/src/ui.rs",      imagwiki),
 );
 
 fn main() {
@@ -108,6 +118,9 @@ fn main() {
         "imag")
         // and add all the subapps as subcommands.
         .subcommand(build_subcommand!("annotate",    imagannotate,      version))
+        .subcommand(build_subcommand!("browse",      imagbrowse,        version))
+        .subcommand(build_subcommand!("category",    imagcategory,      version))
+        .subcommand(build_subcommand!("config",      imagconfig,        version))
         .subcommand(build_subcommand!("diagnostics", imagdiagnostics,   version))
         .subcommand(build_subcommand!("edit",        imagedit,          version))
         .subcommand(build_subcommand!("git",         imaggit,           version))
@@ -118,9 +131,12 @@ fn main() {
         .subcommand(build_subcommand!("link",        imaglink,          version))
         .subcommand(build_subcommand!("mv",          imagmv,            version))
         .subcommand(build_subcommand!("ref",         imagref,           version))
+        .subcommand(build_subcommand!("search",      imagsearch,        version))
+        .subcommand(build_subcommand!("shell",       imagshell,         version))
         .subcommand(build_subcommand!("store",       imagstore,         version))
         .subcommand(build_subcommand!("tag",         imagtag,           version))
         .subcommand(build_subcommand!("view",        imagview,          version))
+        .subcommand(build_subcommand!("agenda",      imagagenda,        version))
         .subcommand(build_subcommand!("bookmark",    imagbookmark,      version))
         .subcommand(build_subcommand!("contact",     imagcontact,       version))
         .subcommand(build_subcommand!("diary",       imagdiary,         version))
@@ -129,13 +145,20 @@ fn main() {
         .subcommand(build_subcommand!("mail",        imagmail,          version))
         .subcommand(build_subcommand!("notes",       imagnotes,         version))
         .subcommand(build_subcommand!("timetrack",   imagtimetrack,     version))
-        .subcommand(build_subcommand!("todo",        imagtodo,          version));
+        .subcommand(build_subcommand!("todo",        imagtodo,          version))
+        .subcommand(build_subcommand!("wiki",        imagwiki,          version));
 
     // Actually generates the completion files
     app.gen_completions("imag", Shell::Bash, "../../../target/");
     app.gen_completions("imag", Shell::Fish, "../../../target/");
     app.gen_completions("imag", Shell::Zsh,  "../../../target/");
 
+    // ... and the ones which are embedded into `imag completions`
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    app.gen_completions("imag", Shell::Bash, &out_dir);
+    app.gen_completions("imag", Shell::Fish, &out_dir);
+    app.gen_completions("imag", Shell::Zsh,  &out_dir);
+
     toplevelbuildscript::build();
 }
 
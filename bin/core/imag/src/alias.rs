@@ -0,0 +1,301 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Aliases from the `imag.aliases` section of the configuration
+//!
+//! There are two kinds of aliases:
+//!
+//! * Simple aliases, which are other names for a command: `store = [ "s", "st" ]` lets `imag s`
+//!   and `imag st` call `imag store`.
+//! * Composite aliases, which expand to one or more command lines:
+//!
+//!   ```toml
+//!   [imag.aliases.standup]
+//!   command = [
+//!       "log --to work \"standup: $@\"",
+//!       "todo list",
+//!   ]
+//!   ```
+//!
+//!   The command lines are split like a shell would do it. `$1`, `$2`, ... are replaced with the
+//!   arguments passed to the alias, `$@` with all of them and `$$` with a `$`. The arguments are
+//!   appended to command lines without placeholders. The commands are called one after another
+//!   and the first failing command aborts the alias.
+
+use std::collections::BTreeMap;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+#[derive(Debug, Default)]
+pub struct Aliases {
+    simple: BTreeMap<String, String>,
+    composite: BTreeMap<String, Vec<String>>,
+}
+
+impl Aliases {
+
+    pub fn from_config(config: Option<&Value>) -> Result<Aliases, String> {
+        let cfg   = config.ok_or_else(|| String::from("No configuration found"))?;
+        let value = cfg
+            .read("imag.aliases")
+            .map_err(|_| String::from("Reading from config failed"));
+
+        let mut aliases = Aliases::default();
+
+        match value? {
+            None                         => {},
+            Some(&Value::Table(ref tbl)) => for (k, v) in tbl {
+                match *v {
+                    Value::String(ref alias) => {
+                        aliases.simple.insert(alias.clone(), k.clone());
+                    },
+                    Value::Array(ref names) => for name in names {
+                        match *name {
+                            Value::String(ref s) => {
+                                aliases.simple.insert(s.clone(), k.clone());
+                            },
+                            _ => {
+                                let e = format!("Not all values are a String in 'imag.aliases.{}'", k);
+                                return Err(e);
+                            }
+                        }
+                    },
+                    Value::Table(ref alias) => {
+                        let commands = composite_commands(k, alias)?;
+                        aliases.composite.insert(k.clone(), commands);
+                    },
+                    _ => {
+                        let msg = format!("Type Error: 'imag.aliases.{}' is not a table, array or string", k);
+                        return Err(msg);
+                    },
+                }
+            },
+            Some(_) => return Err(String::from("Type Error: 'imag.aliases' is not a table")),
+        }
+
+        Ok(aliases)
+    }
+
+    /// The simple aliases, mapping the alias to the command
+    pub fn simple(&self) -> &BTreeMap<String, String> {
+        &self.simple
+    }
+
+    /// The names of all aliases
+    pub fn names<'a>(&'a self) -> impl Iterator<Item = &'a String> + 'a {
+        self.simple.keys().chain(self.composite.keys())
+    }
+
+    /// Resolve `name` to the command lines it stands for, called with `args`
+    ///
+    /// Every command line is the name of the command followed by its arguments. Names which are
+    /// not a composite alias result in one command line, with the simple alias resolved.
+    pub fn expand(&self, name: &str, args: &[String]) -> Result<Vec<Vec<String>>, String> {
+        let lines = match self.composite.get(name) {
+            Some(lines) => lines,
+            None        => {
+                let mut line = vec![self.resolve(name)];
+                line.extend(args.iter().cloned());
+                return Ok(vec![line])
+            },
+        };
+
+        lines
+            .iter()
+            .map(|line| -> Result<Vec<String>, String> {
+                let words = ::shlex::split(line)
+                    .ok_or_else(|| format!("Unbalanced quotes in alias '{}': '{}'", name, line))?;
+
+                let mut expanded = substitute(&words, args)
+                    .map_err(|n| format!("Alias '{}' needs at least {} arguments", name, n))?;
+
+                if expanded.is_empty() {
+                    return Err(format!("Empty command in alias '{}'", name));
+                }
+
+                expanded[0] = self.resolve(&expanded[0]);
+                Ok(expanded)
+            })
+            .collect()
+    }
+
+    /// Resolve the simple alias `name`
+    fn resolve(&self, name: &str) -> String {
+        self.simple.get(name).cloned().unwrap_or_else(|| String::from(name))
+    }
+
+}
+
+/// The command lines of the composite alias `name`
+fn composite_commands(name: &str, alias: &BTreeMap<String, Value>) -> Result<Vec<String>, String> {
+    let type_error = || format!("Type Error: 'imag.aliases.{}.command' is not a string or an array of strings", name);
+
+    match alias.get("command") {
+        Some(&Value::String(ref line)) => Ok(vec![line.clone()]),
+        Some(&Value::Array(ref lines)) => lines
+            .iter()
+            .map(|line| match *line {
+                Value::String(ref line) => Ok(line.clone()),
+                _                       => Err(type_error()),
+            })
+            .collect(),
+        Some(_) => Err(type_error()),
+        None    => Err(format!("'imag.aliases.{}.command' is missing", name)),
+    }
+}
+
+/// Replace the placeholders in `words` with `args`
+///
+/// If there are no placeholders, `args` are appended. On error, the number of arguments which
+/// would have been needed is returned.
+fn substitute(words: &[String], args: &[String]) -> Result<Vec<String>, usize> {
+    let mut result          = vec![];
+    let mut any_placeholder = false;
+
+    for word in words {
+        if word == "$@" {
+            any_placeholder = true;
+            result.extend(args.iter().cloned());
+            continue;
+        }
+
+        let mut expanded = String::new();
+        let mut chars    = word.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                expanded.push(c);
+                continue;
+            }
+
+            match chars.peek().cloned() {
+                Some('$') => {
+                    let _ = chars.next();
+                    expanded.push('$');
+                },
+                Some('@') => {
+                    let _ = chars.next();
+                    any_placeholder = true;
+                    expanded.push_str(&args.join(" "));
+                },
+                Some(d) if d.is_digit(10) => {
+                    let mut number = String::new();
+                    while let Some(d) = chars.peek().cloned() {
+                        if !d.is_digit(10) {
+                            break;
+                        }
+
+                        number.push(d);
+                        let _ = chars.next();
+                    }
+
+                    any_placeholder = true;
+                    let n = number.parse::<usize>().unwrap_or(0); // only digits
+                    match n.checked_sub(1).and_then(|i| args.get(i)) {
+                        Some(arg) => expanded.push_str(arg),
+                        None      => return Err(n),
+                    }
+                },
+                _ => expanded.push('$'),
+            }
+        }
+
+        result.push(expanded);
+    }
+
+    if !any_placeholder {
+        result.extend(args.iter().cloned());
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(s: &str) -> Value {
+        s.parse::<Value>().unwrap()
+    }
+
+    fn strings(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| String::from(*s)).collect()
+    }
+
+    fn aliases() -> Aliases {
+        let config = config(r#"
+            [imag.aliases]
+            store = [ "s", "st" ]
+            tag = "t"
+
+            [imag.aliases.sc]
+            command = "s create"
+
+            [imag.aliases.standup]
+            command = [
+                "log --to work \"standup: $@\"",
+                "t $1 add standup",
+            ]
+        "#);
+
+        Aliases::from_config(Some(&config)).unwrap()
+    }
+
+    #[test]
+    fn test_simple_alias() {
+        let aliases = aliases();
+        assert_eq!(aliases.expand("st", &strings(&["get"])).unwrap(), vec![strings(&["store", "get"])]);
+        assert_eq!(aliases.expand("view", &[]).unwrap(), vec![strings(&["view"])]);
+    }
+
+    #[test]
+    fn test_composite_alias_appends_arguments() {
+        let aliases = aliases();
+        let lines   = aliases.expand("sc", &strings(&["notes/a"])).unwrap();
+        assert_eq!(lines, vec![strings(&["store", "create", "notes/a"])]);
+    }
+
+    #[test]
+    fn test_composite_alias_with_placeholders() {
+        let aliases = aliases();
+        let lines   = aliases.expand("standup", &strings(&["notes/a", "b"])).unwrap();
+
+        assert_eq!(lines, vec![
+            strings(&["log", "--to", "work", "standup: notes/a b"]),
+            strings(&["tag", "notes/a", "add", "standup"]),
+        ]);
+
+        assert!(aliases.expand("standup", &[]).is_err());
+    }
+
+    #[test]
+    fn test_substitute() {
+        let words = strings(&["a", "$@", "x$2y", "$$1", "$"]);
+        let args  = strings(&["1", "2"]);
+        assert_eq!(substitute(&words, &args), Ok(strings(&["a", "1", "2", "x2y", "$1", "$"])));
+        assert_eq!(substitute(&strings(&["$3"]), &args), Err(3));
+    }
+
+    #[test]
+    fn test_invalid_alias() {
+        let config = config("[imag.aliases.x]\nfoo = \"bar\"\n");
+        assert!(Aliases::from_config(Some(&config)).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Completion scripts for the shells and the dynamic candidates they ask `imag __complete` for
+//!
+//! The scripts are generated by clap from the `build_ui` functions of all imag commands when
+//! `imag` is built. They are extended with a function which calls `imag __complete` with the words
+//! on the commandline (the last one being the word which is completed) and which falls back to
+//! the static completion if no candidates are printed.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+use libimagentrytag::tagable::Tagable;
+use libimagentrycategory::store::CategoryStore;
+use libimagdiary::diary::Diary;
+use libimaghabit::store::HabitStore;
+use libimaghabit::habit::HabitTemplate;
+
+const BASH : &str = include_str!(concat!(env!("OUT_DIR"), "/imag.bash"));
+const ZSH  : &str = include_str!(concat!(env!("OUT_DIR"), "/_imag"));
+const FISH : &str = include_str!(concat!(env!("OUT_DIR"), "/imag.fish"));
+
+const BASH_DYNAMIC : &str = r#"
+_imag_dynamic() {
+    local IFS=$'\n'
+    local candidates
+    candidates="$(imag __complete -- "${COMP_WORDS[@]:0:COMP_CWORD+1}" 2>/dev/null)"
+    if [ -n "$candidates" ]; then
+        COMPREPLY=( $(compgen -W "$candidates" -- "${COMP_WORDS[COMP_CWORD]}") )
+    else
+        _imag "$@"
+    fi
+}
+
+complete -F _imag_dynamic -o bashdefault -o default imag
+"#;
+
+const ZSH_DYNAMIC : &str = r#"
+_imag() {
+    local -a candidates
+    candidates=( ${(f)"$(imag __complete -- "${(@)words[1,CURRENT]}" 2>/dev/null)"} )
+    if (( ${#candidates} )); then
+        compadd -a candidates
+    else
+        _imag_static "$@"
+    fi
+}
+
+_imag "$@"
+"#;
+
+const FISH_DYNAMIC : &str = r#"
+complete -c imag -f -a '(imag __complete -- (commandline -opc) (commandline -ct) 2>/dev/null)'
+"#;
+
+/// The completion script for `shell`, one of "bash", "zsh" and "fish"
+pub fn script(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(format!("{}{}", BASH, BASH_DYNAMIC)),
+        "fish" => Some(format!("{}{}", FISH, FISH_DYNAMIC)),
+        "zsh"  => {
+            // zsh calls the function named like the file, so the generated function is renamed
+            // and called from the one which asks for the dynamic candidates
+            let generated = ZSH
+                .replacen("\n_imag() {", "\n_imag_static() {", 1)
+                .replace("\n_imag \"$@\"", "\n");
+            Some(format!("{}{}", generated, ZSH_DYNAMIC))
+        },
+        _      => None,
+    }
+}
+
+/// The things which can be completed from the store
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Commands,
+    Ids,
+    Tags,
+    Categories,
+    Diaries,
+    Wikis,
+    Habits,
+}
+
+/// Options whose value can be completed: (command, option, kind)
+const OPTIONS : &[(&str, &str, Kind)] = &[
+    ("diary",     "--diary",    Kind::Diaries),
+    ("gps",       "--diary",    Kind::Diaries),
+    ("store",     "--id",       Kind::Ids),
+    ("wiki",      "--wiki",     Kind::Wikis),
+];
+
+/// Positional arguments which can be completed: (command, subcommand, position, kind)
+///
+/// The subcommand is empty for arguments of the command itself. The position is the index of the
+/// argument after the (sub)command, `None` matches all arguments.
+const POSITIONALS : &[(&str, &str, Option<usize>, Kind)] = &[
+    ("annotate",  "add",             Some(0), Kind::Ids),
+    ("annotate",  "list",            None,    Kind::Ids),
+    ("annotate",  "remove",          Some(0), Kind::Ids),
+    ("category",  "delete-category", None,    Kind::Categories),
+    ("category",  "get",             None,    Kind::Ids),
+    ("category",  "list-category",   None,    Kind::Categories),
+    ("category",  "set",             Some(0), Kind::Categories),
+    ("category",  "set",             None,    Kind::Ids),
+    ("edit",      "",                None,    Kind::Ids),
+    ("gps",       "add",             None,    Kind::Ids),
+    ("gps",       "get",             None,    Kind::Ids),
+    ("gps",       "remove",          None,    Kind::Ids),
+    ("habit",     "delete",          None,    Kind::Habits),
+    ("habit",     "done",            None,    Kind::Habits),
+    ("habit",     "show",            None,    Kind::Habits),
+    ("link",      "",                None,    Kind::Ids),
+    ("link",      "list",            None,    Kind::Ids),
+    ("link",      "remove",          None,    Kind::Ids),
+    ("link",      "unlink",          None,    Kind::Ids),
+    ("mv",        "",                None,    Kind::Ids),
+    ("ref",       "deref",           None,    Kind::Ids),
+    ("ref",       "remove",          None,    Kind::Ids),
+    ("store",     "get",             None,    Kind::Ids),
+    ("store",     "retrieve",        None,    Kind::Ids),
+    ("tag",       "",                None,    Kind::Ids),
+    ("tag",       "add",             None,    Kind::Tags),
+    ("tag",       "remove",          None,    Kind::Tags),
+    ("view",      "",                None,    Kind::Ids),
+];
+
+/// Find out what the last of `words` is
+///
+/// `words` is the commandline, starting with "imag". `None` means that the word is not completed
+/// dynamically, because it is an option or an argument the store knows nothing about.
+pub fn kind_for(words: &[String], aliases: &BTreeMap<String, String>) -> Option<Kind> {
+    if words.len() <= 2 {
+        return Some(Kind::Commands);
+    }
+
+    let command  = aliases.get(&words[1]).unwrap_or(&words[1]).as_str();
+    let previous = &words[2..words.len() - 1];
+    let current  = &words[words.len() - 1];
+
+    if let Some(option) = previous.last() {
+        let kind = OPTIONS
+            .iter()
+            .find(|&&(cmd, opt, _)| cmd == command && opt == option.as_str())
+            .map(|&(_, _, kind)| kind);
+
+        if kind.is_some() {
+            return kind;
+        }
+    }
+
+    if current.starts_with('-') {
+        return None;
+    }
+
+    let arguments = previous
+        .iter()
+        .filter(|w| !w.starts_with('-'))
+        .map(String::as_str)
+        .collect::<Vec<&str>>();
+
+    let subcommand = arguments
+        .iter()
+        .position(|arg| {
+            POSITIONALS
+                .iter()
+                .any(|&(cmd, scmd, _, _)| cmd == command && !scmd.is_empty() && scmd == *arg)
+        });
+
+    let (subcommand, position) = match subcommand {
+        Some(i) => (arguments[i], arguments.len() - i - 1),
+        None    => ("", arguments.len()),
+    };
+
+    POSITIONALS
+        .iter()
+        .find(|&&(cmd, scmd, pos, _)| {
+            cmd == command && scmd == subcommand && pos.map(|p| p == position).unwrap_or(true)
+        })
+        .map(|&(_, _, _, kind)| kind)
+}
+
+/// The commands `imag` can call (the imag-* executables and the aliases) and its own subcommands
+pub fn command_candidates<'a, I>(names: I) -> Vec<String>
+    where I: Iterator<Item = &'a String>
+{
+    let mut candidates = names
+        .cloned()
+        .chain(vec![String::from("completions"), String::from("help")])
+        .collect::<Vec<String>>();
+
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// The candidates of `kind` from the store
+///
+/// Store ids are completed per collection: if `word` already contains a collection, only the ids
+/// in this collection are listed. Entries are only read, not locked, so completing does not write
+/// them back to the store. Errors are ignored, a broken store simply results in less candidates.
+pub fn store_candidates(store: &Store, kind: Kind, word: &str) -> Vec<String> {
+    let mut candidates = BTreeSet::new();
+
+    match kind {
+        Kind::Commands => {},
+
+        Kind::Ids => {
+            let ids = match word.find('/') {
+                Some(i) => StoreId::new_baseless(PathBuf::from(&word[..i]))
+                    .and_then(|collection| store.entries_below(collection)),
+                None    => store.entries(),
+            };
+
+            for id in ids.into_iter().flat_map(|ids| ids).filter_map(Result::ok) {
+                if let Ok(id) = id.without_base().to_str() {
+                    let _ = candidates.insert(id);
+                }
+            }
+        },
+
+        Kind::Tags => {
+            if let Ok(ids) = store.entries() {
+                for id in ids.filter_map(Result::ok) {
+                    if let Ok(entry) = store.get_copy(id) {
+                        candidates.extend(entry.get_tags().unwrap_or_else(|_| vec![]));
+                    }
+                }
+            }
+        },
+
+        Kind::Categories => {
+            if let Ok(names) = store.all_category_names() {
+                candidates.extend(names.filter_map(Result::ok));
+            }
+        },
+
+        Kind::Diaries => {
+            if let Ok(names) = store.diary_names() {
+                candidates.extend(names.filter_map(Result::ok));
+            }
+        },
+
+        Kind::Wikis => {
+            let wikis = StoreId::new_baseless(PathBuf::from("wiki"))
+                .and_then(|collection| store.entries_below(collection));
+
+            for id in wikis.into_iter().flat_map(|ids| ids).filter_map(Result::ok) {
+                let id = id.without_base();
+                if let Some(name) = id.local().components().nth(1) {
+                    let _ = candidates.insert(name.as_os_str().to_string_lossy().into_owned());
+                }
+            }
+        },
+
+        Kind::Habits => {
+            if let Ok(templates) = store.all_habit_templates() {
+                for id in templates.filter_map(Result::ok) {
+                    if let Ok(entry) = store.get_copy(id) {
+                        candidates.extend(entry.habit_name().ok());
+                    }
+                }
+            }
+        },
+    }
+
+    candidates.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kind(line: &str) -> Option<Kind> {
+        let mut aliases = BTreeMap::new();
+        aliases.insert(String::from("t"), String::from("tag"));
+
+        let mut words = line.split(' ').map(String::from).collect::<Vec<_>>();
+        if line.ends_with(' ') {
+            // The word which is completed is empty
+            words.pop();
+            words.push(String::new());
+        }
+
+        kind_for(&words, &aliases)
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(kind("imag "), Some(Kind::Commands));
+        assert_eq!(kind("imag ta"), Some(Kind::Commands));
+    }
+
+    #[test]
+    fn test_positionals() {
+        assert_eq!(kind("imag view "), Some(Kind::Ids));
+        assert_eq!(kind("imag view --header no"), Some(Kind::Ids));
+        assert_eq!(kind("imag tag notes/a add sh"), Some(Kind::Tags));
+        assert_eq!(kind("imag t notes/a remove "), Some(Kind::Tags));
+        assert_eq!(kind("imag category set "), Some(Kind::Categories));
+        assert_eq!(kind("imag category set work "), Some(Kind::Ids));
+        assert_eq!(kind("imag habit done "), Some(Kind::Habits));
+        assert_eq!(kind("imag annotate "), None);
+    }
+
+    #[test]
+    fn test_options() {
+        assert_eq!(kind("imag diary --diary "), Some(Kind::Diaries));
+        assert_eq!(kind("imag wiki --wiki w"), Some(Kind::Wikis));
+        assert_eq!(kind("imag view --"), None);
+        assert_eq!(kind("imag diary --"), None);
+    }
+}
@@ -34,13 +34,17 @@
 
 extern crate clap;
 #[macro_use] extern crate log;
-extern crate walkdir;
 extern crate toml;
 extern crate toml_query;
-#[macro_use] extern crate is_match;
+extern crate shlex;
 
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagstore;
+extern crate libimagentrytag;
+extern crate libimagentrycategory;
+extern crate libimagdiary;
+extern crate libimaghabit;
 
 use std::env;
 use std::process::exit;
@@ -49,19 +53,28 @@ use std::process::Stdio;
 use std::io::ErrorKind;
 use std::io::{stdout, Stdout, Write};
 use std::collections::BTreeMap;
+use std::path::Path;
 use std::path::PathBuf;
 
-use walkdir::WalkDir;
-use clap::{Arg, ArgMatches, AppSettings, SubCommand};
-use toml::Value;
-use toml_query::read::TomlValueReadExt;
+use clap::{App, Arg, ArgMatches, AppSettings, SubCommand};
+use clap::ErrorKind as ClapErrorKind;
 
-use libimagrt::error::RuntimeErrorKind;
 use libimagrt::runtime::Runtime;
+use libimagrt::runtime::get_layered_config;
+use libimagrt::runtime::get_rtp_match;
+use libimagrt::runtime::get_storepath_match;
 use libimagrt::spec::CliSpec;
+use libimagrt::plugin;
 use libimagerror::io::ToExitCode;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::trace::trace_error;
+use libimagstore::store::Store;
+
+mod alias;
+mod completion;
+
+use alias::Aliases;
+use completion::Kind;
 
 /// Returns the helptext, putting the Strings in cmds as possible
 /// subcommands into it
@@ -106,50 +119,23 @@ fn help_text(cmds: Vec<String>) -> String {
             }))
 }
 
-/// Returns the list of imag-* executables found in $PATH
-fn get_commands(out: &mut Stdout) -> Vec<String> {
-    let mut v = match env::var("PATH") {
-        Err(e) => {
-            let _ = writeln!(out, "PATH error: {:?}", e)
-                .to_exit_code()
-                .unwrap_or_exit();
-            exit(1)
-        },
-
-        Ok(path) => path
-            .split(":")
-            .flat_map(|elem| {
-                WalkDir::new(elem)
-                    .max_depth(1)
-                    .into_iter()
-                    .filter(|path| match *path {
-                        Ok(ref p) => p.file_name().to_str().map_or(false, |f| f.starts_with("imag-")),
-                        Err(_)    => false,
-                    })
-                    .filter_map(Result::ok)
-                    .filter_map(|path| path
-                        .file_name()
-                       .to_str()
-                       .and_then(|s| s.splitn(2, "-").nth(1).map(String::from))
-                    )
-            })
-            .collect::<Vec<String>>()
-    };
-
-    v.sort();
-    v
+/// Returns the commands with their descriptions, as far as the commands tell them
+fn describe_commands(rtp: &Path, commands: &BTreeMap<String, PathBuf>) -> Vec<String> {
+    commands
+        .iter()
+        .map(|(name, executable)| match plugin::plugin_info(rtp, name, executable) {
+            Ok(info) => format!("{:15} {}", name, info.description),
+            Err(e)   => {
+                debug!("No plugin info for {}: {:?}", name, e);
+                name.clone()
+            },
+        })
+        .collect()
 }
 
-
-fn main() {
-    // Initialize the Runtime and build the CLI
-    let appname  = "imag";
-    let version  = make_imag_version!();
-    let about    = "imag - the PIM suite for the commandline";
-    let mut out  = stdout();
-    let commands = get_commands(&mut out);
-    let helptext = help_text(commands.clone());
-    let mut app  = Runtime::get_default_cli_builder(appname, &version, about)
+/// Builds the commandline interface of `imag`, with `helptext` after the generated help
+fn build_app<'a>(version: &'a str, helptext: &'a str) -> App<'a, 'a> {
+    Runtime::get_default_cli_builder("imag", version, "imag - the PIM suite for the commandline")
         .settings(&[AppSettings::AllowExternalSubcommands, AppSettings::ArgRequiredElseHelp])
         .arg(Arg::with_name("version")
              .long("version")
@@ -164,7 +150,54 @@ fn main() {
              .multiple(false)
              .help("Get the versions of the imag commands"))
         .subcommand(SubCommand::with_name("help").help("Show help"))
-        .after_help(helptext.as_str());
+        .subcommand(SubCommand::with_name("completions")
+                    .about("Print the completion script for a shell")
+                    .arg(Arg::with_name("shell")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .possible_values(&["bash", "zsh", "fish"])
+                         .value_name("SHELL")
+                         .help("The shell to print the completion script for")))
+        .subcommand(SubCommand::with_name("__complete")
+                    .setting(AppSettings::Hidden)
+                    .setting(AppSettings::TrailingVarArg)
+                    .about("Print the completion candidates for a commandline, used by the completion scripts")
+                    .arg(Arg::with_name("words")
+                         .index(1)
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(true)
+                         .allow_hyphen_values(true)
+                         .value_name("WORD")))
+        .after_help(helptext)
+}
+
+fn main() {
+    // Initialize the Runtime and build the CLI
+    let version  = make_imag_version!();
+    let mut out  = stdout();
+
+    // The commands are searched in the runtimepath, so the commandline is parsed before the help
+    // text, which lists the commands, can be generated. Asking every command for its description
+    // is only done if the help is shown.
+    let preliminary = build_app(&version, "").get_matches_from_safe(env::args());
+    let wants_help  = match preliminary {
+        Ok(ref m)  => m.subcommand_name() == Some("help"),
+        Err(ref e) => match e.kind {
+            ClapErrorKind::HelpDisplayed | ClapErrorKind::MissingArgumentOrSubcommand => true,
+            _ => false,
+        },
+    };
+    let rtp      = get_rtp_match(&preliminary.unwrap_or_default());
+    let commands = plugin::discover(&rtp);
+    let helptext = if wants_help {
+        help_text(describe_commands(&rtp, &commands))
+    } else {
+        String::new()
+    };
+    let mut app = build_app(&version, &helptext);
 
     let long_help = {
         let mut v = vec![];
@@ -185,25 +218,65 @@ fn main() {
     }
 
     let matches = app.matches();
-    let rtp = ::libimagrt::runtime::get_rtp_match(&matches);
-    let configpath = matches
-        .value_of(Runtime::arg_config_name())
-        .map_or_else(|| rtp.clone(), PathBuf::from);
-    debug!("Config path = {:?}", configpath);
-    let config = match ::libimagrt::configuration::fetch_config(&configpath) {
-            Ok(c) => Some(c),
-            Err(e) => if !is_match!(e.kind(), &RuntimeErrorKind::ConfigNoConfigFileFound) {
-                trace_error(&e);
-                ::std::process::exit(1)
-            } else {
+
+    if let Some(shell) = matches.subcommand_matches("completions").and_then(|m| m.value_of("shell")) {
+        debug!("Printing completions for {}", shell);
+        let script = completion::script(shell).unwrap_or_else(|| exit(1)); // shell is checked by clap
+        let _ = write!(out, "{}", script)
+            .to_exit_code()
+            .unwrap_or_exit();
+        exit(0);
+    }
+
+    let completing = matches.subcommand_name() == Some("__complete");
+    let rtp = get_rtp_match(&matches);
+    let config = match get_layered_config(&matches) {
+        Ok(Some(config)) => Some(config.into_value()),
+        Ok(None)         => {
+            if !completing {
                 println!("No config file found.");
                 println!("Continuing without configuration file");
-                None
-            },
+            }
+            None
+        },
+        Err(e)           => {
+            trace_error(&e);
+            exit(1)
+        },
     };
 
     debug!("matches: {:?}", matches);
 
+    if let Some(scmd) = matches.subcommand_matches("__complete") {
+        let words = scmd
+            .values_of("words")
+            .map(|words| words.map(String::from).collect::<Vec<_>>())
+            .unwrap_or_else(Vec::new);
+        let aliases   = Aliases::from_config(config.as_ref()).unwrap_or_default();
+        let word      = words.last().cloned().unwrap_or_default();
+        let storepath = get_storepath_match(&matches, &rtp);
+
+        let candidates = match completion::kind_for(&words, aliases.simple()) {
+            Some(Kind::Commands) => completion::command_candidates(commands.keys().chain(aliases.names())),
+            Some(kind)           => match Store::new(storepath, &config) {
+                Ok(store) => completion::store_candidates(&store, kind, &word),
+                Err(e)    => {
+                    debug!("Cannot open store for completion: {:?}", e);
+                    vec![]
+                },
+            },
+            None                 => vec![],
+        };
+
+        for candidate in candidates {
+            let _ = writeln!(out, "{}", candidate)
+                .to_exit_code()
+                .unwrap_or_exit();
+        }
+
+        exit(0);
+    }
+
     // Begin checking for arguments
 
     if matches.is_present("version") {
@@ -218,8 +291,8 @@ fn main() {
         debug!("Showing versions");
         commands
             .iter()
-            .map(|command| {
-                match Command::new(format!("imag-{}", command))
+            .map(|(command, executable)| {
+                match Command::new(executable)
                     .stdin(::std::process::Stdio::inherit())
                     .stdout(::std::process::Stdio::piped())
                     .stderr(::std::process::Stdio::inherit())
@@ -244,13 +317,12 @@ fn main() {
         exit(0);
     }
 
-    let aliases = match fetch_aliases(config.as_ref()) {
+    let aliases = match Aliases::from_config(config.as_ref()) {
         Ok(aliases) => aliases,
         Err(e)      => {
-            let _ = writeln!(out, "Error while fetching aliases from configuration file")
+            let _ = writeln!(out, "Error while fetching aliases from configuration file: {}", e)
                 .to_exit_code()
                 .unwrap_or_exit();
-            debug!("Error = {:?}", e);
             let _ = writeln!(out, "Aborting")
                 .to_exit_code()
                 .unwrap_or_exit();
@@ -264,62 +336,26 @@ fn main() {
             // Get all given arguments and further subcommands to pass to
             // the imag-<> binary
             // Providing no arguments is OK, and is therefore ignored here
-            let mut subcommand_args : Vec<String> = match scmd.values_of("") {
+            let subcommand_args : Vec<String> = match scmd.values_of("") {
                 Some(values) => values.map(String::from).collect(),
                 None => Vec::new()
             };
 
-            forward_commandline_arguments(&matches, &mut subcommand_args);
-
-            let subcommand = String::from(subcommand);
-            let subcommand = aliases.get(&subcommand).cloned().unwrap_or(subcommand);
-
-            debug!("Calling 'imag-{}' with args: {:?}", subcommand, subcommand_args);
-
-            // Create a Command, and pass it the gathered arguments
-            match Command::new(format!("imag-{}", subcommand))
-                .stdin(Stdio::inherit())
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .args(&subcommand_args[..])
-                .spawn()
-                .and_then(|mut c| c.wait())
-            {
-                Ok(exit_status) => {
-                    if !exit_status.success() {
-                        debug!("imag-{} exited with non-zero exit code: {:?}", subcommand, exit_status);
-                        eprintln!("imag-{} exited with non-zero exit code", subcommand);
-                        exit(exit_status.code().unwrap_or(1));
-                    }
-                    debug!("Successful exit!");
-                },
-
-                Err(e) => {
-                    debug!("Error calling the subcommand");
-                    match e.kind() {
-                        ErrorKind::NotFound => {
-                            let _ = writeln!(out, "No such command: 'imag-{}'", subcommand)
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            let _ = writeln!(out, "See 'imag --help' for available subcommands")
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            exit(1);
-                        },
-                        ErrorKind::PermissionDenied => {
-                            let _ = writeln!(out, "No permission to execute: 'imag-{}'", subcommand)
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            exit(1);
-                        },
-                        _ => {
-                            let _ = writeln!(out, "Error spawning: {:?}", e)
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            exit(1);
-                        }
-                    }
-                }
+            let lines = aliases
+                .expand(subcommand, &subcommand_args)
+                .unwrap_or_else(|e| {
+                    let _ = writeln!(out, "{}", e)
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                    exit(1)
+                });
+
+            for line in lines {
+                let (subcommand, args) = line.split_first().expect("Aliases expand to non-empty lines");
+                let mut args           = args.to_vec();
+
+                forward_commandline_arguments(&matches, &mut args);
+                run_command(&mut out, &commands, subcommand, &args);
             }
         },
         // Calling for example 'imag --versions' will lead here, as this option does not exit.
@@ -328,47 +364,59 @@ fn main() {
     }
 }
 
-fn fetch_aliases(config: Option<&Value>) -> Result<BTreeMap<String, String>, String> {
-    let cfg   = config.ok_or_else(|| String::from("No configuration found"))?;
-    let value = cfg
-        .read("imag.aliases")
-        .map_err(|_| String::from("Reading from config failed"));
-
-    match value? {
-        None                         => Ok(BTreeMap::new()),
-        Some(&Value::Table(ref tbl)) => {
-            let mut alias_mappings = BTreeMap::new();
-
-            for (k, v) in tbl {
-                match v {
-                    &Value::String(ref alias)      => {
-                        alias_mappings.insert(alias.clone(), k.clone());
-                    },
-                    &Value::Array(ref aliases) => {
-                        for alias in aliases {
-                            match alias {
-                                &Value::String(ref s) => {
-                                    alias_mappings.insert(s.clone(), k.clone());
-                                },
-                                _ => {
-                                    let e = format!("Not all values are a String in 'imag.aliases.{}'", k);
-                                    return Err(e);
-                                }
-                            }
-                        }
-                    },
-
-                    _ => {
-                        let msg = format!("Type Error: 'imag.aliases.{}' is not a table or string", k);
-                        return Err(msg);
-                    },
-                }
+/// Call the command `subcommand` with `args`, exits if the command fails
+fn run_command(out: &mut Stdout, commands: &BTreeMap<String, PathBuf>, subcommand: &str, args: &[String]) {
+    let executable = commands
+        .get(subcommand)
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from(format!("imag-{}", subcommand)));
+
+    debug!("Calling '{}' with args: {:?}", executable.display(), args);
+
+    // Create a Command, and pass it the gathered arguments
+    match Command::new(executable)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .args(args)
+        .spawn()
+        .and_then(|mut c| c.wait())
+    {
+        Ok(exit_status) => {
+            if !exit_status.success() {
+                debug!("imag-{} exited with non-zero exit code: {:?}", subcommand, exit_status);
+                eprintln!("imag-{} exited with non-zero exit code", subcommand);
+                exit(exit_status.code().unwrap_or(1));
             }
-
-            Ok(alias_mappings)
+            debug!("Successful exit!");
         },
 
-        Some(_) => Err(String::from("Type Error: 'imag.aliases' is not a table")),
+        Err(e) => {
+            debug!("Error calling the subcommand");
+            match e.kind() {
+                ErrorKind::NotFound => {
+                    let _ = writeln!(out, "No such command: 'imag-{}'", subcommand)
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                    let _ = writeln!(out, "See 'imag --help' for available subcommands")
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                    exit(1);
+                },
+                ErrorKind::PermissionDenied => {
+                    let _ = writeln!(out, "No permission to execute: 'imag-{}'", subcommand)
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                    exit(1);
+                },
+                _ => {
+                    let _ = writeln!(out, "Error spawning: {:?}", e)
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                    exit(1);
+                }
+            }
+        }
     }
 }
 
@@ -407,7 +455,14 @@ fn forward_commandline_arguments(m: &ArgMatches, scmd: &mut Vec<String>) {
     push(Some("editor"),
          Runtime::arg_editor_name(), m , scmd);
 
+    push(Some("format"),
+         Runtime::arg_output_format_name(), m , scmd);
+
     push(None , Runtime::arg_logdest_name()                         , m , scmd);
 
+    if m.is_present(Runtime::arg_ignore_ids_name()) {
+        scmd.insert(0, String::from("--ignore-ids"));
+    }
+
 }
 
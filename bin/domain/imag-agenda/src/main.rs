@@ -0,0 +1,267 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+#[macro_use] extern crate log;
+extern crate chrono;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagtimeui;
+extern crate libimagentrydatetime;
+extern crate libimagagenda;
+
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::process::exit;
+
+use clap::ArgMatches;
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::NaiveTime;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::setup::generate_runtime_setup_with_config;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::io::ToExitCode;
+use libimagerror::exit::ExitUnwrap;
+use libimagtimeui::natural::parse_datetime;
+use libimagentrydatetime::range::DateTimeRange;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::default_timezone;
+use libimagagenda::collect::agenda;
+use libimagagenda::collect::dated_collections;
+use libimagagenda::error::AgendaError as AE;
+use libimagagenda::ical::export::to_icalendar;
+use libimagagenda::ical::import::import_icalendar;
+use libimagagenda::item::AgendaItem;
+use libimagagenda::item::AgendaItemKind;
+
+mod ui;
+use ui::build_ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup_with_config("imag-agenda",
+                                                &version,
+                                                "Show dated items of all modules chronologically",
+                                                &[],
+                                                &[::libimagentrydatetime::timezone::CONFIG_KEYS,
+                                                  ::libimagagenda::collect::CONFIG_KEYS],
+                                                build_ui);
+
+    let zone = default_timezone(rt.config()).map_err_trace_exit_unwrap(1);
+
+    if let Some(name) = rt.cli().subcommand_name() {
+        debug!("Call {}", name);
+        match name {
+            "export" => export(&rt, zone),
+            "import" => import(&rt, zone),
+            other    => {
+                debug!("Unknown command");
+                let _ = rt.handle_unknown_subcommand("imag-agenda", other, rt.cli())
+                    .map_err_trace_exit_unwrap(1)
+                    .code()
+                    .map(::std::process::exit);
+            },
+        }
+    } else {
+        let items = get_items(&rt, rt.cli(), zone);
+
+        if rt.output_format().is_structured() {
+            let records = items.iter().map(AgendaRecord::from).collect::<Vec<_>>();
+            let _ = rt.output(&records, |_, _| Ok(())).map_err_trace_exit_unwrap(1);
+        } else {
+            print_text(&rt, &items, zone)
+        }
+    }
+}
+
+fn export(rt: &Runtime, zone: Tz) {
+    let scmd  = rt.cli().subcommand_matches("export").unwrap(); // safe by main()
+    let items = get_items(rt, scmd, zone);
+    let ics   = to_icalendar(&items, zone, &ZonedDateTime::now(zone));
+
+    let _ = write!(rt.stdout(), "{}", ics).to_exit_code().unwrap_or_exit();
+}
+
+fn import(rt: &Runtime, zone: Tz) {
+    let scmd = rt.cli().subcommand_matches("import").unwrap(); // safe by main()
+
+    for path in scmd.values_of("import-file").unwrap() { // enforced by clap
+        let mut ics = String::new();
+        let _ = File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut ics))
+            .map_err(AE::from)
+            .map_err_trace_exit_unwrap(1);
+
+        for id in import_icalendar(rt.store(), &ics, zone).map_err_trace_exit_unwrap(1) {
+            let _ = writeln!(rt.stdout(), "{}", id).to_exit_code().unwrap_or_exit();
+        }
+    }
+}
+
+fn get_items(rt: &Runtime, matches: &ArgMatches, zone: Tz) -> Vec<AgendaItem> {
+    let range = get_range(matches, zone);
+    let kinds = match matches.values_of("kind") {
+        Some(values) => values
+            .map(|v| v.parse::<AgendaItemKind>().map_err_trace_exit_unwrap(1))
+            .collect(),
+        None => AgendaItemKind::all(),
+    };
+
+    let collections = dated_collections(rt.config()).map_err_trace_exit_unwrap(1);
+
+    debug!("Collecting {:?} in {:?}", kinds, range);
+    agenda(rt.store(), &range, zone, &kinds, collections.as_ref().map(|c| &c[..]))
+        .map_err_trace_exit_unwrap(1)
+}
+
+fn get_range(matches: &ArgMatches, zone: Tz) -> DateTimeRange {
+    let now   = ZonedDateTime::now(zone).naive_local();
+    let parse = |name: &str| -> Option<NaiveDateTime> {
+        matches.value_of(name).map(|s| {
+            parse_datetime(s, &now).unwrap_or_else(|| {
+                error!("Cannot parse date: '{}'", s);
+                exit(1)
+            })
+        })
+    };
+
+    let (start, end) = if matches.is_present("from") || matches.is_present("to") {
+        let start = parse("from").unwrap_or_else(|| now.date().and_hms(0, 0, 0));
+        let end   = match parse("to") {
+            // A date without time means the whole day
+            Some(end) => if end.time() == NaiveTime::from_hms(0, 0, 0) {
+                end.date().and_hms(23, 59, 59)
+            } else {
+                end
+            },
+            None => start.date().and_hms(23, 59, 59),
+        };
+
+        (start, end)
+    } else {
+        let date          = parse("date").map(|dt| dt.date()).unwrap_or_else(|| now.date());
+        let (first, last) = if matches.is_present("week") {
+            let first = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            (first, first + Duration::days(6))
+        } else if matches.is_present("month") {
+            let first = NaiveDate::from_ymd(date.year(), date.month(), 1);
+            let next  = if date.month() == 12 {
+                NaiveDate::from_ymd(date.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd(date.year(), date.month() + 1, 1)
+            };
+
+            (first, next.pred())
+        } else {
+            (date, date)
+        };
+
+        (first.and_hms(0, 0, 0), last.and_hms(23, 59, 59))
+    };
+
+    DateTimeRange::new(start, end).map_err_trace_exit_unwrap(1)
+}
+
+fn print_text(rt: &Runtime, items: &[AgendaItem], zone: Tz) {
+    let mut day = None;
+
+    for item in items {
+        let start = item.start().in_zone(zone).naive_local();
+
+        if day != Some(start.date()) {
+            day = Some(start.date());
+            let _ = writeln!(rt.stdout(), "{}", start.format("%A, %Y-%m-%d"))
+                .to_exit_code()
+                .unwrap_or_exit();
+        }
+
+        let time = if item.is_all_day() {
+            String::from("all day")
+        } else {
+            match item.end() {
+                Some(end) => {
+                    let end = end.in_zone(zone).naive_local();
+                    if end.date() == start.date() {
+                        format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+                    } else {
+                        format!("{}-{}", start.format("%H:%M"), end.format("%Y-%m-%d %H:%M"))
+                    }
+                },
+                None => start.format("%H:%M").to_string(),
+            }
+        };
+
+        let _ = writeln!(rt.stdout(), "  {time: <11} {kind: <11} {title}",
+                         time  = time,
+                         kind  = item.kind(),
+                         title = item.title())
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+}
+
+/// An item of the agenda, as printed with `--format json` or `--format toml`
+///
+/// Dates are in RFC 3339 format.
+#[derive(Serialize)]
+struct AgendaRecord {
+    kind: String,
+    start: String,
+    end: Option<String>,
+    all_day: bool,
+    title: String,
+    id: String,
+}
+
+impl<'a> From<&'a AgendaItem> for AgendaRecord {
+    fn from(item: &'a AgendaItem) -> AgendaRecord {
+        AgendaRecord {
+            kind: item.kind().to_string(),
+            start: item.start().to_rfc3339(),
+            end: item.end().map(|e| e.to_rfc3339()),
+            all_day: item.is_all_day(),
+            title: item.title().clone(),
+            id: item.id().local().display().to_string(),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use clap::{Arg, App, SubCommand};
+
+pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app
+        .args(&range_args())
+
+        .subcommand(SubCommand::with_name("export")
+                   .about("Export the agenda as iCalendar (.ics) to stdout")
+                   .version("0.1")
+                   .args(&range_args()))
+
+        .subcommand(SubCommand::with_name("import")
+                   .about("Import events and todos from iCalendar (.ics) files")
+                   .version("0.1")
+                   .arg(Arg::with_name("import-file")
+                        .index(1)
+                        .takes_value(true)
+                        .multiple(true)
+                        .required(true)
+                        .help("The .ics files to import")
+                        .value_name("FILE")))
+}
+
+/// Arguments to select the range of the agenda and the kinds of items
+fn range_args<'a>() -> Vec<Arg<'a, 'a>> {
+    vec![
+        Arg::with_name("day")
+             .long("day")
+             .short("d")
+             .takes_value(false)
+             .required(false)
+             .conflicts_with_all(&["week", "month"])
+             .help("Show the agenda for a day (default)"),
+
+        Arg::with_name("week")
+             .long("week")
+             .short("w")
+             .takes_value(false)
+             .required(false)
+             .conflicts_with_all(&["day", "month"])
+             .help("Show the agenda for a week (monday to sunday)"),
+
+        Arg::with_name("month")
+             .long("month")
+             .short("m")
+             .takes_value(false)
+             .required(false)
+             .conflicts_with_all(&["day", "week"])
+             .help("Show the agenda for a month"),
+
+        Arg::with_name("date")
+             .index(1)
+             .takes_value(true)
+             .required(false)
+             .help("Show the day, week or month of this date, for example \"2018-05-14\" or \"next monday\". Defaults to today.")
+             .value_name("DATE"),
+
+        Arg::with_name("from")
+             .long("from")
+             .short("f")
+             .takes_value(true)
+             .required(false)
+             .conflicts_with_all(&["day", "week", "month", "date"])
+             .help("Show the agenda starting at this date. Defaults to today.")
+             .value_name("DATE"),
+
+        Arg::with_name("to")
+             .long("to")
+             .short("t")
+             .takes_value(true)
+             .required(false)
+             .conflicts_with_all(&["day", "week", "month", "date"])
+             .help("Show the agenda until this date (inclusive). Defaults to the end of the start day.")
+             .value_name("DATE"),
+
+        Arg::with_name("kind")
+             .long("kind")
+             .short("k")
+             .takes_value(true)
+             .multiple(true)
+             .required(false)
+             .possible_values(&["diary", "habit", "timetrack", "todo", "birthday", "anniversary", "date"])
+             .help("Only show items of this kind. Multiple possible. Defaults to all kinds.")
+             .value_name("KIND"),
+    ]
+}
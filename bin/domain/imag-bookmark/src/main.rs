@@ -48,7 +48,9 @@ use std::process::exit;
 use toml_query::read::TomlValueReadTypeExt;
 
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 use libimagbookmark::collection::BookmarkCollection;
 use libimagbookmark::collection::BookmarkCollectionStore;
 use libimagbookmark::error::BookmarkError as BE;
@@ -61,12 +63,24 @@ mod ui;
 
 use ui::build_ui;
 
+/// The configuration keys imag-bookmark reads
+const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "bookmark.default_collection",
+        kind: ValueType::String,
+        default: None,
+        doc: "The collection which is used if none is passed",
+    },
+];
+
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-bookmark",
-                                    &version,
-                                    "Bookmark collection tool",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_config("imag-bookmark",
+                                                &version,
+                                                "Bookmark collection tool",
+                                                &["bookmark"],
+                                                &[CONFIG_KEYS],
+                                                build_ui);
 
     rt.cli()
         .subcommand_name()
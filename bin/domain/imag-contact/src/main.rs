@@ -49,6 +49,7 @@ extern crate libimagerror;
 extern crate libimagutil;
 extern crate libimaginteraction;
 extern crate libimagentryedit;
+extern crate libimagentryfilter;
 
 use std::process::exit;
 use std::path::PathBuf;
@@ -60,7 +61,9 @@ use toml_query::read::TomlValueReadTypeExt;
 use walkdir::WalkDir;
 
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 use libimagerror::str::ErrFromStr;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::io::ToExitCode;
@@ -71,6 +74,7 @@ use libimagcontact::error::ContactError as CE;
 use libimagcontact::contact::Contact;
 use libimagcontact::deser::DeserVcard;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
+use libimagentryfilter::cli::query_from_matches;
 
 mod ui;
 mod util;
@@ -80,12 +84,30 @@ use ui::build_ui;
 use util::build_data_object_for_handlebars;
 use create::create;
 
+/// The configuration keys imag-contact reads
+const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "contact.list_format",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for listing contacts",
+    },
+    ConfigKey {
+        path: "contact.show_format",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for showing a contact",
+    },
+];
+
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-contact",
-                                    &version,
-                                    "Contact management tool",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_config("imag-contact",
+                                                &version,
+                                                "Contact management tool",
+                                                &["contact"],
+                                                &[CONFIG_KEYS],
+                                                build_ui);
 
 
     rt.cli()
@@ -112,6 +134,7 @@ fn main() {
 fn list(rt: &Runtime) {
     let scmd        = rt.cli().subcommand_matches("list").unwrap();
     let list_format = get_contact_print_format("contact.list_format", rt, &scmd);
+    let query       = query_from_matches(rt.store(), scmd).map_err_trace_exit_unwrap(1);
 
     let iterator = rt
         .store()
@@ -124,8 +147,13 @@ fn list(rt: &Runtime) {
                 .ok_or_else(|| CE::from("StoreId not found".to_owned()))
                 .map_err_trace_exit_unwrap(1);
 
-            fle.deser().map_err_trace_exit_unwrap(1)
+            fle
+        })
+        .filter(|fle| match query {
+            Some(ref q) => q.matches(&fle).map_err_trace_exit_unwrap(1),
+            None        => true,
         })
+        .map(|fle| fle.deser().map_err_trace_exit_unwrap(1))
         .enumerate();
 
     if scmd.is_present("json") {
@@ -19,11 +19,16 @@
 
 use clap::{Arg, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .subcommand(SubCommand::with_name("list")
                    .about("List contacts")
                    .version("0.1")
+                   .arg(where_arg())
+                   .arg(saved_arg())
                    .arg(Arg::with_name("filter")
                         .index(1)
                         .takes_value(true)
@@ -19,18 +19,22 @@
 
 use clap::ArgMatches;
 use chrono::NaiveDateTime;
-use chrono::Local;
 use chrono::Timelike;
 
 use libimagdiary::diary::Diary;
 use libimagdiary::error::DiaryErrorKind as DEK;
 use libimagdiary::error::ResultExt;
+use libimagdiary::error::Result;
 use libimagentryedit::edit::Edit;
+use libimagentrymarkdown::section::MarkdownSections;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::default_timezone;
 use libimagrt::runtime::Runtime;
 use libimagerror::trace::MapErrTrace;
 use libimagutil::warn_exit::warn_exit;
 use libimagstore::store::FileLockEntry;
 use libimagstore::store::Store;
+use libimagtimeui::natural::parse_datetime;
 
 use util::get_diary_name;
 use util::get_diary_timed_config;
@@ -41,8 +45,12 @@ pub fn create(rt: &Runtime) {
         .unwrap_or_else( || warn_exit("No diary selected. Use either the configuration file or the commandline option", 1));
 
     let mut entry = create_entry(rt.store(), &diaryname, rt);
+    let create    = rt.cli().subcommand_matches("create").unwrap();
 
-    let res = if rt.cli().subcommand_matches("create").unwrap().is_present("no-edit") {
+    let res = if let Some(section) = create.value_of("section") {
+        debug!("Editing section '{}' of diary entry", section);
+        edit_section(rt, &mut entry, section, create)
+    } else if create.is_present("no-edit") {
         debug!("Not editing new diary entry");
         Ok(())
     } else {
@@ -52,13 +60,51 @@ pub fn create(rt: &Runtime) {
     };
 
     let _ = res.map_err_trace_exit_unwrap(1);
+    let _ = rt.report_touched(&entry).map_err_trace_exit_unwrap(1);
     info!("Ok!");
 }
 
+fn edit_section(rt: &Runtime, entry: &mut FileLockEntry, section: &str, create: &ArgMatches)
+    -> Result<()>
+{
+    if let Some(text) = create.value_of("text") {
+        entry.append_to_section(section, text);
+        return Ok(())
+    }
+
+    if create.is_present("no-edit") {
+        if entry.get_section(section).is_none() {
+            entry.append_to_section(section, "");
+        }
+        return Ok(())
+    }
+
+    match entry.get_section(section) {
+        Some(mut body) => {
+            let _ = body.edit_content(rt).chain_err(|| DEK::DiaryEditError)?;
+            entry.replace_section(section, &body).chain_err(|| DEK::DiaryEditError)
+        },
+        None => {
+            let mut body = String::new();
+            let _        = body.edit_content(rt).chain_err(|| DEK::DiaryEditError)?;
+            entry.append_to_section(section, &body);
+            Ok(())
+        },
+    }
+}
+
 fn create_entry<'a>(diary: &'a Store, diaryname: &str, rt: &Runtime) -> FileLockEntry<'a> {
     use util::parse_timed_string;
 
     let create = rt.cli().subcommand_matches("create").unwrap();
+    let tz     = default_timezone(rt.config()).map_err_trace_exit_unwrap(1);
+    let date   = create.value_of("date").map(|d| {
+        let now = ZonedDateTime::now(tz).naive_local();
+        parse_datetime(d, &now).unwrap_or_else(|| {
+            error!("Cannot parse date: '{}'", d);
+            ::std::process::exit(1)
+        })
+    });
 
     create.value_of("timed")
         .map(|t| parse_timed_string(t, diaryname).map_err_trace_exit_unwrap(1))
@@ -74,12 +120,17 @@ fn create_entry<'a>(diary: &'a Store, diaryname: &str, rt: &Runtime) -> FileLock
             }
         })
         .map(|timed| {
-            let time = create_id_from_clispec(&create, &diaryname, timed);
-            diary.new_entry_at(&diaryname, &time).chain_err(|| DEK::StoreWriteError)
+            let ndt  = date.unwrap_or_else(|| ZonedDateTime::now(tz).naive_local());
+            let time = create_id_from_clispec(&create, &diaryname, timed, ndt);
+            let time = ZonedDateTime::from_local(&time, tz).map_err_trace_exit_unwrap(1);
+            diary.new_entry_at_zoned(&diaryname, &time).chain_err(|| DEK::StoreWriteError)
         })
         .unwrap_or_else(|| {
             debug!("Creating non-timed entry");
-            diary.new_entry_today(diaryname)
+            match date {
+                Some(ndt) => diary.new_entry_at(diaryname, &ndt.date().and_hms(0, 0, 0)),
+                None      => diary.new_entry_today(diaryname),
+            }
         })
         .map(|e| {
             debug!("Created: {}", e.get_location());
@@ -89,12 +140,11 @@ fn create_entry<'a>(diary: &'a Store, diaryname: &str, rt: &Runtime) -> FileLock
 }
 
 
-fn create_id_from_clispec(create: &ArgMatches, diaryname: &str, timed_type: Timed) -> NaiveDateTime {
+fn create_id_from_clispec(create: &ArgMatches, diaryname: &str, timed_type: Timed, ndt: NaiveDateTime)
+    -> NaiveDateTime
+{
     use std::str::FromStr;
 
-    let dt  = Local::now();
-    let ndt = dt.naive_local();
-
     match timed_type {
         Timed::Daily => {
             debug!("Creating daily-timed entry");
@@ -19,18 +19,27 @@
 
 use std::io::Write;
 
+use chrono::naive::MAX_DATE;
+use chrono::naive::MIN_DATE;
+use chrono::naive::NaiveDateTime;
+use chrono::naive::NaiveTime;
+
 use libimagdiary::diary::Diary;
 use libimagrt::runtime::Runtime;
 use libimagutil::warn_exit::warn_exit;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
-use libimagerror::io::ToExitCode;
-use libimagerror::exit::ExitUnwrap;
 use libimagutil::debug_result::*;
 use libimagdiary::diaryid::DiaryId;
 use libimagdiary::diaryid::FromStoreId;
 use libimagdiary::error::Result;
+use libimagdiary::iter::DiaryEntryIterator;
+use libimagentrydatetime::range::DateTimeRange;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::default_timezone;
+use libimagtimeui::natural::parse_datetime;
 use libimagstore::storeid::IntoStoreId;
+use libimagentryfilter::cli::query_from_matches;
 
 use util::get_diary_name;
 
@@ -38,7 +47,13 @@ pub fn list(rt: &Runtime) {
     let diaryname = get_diary_name(rt)
         .unwrap_or_else(|| warn_exit("No diary selected. Use either the configuration file or the commandline option", 1));
 
-    let mut ids = Diary::entries(rt.store(), &diaryname)
+    let query = rt
+        .cli()
+        .subcommand_matches("list")
+        .map(|scmd| query_from_matches(rt.store(), scmd).map_err_trace_exit_unwrap(1))
+        .and_then(|q| q);
+
+    let mut ids = entries(rt, &diaryname)
         .map_dbg_str("Ok")
         .map_err_trace_exit_unwrap(1)
         .trace_unwrap_exit(1)
@@ -50,10 +65,85 @@ pub fn list(rt: &Runtime) {
         [id.year() as u32, id.month(), id.day(), id.hour(), id.minute(), id.second()]
     });
 
-    for id in ids.into_iter().map(|id| id.into_storeid().map_err_trace_exit_unwrap(1)) {
-        writeln!(rt.stdout(), "{}", id)
-            .to_exit_code()
-            .unwrap_or_exit();
+    let mut records = vec![];
+    for diary_id in ids {
+        let id = diary_id.clone().into_storeid().map_err_trace_exit_unwrap(1);
+
+        if let Some(ref query) = query {
+            let matches = rt
+                .store()
+                .get(id.clone())
+                .map_err_trace_exit_unwrap(1)
+                .map(|entry| query.matches(&entry).map_err_trace_exit_unwrap(1))
+                .unwrap_or(false);
+
+            if !matches {
+                continue;
+            }
+        }
+
+        records.push(DiaryEntryRecord {
+            id: format!("{}", id),
+            diary: diary_id.diary_name().clone(),
+            date: format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                          diary_id.year(),
+                          diary_id.month(),
+                          diary_id.day(),
+                          diary_id.hour(),
+                          diary_id.minute(),
+                          diary_id.second()),
+        });
     }
+
+    rt.output(&records, |out, record| writeln!(out, "{}", record.id))
+        .map_err_trace_exit_unwrap(1);
+}
+
+/// A diary entry, as printed by `list`
+#[derive(Serialize)]
+struct DiaryEntryRecord {
+    id: String,
+    diary: String,
+    date: String,
 }
 
+/// Get the entries of the diary, in the range passed with --from/--to if any
+///
+/// If both --from and --to are passed, only the relevant parts of the store are traversed.
+fn entries(rt: &Runtime, diaryname: &str) -> Result<DiaryEntryIterator> {
+    let scmd = match rt.cli().subcommand_matches("list") {
+        Some(scmd) => scmd,
+        None       => return Diary::entries(rt.store(), diaryname),
+    };
+
+    let now      = ZonedDateTime::now(default_timezone(rt.config()).map_err_trace_exit_unwrap(1)).naive_local();
+    let get_date = |name: &str| scmd.value_of(name).map(|s| {
+        parse_datetime(s, &now).unwrap_or_else(|| {
+            error!("Cannot parse date: '{}'", s);
+            ::std::process::exit(1)
+        })
+    });
+
+    let from = get_date("from");
+    let to   = get_date("to").map(|to| {
+        // A date without time means the whole day
+        if to.time() == NaiveTime::from_hms(0, 0, 0) {
+            to.date().and_hms(23, 59, 59)
+        } else {
+            to
+        }
+    });
+
+    let range = |from: NaiveDateTime, to: NaiveDateTime| DateTimeRange::new(from, to)
+        .map_err_trace_exit_unwrap(1);
+
+    match (from, to) {
+        (None, None)           => Diary::entries(rt.store(), diaryname),
+        (Some(from), Some(to)) => Diary::entries_in_range(rt.store(), diaryname, &range(from, to)),
+        (from, to)             => {
+            let from = from.unwrap_or_else(|| MIN_DATE.and_hms(0, 0, 0));
+            let to   = to.unwrap_or_else(|| MAX_DATE.and_hms(23, 59, 59));
+            Diary::entries(rt.store(), diaryname).map(|iter| iter.range(range(from, to)))
+        },
+    }
+}
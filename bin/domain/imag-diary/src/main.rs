@@ -38,9 +38,14 @@ extern crate chrono;
 extern crate toml;
 extern crate toml_query;
 extern crate itertools;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 
 extern crate libimagdiary;
 extern crate libimagentryedit;
+extern crate libimagentrymarkdown;
+extern crate libimagentryfilter;
+extern crate libimagentrydatetime;
 extern crate libimagentryview;
 extern crate libimagerror;
 extern crate libimaginteraction;
@@ -51,7 +56,7 @@ extern crate libimagutil;
 
 use std::io::Write;
 
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
 use libimagrt::runtime::Runtime;
 use libimagerror::trace::MapErrTrace;
 
@@ -71,10 +76,12 @@ use view::view;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-diary",
-                                    &version,
-                                    "Personal Diary/Diaries",
-                                    ui::build_ui);
+    let rt = generate_runtime_setup_with_config("imag-diary",
+                                                &version,
+                                                "Personal Diary/Diaries",
+                                                &["diary"],
+                                                &[::libimagdiary::config::CONFIG_KEYS, ::libimagentrydatetime::timezone::CONFIG_KEYS],
+                                                ui::build_ui);
 
     rt.cli()
         .subcommand_name()
@@ -19,6 +19,9 @@
 
 use clap::{Arg, ArgGroup, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
        .arg(Arg::with_name("diaryname")
@@ -42,6 +45,29 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(false)
                         .help("Do not edit after creating"))
 
+                   .arg(Arg::with_name("section")
+                        .long("section")
+                        .short("s")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("HEADING")
+                        .help("Only edit this section of the entry, e.g. \"## Meeting notes\". The section is created if it does not exist."))
+
+                   .arg(Arg::with_name("text")
+                        .long("text")
+                        .takes_value(true)
+                        .required(false)
+                        .requires("section")
+                        .value_name("TEXT")
+                        .help("Append this text to the section passed with --section instead of calling the editor"))
+
+                   .arg(Arg::with_name("date")
+                        .long("date")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("DATE")
+                        .help("Create the entry for this date instead of now. Either YYYY-MM-DD[THH[:mm[:ss]]] or natural language like 'yesterday', 'last friday 20:00' or '2 days ago'"))
+
                    .arg(Arg::with_name("timed")
                         .long("timed")
                         .short("t")
@@ -79,7 +105,21 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 
         .subcommand(SubCommand::with_name("list")
                    .about("List diary entries")
-                   .version("0.1"))
+                   .version("0.1")
+                   .arg(Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("DATE")
+                        .help("Only list entries from this date-time on. Either YYYY-MM-DD[THH[:mm[:ss]]] or natural language like 'last monday' or '2 weeks ago'"))
+                   .arg(Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .required(false)
+                        .value_name("DATE")
+                        .help("Only list entries up to this date-time. A date without time includes the whole day"))
+                   .arg(where_arg())
+                   .arg(saved_arg()))
 
         .subcommand(SubCommand::with_name("delete")
                    .about("Delete a diary entry")
@@ -39,6 +39,8 @@ extern crate toml_query;
 extern crate kairos;
 extern crate chrono;
 extern crate prettytable;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 
 extern crate libimaghabit;
 extern crate libimagstore;
@@ -46,6 +48,9 @@ extern crate libimagstore;
 extern crate libimagerror;
 extern crate libimagutil;
 extern crate libimaginteraction;
+extern crate libimagentryfilter;
+extern crate libimagentrydatetime;
+extern crate libimagtimeui;
 
 use std::io::Write;
 use std::process::exit;
@@ -55,7 +60,7 @@ use prettytable::cell::Cell;
 use prettytable::row::Row;
 
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
 use libimagerror::trace::{MapErrTrace, trace_error};
 use libimagerror::iter::TraceIterator;
 use libimagerror::exit::ExitUnwrap;
@@ -67,15 +72,20 @@ use libimagstore::store::FileLockEntry;
 use libimagstore::store::Store;
 use libimagstore::storeid::StoreId;
 use libimaginteraction::ask::ask_bool;
+use libimagentryfilter::cli::query_from_matches;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::default_timezone;
 
 mod ui;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-habit",
-                                    &version,
-                                    "Habit tracking tool",
-                                    ui::build_ui);
+    let rt = generate_runtime_setup_with_config("imag-habit",
+                                                &version,
+                                                "Habit tracking tool",
+                                                &["habit"],
+                                                &[::libimagentrydatetime::timezone::CONFIG_KEYS],
+                                                ui::build_ui);
 
 
     let _ = rt
@@ -104,34 +114,45 @@ fn main() {
 }
 
 fn create(rt: &Runtime) {
-    use kairos::parser::parse as kairos_parse;
-    use kairos::parser::Parsed;
+    use libimagtimeui::natural::parse_natural_date;
+
     let scmd  = rt.cli().subcommand_matches("create").unwrap();                      // safe by call from main()
     let name  = scmd.value_of("create-name").map(String::from).unwrap();             // safe by clap
     let recu  = scmd.value_of("create-date-recurr-spec").map(String::from).unwrap(); // safe by clap
     let comm  = scmd.value_of("create-comment").map(String::from).unwrap();          // safe by clap
     let date  = scmd.value_of("create-date").unwrap();                               // safe by clap
 
-    let parsedate = |d, pname| match kairos_parse(d).map_err_trace_exit_unwrap(1) {
-        Parsed::TimeType(tt) => match tt.calculate() {
-            Ok(tt) => match tt.get_moment() {
-                Some(mom) => mom.date(),
-                None => {
-                    debug!("TimeType yielded: '{:?}'", tt);
-                    error!("Error: '{}' parameter does not yield a point in time", pname);
-                    exit(1);
+    let today     = get_today(rt);
+    let parsedate = |d, pname| match parse_natural_date(d, &today) {
+        Some(date) => date,
+        None       => parse_kairos_date(d, pname),
+    };
+
+    fn parse_kairos_date(d: &str, pname: &str) -> chrono::NaiveDate {
+        use kairos::parser::parse as kairos_parse;
+        use kairos::parser::Parsed;
+
+        match kairos_parse(d).map_err_trace_exit_unwrap(1) {
+            Parsed::TimeType(tt) => match tt.calculate() {
+                Ok(tt) => match tt.get_moment() {
+                    Some(mom) => mom.date(),
+                    None => {
+                        debug!("TimeType yielded: '{:?}'", tt);
+                        error!("Error: '{}' parameter does not yield a point in time", pname);
+                        exit(1);
+                    },
                 },
+                Err(e) => {
+                    error!("Error: '{:?}'", e);
+                    exit(1);
+                }
             },
-            Err(e) => {
-                error!("Error: '{:?}'", e);
+            _ => {
+                error!("Error: '{}' parameter does not yield a point in time", pname);
                 exit(1);
-            }
-        },
-        _ => {
-            error!("Error: '{}' parameter does not yield a point in time", pname);
-            exit(1);
-        },
-    };
+            },
+        }
+    }
 
     debug!("Building habit: name = {name}, basedate = {date}, recurr = {recu}, comment = {comm}",
            name = name,
@@ -245,7 +266,7 @@ fn today(rt: &Runtime, future: bool) {
             (true, rt.cli().subcommand_matches("status").unwrap().is_present("status-done"))
         }
     };
-    let today = ::chrono::offset::Local::today().naive_local();
+    let today = get_today(rt);
 
     let relevant : Vec<_> = { // scope, to have variable non-mutable in outer scope
         let mut relevant : Vec<_> = rt
@@ -371,20 +392,33 @@ fn today(rt: &Runtime, future: bool) {
     }
 }
 
+/// A habit, as printed by `list`
+#[derive(Serialize)]
+struct HabitRecord {
+    name: String,
+    basedate: String,
+    recur: String,
+    comment: String,
+    next_due: Option<String>,
+}
+
 fn list(rt: &Runtime) {
-    fn lister_fn(h: &FileLockEntry) -> Vec<String> {
+    fn lister_fn(h: &FileLockEntry) -> HabitRecord {
         debug!("Listing: {:?}", h);
         let name     = h.habit_name().map_err_trace_exit_unwrap(1);
         let basedate = h.habit_basedate().map_err_trace_exit_unwrap(1);
         let recur    = h.habit_recur_spec().map_err_trace_exit_unwrap(1);
         let comm     = h.habit_comment().map_err_trace_exit_unwrap(1);
         let due      = h.next_instance_date().map_err_trace_exit_unwrap(1)
-            .map(date_to_string_helper)
-            .unwrap_or_else(|| String::from("<finished>"));
-
-        let v = vec![name, basedate, recur, comm, due];
-        debug!(" -> {:?}", v);
-        v
+            .map(date_to_string_helper);
+
+        HabitRecord {
+            name: name,
+            basedate: basedate,
+            recur: recur,
+            comment: comm,
+            next_due: due,
+        }
     }
 
     let header = ["#", "Name", "Basedate", "Recurr", "Comment", "Next Due"]
@@ -392,11 +426,13 @@ fn list(rt: &Runtime) {
         .map(|s| Cell::new(s))
         .collect::<Vec<Cell>>();
 
-    let mut empty = true;
-    let mut table = Table::new();
-    table.set_titles(Row::new(header));
+    let query = rt
+        .cli()
+        .subcommand_matches("list")
+        .map(|scmd| query_from_matches(rt.store(), scmd).map_err_trace_exit_unwrap(1))
+        .and_then(|q| q);
 
-    let _ = rt
+    let records = rt
         .store()
         .all_habit_templates()
         .map_err_trace_exit_unwrap(1)
@@ -412,16 +448,29 @@ fn list(rt: &Runtime) {
                 None
             },
         })
-        .enumerate()
-        .for_each(|(i, e)| {
-            let mut v = vec![format!("{}", i)];
-            let mut list = lister_fn(&e);
-            v.append(&mut list);
-            table.add_row(v.iter().map(|s| Cell::new(s)).collect());
-            empty = false;
-        });
+        .filter(|h| match query {
+            Some(ref q) => q.matches(&h).map_err_trace_exit_unwrap(1),
+            None        => true,
+        })
+        .map(|e| lister_fn(&e))
+        .collect::<Vec<_>>();
 
-    if !empty {
+    if rt.output_format().is_structured() {
+        return rt.output(&records, |_, _| Ok(())).map_err_trace_exit_unwrap(1);
+    }
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(header));
+
+    for (i, record) in records.iter().enumerate() {
+        let due = record.next_due.clone().unwrap_or_else(|| String::from("<finished>"));
+        let v   = vec![format!("{}", i), record.name.clone(), record.basedate.clone(),
+                       record.recur.clone(), record.comment.clone(), due];
+        debug!(" -> {:?}", v);
+        table.add_row(v.iter().map(|s| Cell::new(s)).collect());
+    }
+
+    if !records.is_empty() {
         let _ = table.print(&mut rt.stdout()).to_exit_code().unwrap_or_exit();
     }
 }
@@ -505,7 +554,7 @@ fn done(rt: &Runtime) {
     let scmd = rt.cli().subcommand_matches("done").unwrap(); // safe by call from main()
     let names : Vec<_> = scmd.values_of("done-name").unwrap().map(String::from).collect();
 
-    let today = ::chrono::offset::Local::today().naive_local();
+    let today = get_today(rt);
 
     let relevant : Vec<_> = { // scope, to have variable non-mutable in outer scope
         let mut relevant : Vec<_> = rt
@@ -549,6 +598,12 @@ fn done(rt: &Runtime) {
     info!("Done.");
 }
 
+/// Get the current date in the configured timezone
+fn get_today(rt: &Runtime) -> chrono::NaiveDate {
+    let tz = default_timezone(rt.config()).map_err_trace_exit_unwrap(1);
+    ZonedDateTime::now(tz).naive_local().date()
+}
+
 /// Helper function for `Iterator::filter_map()`ing `all_habit_templates()` and `Store::get` them.
 fn get_from_store<'a>(store: &'a Store, id: StoreId) -> Option<FileLockEntry<'a>> {
     match store.get(id.clone()) {
@@ -19,6 +19,9 @@
 
 use clap::{Arg, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .subcommand(SubCommand::with_name("status")
@@ -51,7 +54,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(true)
                         .takes_value(true)
                         .value_name("DATE")
-                        .help("Date when the first instance should be done"))
+                        .help("Date when the first instance should be done (eg: '2018-03-04', 'tomorrow', 'next monday')"))
                    .arg(Arg::with_name("create-date-recurr-spec")
                         .long("recurr")
                         .short("r")
@@ -107,6 +110,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
         .subcommand(SubCommand::with_name("list")
                    .about("List Habits")
                    .version("0.1")
+                   .arg(where_arg())
+                   .arg(saved_arg())
                    .arg(Arg::with_name("list-long")
                         .long("long")
                         .short("l")
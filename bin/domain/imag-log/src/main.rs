@@ -43,12 +43,16 @@ extern crate libimaglog;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagerror;
+extern crate libimagentryfilter;
 extern crate libimagdiary;
+extern crate libimagutil;
 
 use std::io::Write;
 
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::io::ToExitCode;
 use libimagerror::exit::ExitUnwrap;
@@ -57,6 +61,7 @@ use libimagdiary::diary::Diary;
 use libimaglog::log::Log;
 use libimaglog::error::LogError as LE;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
+use libimagentryfilter::cli::query_from_matches;
 
 mod ui;
 use ui::build_ui;
@@ -64,12 +69,30 @@ use ui::build_ui;
 use toml::Value;
 use itertools::Itertools;
 
+/// The configuration keys imag-log reads
+const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "log.logs",
+        kind: ValueType::Array,
+        default: None,
+        doc: "The diaries which can be logged to",
+    },
+    ConfigKey {
+        path: "log.default",
+        kind: ValueType::String,
+        default: None,
+        doc: "The diary which is logged to if none is passed",
+    },
+];
+
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-log",
-                                    &version,
-                                    "Overlay to imag-diary to 'log' single lines of text",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_config("imag-log",
+                                                &version,
+                                                "Overlay to imag-diary to 'log' single lines of text",
+                                                &["diary"],
+                                                &[CONFIG_KEYS, ::libimagdiary::config::CONFIG_KEYS],
+                                                build_ui);
 
 
     if let Some(scmd) = rt.cli() .subcommand_name() {
@@ -98,6 +121,7 @@ fn main() {
             .map(|mut fle| {
                 let _ = fle.make_log_entry().map_err_trace_exit_unwrap(1);
                 *fle.get_content_mut() = text;
+                let _ = rt.report_touched(&fle).map_err_trace_exit_unwrap(1);
             })
             .map_err_trace_exit_unwrap(1);
     }
@@ -109,7 +133,8 @@ fn show(rt: &Runtime) {
     use libimagdiary::iter::DiaryEntryIterator;
     use libimagdiary::entry::DiaryEntry;
 
-    let scmd = rt.cli().subcommand_matches("show").unwrap(); // safed by main()
+    let scmd  = rt.cli().subcommand_matches("show").unwrap(); // safed by main()
+    let query = query_from_matches(rt.store(), scmd).map_err_trace_exit_unwrap(1);
     let iters : Vec<DiaryEntryIterator> = match scmd.values_of("show-name") {
         Some(values) => values
             .map(|diary_name| Diary::entries(rt.store(), diary_name).map_err_trace_exit_unwrap(1))
@@ -148,6 +173,10 @@ fn show(rt: &Runtime) {
                 opt
             })
             .filter(|e| e.is_log().map_err_trace_exit_unwrap(1))
+            .filter(|e| match query {
+                Some(ref q) => q.matches(&e).map_err_trace_exit_unwrap(1),
+                None        => true,
+            })
             .map(|entry| (entry.diary_id().map_err_trace_exit_unwrap(1), entry))
             .sorted_by_key(|&(ref id, _)| id.clone())
             .into_iter()
@@ -19,6 +19,9 @@
 
 use clap::{Arg, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
        .arg(Arg::with_name("diaryname")
@@ -40,6 +43,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .about("View log(s)")
                    .version("0.1")
                    .alias("list")
+                   .arg(where_arg())
+                   .arg(saved_arg())
 
                    .arg(Arg::with_name("show-all")
                         .long("all")
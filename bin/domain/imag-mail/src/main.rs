@@ -48,7 +48,7 @@ use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagmail::mail::Mail;
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagutil::info_result::*;
 
 mod ui;
@@ -57,10 +57,11 @@ use ui::build_ui;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-mail",
-                                    &version,
-                                    "Mail collection tool",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-mail",
+                                                     &version,
+                                                     "Mail collection tool",
+                                                     &["mail"],
+                                                     build_ui);
 
     rt.cli()
         .subcommand_name()
@@ -39,6 +39,7 @@ extern crate itertools;
 extern crate libimagnotes;
 #[macro_use] extern crate libimagrt;
 extern crate libimagentryedit;
+extern crate libimagentryfilter;
 extern crate libimagerror;
 extern crate libimagutil;
 extern crate libimagstore;
@@ -50,7 +51,7 @@ use itertools::Itertools;
 
 use libimagentryedit::edit::Edit;
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
 use libimagnotes::note::Note;
 use libimagnotes::notestore::*;
@@ -60,6 +61,7 @@ use libimagerror::io::ToExitCode;
 use libimagerror::iter::TraceIterator;
 use libimagutil::info_result::*;
 use libimagutil::warn_result::WarnResult;
+use libimagentryfilter::cli::query_from_matches;
 
 
 mod ui;
@@ -67,10 +69,11 @@ use ui::build_ui;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-notes",
-                                    &version,
-                                    "Note taking helper",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-notes",
+                                                     &version,
+                                                     "Note taking helper",
+                                                     &["notes"],
+                                                     build_ui);
 
     rt.cli()
         .subcommand_name()
@@ -109,6 +112,8 @@ fn create(rt: &Runtime) {
             .map_warn_err_str("Editing failed")
             .map_err_trace_exit_unwrap(1);
     }
+
+    let _ = rt.report_touched(&note).map_err_trace_exit_unwrap(1);
 }
 
 fn delete(rt: &Runtime) {
@@ -129,6 +134,8 @@ fn edit(rt: &Runtime) {
                 .edit_content(rt)
                 .map_warn_err_str("Editing failed")
                 .map_err_trace_exit_unwrap(1);
+
+            let _ = rt.report_touched(&note).map_err_trace_exit_unwrap(1);
         })
         .unwrap_or_else(|| {
             error!("Cannot find note with name '{}'", name);
@@ -138,6 +145,12 @@ fn edit(rt: &Runtime) {
 fn list(rt: &Runtime) {
     use std::cmp::Ordering;
 
+    let query = rt
+        .cli()
+        .subcommand_matches("list")
+        .map(|scmd| query_from_matches(rt.store(), scmd).map_err_trace_exit_unwrap(1))
+        .and_then(|q| q);
+
     let _ = rt
         .store()
         .all_notes()
@@ -148,6 +161,10 @@ fn list(rt: &Runtime) {
             error!("Fatal: Nonexistent entry where entry should exist");
             exit(1)
         }))
+        .filter(|note| match query {
+            Some(ref q) => q.matches(&note).map_err_trace_exit_unwrap(1),
+            None        => true,
+        })
         .sorted_by(|note_a, note_b| if let (Ok(a), Ok(b)) = (note_a.get_name(), note_b.get_name()) {
             return a.cmp(&b)
         } else {
@@ -19,6 +19,9 @@
 
 use clap::{Arg, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .subcommand(SubCommand::with_name("create")
@@ -61,6 +64,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 
         .subcommand(SubCommand::with_name("list")
                    .about("List Notes")
-                   .version("0.1"))
+                   .version("0.1")
+                   .arg(where_arg())
+                   .arg(saved_arg()))
 
 }
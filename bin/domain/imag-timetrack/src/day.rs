@@ -24,24 +24,24 @@ use filters::filter::Filter;
 use chrono::NaiveDateTime;
 
 use libimagerror::trace::trace_error;
-use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
 use libimagerror::io::ToExitCode;
 use libimagstore::store::FileLockEntry;
 use libimagtimetrack::error::TimeTrackError as TTE;
-use libimagtimetrack::timetrackingstore::TimeTrackStore;
 use libimagtimetrack::timetracking::TimeTracking;
 use libimagtimetrack::tag::TimeTrackingTag;
 use libimagtimetrack::iter::filter::*;
 
 use libimagrt::runtime::Runtime;
 
+use util::timetrackings_between;
+
 
 pub fn day(rt: &Runtime) -> i32 {
     let (_, cmd) = rt.cli().subcommand();
     let cmd = cmd.unwrap(); // checked in main()
 
-    let filter = {
+    let (start, end, filter) = {
         let start = match cmd.value_of("start").map(NaiveDateTime::from_str) {
             None    => ::chrono::offset::Local::today().and_hms(0, 0, 0).naive_local(),
             Some(s) => match s.map_err(TTE::from) {
@@ -83,12 +83,10 @@ pub fn day(rt: &Runtime) -> i32 {
             }
         };
 
-        tags_filter.and(start_time_filter).and(end_time_filter)
+        (start, end, tags_filter.and(start_time_filter).and(end_time_filter))
     };
 
-    rt.store()
-        .get_timetrackings()
-        .map_err_trace_exit_unwrap(1)
+    timetrackings_between(rt, Some(start), Some(end))
         .trace_unwrap()
         .filter(|e| filter.filter(e))
         .map(|e| -> Result<_, TTE> {
@@ -31,12 +31,15 @@ use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
 use libimagstore::store::FileLockEntry;
 use libimagtimetrack::error::TimeTrackError;
-use libimagtimetrack::timetrackingstore::TimeTrackStore;
 use libimagtimetrack::timetracking::TimeTracking;
 use libimagtimetrack::error::Result;
 
 use libimagrt::runtime::Runtime;
 
+use util::get_timezone;
+use util::format_duration;
+use util::timetrackings_between;
+
 pub fn list(rt: &Runtime) -> i32 {
     let (_, cmd) = rt.cli().subcommand();
     let cmd = cmd.unwrap(); // checked in main()
@@ -113,56 +116,69 @@ pub fn list_impl(rt: &Runtime,
     };
 
     let filter = start_time_filter.and(end_time_filter);
+    let tz     = get_timezone(rt);
 
-    let mut table = Table::new();
-    table.set_titles(Row::new(["Tag", "Start", "End"].into_iter().map(|s| Cell::new(s)).collect()));
+    // Not ended timetrackings may have been started after `end`
+    let range_end = if list_not_ended { None } else { end };
 
-    rt.store()
-        .get_timetrackings()
-        .map_err_trace_exit_unwrap(1)
+    let records = timetrackings_between(rt, start, range_end)
         .trace_unwrap()
         .filter(|e| filter.filter(e))
-        .fold(Ok(table), |acc: Result<_>, e| {
-            acc.and_then(|mut tab: Table| {
-                debug!("Processing {:?}", e.get_location());
-
-                let tag   = e.get_timetrack_tag()?;
-                debug!(" -> tag = {:?}", tag);
-
-                let start = e.get_start_datetime()?;
-                debug!(" -> start = {:?}", start);
-
-                let end   = e.get_end_datetime()?;
-                debug!(" -> end = {:?}", end);
-
-                let v = match (start, end) {
-                    (None, _)          => vec![String::from(tag.as_str()), String::from(""), String::from("")],
-                    (Some(s), None)    => {
-                        vec![
-                            String::from(tag.as_str()),
-                            format!("{}", s),
-                            String::from(""),
-                        ]
-                    },
-                    (Some(s), Some(e)) => {
-                        vec![
-                            String::from(tag.as_str()),
-                            format!("{}", s),
-                            format!("{}", e),
-                        ]
-                    },
-                };
-
-                let cells : Vec<Cell> = v
-                    .into_iter()
-                    .map(|s| Cell::new(&s))
-                    .collect();
-                tab.add_row(Row::new(cells));
-
-                Ok(tab)
+        .map(|e| -> Result<TimeTrackingRecord> {
+            debug!("Processing {:?}", e.get_location());
+
+            let tag   = e.get_timetrack_tag()?;
+            debug!(" -> tag = {:?}", tag);
+
+            let start = e.get_start_datetime()?;
+            debug!(" -> start = {:?}", start);
+
+            let end   = e.get_end_datetime()?;
+            debug!(" -> end = {:?}", end);
+
+            let duration = e.duration(tz)?;
+            debug!(" -> duration = {:?}", duration);
+
+            let (start, end, duration) = match (start, end, duration) {
+                (None, _, _)          => (None, None, None),
+                (Some(s), None, _)    => (Some(format!("{}", s)), None, None),
+                (Some(s), Some(e), d) => (Some(format!("{}", s)), Some(format!("{}", e)), d.map(format_duration)),
+            };
+
+            Ok(TimeTrackingRecord {
+                tag: String::from(tag.as_str()),
+                start: start,
+                end: end,
+                duration: duration,
             })
         })
-        .map_err_trace_exit_unwrap(1)
+        .collect::<Result<Vec<_>>>()
+        .map_err_trace_exit_unwrap(1);
+
+    if rt.output_format().is_structured() {
+        return rt.output(&records, |_, _| Ok(()))
+            .map(|_| 0)
+            .map_err_trace()
+            .unwrap_or(1);
+    }
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(["Tag", "Start", "End", "Duration"].into_iter().map(|s| Cell::new(s)).collect()));
+
+    for record in records {
+        let cells : Vec<Cell> = vec![
+                Some(record.tag),
+                record.start,
+                record.end,
+                record.duration,
+            ]
+            .into_iter()
+            .map(|s| Cell::new(&s.unwrap_or_else(String::new)))
+            .collect();
+        table.add_row(Row::new(cells));
+    }
+
+    table
         .print(&mut rt.stdout())
         .map_err(|_| TimeTrackError::from(String::from("Failed printing table")))
         .map(|_| 0)
@@ -170,3 +186,11 @@ pub fn list_impl(rt: &Runtime,
         .unwrap_or(1)
 }
 
+/// A timetracking, as printed by `list`
+#[derive(Serialize)]
+struct TimeTrackingRecord {
+    tag: String,
+    start: Option<String>,
+    end: Option<String>,
+    duration: Option<String>,
+}
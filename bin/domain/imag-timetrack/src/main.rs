@@ -41,12 +41,16 @@ extern crate filters;
 extern crate itertools;
 extern crate prettytable;
 extern crate kairos;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 
 extern crate libimagerror;
 extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagtimetrack;
 extern crate libimagutil;
+extern crate libimagentrydatetime;
+extern crate libimagtimeui;
 
 mod cont;
 mod day;
@@ -56,6 +60,7 @@ mod start;
 mod stop;
 mod track;
 mod ui;
+mod util;
 mod week;
 mod year;
 
@@ -70,15 +75,17 @@ use ui::build_ui;
 use week::week;
 use year::year;
 
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_config;
 use libimagerror::trace::MapErrTrace;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-timetrack",
-                                    &version,
-                                    "Time tracking module",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_config("imag-timetrack",
+                                                &version,
+                                                "Time tracking module",
+                                                &["timetrack"],
+                                                &[::libimagentrydatetime::timezone::CONFIG_KEYS],
+                                                build_ui);
 
     let command = rt.cli().subcommand_name();
     let retval  = if let Some(command) = command {
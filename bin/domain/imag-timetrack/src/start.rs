@@ -17,27 +17,25 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use std::str::FromStr;
-
-use chrono::naive::NaiveDateTime;
-
 use libimagrt::runtime::Runtime;
-use libimagerror::trace::trace_error;
-use libimagtimetrack::error::TimeTrackError as TTE;
 use libimagtimetrack::tag::TimeTrackingTag;
 use libimagtimetrack::timetrackingstore::TimeTrackStore;
 use libimagerror::trace::MapErrTrace;
+use libimagentrydatetime::timezone::ZonedDateTime;
+
+use util::get_timezone;
+use util::parse_cli_datetime;
 
 pub fn start(rt: &Runtime) -> i32 {
     let (_, cmd) = rt.cli().subcommand();
     let cmd = cmd.unwrap(); // checked in main()
 
+    let tz    = get_timezone(rt);
     let start = match cmd.value_of("start-time") {
-        None | Some("now") => ::chrono::offset::Local::now().naive_local(),
-        Some(ndt)          => match NaiveDateTime::from_str(ndt).map_err(TTE::from) {
-            Ok(ndt) => ndt,
-            Err(e) =>  {
-                trace_error(&e);
+        None | Some("now") => ZonedDateTime::now(tz),
+        Some(s)            => match parse_cli_datetime(s, tz) {
+            Some(zdt) => zdt,
+            None      => {
                 error!("Cannot continue, not having start time");
                 return 1
             },
@@ -50,7 +48,7 @@ pub fn start(rt: &Runtime) -> i32 {
         .map(TimeTrackingTag::from)
         .fold(0, |acc, ttt| {
             rt.store()
-              .create_timetracking_at(&start, &ttt)
+              .create_timetracking_at_zoned(&start, &ttt)
               .map_err_trace()
               .map(|_| acc)
               .unwrap_or(1)
@@ -17,17 +17,12 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use std::str::FromStr;
-
 use filters::filter::Filter;
-use chrono::NaiveDateTime;
 
-use libimagerror::trace::trace_error;
 use libimagerror::iter::TraceIterator;
 use libimagerror::trace::MapErrTrace;
 use libimagrt::runtime::Runtime;
 
-use libimagtimetrack::error::TimeTrackError as TTE;
 use libimagtimetrack::timetracking::TimeTracking;
 use libimagtimetrack::tag::TimeTrackingTag;
 use libimagtimetrack::timetrackingstore::*;
@@ -35,18 +30,22 @@ use libimagtimetrack::iter::filter::has_end_time;
 use libimagtimetrack::iter::filter::has_one_of_tags;
 use libimagutil::warn_result::*;
 use libimagutil::debug_result::*;
+use libimagentrydatetime::timezone::ZonedDateTime;
+
+use util::get_timezone;
+use util::parse_cli_datetime;
 
 pub fn stop(rt: &Runtime) -> i32 {
     let (_, cmd) = rt.cli().subcommand();
     let cmd = cmd.unwrap(); // checked in main()
 
+    let tz        = get_timezone(rt);
     let stop_time = match cmd.value_of("stop-time") {
-        None | Some("now") => ::chrono::offset::Local::now().naive_local(),
-        Some(ndt)          => match NaiveDateTime::from_str(ndt).map_err(TTE::from) {
-            Ok(ndt) => ndt,
-            Err(e) =>  {
-                trace_error(&e);
-                error!("Cannot continue, not having start time");
+        None | Some("now") => ZonedDateTime::now(tz),
+        Some(s)            => match parse_cli_datetime(s, tz) {
+            Some(zdt) => zdt,
+            None      => {
+                error!("Cannot continue, not having stop time");
                 return 1
             },
         }
@@ -92,7 +91,7 @@ pub fn stop(rt: &Runtime) -> i32 {
         // for each of these timetrackings, end them
         // for each result, print the backtrace (if any)
         .fold(0, |acc, mut elem| {
-            elem.set_end_datetime(stop_time.clone())
+            elem.set_end_datetime_zoned(&stop_time)
                 .map_dbg(|e| format!("Setting end time worked: {:?}", e))
                 .map(|_| acc)
                 .map_err_trace_exit_unwrap(1)
@@ -17,21 +17,17 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use std::process::exit;
-
 use clap::ArgMatches;
-use chrono::naive::NaiveDate;
-use chrono::naive::NaiveDateTime;
 
 use libimagrt::runtime::Runtime;
-use libimagerror::trace::trace_error;
-use libimagtimetrack::error::TimeTrackError as TTE;
 use libimagtimetrack::tag::TimeTrackingTag;
 use libimagtimetrack::timetrackingstore::TimeTrackStore;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
 use libimagerror::trace::MapErrTrace;
 
-const DATE_TIME_PARSE_FMT : &'static str    = "%Y-%m-%dT%H:%M:%S";
-const DATE_PARSE_FMT : &'static str         = "%Y-%m-%d";
+use util::get_timezone;
+use util::parse_cli_datetime;
 
 pub fn track(rt: &Runtime) -> i32 {
     let (_, cmd) = rt.cli().subcommand();
@@ -39,40 +35,29 @@ pub fn track(rt: &Runtime) -> i32 {
 
     // Gets the appropriate time from the commandline or None on error (errors already logged, so
     // callee can directly return in case of error
-    fn get_time(cmd: &ArgMatches, clap_name: &str, errname: &str) -> Option<NaiveDateTime> {
+    fn get_time(cmd: &ArgMatches, clap_name: &str, errname: &str, tz: Tz) -> Option<ZonedDateTime> {
         match cmd.value_of(clap_name) {
-            Some("now") => Some(::chrono::offset::Local::now().naive_local()),
-            Some(els) => {
-                match NaiveDateTime::parse_from_str(els, DATE_TIME_PARSE_FMT).map_err(TTE::from) {
-                    Ok(ndt) => Some(ndt),
-                    Err(e_ndt) => {
-                        match NaiveDate::parse_from_str(els, DATE_PARSE_FMT).map_err(TTE::from) {
-                            Ok(ndt) => Some(ndt.and_hms(0, 0, 0)),
-                            Err(e_nd) => {
-                                error!("Cannot parse date {}:", errname);
-                                trace_error(&e_nd);
-
-                                error!("Cannot parse date-time {}:", errname);
-                                trace_error(&e_ndt);
-                                exit(1)
-                            }
-                        }
-                    }
+            Some(s) => {
+                let zdt = parse_cli_datetime(s, tz);
+                if zdt.is_none() {
+                    error!("Cannot use date-time {}", errname);
                 }
-            }
+                zdt
+            },
             None => {
                 error!("Not specified in commandline: {}", clap_name);
-                exit(1)
+                None
             }
         }
     }
 
-    let start = match get_time(&cmd, "start-time", "start") {
+    let tz    = get_timezone(rt);
+    let start = match get_time(&cmd, "start-time", "start", tz) {
         Some(t) => t,
         None    => return 1,
     };
 
-    let stop = match get_time(&cmd, "end-time", "stop") {
+    let stop = match get_time(&cmd, "end-time", "stop", tz) {
         Some(t) => t,
         None    => return 1,
     };
@@ -83,7 +68,7 @@ pub fn track(rt: &Runtime) -> i32 {
         .map(TimeTrackingTag::from)
         .fold(0, |acc, ttt| {
             rt.store()
-              .create_timetracking(&start, &stop, &ttt)
+              .create_timetracking_zoned(&start, &stop, &ttt)
               .map_err_trace()
               .map(|_| acc)
               .unwrap_or(1)
@@ -54,7 +54,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("start-time")
                         .index(1)
                         .required(true)
-                        .help("Start-time when to start the timetracking (use 'now' for current time)"))
+                        .help("Start-time when to start the timetracking (use 'now' for current time, natural language like '9am' or 'yesterday 14:00' is accepted)"))
                    .arg(Arg::with_name("tags")
                         .index(2)
                         .required(true)
@@ -68,7 +68,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("end-time")
                         .index(1)
                         .required(false)
-                        .help("End-time when to stop the timetracking (use 'now' for current time)"))
+                        .help("End-time when to stop the timetracking (use 'now' for current time, natural language like '9am' or 'yesterday 14:00' is accepted)"))
                    .arg(Arg::with_name("tags")
                         .index(2)
                         .required(false)
@@ -82,11 +82,11 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("start-time")
                         .index(1)
                         .required(true)
-                        .help("Start-time when to start the timetracking"))
+                        .help("Start-time when to start the timetracking (natural language like 'yesterday 9am' is accepted)"))
                    .arg(Arg::with_name("end-time")
                         .index(2)
                         .required(true)
-                        .help("End-time when to stop the timetracking"))
+                        .help("End-time when to stop the timetracking (natural language like 'yesterday 17:00' is accepted)"))
                    .arg(Arg::with_name("tags")
                         .index(3)
                         .required(true)
@@ -0,0 +1,78 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use chrono::Duration;
+use chrono::NaiveDateTime;
+
+use libimagrt::runtime::Runtime;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::trace::trace_error;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::default_timezone;
+use libimagtimeui::natural::parse_datetime;
+use libimagentrydatetime::range::DateTimeRange;
+use libimagtimetrack::iter::get::TimeTrackingsGetIterator;
+use libimagtimetrack::timetrackingstore::TimeTrackStore;
+
+/// The configured default timezone, in which times passed on the commandline are interpreted
+pub fn get_timezone(rt: &Runtime) -> Tz {
+    default_timezone(rt.config()).map_err_trace_exit_unwrap(1)
+}
+
+/// Parse a date-time passed on the commandline, either in the "YYYY-MM-DD[THH[:mm[:ss]]]" format
+/// or in natural language ("tomorrow 9am", "last monday 14:00", "2 hours ago"), relative to now
+///
+/// Errors are logged, `None` is returned in this case.
+pub fn parse_cli_datetime(s: &str, tz: Tz) -> Option<ZonedDateTime> {
+    let now = ZonedDateTime::now(tz);
+    match parse_datetime(s, &now.naive_local()) {
+        Some(ndt) => ZonedDateTime::from_local(&ndt, tz).map_err(|e| trace_error(&e)).ok(),
+        None      => {
+            error!("Cannot parse date-time: '{}'", s);
+            None
+        },
+    }
+}
+
+/// Get the timetrackings to check for being started between `start` and `end`
+///
+/// If both are given, only the parts of the store which can contain these timetrackings are
+/// traversed instead of all timetrackings. The start times still have to be filtered.
+pub fn timetrackings_between(rt: &Runtime, start: Option<NaiveDateTime>, end: Option<NaiveDateTime>)
+    -> TimeTrackingsGetIterator
+{
+    let range = match (start, end) {
+        (Some(start), Some(end)) => DateTimeRange::new(start, end).ok(),
+        _                        => None,
+    };
+
+    match range {
+        Some(range) => rt.store().get_timetrackings_in_range(&range),
+        None        => rt.store().get_timetrackings(),
+    }.map_err_trace_exit_unwrap(1)
+}
+
+/// Format a duration as "hours:minutes:seconds"
+pub fn format_duration(d: Duration) -> String {
+    let sign = if d < Duration::zero() { "-" } else { "" };
+    let secs = d.num_seconds().abs();
+
+    format!("{}{}:{:02}:{:02}", sign, secs / 3600, (secs % 3600) / 60, secs % 60)
+}
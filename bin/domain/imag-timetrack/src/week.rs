@@ -24,22 +24,22 @@ use filters::filter::Filter;
 use chrono::NaiveDateTime;
 
 use libimagerror::trace::trace_error;
-use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
 use libimagerror::io::ToExitCode;
 use libimagstore::store::FileLockEntry;
 use libimagtimetrack::error::TimeTrackError as TTE;
-use libimagtimetrack::timetrackingstore::TimeTrackStore;
 use libimagtimetrack::timetracking::TimeTracking;
 use libimagtimetrack::tag::TimeTrackingTag;
 use libimagtimetrack::iter::filter::*;
 
 use libimagrt::runtime::Runtime;
 
+use util::timetrackings_between;
+
 pub fn week(rt: &Runtime) -> i32 {
     let cmd = rt.cli().subcommand().1.unwrap(); // checked in main
 
-    let filter = {
+    let (start, end, filter) = {
         use chrono::offset::Local;
         use chrono::naive::NaiveDate;
         use chrono::Weekday;
@@ -96,12 +96,10 @@ pub fn week(rt: &Runtime) -> i32 {
             }
         };
 
-        tags_filter.and(start_time_filter).and(end_time_filter)
+        (start, end, tags_filter.and(start_time_filter).and(end_time_filter))
     };
 
-    rt.store()
-        .get_timetrackings()
-        .map_err_trace_exit_unwrap(1)
+    timetrackings_between(rt, Some(start), Some(end))
         .trace_unwrap()
         .filter(|e| filter.filter(e))
         .map(|e| -> Result<_, TTE> {
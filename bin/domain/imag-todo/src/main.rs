@@ -37,36 +37,48 @@ extern crate clap;
 extern crate toml;
 extern crate toml_query;
 #[macro_use] extern crate is_match;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
 
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagstore;
 extern crate libimagtodo;
+extern crate libimagentrymarkdown;
 
 use std::process::{Command, Stdio};
 use std::io::stdin;
 use std::io::Write;
+use std::path::PathBuf;
 
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagtodo::taskstore::TaskStore;
+use libimagtodo::task::Task;
+use libimagtodo::task::DUE_FMT;
 use libimagerror::trace::{MapErrTrace, trace_error};
 use libimagerror::iter::TraceIterator;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
+use libimagentrymarkdown::tasklist::MarkdownTaskList;
+use libimagstore::storeid::IntoStoreId;
 
 mod ui;
 
 use ui::build_ui;
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-todo",
-                                    &version,
-                                    "Interface with taskwarrior",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-todo",
+                                                     &version,
+                                                     "Interface with taskwarrior",
+                                                     &["todo"],
+                                                     build_ui);
 
     match rt.cli().subcommand_name() {
         Some("tw-hook") => tw_hook(&rt),
         Some("list") => list(&rt),
+        Some("open-checkboxes") => open_checkboxes(&rt),
+        Some("toggle-checkbox") => toggle_checkbox(&rt),
         Some(other) => {
             debug!("Unknown command");
             let _ = rt.handle_unknown_subcommand("imag-todo", other, rt.cli())
@@ -109,6 +121,14 @@ fn tw_hook(rt: &Runtime) {
     }
 }
 
+/// A task, as printed by `list` in a structured output format
+#[derive(Serialize)]
+struct TaskRecord {
+    id: String,
+    uuid: String,
+    due: Option<String>,
+}
+
 fn list(rt: &Runtime) {
     use toml_query::read::TomlValueReadTypeExt;
 
@@ -125,11 +145,20 @@ fn list(rt: &Runtime) {
     let res = rt.store().all_tasks() // get all tasks
         .map(|iter| { // and if this succeeded
             // filter out the ones were we can read the uuid
-            let uuids : Vec<_> = iter.trace_unwrap_exit(1).filter_map(|storeid| {
+            let records : Vec<_> = iter.trace_unwrap_exit(1).filter_map(|storeid| {
                 match rt.store().retrieve(storeid) {
                     Ok(fle) => {
                         match fle.get_header().read_string("todo.uuid") {
-                            Ok(Some(ref u)) => Some(u.clone()),
+                            Ok(Some(ref u)) => Some(TaskRecord {
+                                id: format!("{}", fle.get_location()),
+                                uuid: u.clone(),
+                                due: fle
+                                    .get_due()
+                                    .map_err_trace()
+                                    .ok()
+                                    .and_then(|due| due)
+                                    .map(|due| due.format(DUE_FMT).to_string()),
+                            }),
                             Ok(None) => {
                                 error!("Header missing field in {}", fle.get_location());
                                 None
@@ -150,6 +179,12 @@ fn list(rt: &Runtime) {
             })
             .collect();
 
+            if rt.output_format().is_structured() {
+                return rt.output(&records, |_, _| Ok(())).map_err_trace_exit_unwrap(1);
+            }
+
+            let uuids = records.into_iter().map(|r| r.uuid).collect::<Vec<_>>();
+
             // compose a `task` call with them, ...
             let outstring = if verbose { // ... if verbose
                 let output = Command::new("task")
@@ -176,3 +211,66 @@ fn list(rt: &Runtime) {
     res.map_err_trace().ok();
 }
 
+fn open_checkboxes(rt: &Runtime) {
+    let scmd = rt.cli().subcommand_matches("open-checkboxes").unwrap();
+    let all  = scmd.is_present("all");
+    let out  = rt.stdout();
+    let mut outlock = out.lock();
+
+    rt.store()
+        .entries()
+        .map_err_trace_exit_unwrap(1)
+        .into_get_iter()
+        .filter_map(|res| res.map_err_trace_exit_unwrap(1))
+        .for_each(|entry| {
+            for item in entry.tasks().into_iter().filter(|item| all || !item.checked) {
+                let mark = if item.checked { "x" } else { " " };
+                let _ = writeln!(outlock, "{}:{}: [{}] {}", entry.get_location(), item.line, mark, item.text)
+                    .to_exit_code()
+                    .unwrap_or_exit();
+            }
+        });
+}
+
+fn toggle_checkbox(rt: &Runtime) {
+    let scmd = rt.cli().subcommand_matches("toggle-checkbox").unwrap();
+    let reference = scmd.value_of("reference").unwrap(); // safe by clap
+
+    // The reference is printed by `open-checkboxes` as "<storeid>:<line>". The store id itself may
+    // contain a colon, so we split at the last one.
+    let (id, line) = match reference.rfind(':') {
+        Some(pos) => (&reference[..pos], &reference[(pos + 1)..]),
+        None      => {
+            error!("Reference '{}' is not of the form <storeid>:<line>", reference);
+            ::std::process::exit(1)
+        },
+    };
+
+    let line = line.parse::<usize>().unwrap_or_else(|e| {
+        error!("Cannot parse line number '{}': {}", line, e);
+        ::std::process::exit(1)
+    });
+
+    let id = PathBuf::from(id).into_storeid().map_err_trace_exit_unwrap(1);
+
+    let mut entry = rt
+        .store()
+        .get(id.clone())
+        .map_err_trace_exit_unwrap(1)
+        .unwrap_or_else(|| {
+            error!("Entry {} does not exist", id);
+            ::std::process::exit(1)
+        });
+
+    let checked = if scmd.is_present("check") {
+        let _ = entry.set_task_state(line, true).map_err_trace_exit_unwrap(1);
+        true
+    } else if scmd.is_present("uncheck") {
+        let _ = entry.set_task_state(line, false).map_err_trace_exit_unwrap(1);
+        false
+    } else {
+        entry.toggle_task(line).map_err_trace_exit_unwrap(1)
+    };
+
+    info!("{}:{} is now {}", id, line, if checked { "checked" } else { "unchecked" });
+}
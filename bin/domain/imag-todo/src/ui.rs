@@ -58,4 +58,43 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .help("Asks taskwarrior for all the details")
                         )
                    )
+
+        .subcommand(SubCommand::with_name("open-checkboxes")
+                    .about("List unchecked task list items ('- [ ] foo') of all entries in the store")
+                    .version("0.1")
+
+                    .arg(Arg::with_name("all")
+                         .long("all")
+                         .short("a")
+                         .takes_value(false)
+                         .required(false)
+                         .help("Also list checked items"))
+                   )
+
+        .subcommand(SubCommand::with_name("toggle-checkbox")
+                    .about("Toggle a task list item")
+                    .version("0.1")
+
+                    .arg(Arg::with_name("reference")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .value_name("STOREID:LINE")
+                         .help("The item to toggle, as printed by 'open-checkboxes'"))
+
+                    .arg(Arg::with_name("check")
+                         .long("check")
+                         .takes_value(false)
+                         .required(false)
+                         .conflicts_with("uncheck")
+                         .help("Check the item instead of toggling it"))
+
+                    .arg(Arg::with_name("uncheck")
+                         .long("uncheck")
+                         .takes_value(false)
+                         .required(false)
+                         .conflicts_with("check")
+                         .help("Uncheck the item instead of toggling it"))
+                   )
 }
@@ -27,29 +27,34 @@ extern crate libimagerror;
 extern crate libimagstore;
 extern crate libimagwiki;
 extern crate libimagentryedit;
+extern crate libimagentryfilter;
 extern crate libimagentrylink;
+extern crate libimagentrymarkdown;
 extern crate libimagutil;
 
 use std::io::Write;
 
 use libimagrt::runtime::Runtime;
-use libimagrt::setup::generate_runtime_setup;
+use libimagrt::setup::generate_runtime_setup_with_collections;
 use libimagerror::iter::TraceIterator;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagwiki::store::WikiStore;
 use libimagentryedit::edit::{Edit, EditHeader};
+use libimagentrymarkdown::section::MarkdownSections;
+use libimagentryfilter::cli::query_from_matches;
 
 mod ui;
 use ui::build_ui;
 
 fn main() {
     let version = make_imag_version!();
-    let rt = generate_runtime_setup("imag-wiki",
-                                    &version,
-                                    "Personal wiki",
-                                    build_ui);
+    let rt = generate_runtime_setup_with_collections("imag-wiki",
+                                                     &version,
+                                                     "Personal wiki",
+                                                     &["wiki"],
+                                                     build_ui);
 
     let wiki_name = rt.cli().value_of("wikiname").unwrap_or("default");
 
@@ -59,6 +64,7 @@ fn main() {
         Some("create")      => create(&rt, wiki_name),
         Some("create-wiki") => create_wiki(&rt),
         Some("show")        => show(&rt, wiki_name),
+        Some("append")      => append(&rt, wiki_name),
         Some("delete")      => delete(&rt, wiki_name),
         Some(other)         => {
             debug!("Unknown command");
@@ -78,6 +84,7 @@ fn ids(rt: &Runtime, wiki_name: &str) {
     } else {
         String::from("")
     };
+    let query = query_from_matches(rt.store(), scmd).map_err_trace_exit_unwrap(1);
 
     let out         = rt.stdout();
     let mut outlock = out.lock();
@@ -92,6 +99,15 @@ fn ids(rt: &Runtime, wiki_name: &str) {
         .all_ids()
         .map_err_trace_exit_unwrap(1)
         .trace_unwrap_exit(1)
+        .filter(|id| match query {
+            Some(ref q) => rt
+                .store()
+                .get(id.clone())
+                .map_err_trace_exit_unwrap(1)
+                .map(|entry| q.matches(&entry).map_err_trace_exit_unwrap(1))
+                .unwrap_or(false),
+            None => true,
+        })
         .for_each(|id| {
             let _ = writeln!(outlock, "{}{}", prefix, id)
                 .to_exit_code()
@@ -220,6 +236,7 @@ fn show(rt: &Runtime, wiki_name: &str) {
             ::std::process::exit(1)
         });
 
+    let section     = scmd.value_of("show-section");
     let out         = rt.stdout();
     let mut outlock = out.lock();
 
@@ -236,12 +253,58 @@ fn show(rt: &Runtime, wiki_name: &str) {
                 .to_exit_code()
                 .unwrap_or_exit();
 
-        writeln!(outlock, "{}", entry.get_content())
+        let content = match section {
+            Some(section) => entry.get_section(section).unwrap_or_else(|| {
+                error!("No section '{}' in wiki entry '{}'", section, name);
+                ::std::process::exit(1)
+            }),
+            None => entry.get_content().clone(),
+        };
+
+        writeln!(outlock, "{}", content)
                 .to_exit_code()
                 .unwrap_or_exit();
     }
 }
 
+fn append(rt: &Runtime, wiki_name: &str) {
+    use libimagwiki::entry::WikiEntry;
+
+    let scmd    = rt.cli().subcommand_matches("append").unwrap(); // safed by clap
+    let name    = scmd.value_of("append-name").unwrap(); // safe by clap
+    let section = scmd.value_of("append-section").unwrap(); // safe by clap
+
+    let wiki = rt
+        .store()
+        .get_wiki(&wiki_name)
+        .map_err_trace_exit_unwrap(1)
+        .unwrap_or_else(|| {
+            error!("No wiki '{}' found", wiki_name);
+            ::std::process::exit(1)
+        });
+
+    let mut entry = wiki
+        .get_entry(&name)
+        .map_err_trace_exit_unwrap(1)
+        .unwrap_or_else(|| {
+            error!("No wiki entry '{}' found in wiki '{}'", name, wiki_name);
+            ::std::process::exit(1)
+        });
+
+    let text = match scmd.value_of("append-text") {
+        Some(text) => String::from(text),
+        None       => {
+            let mut text = String::new();
+            let _        = text.edit_content(rt).map_err_trace_exit_unwrap(1);
+            text
+        },
+    };
+
+    entry.append_to_section(section, &text);
+
+    let _ = entry.autolink(rt.store()).map_err_trace_exit_unwrap(1);
+}
+
 fn delete(rt: &Runtime, wiki_name: &str) {
     use libimagentrylink::internal::InternalLinker;
 
@@ -19,6 +19,9 @@
 
 use clap::{Arg, App, SubCommand};
 
+use libimagentryfilter::cli::where_arg;
+use libimagentryfilter::cli::saved_arg;
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .arg(Arg::with_name("wikiname")
@@ -33,6 +36,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
         .subcommand(SubCommand::with_name("ids")
                    .about("List all ids in this wiki")
                    .version("0.1")
+                   .arg(where_arg())
+                   .arg(saved_arg())
 
                    .arg(Arg::with_name("ids-full")
                         .long("full")
@@ -145,6 +150,45 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(true)
                         .multiple(true)
                         .help("Name of the entry/entries to show (if not passed, all are shown)."))
+
+                   .arg(Arg::with_name("show-section")
+                        .long("section")
+                        .short("s")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .value_name("HEADING")
+                        .help("Only show this section of the entry/entries, e.g. \"## Meeting notes\"."))
+                   )
+
+        .subcommand(SubCommand::with_name("append")
+                   .about("Append text to a section of a wiki entry")
+                   .version("0.1")
+
+                   .arg(Arg::with_name("append-name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NAME")
+                        .help("Name of the entry to append to."))
+
+                   .arg(Arg::with_name("append-section")
+                        .long("section")
+                        .short("s")
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("HEADING")
+                        .help("Section to append to, e.g. \"## Meeting notes\". The section is created if it does not exist."))
+
+                   .arg(Arg::with_name("append-text")
+                        .index(2)
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .value_name("TEXT")
+                        .help("Text to append. If not passed, the editor is opened."))
                    )
 
 
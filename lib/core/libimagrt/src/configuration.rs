@@ -17,16 +17,137 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::path::Path;
 use std::path::PathBuf;
 
 use toml::Value;
 use clap::App;
 
+use libimagstore::store::STORE_CONFIG_FILE_NAME;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
+
 use error::RuntimeError as RE;
 use error::RuntimeErrorKind as REK;
 use error::Result;
 use error::ResultExt;
 
+/// The configuration keys the runtime reads
+pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "rt.editor",
+        kind: ValueType::String,
+        default: None,
+        doc: "The editor command, defaults to $EDITOR",
+    },
+    ConfigKey {
+        path: "imag.validate-config",
+        kind: ValueType::Boolean,
+        default: Some("false"),
+        doc: "Whether every command checks the configuration when it starts and warns about problems",
+    },
+    ConfigKey {
+        path: TRUSTED_PROJECTS_CONFIG_PATH,
+        kind: ValueType::Array,
+        default: None,
+        doc: "The directories whose per-project configuration file is loaded, it is ignored everywhere else",
+    },
+    ConfigKey {
+        path: "imag.aliases",
+        kind: ValueType::Table,
+        default: None,
+        doc: "Aliases for commands, see the documentation of the imag command",
+    },
+    ConfigKey {
+        path: "imag.logging.level",
+        kind: ValueType::String,
+        default: None,
+        doc: "The log level: 'trace', 'debug', 'info', 'warn' or 'error'",
+    },
+    ConfigKey {
+        path: "imag.logging.destinations",
+        kind: ValueType::Array,
+        default: None,
+        doc: "The files to log to, '-' is stderr",
+    },
+    ConfigKey {
+        path: "imag.logging.format.trace",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for trace messages",
+    },
+    ConfigKey {
+        path: "imag.logging.format.debug",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for debug messages",
+    },
+    ConfigKey {
+        path: "imag.logging.format.info",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for info messages",
+    },
+    ConfigKey {
+        path: "imag.logging.format.warn",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for warn messages",
+    },
+    ConfigKey {
+        path: "imag.logging.format.error",
+        kind: ValueType::String,
+        default: None,
+        doc: "The handlebars template for error messages",
+    },
+    ConfigKey {
+        path: "imag.logging.file-format",
+        kind: ValueType::String,
+        default: Some("text"),
+        doc: "The format of log files: 'text' (rendered with the templates) or 'json' (one JSON record per line)",
+    },
+    ConfigKey {
+        path: "imag.logging.rotation.max-size",
+        kind: ValueType::Integer,
+        default: Some("0"),
+        doc: "Rotate a log file before it grows bigger than this many bytes, 0 disables it",
+    },
+    ConfigKey {
+        path: "imag.logging.rotation.interval",
+        kind: ValueType::String,
+        default: Some("never"),
+        doc: "Rotate log files 'hourly', 'daily', 'weekly' or 'never'",
+    },
+    ConfigKey {
+        path: "imag.logging.rotation.keep",
+        kind: ValueType::Integer,
+        default: Some("5"),
+        doc: "The number of rotated log files to keep",
+    },
+    ConfigKey {
+        path: "imag.logging.modules.*.enabled",
+        kind: ValueType::Boolean,
+        default: None,
+        doc: "Whether the module logs at all",
+    },
+    ConfigKey {
+        path: "imag.logging.modules.*.level",
+        kind: ValueType::String,
+        default: None,
+        doc: "The log level of the module",
+    },
+    ConfigKey {
+        path: "imag.logging.modules.*.destinations",
+        kind: ValueType::Array,
+        default: None,
+        doc: "The files the module logs to, in addition to the global destinations",
+    },
+];
+
 /// Get a new configuration object.
 ///
 /// The passed runtimepath is used for searching the configuration file, whereas several file
@@ -35,38 +156,15 @@ use error::ResultExt;
 ///
 /// If that doesn't work either, an error is returned.
 pub fn fetch_config(searchpath: &PathBuf) -> Result<Value> {
-    use std::env;
     use std::fs::File;
     use std::io::Read;
     use std::io::Write;
     use std::io::stderr;
 
-    use xdg_basedir;
-    use itertools::Itertools;
-
-    use libimagutil::variants::generate_variants as gen_vars;
     use libimagerror::trace::trace_error;
 
-    let variants = vec!["config", "config.toml", "imagrc", "imagrc.toml"];
-    let modifier = |base: &PathBuf, v: &'static str| {
-        let mut base = base.clone();
-        base.push(String::from(v));
-        base
-    };
-
-    let vals = vec![
-        vec![searchpath.clone()],
-        gen_vars(searchpath, variants.clone(), &modifier),
-
-        env::var("HOME").map(|home| gen_vars(&PathBuf::from(home), variants.clone(), &modifier))
-                        .unwrap_or(vec![]),
-
-        xdg_basedir::get_data_home().map(|data_dir| gen_vars(&data_dir, variants.clone(), &modifier))
-                                    .unwrap_or(vec![]),
-    ];
-
-    Itertools::flatten(vals.iter())
-        .filter(|path| path.exists() && path.is_file())
+    config_file_candidates(searchpath)
+        .iter()
         .filter_map(|path| {
             let content = {
                 let f = File::open(path);
@@ -99,6 +197,42 @@ pub fn fetch_config(searchpath: &PathBuf) -> Result<Value> {
         .ok_or(RE::from_kind(REK::ConfigNoConfigFileFound))
 }
 
+/// Get all paths where a user configuration file may live, in the order they are tried.
+///
+/// The `searchpath` itself comes first, then the variants of the configuration file name in
+/// the `searchpath`, the home directory and the XDG data directory.
+fn config_file_candidates(searchpath: &PathBuf) -> Vec<PathBuf> {
+    use std::env;
+
+    use xdg_basedir;
+    use itertools::Itertools;
+
+    use libimagutil::variants::generate_variants as gen_vars;
+
+    let variants = vec!["config", "config.toml", "imagrc", "imagrc.toml"];
+    let modifier = |base: &PathBuf, v: &'static str| {
+        let mut base = base.clone();
+        base.push(String::from(v));
+        base
+    };
+
+    let vals = vec![
+        vec![searchpath.clone()],
+        gen_vars(searchpath, variants.clone(), &modifier),
+
+        env::var("HOME").map(|home| gen_vars(&PathBuf::from(home), variants.clone(), &modifier))
+                        .unwrap_or(vec![]),
+
+        xdg_basedir::get_data_home().map(|data_dir| gen_vars(&data_dir, variants.clone(), &modifier))
+                                    .unwrap_or(vec![]),
+    ];
+
+    Itertools::flatten(vals.iter())
+        .filter(|path| path.exists() && path.is_file())
+        .cloned()
+        .collect()
+}
+
 /// Override the configuration.
 /// The `v` parameter is expected to contain 'key=value' pairs where the key is a path in the
 /// TOML tree, the value to be an appropriate value.
@@ -157,6 +291,501 @@ fn into_value(value: &Value, s: String) -> Option<Value> {
     }
 }
 
+/// The path of the system-wide configuration file
+pub const SYSTEM_CONFIG_PATH : &'static str = "/etc/imag/imagrc.toml";
+
+/// The name of the per-project configuration file
+///
+/// It is searched in the current working directory and all of its parents.
+pub const PROJECT_CONFIG_FILE_NAME : &'static str = ".imagrc.toml";
+
+/// Path of the directories whose per-project configuration is loaded
+///
+/// A per-project configuration file can be put in any directory, for example in a cloned
+/// repository, and it can set commands like `rt.editor`. So it is only loaded if the user (or the
+/// system-wide configuration) trusts the directory it is in.
+pub const TRUSTED_PROJECTS_CONFIG_PATH : &'static str = "imag.trusted-projects";
+
+/// The layers a configuration is merged from, ordered from lowest to highest precedence
+///
+/// The per-store configuration is below the user configuration, because a store can be shared
+/// with others, who should not be able to override the settings of the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    System,
+    Store,
+    User,
+    Project,
+    Environment,
+    CommandLine,
+}
+
+impl Layer {
+
+    /// Get the layer for a name as printed by the `Display` implementation
+    pub fn from_name(name: &str) -> Option<Layer> {
+        match name {
+            "system"      => Some(Layer::System),
+            "user"        => Some(Layer::User),
+            "store"       => Some(Layer::Store),
+            "project"     => Some(Layer::Project),
+            "environment" => Some(Layer::Environment),
+            "commandline" => Some(Layer::CommandLine),
+            _             => None,
+        }
+    }
+
+}
+
+impl Display for Layer {
+
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let name = match *self {
+            Layer::System      => "system",
+            Layer::User        => "user",
+            Layer::Store       => "store",
+            Layer::Project     => "project",
+            Layer::Environment => "environment",
+            Layer::CommandLine => "commandline",
+        };
+
+        write!(f, "{}", name)
+    }
+
+}
+
+/// Where a configuration value came from
+///
+/// The source is the file the value was read from, the name of the environment variable or
+/// the commandline flag which set it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    layer: Layer,
+    source: String,
+}
+
+impl Origin {
+
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+}
+
+impl Display for Origin {
+
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{} ({})", self.layer, self.source)
+    }
+
+}
+
+/// A configuration merged from several layers
+///
+/// Tables are merged recursively, all other values (including arrays) of a higher layer replace
+/// the ones of lower layers. For each value, the origin is remembered.
+///
+/// A configuration file may include other files with an `include = ["path", ...]` key. Paths are
+/// relative to the including file. Included files are merged before the including file, so the
+/// including file wins.
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    value: Value,
+    origins: BTreeMap<String, Origin>,
+    files: BTreeMap<Layer, PathBuf>,
+}
+
+impl LayeredConfig {
+
+    /// Load the configuration for the store at `storepath`
+    ///
+    /// The following files are merged, later ones overriding values of earlier ones:
+    ///
+    /// 1. The system-wide configuration at `SYSTEM_CONFIG_PATH`
+    /// 1. The per-store configuration, `STORE_CONFIG_FILE_NAME` inside the store
+    /// 1. The user configuration, searched from `searchpath` like `fetch_config()` does
+    /// 1. The per-project configuration, `PROJECT_CONFIG_FILE_NAME` in `workdir` or its parents,
+    ///    if its directory is listed in `TRUSTED_PROJECTS_CONFIG_PATH` by the files before
+    ///
+    /// Returns `None` if none of these files exists.
+    pub fn load(searchpath: &PathBuf, storepath: &PathBuf, workdir: Option<&PathBuf>)
+        -> Result<Option<LayeredConfig>>
+    {
+        let mut config     = LayeredConfig::empty();
+        let store_file     = storepath.join(STORE_CONFIG_FILE_NAME);
+        let project_file   = workdir
+            .and_then(|dir| find_project_config(dir))
+            .and_then(|file| if is_same_file(&file, &store_file) { None } else { Some(file) });
+
+        let files = vec![
+            (Layer::System, Some(PathBuf::from(SYSTEM_CONFIG_PATH))),
+            (Layer::Store, Some(store_file)),
+            (Layer::User, config_file_candidates(searchpath).into_iter().next()),
+        ];
+
+        for (layer, file) in files {
+            if let Some(file) = file {
+                config.load_file(layer, file)?;
+            }
+        }
+
+        if let Some(file) = project_file {
+            if is_trusted_project(&config.value, &file)? {
+                config.load_file(Layer::Project, file)?;
+            } else {
+                warn!("Ignoring {}, its directory is not listed in {}",
+                      file.display(),
+                      TRUSTED_PROJECTS_CONFIG_PATH);
+            }
+        }
+
+        if config.files.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(config))
+        }
+    }
+
+    fn empty() -> LayeredConfig {
+        LayeredConfig {
+            value: Value::Table(BTreeMap::new()),
+            origins: BTreeMap::new(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    fn load_file(&mut self, layer: Layer, file: PathBuf) -> Result<()> {
+        if file.is_file() {
+            debug!("Loading {} configuration from {:?}", layer, file);
+            self.merge_file(layer, &file, &mut vec![])?;
+            let _ = self.files.insert(layer, file);
+        }
+
+        Ok(())
+    }
+
+    fn merge_file(&mut self, layer: Layer, path: &Path, visited: &mut Vec<PathBuf>) -> Result<()> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if visited.contains(&path) {
+            return Err(RE::from_kind(REK::ConfigIncludeCycle(path.display().to_string())));
+        }
+
+        let mut value = read_config_file(&path)?;
+
+        visited.push(path.clone());
+        for include in take_includes(&mut value)? {
+            let include = path
+                .parent()
+                .map(|dir| dir.join(&include))
+                .unwrap_or_else(|| PathBuf::from(&include));
+
+            self.merge_file(layer, &include, visited)?;
+        }
+        let _ = visited.pop();
+
+        self.merge(layer, path.display().to_string(), value);
+        Ok(())
+    }
+
+    fn merge(&mut self, layer: Layer, source: String, value: Value) {
+        let origin = Origin {
+            layer: layer,
+            source: source,
+        };
+
+        merge_value(&mut self.value, value, "", &origin, &mut self.origins)
+    }
+
+    /// Override values from environment variables
+    ///
+    /// The value at `section.key` is overridden by the variable `IMAG_SECTION_KEY`, dashes in
+    /// the path are replaced by underscores, too. Only values which are already present in the
+    /// configuration can be overridden and the new value must be convertible to their type.
+    pub fn apply_env<I>(&mut self, vars: I) -> Result<()>
+        where I: IntoIterator<Item = (String, String)>
+    {
+        let names = self.origins
+            .keys()
+            .map(|path| (env_var_name(path), path.clone()))
+            .collect::<Vec<(String, String)>>();
+
+        for (var, value) in vars {
+            for &(ref name, ref path) in names.iter() {
+                if *name != var {
+                    continue
+                }
+
+                let origin = Origin {
+                    layer: Layer::Environment,
+                    source: var.clone(),
+                };
+
+                self.set_existing(path, value.clone(), origin)
+                    .chain_err(|| REK::ConfigEnvOverrideError(var.clone()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Override values from 'key=value' pairs as passed with `--override-config`
+    ///
+    /// Like with `override_config()`, the key must be present in the configuration and the value
+    /// must be convertible to its type.
+    pub fn apply_overrides(&mut self, specs: Vec<String>) -> Result<()> {
+        use libimagutil::key_value_split::IntoKeyValue;
+
+        for spec in specs {
+            let (key, value) : (String, String) = match spec.clone().into_kv() {
+                Some(kv) => kv.into(),
+                None     => {
+                    warn!("Could not split '{}' at '=' - will be ignored", spec);
+                    continue
+                },
+            };
+
+            let origin = Origin {
+                layer: Layer::CommandLine,
+                source: String::from("--override-config"),
+            };
+
+            self.set_existing(&key, value, origin)
+                .chain_err(|| REK::ConfigOverrideError)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_existing(&mut self, path: &str, value: String, origin: Origin) -> Result<()> {
+        use toml_query::read::TomlValueReadExt;
+        use toml_query::insert::TomlValueInsertExt;
+
+        let new = {
+            let current = self.value
+                .read(path)?
+                .ok_or_else(|| RE::from_kind(REK::ConfigOverrideKeyNotAvailable))?;
+
+            into_value(current, value)
+                .ok_or_else(|| RE::from_kind(REK::ConfigOverrideTypeNotMatching))?
+        };
+
+        info!("Successfully overridden: {} = {} from {}", path, new, origin);
+        let _ = self.value.insert(path, new)?;
+        remove_origins(&mut self.origins, path);
+        let _ = self.origins.insert(String::from(path), origin);
+        Ok(())
+    }
+
+    /// Get the merged configuration
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Get the origin of the value at `path`
+    ///
+    /// Only values which are not tables have an origin, use `origins_below()` for tables.
+    pub fn origin(&self, path: &str) -> Option<&Origin> {
+        self.origins.get(path)
+    }
+
+    /// Get the paths and origins of all values at or below `path`
+    pub fn origins_below(&self, path: &str) -> Vec<(&String, &Origin)> {
+        self.origins
+            .iter()
+            .filter(|&(key, _)| is_below(key, path))
+            .collect()
+    }
+
+    /// Get the files which were loaded, without the included ones
+    pub fn files(&self) -> &BTreeMap<Layer, PathBuf> {
+        &self.files
+    }
+
+}
+
+/// Set `key` to `value` in the configuration file at `path`
+///
+/// The value is parsed as TOML value, if that fails it is used as a string. The file is created if
+/// it does not exist yet. Note that the file is rewritten, so comments in it get lost.
+pub fn set_in_file(path: &Path, key: &str, value: &str) -> Result<()> {
+    use std::fs::File;
+    use std::fs::create_dir_all;
+    use std::io::Write;
+
+    use toml_query::insert::TomlValueInsertExt;
+
+    let mut config = if path.exists() {
+        read_config_file(path)?
+    } else {
+        Value::Table(BTreeMap::new())
+    };
+
+    let _ = config.insert(key, parse_value(value))?;
+
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir)?;
+    }
+
+    let content = ::toml::ser::to_string(&config)?;
+    File::create(path)?
+        .write_all(content.as_bytes())
+        .map_err(RE::from)
+}
+
+fn parse_value(s: &str) -> Value {
+    ::toml::de::from_str::<Value>(&format!("value = {}", s))
+        .ok()
+        .and_then(|v| match v {
+            Value::Table(mut t) => t.remove("value"),
+            _                   => None,
+        })
+        .unwrap_or_else(|| Value::String(String::from(s)))
+}
+
+fn read_config_file(path: &Path) -> Result<Value> {
+    use std::fs::File;
+    use std::io::Read;
+
+    let mut content = String::new();
+    let _ = File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut content))
+        .chain_err(|| REK::ConfigFileError(path.display().to_string()))?;
+
+    ::toml::de::from_str(&content).chain_err(|| REK::ConfigFileError(path.display().to_string()))
+}
+
+fn take_includes(value: &mut Value) -> Result<Vec<String>> {
+    let type_error = || RE::from_kind(REK::ConfigTypeError(String::from("include"), "Array<String>"));
+
+    let include = match *value {
+        Value::Table(ref mut table) => table.remove("include"),
+        _                           => None,
+    };
+
+    match include {
+        None                      => Ok(vec![]),
+        Some(Value::String(s))    => Ok(vec![s]),
+        Some(Value::Array(elems)) => elems
+            .into_iter()
+            .map(|elem| match elem {
+                Value::String(s) => Ok(s),
+                _                => Err(type_error()),
+            })
+            .collect(),
+        Some(_)                   => Err(type_error()),
+    }
+}
+
+fn find_project_config(workdir: &PathBuf) -> Option<PathBuf> {
+    let mut dir = Some(workdir.as_path());
+    while let Some(d) = dir {
+        let file = d.join(PROJECT_CONFIG_FILE_NAME);
+        if file.is_file() {
+            return Some(file)
+        }
+        dir = d.parent();
+    }
+
+    None
+}
+
+/// Whether the directory of the per-project configuration file `file` is listed in
+/// `TRUSTED_PROJECTS_CONFIG_PATH` in `config`
+fn is_trusted_project(config: &Value, file: &Path) -> Result<bool> {
+    use toml_query::read::TomlValueReadExt;
+
+    let type_error = || RE::from_kind(REK::ConfigTypeError(String::from(TRUSTED_PROJECTS_CONFIG_PATH), "Array<String>"));
+    let dir        = match file.parent() {
+        Some(dir) => dir,
+        None      => return Ok(false),
+    };
+
+    match config.read(TRUSTED_PROJECTS_CONFIG_PATH)? {
+        None                         => Ok(false),
+        Some(&Value::Array(ref ary)) => {
+            for trusted in ary {
+                let trusted = trusted.as_str().ok_or_else(&type_error)?;
+                if is_same_file(Path::new(trusted), dir) {
+                    return Ok(true)
+                }
+            }
+
+            Ok(false)
+        },
+        Some(_) => Err(type_error()),
+    }
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _              => a == b,
+    }
+}
+
+fn merge_value(target: &mut Value,
+               source: Value,
+               path: &str,
+               origin: &Origin,
+               origins: &mut BTreeMap<String, Origin>)
+{
+    match source {
+        Value::Table(table) => {
+            if !is_match!(*target, Value::Table(_)) {
+                remove_origins(origins, path);
+                *target = Value::Table(BTreeMap::new());
+            }
+
+            if let Value::Table(ref mut target) = *target {
+                for (key, value) in table {
+                    let child_path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+
+                    let child = target.entry(key).or_insert_with(|| Value::Table(BTreeMap::new()));
+                    merge_value(child, value, &child_path, origin, origins);
+                }
+            }
+        },
+
+        other => {
+            remove_origins(origins, path);
+            *target = other;
+            let _ = origins.insert(String::from(path), origin.clone());
+        },
+    }
+}
+
+fn remove_origins(origins: &mut BTreeMap<String, Origin>, path: &str) {
+    let stale = origins
+        .keys()
+        .filter(|key| is_below(key, path))
+        .cloned()
+        .collect::<Vec<String>>();
+
+    for key in stale {
+        let _ = origins.remove(&key);
+    }
+}
+
+fn is_below(key: &str, path: &str) -> bool {
+    path.is_empty() || key == path || (key.starts_with(path) && key[path.len()..].starts_with('.'))
+}
+
+fn env_var_name(path: &str) -> String {
+    format!("IMAG_{}", path.to_uppercase().replace('.', "_").replace('-', "_"))
+}
+
 pub trait InternalConfiguration {
     fn enable_logging(&self) -> bool {
         true
@@ -169,3 +798,151 @@ pub trait InternalConfiguration {
 
 impl<'a> InternalConfiguration for App<'a, 'a> {}
 
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write;
+
+    use toml::de::from_str;
+    use toml_query::read::TomlValueReadExt;
+
+    use super::*;
+
+    fn config(layers: Vec<(Layer, &'static str, &'static str)>) -> LayeredConfig {
+        let mut config = LayeredConfig::empty();
+        for (layer, source, content) in layers {
+            config.merge(layer, String::from(source), from_str::<Value>(content).unwrap());
+        }
+        config
+    }
+
+    fn read<'a>(config: &'a LayeredConfig, path: &str) -> &'a Value {
+        config.value().read(path).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let config = config(vec![
+            (Layer::System, "system", "[a]\nb = 1\nc = \"x\"\n[d]\ne = [1, 2]\n"),
+            (Layer::Store,  "store",  "f = true\n"),
+            (Layer::User,   "user",   "[a]\nb = 2\n[d]\ne = [3]\n"),
+        ]);
+
+        assert_eq!(*read(&config, "a.b"), Value::Integer(2));
+        assert_eq!(*read(&config, "a.c"), Value::String(String::from("x")));
+        assert_eq!(*read(&config, "d.e"), Value::Array(vec![Value::Integer(3)]));
+        assert_eq!(*read(&config, "f"), Value::Boolean(true));
+
+        assert_eq!(config.origin("a.b").unwrap().layer(), Layer::User);
+        assert_eq!(config.origin("a.c").unwrap().source(), "system");
+        assert_eq!(config.origin("f").unwrap().layer(), Layer::Store);
+        assert!(config.origin("a").is_none());
+        assert_eq!(config.origins_below("a").len(), 2);
+    }
+
+    #[test]
+    fn test_table_replaces_value() {
+        let config = config(vec![
+            (Layer::System, "system", "a = 1\n"),
+            (Layer::User,   "user",   "[a]\nb = 2\n"),
+        ]);
+
+        assert!(config.origin("a").is_none());
+        assert_eq!(config.origin("a.b").unwrap().layer(), Layer::User);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = config(vec![(Layer::User, "user", "[store]\nimplicit-create = false\n")]);
+
+        let vars = vec![
+            (String::from("IMAG_STORE_IMPLICIT_CREATE"), String::from("true")),
+            (String::from("IMAG_STORE_UNKNOWN"), String::from("true")),
+        ];
+        assert!(config.apply_env(vars).is_ok());
+        assert_eq!(*read(&config, "store.implicit-create"), Value::Boolean(true));
+        assert_eq!(config.origin("store.implicit-create").unwrap().layer(), Layer::Environment);
+        assert!(config.value().read("store.unknown").unwrap().is_none());
+
+        let vars = vec![(String::from("IMAG_STORE_IMPLICIT_CREATE"), String::from("no"))];
+        assert!(config.apply_env(vars).is_err());
+    }
+
+    #[test]
+    fn test_commandline_overrides() {
+        let mut config = config(vec![(Layer::User, "user", "[a]\nb = 1\n")]);
+
+        assert!(config.apply_overrides(vec![String::from("a.b=5")]).is_ok());
+        assert_eq!(*read(&config, "a.b"), Value::Integer(5));
+        assert_eq!(config.origin("a.b").unwrap().layer(), Layer::CommandLine);
+
+        assert!(config.apply_overrides(vec![String::from("a.c=5")]).is_err());
+    }
+
+    #[test]
+    fn test_load_layers() {
+        let dir     = env::temp_dir().join(format!("imag-config-load-test-{}", ::std::process::id()));
+        let store   = dir.join("store");
+        let project = dir.join("project");
+        let workdir = project.join("sub");
+        let _       = ::std::fs::create_dir_all(&store).unwrap();
+        let _       = ::std::fs::create_dir_all(&workdir).unwrap();
+
+        let write = |path: PathBuf, content: &str| {
+            File::create(path).unwrap().write_all(content.as_bytes()).unwrap();
+        };
+        write(dir.join("user.toml"), "[rt]\neditor = \"vim\"\n");
+        write(store.join(STORE_CONFIG_FILE_NAME), "[rt]\neditor = \"store-editor\"\n[store]\nimplicit-create = true\n");
+        write(project.join(PROJECT_CONFIG_FILE_NAME), "[rt]\neditor = \"project-editor\"\n");
+
+        let untrusted = LayeredConfig::load(&dir.join("user.toml"), &store, Some(&workdir)).unwrap().unwrap();
+
+        let trust = format!("[rt]\neditor = \"vim\"\n[imag]\ntrusted-projects = [{:?}]\n",
+                            project.display().to_string());
+        write(dir.join("user.toml"), &trust);
+        let trusted = LayeredConfig::load(&dir.join("user.toml"), &store, Some(&workdir)).unwrap().unwrap();
+        let _       = ::std::fs::remove_dir_all(&dir);
+
+        // The user configuration overrides the one of the store
+        assert_eq!(*read(&untrusted, "rt.editor"), Value::String(String::from("vim")));
+        assert_eq!(*read(&untrusted, "store.implicit-create"), Value::Boolean(true));
+        assert_eq!(untrusted.origin("rt.editor").unwrap().layer(), Layer::User);
+
+        assert_eq!(*read(&trusted, "rt.editor"), Value::String(String::from("project-editor")));
+        assert_eq!(trusted.origin("rt.editor").unwrap().layer(), Layer::Project);
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = env::temp_dir().join(format!("imag-config-test-{}", ::std::process::id()));
+        let _   = ::std::fs::create_dir_all(&dir).unwrap();
+
+        let write = |name: &str, content: &str| {
+            File::create(dir.join(name)).unwrap().write_all(content.as_bytes()).unwrap();
+        };
+        write("main.toml", "include = [\"other.toml\"]\na = 1\n");
+        write("other.toml", "a = 2\nb = 3\n");
+        write("cycle.toml", "include = \"cycle.toml\"\n");
+
+        let mut config = LayeredConfig::empty();
+        let included   = config.merge_file(Layer::User, &dir.join("main.toml"), &mut vec![]);
+        let cycle      = LayeredConfig::empty().merge_file(Layer::User, &dir.join("cycle.toml"), &mut vec![]);
+        let _          = ::std::fs::remove_dir_all(&dir);
+
+        assert!(included.is_ok());
+        assert!(cycle.is_err());
+        assert_eq!(*read(&config, "a"), Value::Integer(1));
+        assert_eq!(*read(&config, "b"), Value::Integer(3));
+        assert!(config.value().read("include").unwrap().is_none());
+        assert!(config.origin("b").unwrap().source().ends_with("other.toml"));
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("1"), Value::Integer(1));
+        assert_eq!(parse_value("[\"a\"]"), Value::Array(vec![Value::String(String::from("a"))]));
+        assert_eq!(parse_value("some text"), Value::String(String::from("some text")));
+    }
+}
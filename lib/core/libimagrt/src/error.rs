@@ -22,11 +22,17 @@ error_chain! {
         RuntimeError, RuntimeErrorKind, ResultExt, Result;
     }
 
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+    }
+
     foreign_links {
         IO(::std::io::Error);
         TomlDeError(::toml::de::Error);
         TomlQueryError(::toml_query::error::Error);
         HandlebarsTemplateError(::handlebars::TemplateError);
+        JsonError(::serde_json::Error);
+        TomlSerError(::toml::ser::Error);
     }
 
     errors {
@@ -35,6 +41,26 @@ error_chain! {
             display("Could not instantiate")
         }
 
+        UnknownOutputFormat(s: String) {
+            description("Unknown output format")
+            display("Unknown output format: '{}'", s)
+        }
+
+        MalformedPipeInput(s: String) {
+            description("Malformed input on stdin, expected store ids or JSON records with an id")
+            display("Malformed input on stdin, expected store ids or JSON records with an id: '{}'", s)
+        }
+
+        NoIdsPassed {
+            description("No ids passed, neither on the commandline nor on stdin")
+            display("No ids passed, neither on the commandline nor on stdin")
+        }
+
+        NoPluginInfo(executable: String) {
+            description("Command does not print plugin info")
+            display("Command does not print plugin info: '{}'", executable)
+        }
+
         IOError {
             description("IO Error")
             display("IO Error")
@@ -70,6 +96,16 @@ error_chain! {
             display("Invalid log level specification: Only 'trace', 'debug', 'info', 'warn', 'error' are allowed")
         }
 
+        InvalidLogFileFormat(format: String) {
+            description("Invalid log file format: Only 'text' and 'json' are allowed")
+            display("Invalid log file format '{}': Only 'text' and 'json' are allowed", format)
+        }
+
+        InvalidLogRotationInterval(interval: String) {
+            description("Invalid log rotation interval: Only 'hourly', 'daily', 'weekly' and 'never' are allowed")
+            display("Invalid log rotation interval '{}': Only 'hourly', 'daily', 'weekly' and 'never' are allowed", interval)
+        }
+
         ConfigMissingLoggingFormatTrace {
             description("Missing config for logging format for trace logging")
             display("Missing config for logging format for trace logging")
@@ -105,6 +141,21 @@ error_chain! {
             display("Configuration: No config file found")
         }
 
+        ConfigFileError(path: String) {
+            description("Configuration: Could not read config file")
+            display("Configuration: Could not read config file '{}'", path)
+        }
+
+        ConfigIncludeCycle(path: String) {
+            description("Configuration: Config file includes itself")
+            display("Configuration: Config file includes itself: '{}'", path)
+        }
+
+        ConfigEnvOverrideError(var: String) {
+            description("Configuration: Config override from environment failed")
+            display("Configuration: Config override from environment variable '{}' failed", var)
+        }
+
         ConfigOverrideError {
             description("Configuration: Config override error")
             display("Configuration: Config override error")
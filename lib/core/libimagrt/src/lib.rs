@@ -48,6 +48,10 @@ extern crate toml;
 extern crate toml_query;
 #[macro_use] extern crate is_match;
 extern crate atty;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
+extern crate serde_json;
+extern crate chrono;
 
 extern crate libimagstore;
 extern crate libimagutil;
@@ -58,7 +62,11 @@ pub mod error;
 pub mod configuration;
 pub mod logger;
 pub mod io;
+pub mod output;
+pub mod pipe;
+pub mod plugin;
 pub mod runtime;
+pub mod schema;
 pub mod setup;
 pub mod spec;
 pub mod version;
@@ -17,12 +17,19 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+use std::io::ErrorKind;
 use std::io::Write;
 use std::io::stderr;
 use std::collections::BTreeMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::ops::Deref;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use error::RuntimeErrorKind as EK;
 use error::RuntimeError as RE;
@@ -35,13 +42,14 @@ use toml::Value;
 use toml_query::read::TomlValueReadExt;
 use toml_query::read::TomlValueReadTypeExt;
 use handlebars::Handlebars;
+use chrono::Local;
 
 type ModuleName = String;
 type Result<T> = ::std::result::Result<T, RE>;
 
 enum LogDestination {
     Stderr,
-    File(Arc<Mutex<::std::fs::File>>),
+    File(Arc<Mutex<LogFile>>),
 }
 
 impl Default for LogDestination {
@@ -58,6 +66,303 @@ struct ModuleSettings {
     destinations:   Option<Vec<LogDestination>>,
 }
 
+/// The format of the log files, messages to stderr are always text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFileFormat {
+    /// Messages rendered with the `imag.logging.format` templates
+    Text,
+
+    /// One `JsonRecord` per line
+    Json,
+}
+
+/// A log record as it is written to log files in the JSON format
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JsonRecord {
+    pub timestamp: String,
+    pub level: String,
+    pub module: String,
+    pub target: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub command: String,
+    pub pid: u32,
+
+    /// The id of the entry the store worked on when the record was logged
+    #[serde(default)]
+    pub store_id: Option<String>,
+
+    pub message: String,
+}
+
+impl JsonRecord {
+
+    /// Parse a line of a JSON log file, None if it is not a record
+    pub fn parse(line: &str) -> Option<JsonRecord> {
+        ::serde_json::from_str(line).ok()
+    }
+
+    /// The level of the record, None if it is not a valid level
+    pub fn level(&self) -> Option<Level> {
+        match_log_level_str(&self.level.to_lowercase()).ok()
+    }
+
+}
+
+/// When log files are rotated
+///
+/// A log file is rotated when writing to it would make it bigger than `max_size` bytes or when it
+/// was last written in an earlier interval. Intervals are counted from the unix epoch, so a daily
+/// rotation happens at midnight UTC. The rotated files are named `<file>.1` (the newest) to
+/// `<file>.<keep>`, older ones are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    max_size : Option<u64>,
+    interval : Option<u64>,
+    keep     : usize,
+}
+
+impl Default for Rotation {
+    fn default() -> Rotation {
+        Rotation {
+            max_size : None,
+            interval : None,
+            keep     : 5,
+        }
+    }
+}
+
+impl Rotation {
+
+    /// The number of the interval `secs` (seconds since the unix epoch) is in
+    fn period(&self, secs: u64) -> Option<u64> {
+        self.interval.map(|i| secs / i)
+    }
+
+    /// Whether a file of `size` bytes which was last written in `period` has to be rotated before
+    /// `len` bytes are written at `now`
+    fn is_due(&self, size: u64, len: u64, period: Option<u64>, now: u64) -> bool {
+        size > 0 && (self.max_size.map(|max| size + len > max).unwrap_or(false) ||
+                     period != self.period(now))
+    }
+
+}
+
+/// A log file destination, which is rotated as configured
+struct LogFile {
+    path     : PathBuf,
+    file     : File,
+    size     : u64,
+    period   : Option<u64>,
+    rotation : Rotation,
+}
+
+impl LogFile {
+
+    fn open(path: PathBuf, rotation: Rotation) -> ::std::io::Result<LogFile> {
+        let now = secs_since_epoch(SystemTime::now());
+
+        if let Ok(metadata) = ::std::fs::metadata(&path) {
+            let written = metadata.modified().map(secs_since_epoch).unwrap_or(now);
+
+            if rotation.is_due(metadata.len(), 0, rotation.period(written), now) {
+                rotate_files(&path, rotation.keep)?;
+            }
+        }
+
+        let file = OpenOptions::new().append(true).create(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(LogFile {
+            path     : path,
+            file     : file,
+            size     : size,
+            period   : rotation.period(now),
+            rotation : rotation,
+        })
+    }
+
+    /// Write `text` as a line, rotating the files before if necessary
+    ///
+    /// Other imag processes may write to and rotate the same file. So the file is reopened if
+    /// the path does not point to it anymore, and the size is read from the file itself.
+    fn write_line(&mut self, text: &str) -> ::std::io::Result<()> {
+        let now = secs_since_epoch(SystemTime::now());
+        let len = text.len() as u64 + 1;
+
+        if !is_same_file(&self.file, &self.path) {
+            self.reopen(now)?;
+        }
+
+        self.size = self.file.metadata()?.len();
+
+        if self.rotation.is_due(self.size, len, self.period, now) {
+            rotate_files(&self.path, self.rotation.keep)?;
+            self.reopen(now)?;
+        }
+
+        write!(self.file, "{}\n", text)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn reopen(&mut self, now: u64) -> ::std::io::Result<()> {
+        self.file   = OpenOptions::new().append(true).create(true).open(&self.path)?;
+        self.size   = self.file.metadata()?.len();
+        self.period = self.rotation.period(now);
+        Ok(())
+    }
+
+}
+
+/// The log files of all destinations
+///
+/// A file which is used by several destinations (for example globally and for a module) is only
+/// opened once, so it is also only rotated once.
+struct LogFiles {
+    rotation : Rotation,
+    files    : BTreeMap<PathBuf, Arc<Mutex<LogFile>>>,
+}
+
+impl LogFiles {
+
+    fn new(rotation: Rotation) -> LogFiles {
+        LogFiles {
+            rotation : rotation,
+            files    : BTreeMap::new(),
+        }
+    }
+
+    fn open(&mut self, path: &Path) -> ::std::io::Result<Arc<Mutex<LogFile>>> {
+        let path = canonical_log_path(path);
+        if let Some(file) = self.files.get(&path) {
+            return Ok(file.clone());
+        }
+
+        let file = Arc::new(Mutex::new(LogFile::open(path.clone(), self.rotation)?));
+        let _    = self.files.insert(path, file.clone());
+        Ok(file)
+    }
+
+}
+
+/// The canonical path of a log file, which does not have to exist yet
+fn canonical_log_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _                                               => Path::new("."),
+    };
+
+    match (parent.canonicalize(), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _                     => path.to_path_buf(),
+    }
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// The path of the `n`th rotated file of the log file `path`
+pub fn rotated_file(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// The rotated files of the log file `path` which exist, the oldest first
+pub fn rotated_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = (1..)
+        .map(|n| rotated_file(path, n))
+        .take_while(|file| file.exists())
+        .collect::<Vec<_>>();
+
+    files.reverse();
+    files
+}
+
+/// Whether `file` is still the file at `path`, which is not the case after another process
+/// rotated it
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), ::std::fs::metadata(path)) {
+        (Ok(open), Ok(current)) => open.dev() == current.dev() && open.ino() == current.ino(),
+        _                       => false,
+    }
+}
+
+/// Whether `file` is still the file at `path`, which is not the case after another process
+/// rotated it
+///
+/// Without inodes, the file is assumed to be the same if it has the same size.
+#[cfg(not(unix))]
+fn is_same_file(file: &File, path: &Path) -> bool {
+    match (file.metadata(), ::std::fs::metadata(path)) {
+        (Ok(open), Ok(current)) => open.len() == current.len(),
+        _                       => false,
+    }
+}
+
+/// Rotate the log file `path`
+///
+/// Other processes may rotate the same files at the same time, so a file which is already gone
+/// was moved by them and is not an error.
+fn rotate_files(path: &Path, keep: usize) -> ::std::io::Result<()> {
+    if keep == 0 {
+        return ignore_not_found(::std::fs::remove_file(path))
+    }
+
+    for n in (1..keep).rev() {
+        let from = rotated_file(path, n);
+        if from.exists() {
+            let _ = ignore_not_found(::std::fs::rename(&from, rotated_file(path, n + 1)))?;
+        }
+    }
+
+    ignore_not_found(::std::fs::rename(path, rotated_file(path, 1)))
+}
+
+fn ignore_not_found(result: ::std::io::Result<()>) -> ::std::io::Result<()> {
+    match result {
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        other                                         => other,
+    }
+}
+
+/// The log files configured in `imag.logging.destinations` and the destinations of the modules
+pub fn configured_log_files(config: &Value) -> Vec<PathBuf> {
+    let mut destinations = vec![];
+
+    if let Ok(Some(&Value::Array(ref a))) = config.read("imag.logging.destinations") {
+        destinations.extend(a.iter());
+    }
+
+    if let Ok(Some(&Value::Table(ref t))) = config.read("imag.logging.modules") {
+        for module in t.values() {
+            if let Ok(Some(&Value::Array(ref a))) = module.read("destinations") {
+                destinations.extend(a.iter());
+            }
+        }
+    }
+
+    let mut files = destinations
+        .into_iter()
+        .filter_map(Value::as_str)
+        .filter(|d| *d != "-")
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
+    files.sort();
+    files.dedup();
+    files
+}
+
 /// Logger implementation for `log` crate.
 pub struct ImagLogger {
     global_loglevel     : Level,
@@ -70,6 +375,8 @@ pub struct ImagLogger {
     // global_format_warn  : ,
     // global_format_error : ,
     module_settings     : BTreeMap<ModuleName, ModuleSettings>,
+    file_format         : LogFileFormat,
+    command             : String,
 
     handlebars: Handlebars,
 }
@@ -106,10 +413,23 @@ impl ImagLogger {
             handlebars.register_template_string("ERROR", fmt)?; // name must be uppercase
         }
 
+        let mut files = LogFiles::new(aggregate_rotation(config)?);
+        let command  = ::std::env::args()
+            .next()
+            .and_then(|arg| {
+                Path::new(&arg)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| String::from("imag"));
+
         Ok(ImagLogger {
             global_loglevel     : aggregate_global_loglevel(matches, config)?,
-            global_destinations : aggregate_global_destinations(matches, config)?,
-            module_settings     : aggregate_module_settings(matches, config)?,
+            global_destinations : aggregate_global_destinations(matches, config, &mut files)?,
+            module_settings     : aggregate_module_settings(matches, config, &mut files)?,
+            file_format         : aggregate_file_format(config)?,
+            command             : command,
             handlebars          : handlebars,
         })
     }
@@ -143,7 +463,9 @@ impl Log for ImagLogger {
             return;
         }
 
-        let mut data = BTreeMap::new();
+        let timestamp = Local::now().to_rfc3339();
+        let store_id  = ::libimagstore::logging::current_id().map(|id| format!("{}", id));
+        let mut data  = BTreeMap::new();
 
         {
             data.insert("level",        format!("{}", record.level()));
@@ -152,6 +474,8 @@ impl Log for ImagLogger {
             data.insert("line",         format!("{}", record.line().unwrap_or(0)));
             data.insert("target",       String::from(record.target()));
             data.insert("message",      format!("{}", record.args()));
+            data.insert("timestamp",    timestamp.clone());
+            data.insert("store_id",     store_id.clone().unwrap_or_default());
         }
 
         let logtext = self
@@ -159,6 +483,27 @@ impl Log for ImagLogger {
             .render(&format!("{}", record.level()), &data)
             .unwrap_or_else(|e| format!("Failed rendering logging data: {:?}\n", e));
 
+        let filetext = match self.file_format {
+            LogFileFormat::Text => logtext.clone(),
+            LogFileFormat::Json => {
+                let json = JsonRecord {
+                    timestamp : timestamp,
+                    level     : format!("{}", record.level()),
+                    module    : String::from(record.module_path().unwrap_or("")),
+                    target    : String::from(record.target()),
+                    file      : record.file().map(String::from),
+                    line      : record.line(),
+                    command   : self.command.clone(),
+                    pid       : ::std::process::id(),
+                    store_id  : store_id,
+                    message   : format!("{}", record.args()),
+                };
+
+                ::serde_json::to_string(&json)
+                    .unwrap_or_else(|e| format!("Failed rendering logging data: {:?}", e))
+            },
+        };
+
         let log_to_destination = |d: &LogDestination| match d {
             &LogDestination::Stderr => {
                 let _ = write!(stderr(), "{}\n", logtext);
@@ -168,9 +513,7 @@ impl Log for ImagLogger {
                 let _ = arc_mutex_logdest
                     .deref()
                     .lock()
-                    .map(|mut logdest| {
-                        write!(logdest, "{}\n", logtext)
-                    });
+                    .map(|mut logdest| logdest.write_line(&filetext));
             }
         };
 
@@ -260,18 +603,11 @@ fn aggregate_global_loglevel(matches: &ArgMatches, config: Option<&Value>) -> Re
     }
 }
 
-fn translate_destination(raw: &str) -> Result<LogDestination> {
-    use std::fs::OpenOptions;
-
+fn translate_destination(raw: &str, files: &mut LogFiles) -> Result<LogDestination> {
     match raw {
         "-" => Ok(LogDestination::Stderr),
         other => {
-            OpenOptions::new()
-                .append(true)
-                .create(true)
-                .open(other)
-                .map(Mutex::new)
-                .map(Arc::new)
+            files.open(Path::new(other))
                 .map(LogDestination::File)
                 .chain_err(|| EK::IOLogFileOpenError)
         }
@@ -279,24 +615,24 @@ fn translate_destination(raw: &str) -> Result<LogDestination> {
 }
 
 
-fn translate_destinations(raw: &Vec<Value>) -> Result<Vec<LogDestination>> {
-    raw.iter()
-        .fold(Ok(vec![]), |acc, val| {
-            acc.and_then(|mut v| {
-                let dest = val.as_str()
-                    .ok_or_else(|| {
-                        let path = "imag.logging.modules.<mod>.destinations".to_owned();
-                        let ty   = "Array<String>";
-                        RE::from_kind(EK::ConfigTypeError(path, ty))
-                    })
-                    .and_then(translate_destination)?;
-                v.push(dest);
-                Ok(v)
-            })
-        })
+fn translate_destinations(raw: &Vec<Value>, files: &mut LogFiles) -> Result<Vec<LogDestination>> {
+    let mut destinations = vec![];
+
+    for val in raw {
+        let dest = val.as_str()
+            .ok_or_else(|| {
+                let path = "imag.logging.modules.<mod>.destinations".to_owned();
+                let ty   = "Array<String>";
+                RE::from_kind(EK::ConfigTypeError(path, ty))
+            })?;
+
+        destinations.push(translate_destination(dest, files)?);
+    }
+
+    Ok(destinations)
 }
 
-fn aggregate_global_destinations(matches: &ArgMatches, config: Option<&Value>)
+fn aggregate_global_destinations(matches: &ArgMatches, config: Option<&Value>, files: &mut LogFiles)
     -> Result<Vec<LogDestination>>
 {
 
@@ -310,18 +646,17 @@ fn aggregate_global_destinations(matches: &ArgMatches, config: Option<&Value>)
                 let ty   = "Array";
                 RE::from_kind(EK::ConfigTypeError(path, ty))
             })
-            .and_then(translate_destinations),
+            .and_then(|d| translate_destinations(d, files)),
         None => {
             if let Some(values) = matches.value_of(Runtime::arg_logdest_name()) {
                 // parse logdest specification from commandline
 
-                values.split(",")
-                    .fold(Ok(vec![]), move |acc, dest| {
-                        acc.and_then(|mut v| {
-                            v.push(translate_destination(dest)?);
-                            Ok(v)
-                        })
-                    })
+                let mut destinations = vec![];
+                for dest in values.split(",") {
+                    destinations.push(translate_destination(dest, files)?);
+                }
+
+                Ok(destinations)
             } else {
                 Ok(vec![ LogDestination::default() ])
             }
@@ -377,7 +712,49 @@ fn aggregate_global_format_error(config: Option<&Value>)
                             config)
 }
 
-fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>)
+fn aggregate_file_format(config: Option<&Value>) -> Result<LogFileFormat> {
+    let format = match config {
+        Some(cfg) => cfg.read_string("imag.logging.file-format")?,
+        None      => None,
+    };
+
+    match format.as_ref().map(|s| s.as_str()) {
+        None | Some("text") => Ok(LogFileFormat::Text),
+        Some("json")        => Ok(LogFileFormat::Json),
+        Some(other)         => Err(RE::from_kind(EK::InvalidLogFileFormat(other.to_owned()))),
+    }
+}
+
+fn aggregate_rotation(config: Option<&Value>) -> Result<Rotation> {
+    let mut rotation = Rotation::default();
+
+    let cfg = match config {
+        Some(cfg) => cfg,
+        None      => return Ok(rotation),
+    };
+
+    if let Some(max_size) = cfg.read_int("imag.logging.rotation.max-size")? {
+        rotation.max_size = if max_size > 0 { Some(max_size as u64) } else { None };
+    }
+
+    if let Some(interval) = cfg.read_string("imag.logging.rotation.interval")? {
+        rotation.interval = match interval.as_str() {
+            "hourly" => Some(60 * 60),
+            "daily"  => Some(60 * 60 * 24),
+            "weekly" => Some(60 * 60 * 24 * 7),
+            "never"  => None,
+            other    => return Err(RE::from_kind(EK::InvalidLogRotationInterval(other.to_owned()))),
+        };
+    }
+
+    if let Some(keep) = cfg.read_int("imag.logging.rotation.keep")? {
+        rotation.keep = if keep > 0 { keep as usize } else { 0 };
+    }
+
+    Ok(rotation)
+}
+
+fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>, files: &mut LogFiles)
     -> Result<BTreeMap<ModuleName, ModuleSettings>>
 {
     // Helper macro to return the error from Some(Err(_)) and map everything else to an
@@ -408,7 +785,7 @@ fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>)
                                         let ty = "Array";
                                         RE::from_kind(EK::ConfigTypeError(path, ty))
                                     })
-                                    .and_then(translate_destinations)
+                                    .and_then(|d| translate_destinations(d, files))
                             })
                     };
 
@@ -457,3 +834,160 @@ fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>)
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    use toml::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_rotation_is_due() {
+        let rotation = Rotation {
+            max_size : Some(10),
+            interval : Some(100),
+            keep     : 2,
+        };
+
+        assert!(!rotation.is_due(0, 20, Some(0), 500));
+        assert!(!rotation.is_due(5, 5, Some(5), 500));
+        assert!(rotation.is_due(5, 6, Some(5), 500));
+        assert!(rotation.is_due(5, 1, Some(4), 500));
+        assert!(!Rotation::default().is_due(1000, 1000, None, 500));
+    }
+
+    #[test]
+    fn test_rotate_files() {
+        let dir  = env::temp_dir().join(format!("imag-logger-test-{}", ::std::process::id()));
+        let _    = ::std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("imag.log");
+
+        let rotation = Rotation {
+            max_size : Some(3),
+            interval : None,
+            keep     : 2,
+        };
+
+        {
+            let mut file = LogFile::open(path.clone(), rotation).unwrap();
+            for line in &["a", "b", "c", "d"] {
+                file.write_line(line).unwrap();
+            }
+        }
+
+        let read = |path: &PathBuf| {
+            let mut s = String::new();
+            File::open(path).unwrap().read_to_string(&mut s).unwrap();
+            s
+        };
+
+        let current = read(&path);
+        let files   = rotated_files(&path);
+        let newest  = read(&rotated_file(&path, 1));
+        let oldest  = read(&rotated_file(&path, 2));
+        let _       = ::std::fs::remove_dir_all(&dir);
+
+        assert_eq!(current, "d\n");
+        assert_eq!(newest, "c\n");
+        assert_eq!(oldest, "b\n");
+        assert_eq!(files, vec![rotated_file(&path, 2), rotated_file(&path, 1)]);
+    }
+
+    #[test]
+    fn test_rotation_by_another_process() {
+        let dir  = env::temp_dir().join(format!("imag-logger-concurrent-test-{}", ::std::process::id()));
+        let _    = ::std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("imag.log");
+
+        let rotation = Rotation {
+            max_size : Some(3),
+            interval : None,
+            keep     : 2,
+        };
+
+        {
+            // Two processes which write to the same file
+            let mut first  = LogFile::open(path.clone(), rotation).unwrap();
+            let mut second = LogFile::open(path.clone(), rotation).unwrap();
+
+            first.write_line("a").unwrap();
+            first.write_line("b").unwrap(); // rotates "a\n" away
+            second.write_line("c").unwrap(); // rotates "b\n" away, after reopening the file
+        }
+
+        let read = |path: &PathBuf| {
+            let mut s = String::new();
+            File::open(path).unwrap().read_to_string(&mut s).unwrap();
+            s
+        };
+
+        let current = read(&path);
+        let newest  = read(&rotated_file(&path, 1));
+        let oldest  = read(&rotated_file(&path, 2));
+        let missing = rotate_files(&dir.join("missing.log"), 2);
+        let _       = ::std::fs::remove_dir_all(&dir);
+
+        assert_eq!(current, "c\n");
+        assert_eq!(newest, "b\n");
+        assert_eq!(oldest, "a\n");
+        assert!(missing.is_ok());
+    }
+
+    #[test]
+    fn test_log_files_are_shared() {
+        let dir  = env::temp_dir().join(format!("imag-logger-shared-test-{}", ::std::process::id()));
+        let _    = ::std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("imag.log");
+
+        let mut files = LogFiles::new(Rotation::default());
+        let first     = files.open(&path).unwrap();
+        let second    = files.open(&dir.join(".").join("imag.log")).unwrap();
+        let other     = files.open(&dir.join("other.log")).unwrap();
+        let _         = ::std::fs::remove_dir_all(&dir);
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(2, files.files.len());
+    }
+
+    #[test]
+    fn test_json_record() {
+        let record = JsonRecord {
+            timestamp : String::from("2018-01-01T00:00:00+00:00"),
+            level     : String::from("DEBUG"),
+            module    : String::from("libimagstore::store"),
+            target    : String::from("libimagstore::store"),
+            file      : None,
+            line      : Some(1),
+            command   : String::from("imag-diary"),
+            pid       : 1,
+            store_id  : Some(String::from("diary/default/2018/01/01/00:00:00")),
+            message   : String::from("Creating id"),
+        };
+
+        let line = ::serde_json::to_string(&record).unwrap();
+        assert_eq!(JsonRecord::parse(&line), Some(record.clone()));
+        assert_eq!(record.level(), Some(Level::Debug));
+        assert_eq!(JsonRecord::parse("[imag][DEBUG]: Creating id"), None);
+    }
+
+    #[test]
+    fn test_configured_log_files() {
+        let config = ::toml::de::from_str::<Value>(r#"
+            [imag.logging]
+            destinations = [ "-", "/tmp/imag.log" ]
+
+            [imag.logging.modules.libimagstore]
+            destinations = [ "/tmp/store.log", "/tmp/imag.log" ]
+        "#).unwrap();
+
+        assert_eq!(configured_log_files(&config),
+                   vec![PathBuf::from("/tmp/imag.log"), PathBuf::from("/tmp/store.log")]);
+    }
+
+}
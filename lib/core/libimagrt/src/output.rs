@@ -0,0 +1,176 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Structured output of records, in the format selected with `--format`
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Error as FmtError;
+use std::io::Write;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use error::RuntimeError;
+use error::RuntimeErrorKind;
+
+/// The format commands print their results in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text, as each command defines it
+    Text,
+
+    /// A JSON array of records
+    Json,
+
+    /// A TOML document with an array of tables named "records"
+    Toml,
+}
+
+impl OutputFormat {
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Toml => "toml",
+        }
+    }
+
+    /// Whether the output is meant for machines instead of humans
+    pub fn is_structured(&self) -> bool {
+        *self != OutputFormat::Text
+    }
+
+}
+
+impl Default for OutputFormat {
+    fn default() -> OutputFormat {
+        OutputFormat::Text
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FmtError> {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = RuntimeError;
+
+    fn from_str(s: &str) -> Result<OutputFormat, RuntimeError> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "toml" => Ok(OutputFormat::Toml),
+            other  => Err(RuntimeError::from_kind(RuntimeErrorKind::UnknownOutputFormat(String::from(other)))),
+        }
+    }
+}
+
+/// Write `records` to `out` in `format`
+///
+/// For `OutputFormat::Text`, `text` is called to write each record.
+pub fn write_records<W, T, F>(out: &mut W, format: OutputFormat, records: &[T], text: F)
+    -> Result<(), RuntimeError>
+    where W: Write,
+          T: Serialize,
+          F: Fn(&mut W, &T) -> ::std::io::Result<()>
+{
+    match format {
+        OutputFormat::Text => {
+            for record in records {
+                let _ = text(out, record)?;
+            }
+        },
+
+        OutputFormat::Json => {
+            let json = ::serde_json::to_string(records)?;
+            let _    = writeln!(out, "{}", json)?;
+        },
+
+        OutputFormat::Toml => {
+            let mut document = BTreeMap::new();
+            let _            = document.insert("records", records);
+            let toml         = ::toml::to_string(&document)?;
+            let _            = write!(out, "{}", toml)?;
+        },
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::*;
+
+    use serde_json::Value as JsonValue;
+
+    fn record(id: &str, tags: &[&str]) -> JsonValue {
+        let mut map = ::serde_json::Map::new();
+        let tags    = tags.iter().map(|t| JsonValue::String(String::from(*t))).collect();
+        let _       = map.insert(String::from("id"), JsonValue::String(String::from(id)));
+        let _       = map.insert(String::from("tags"), JsonValue::Array(tags));
+        JsonValue::Object(map)
+    }
+
+    fn records() -> Vec<JsonValue> {
+        vec![record("a", &["x"]), record("b", &[])]
+    }
+
+    fn render(format: OutputFormat) -> String {
+        let mut out = vec![];
+        write_records(&mut out, format, &records(), |out, r| writeln!(out, "{}", r["id"].as_str().unwrap())).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!(OutputFormat::Json, "json".parse::<OutputFormat>().unwrap());
+        assert_eq!(OutputFormat::Toml, "toml".parse::<OutputFormat>().unwrap());
+        assert_eq!(OutputFormat::Text, "text".parse::<OutputFormat>().unwrap());
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_text_output() {
+        assert_eq!("a\nb\n", render(OutputFormat::Text));
+    }
+
+    #[test]
+    fn test_json_output() {
+        assert_eq!("[{\"id\":\"a\",\"tags\":[\"x\"]},{\"id\":\"b\",\"tags\":[]}]\n",
+                   render(OutputFormat::Json));
+    }
+
+    #[test]
+    fn test_toml_output() {
+        let toml  = render(OutputFormat::Toml);
+        let value = toml.parse::<::toml::Value>().unwrap();
+        let recs  = value.get("records").and_then(|v| v.as_array()).unwrap();
+
+        assert_eq!(2, recs.len());
+        assert_eq!(Some("a"), recs[0].get("id").and_then(|v| v.as_str()));
+    }
+}
+
@@ -0,0 +1,177 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The protocol imag commands use to pass entries to each other on stdin/stdout
+//!
+//! Entries are passed by their ids. The input of a command may contain
+//!
+//! * one store id per line, either relative to the store or as absolute path into the store
+//! * one JSON record per line, which is an object with an "id" member
+//! * a JSON array of such records, as printed with `--format json`
+//!
+//! so the output of every imag command which prints ids, in either format, can be piped into
+//! every other imag command which takes ids.
+//!
+//! Commands which alter entries print the ids of the altered entries when their stdout is a pipe,
+//! either as one id per line or, with `--format json`, as one JSON record per line which contains
+//! the id and the header of the entry.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde_json::Map;
+use serde_json::Value as JsonValue;
+use toml::Value;
+
+use libimagstore::storeid::StoreId;
+
+use error::RuntimeError as RE;
+use error::RuntimeErrorKind as REK;
+use error::Result;
+
+/// Parse the ids passed in `input`
+///
+/// `store_path` is used to make absolute paths into the store relative to the store.
+pub fn parse_ids(input: &str, store_path: &PathBuf) -> Result<Vec<StoreId>> {
+    let trimmed = input.trim();
+
+    if trimmed.starts_with('[') {
+        return match ::serde_json::from_str(trimmed)? {
+            JsonValue::Array(records) => records
+                .iter()
+                .map(|record| id_from_record(record, store_path))
+                .collect(),
+            _ => Err(RE::from_kind(REK::MalformedPipeInput(String::from(trimmed)))),
+        }
+    }
+
+    trimmed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| -> Result<StoreId> {
+            if line.starts_with('{') {
+                let record : JsonValue = ::serde_json::from_str(line)?;
+                id_from_record(&record, store_path)
+            } else {
+                parse_id(line, store_path)
+            }
+        })
+        .collect()
+}
+
+fn id_from_record(record: &JsonValue, store_path: &PathBuf) -> Result<StoreId> {
+    match record.get("id") {
+        Some(&JsonValue::String(ref id)) => parse_id(id, store_path),
+        _ => Err(RE::from_kind(REK::MalformedPipeInput(record.to_string()))),
+    }
+}
+
+fn parse_id(id: &str, store_path: &PathBuf) -> Result<StoreId> {
+    let path = PathBuf::from(id);
+    let id   = if path.is_absolute() {
+        StoreId::from_full_path(store_path, path).map(StoreId::without_base)
+    } else {
+        StoreId::new_baseless(path)
+    };
+
+    id.map_err(RE::from)
+}
+
+/// Write `id` as one line of text
+pub fn write_id<W: Write>(out: &mut W, id: &StoreId) -> Result<()> {
+    let id = id.clone().without_base().to_str()?;
+    writeln!(out, "{}", id).map_err(RE::from)
+}
+
+/// Write `id` and, if passed, the `header` of the entry as one line of JSON
+pub fn write_record<W: Write>(out: &mut W, id: &StoreId, header: Option<&Value>) -> Result<()> {
+    let mut record = Map::new();
+    let id         = id.clone().without_base().to_str()?;
+    let _          = record.insert(String::from("id"), JsonValue::String(id));
+
+    if let Some(header) = header {
+        let _ = record.insert(String::from("header"), ::serde_json::to_value(header)?);
+    }
+
+    let json = ::serde_json::to_string(&JsonValue::Object(record))?;
+    writeln!(out, "{}", json).map_err(RE::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn store_path() -> PathBuf {
+        PathBuf::from("/home/user/.imag/store")
+    }
+
+    fn ids(input: &str) -> Vec<String> {
+        parse_ids(input, &store_path())
+            .unwrap()
+            .into_iter()
+            .map(|id| id.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_id_lines() {
+        assert_eq!(ids("diary/a\n\n  notes/b  \n"), vec!["diary/a", "notes/b"]);
+    }
+
+    #[test]
+    fn test_parse_absolute_id_lines() {
+        assert_eq!(ids("/home/user/.imag/store/diary/a\n"), vec!["diary/a"]);
+        assert!(parse_ids("/somewhere/else\n", &store_path()).is_err());
+    }
+
+    #[test]
+    fn test_parse_json_lines() {
+        let input = "{\"id\":\"diary/a\",\"header\":{}}\n{\"id\":\"notes/b\"}\n";
+        assert_eq!(ids(input), vec!["diary/a", "notes/b"]);
+    }
+
+    #[test]
+    fn test_parse_json_array() {
+        let input = "[{\"id\":\"diary/a\",\"tags\":[]},{\"id\":\"notes/b\",\"tags\":[\"x\"]}]\n";
+        assert_eq!(ids(input), vec!["diary/a", "notes/b"]);
+    }
+
+    #[test]
+    fn test_parse_record_without_id() {
+        assert!(parse_ids("{\"name\":\"diary/a\"}\n", &store_path()).is_err());
+        assert!(parse_ids("[1, 2]", &store_path()).is_err());
+    }
+
+    #[test]
+    fn test_write_and_parse_roundtrip() {
+        let id         = StoreId::new(Some(store_path()), PathBuf::from("diary/a")).unwrap();
+        let header     = "[imag]\nversion = \"0.9.0\"\n".parse::<Value>().unwrap();
+        let mut out    = vec![];
+
+        write_id(&mut out, &id).unwrap();
+        write_record(&mut out, &id, Some(&header)).unwrap();
+        write_record(&mut out, &id, None).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().next(), Some("diary/a"));
+        assert!(out.contains("\"version\":\"0.9.0\""));
+        assert_eq!(ids(&out), vec!["diary/a", "diary/a", "diary/a"]);
+    }
+}
@@ -0,0 +1,322 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Discovery of imag commands and the protocol to ask them what they are
+//!
+//! Every `imag-<name>` executable in `$PATH` or in the plugin directory of the runtimepath
+//! (`<rtp>/plugins`) is an imag command, which `imag` can call as `imag <name>`. The plugin
+//! directory takes precedence over `$PATH`.
+//!
+//! A command which is called with `--imag-plugin-info` as its only argument prints its plugin info
+//! as TOML and exits:
+//!
+//! ```toml
+//! name = "imag-foo"
+//! version = "0.1.0"
+//! description = "Manage foos"
+//! collections = ["foo"]
+//!
+//! [[config]]
+//! path = "foo.default"
+//! type = "string"
+//! doc = "The foo which is used if none is passed"
+//! ```
+//!
+//! `collections` are the collections in the store the command stores its entries in, `config`
+//! are the configuration keys the command reads (see the `schema` module). Both may be left out.
+//! All commands which use `setup::generate_runtime_setup()` implement this protocol.
+//!
+//! A command which does not answer within `PLUGIN_INFO_TIMEOUT_MS` is killed and treated as if it
+//! did not implement the protocol. To avoid running the command at all, its plugin info can be
+//! put into a manifest `<rtp>/plugins/<name>.toml`, which is read instead (see `plugin_info()`).
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::fs::read_dir;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use error::RuntimeError as RE;
+use error::RuntimeErrorKind as REK;
+use error::Result;
+use schema::ConfigKeyInfo;
+
+/// The flag a command is called with to print its plugin info
+pub const PLUGIN_INFO_FLAG : &str = "--imag-plugin-info";
+
+/// How long a command may take to print its plugin info
+pub const PLUGIN_INFO_TIMEOUT_MS : u64 = 2000;
+
+/// What a command tells about itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    #[serde(default)]
+    pub collections: Vec<String>,
+    #[serde(default)]
+    pub config: Vec<ConfigKeyInfo>,
+}
+
+impl PluginInfo {
+
+    pub fn new(name: &str, version: &str, description: &str, collections: &[&str]) -> PluginInfo {
+        PluginInfo {
+            name: String::from(name),
+            version: String::from(version),
+            description: String::from(description),
+            collections: collections.iter().map(|c| String::from(*c)).collect(),
+            config: vec![],
+        }
+    }
+
+    /// Set the configuration keys the command reads
+    pub fn with_config(mut self, config: Vec<ConfigKeyInfo>) -> PluginInfo {
+        self.config = config;
+        self
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        ::toml::ser::to_string(self).map_err(RE::from)
+    }
+
+    pub fn from_toml(s: &str) -> Result<PluginInfo> {
+        ::toml::de::from_str(s).map_err(RE::from)
+    }
+
+    /// Ask the command `executable` for its plugin info
+    ///
+    /// Fails if the command does not implement the protocol or does not exit within
+    /// `PLUGIN_INFO_TIMEOUT_MS`, in which case it is killed.
+    pub fn query(executable: &Path) -> Result<PluginInfo> {
+        let no_info = || RE::from_kind(REK::NoPluginInfo(format!("{}", executable.display())));
+
+        let mut child = Command::new(executable)
+            .arg(PLUGIN_INFO_FLAG)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        // The output is read in a thread, so a command which prints a lot cannot block on a full
+        // pipe while we wait for it to exit
+        let mut stdout = child.stdout.take().ok_or_else(|| no_info())?;
+        let reader     = thread::spawn(move || {
+            let mut buf = vec![];
+            stdout.read_to_end(&mut buf).map(|_| buf)
+        });
+
+        let timeout = Duration::from_millis(PLUGIN_INFO_TIMEOUT_MS);
+        let start   = Instant::now();
+        let status  = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            if start.elapsed() >= timeout {
+                debug!("{} did not print its plugin info in time", executable.display());
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(no_info());
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        if !status.success() {
+            return Err(no_info());
+        }
+
+        let output = reader.join().map_err(|_| no_info())??;
+        String::from_utf8(output)
+            .map_err(|_| no_info())
+            .and_then(|s| PluginInfo::from_toml(&s).map_err(|_| no_info()))
+    }
+
+    /// Read the plugin info from the manifest file `path`
+    pub fn from_manifest(path: &Path) -> Result<PluginInfo> {
+        let mut s = String::new();
+        let _     = File::open(path)?.read_to_string(&mut s)?;
+        PluginInfo::from_toml(&s)
+    }
+
+}
+
+/// The plugin directory in the runtimepath `rtp`
+pub fn plugin_dir(rtp: &Path) -> PathBuf {
+    rtp.join("plugins")
+}
+
+/// The path of the manifest of the command `name` (without the "imag-" prefix)
+pub fn manifest_path(rtp: &Path, name: &str) -> PathBuf {
+    plugin_dir(rtp).join(format!("{}.toml", name))
+}
+
+/// Get the plugin info of the command `name`, which is the executable `executable`
+///
+/// The manifest of the command is read if there is one, otherwise the command is asked.
+pub fn plugin_info(rtp: &Path, name: &str, executable: &Path) -> Result<PluginInfo> {
+    let manifest = manifest_path(rtp, name);
+    if manifest.is_file() {
+        PluginInfo::from_manifest(&manifest)
+    } else {
+        PluginInfo::query(executable)
+    }
+}
+
+/// Find all imag commands, by name (without the "imag-" prefix)
+pub fn discover(rtp: &Path) -> BTreeMap<String, PathBuf> {
+    let mut dirs = vec![plugin_dir(rtp)];
+    if let Some(path) = env::var_os("PATH") {
+        dirs.extend(env::split_paths(&path));
+    }
+
+    let mut commands = BTreeMap::new();
+    for dir in dirs {
+        for (name, executable) in commands_in(&dir) {
+            let _ = commands.entry(name).or_insert(executable);
+        }
+    }
+
+    commands
+}
+
+/// The imag commands in the directory `dir`
+fn commands_in(dir: &Path) -> Vec<(String, PathBuf)> {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_)      => return vec![],
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            entry.file_name()
+                .to_str()
+                .and_then(|f| if f.starts_with("imag-") { Some(String::from(&f[5..])) } else { None })
+                .map(|name| (name, entry.path()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use super::*;
+
+    #[test]
+    fn test_plugin_info_roundtrip() {
+        let info = PluginInfo::new("imag-foo", "0.1.0", "Manage foos", &["foo"]);
+        let toml = info.to_toml().unwrap();
+
+        assert!(toml.contains("name = \"imag-foo\""));
+        assert_eq!(PluginInfo::from_toml(&toml).unwrap(), info);
+    }
+
+    #[test]
+    fn test_plugin_info_with_config() {
+        use libimagutil::config_schema::{ConfigKey, ValueType};
+
+        let keys : &[ConfigKey] = &[ConfigKey {
+            path: "foo.default",
+            kind: ValueType::String,
+            default: None,
+            doc: "The default foo",
+        }];
+
+        let info = PluginInfo::new("imag-foo", "0.1.0", "Manage foos", &["foo"])
+            .with_config(::schema::key_infos(&[keys]));
+        let toml = info.to_toml().unwrap();
+
+        assert!(toml.contains("type = \"string\""));
+        assert_eq!(PluginInfo::from_toml(&toml).unwrap(), info);
+    }
+
+    #[test]
+    fn test_plugin_info_without_collections() {
+        let info = PluginInfo::from_toml("name = \"imag-foo\"\nversion = \"0.1.0\"\ndescription = \"\"\n");
+        assert!(info.unwrap().collections.is_empty());
+        assert!(PluginInfo::from_toml("name = \"imag-foo\"\n").is_err());
+    }
+
+    #[test]
+    fn test_commands_in() {
+        let dir = env::temp_dir().join(format!("imag-plugin-test-{}", ::std::process::id()));
+        let _   = ::std::fs::create_dir_all(&dir).unwrap();
+        let _   = File::create(dir.join("imag-foo")).unwrap();
+        let _   = File::create(dir.join("other")).unwrap();
+
+        let commands = commands_in(&dir);
+        let _        = ::std::fs::remove_dir_all(&dir);
+
+        assert_eq!(commands, vec![(String::from("foo"), dir.join("imag-foo"))]);
+    }
+
+    #[test]
+    fn test_plugin_info_from_manifest() {
+        use std::io::Write;
+
+        let rtp = env::temp_dir().join(format!("imag-plugin-manifest-test-{}", ::std::process::id()));
+        let _   = ::std::fs::create_dir_all(plugin_dir(&rtp)).unwrap();
+
+        let info = PluginInfo::new("imag-foo", "0.1.0", "Manage foos", &["foo"]);
+        let _    = File::create(manifest_path(&rtp, "foo"))
+            .unwrap()
+            .write_all(info.to_toml().unwrap().as_bytes())
+            .unwrap();
+
+        // The executable does not exist, so the info must come from the manifest
+        let read     = plugin_info(&rtp, "foo", &rtp.join("imag-foo"));
+        let commands = commands_in(&plugin_dir(&rtp));
+        let _        = ::std::fs::remove_dir_all(&rtp);
+
+        assert_eq!(read.unwrap(), info);
+        assert!(commands.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_query_times_out() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir  = env::temp_dir().join(format!("imag-plugin-timeout-test-{}", ::std::process::id()));
+        let exe  = dir.join("imag-sleep");
+        let _    = ::std::fs::create_dir_all(&dir).unwrap();
+        let _    = File::create(&exe).unwrap().write_all(b"#!/bin/sh\nexec sleep 30\n").unwrap();
+        let _    = ::std::fs::set_permissions(&exe, ::std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let start  = Instant::now();
+        let result = PluginInfo::query(&exe);
+        let _      = ::std::fs::remove_dir_all(&dir);
+
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_millis(PLUGIN_INFO_TIMEOUT_MS * 5));
+    }
+}
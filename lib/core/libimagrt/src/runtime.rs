@@ -21,25 +21,35 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::env;
 use std::process::exit;
+use std::io::Read;
 use std::io::Stdin;
+use std::str::FromStr;
 use std::sync::Arc;
 
 pub use clap::App;
 use clap::AppSettings;
+use serde::Serialize;
 use toml::Value;
 use toml_query::read::TomlValueReadExt;
 
 use clap::{Arg, ArgMatches};
 
-use configuration::{fetch_config, override_config, InternalConfiguration};
+use configuration::{InternalConfiguration, LayeredConfig};
 use error::RuntimeError;
 use error::RuntimeErrorKind;
 use error::ResultExt;
 use logger::ImagLogger;
 use io::OutputProxy;
+use output::OutputFormat;
+use output::write_records;
+use pipe::parse_ids;
+use pipe::write_id;
+use pipe::write_record;
 
 use libimagerror::trace::*;
+use libimagstore::store::Entry;
 use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
 use libimagstore::file_abstraction::InMemoryFileAbstraction;
 use libimagutil::debug_result::DebugResult;
 use spec::CliSpec;
@@ -57,45 +67,24 @@ pub struct Runtime<'a> {
 
 impl<'a> Runtime<'a> {
 
-    /// Gets the CLI spec for the program and retreives the config file path (or uses the default on
-    /// in $HOME/.imag/config, $XDG_CONFIG_DIR/imag/config or from env("$IMAG_CONFIG")
-    /// and builds the Runtime object with it.
+    /// Gets the CLI spec for the program and loads the layered configuration (see
+    /// `get_layered_config()`) and builds the Runtime object with it.
     ///
     /// The cli_app object should be initially build with the ::get_default_cli_builder() function.
     pub fn new<C>(cli_app: C) -> Result<Runtime<'a>, RuntimeError>
         where C: Clone + CliSpec<'a> + InternalConfiguration
     {
-        use libimagerror::trace::trace_error;
-
         let matches = cli_app.clone().matches();
 
-        let rtp = get_rtp_match(&matches);
-
-        let configpath = matches.value_of(Runtime::arg_config_name())
-                                .map_or_else(|| rtp.clone(), PathBuf::from);
-
-        debug!("Config path = {:?}", configpath);
-
-        let config = match fetch_config(&configpath) {
-            Err(e) => if !is_match!(e.kind(), &RuntimeErrorKind::ConfigNoConfigFileFound) {
-                return Err(e).chain_err(|| RuntimeErrorKind::Instantiate);
-            } else {
+        let config = match get_layered_config(&matches) {
+            Err(e) => return Err(e).chain_err(|| RuntimeErrorKind::Instantiate),
+            Ok(None) => {
                 eprintln!("No config file found.");
                 eprintln!("Maybe try to use 'imag-init' to initialize imag?");
                 eprintln!("Continuing without configuration file");
                 None
             },
-
-            Ok(mut config) => {
-                if let Err(e) = override_config(&mut config, get_override_specs(&matches)) {
-                    error!("Could not apply config overrides");
-                    trace_error(&e);
-
-                    // TODO: continue question (interactive)
-                }
-
-                Some(config)
-            }
+            Ok(Some(config)) => Some(config.into_value()),
         };
 
         Runtime::_new(cli_app, matches, config)
@@ -118,14 +107,8 @@ impl<'a> Runtime<'a> {
             Runtime::init_logger(&matches, config.as_ref())
         }
 
-        let rtp = get_rtp_match(&matches);
-
-        let storepath = matches.value_of(Runtime::arg_storepath_name())
-                                .map_or_else(|| {
-                                    let mut spath = rtp.clone();
-                                    spath.push("store");
-                                    spath
-                                }, PathBuf::from);
+        let rtp       = get_rtp_match(&matches);
+        let storepath = get_storepath_match(&matches, &rtp);
 
         debug!("RTP path    = {:?}", rtp);
         debug!("Store path  = {:?}", storepath);
@@ -158,6 +141,8 @@ impl<'a> Runtime<'a> {
     ///   * -c <file> | --config <file> for alternative configuration file
     ///   * -r <path> | --rtp <path> for alternative runtimepath
     ///   * --store <path> for alternative store path
+    ///   * --format <text|json|toml> for the output format of commands which support it
+    ///   * --ignore-ids for not reading ids from a piped stdin and not printing them to a piped stdout
     /// Each has the appropriate help text included.
     ///
     /// The `appname` shall be "imag-<command>".
@@ -234,6 +219,20 @@ impl<'a> Runtime<'a> {
                 .takes_value(true)
                 .value_name("LOGDESTS"))
 
+            .arg(Arg::with_name(Runtime::arg_output_format_name())
+                .long("format")
+                .help("Output format. Commands which list things print them as structured records with 'json' or 'toml'")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["text", "json", "toml"])
+                .value_name("FORMAT"))
+
+            .arg(Arg::with_name(Runtime::arg_ignore_ids_name())
+                .long("ignore-ids")
+                .help("Do not read ids from stdin and do not print the ids of altered entries to stdout if these are pipes")
+                .required(false)
+                .takes_value(false))
+
     }
 
     /// Get the argument names of the Runtime which are available
@@ -247,6 +246,8 @@ impl<'a> Runtime<'a> {
             Runtime::arg_runtimepath_name(),
             Runtime::arg_storepath_name(),
             Runtime::arg_editor_name(),
+            Runtime::arg_output_format_name(),
+            Runtime::arg_ignore_ids_name(),
         ]
     }
 
@@ -290,6 +291,16 @@ impl<'a> Runtime<'a> {
         "editor"
     }
 
+    /// Get the output format argument name for the Runtime
+    pub fn arg_output_format_name() -> &'static str {
+        "runtime-output-format"
+    }
+
+    /// Get the argument name for not using the pipe protocol
+    pub fn arg_ignore_ids_name() -> &'static str {
+        "ignore-ids"
+    }
+
     /// Extract the Store object from the Runtime object, destroying the Runtime object
     ///
     /// # Warning
@@ -419,6 +430,98 @@ impl<'a> Runtime<'a> {
         Some(::std::io::stdin())
     }
 
+    /// Get the output format which was selected with `--format`
+    pub fn output_format(&self) -> OutputFormat {
+        self.cli()
+            .value_of(Runtime::arg_output_format_name())
+            .and_then(|f| OutputFormat::from_str(f).ok()) // safe by clap
+            .unwrap_or_default()
+    }
+
+    /// Print `records` to stdout in the output format which was selected with `--format`
+    ///
+    /// With "json", the records are printed as a JSON array, with "toml" as an array of tables
+    /// named "records". With "text" (the default), `text` is called for each record, so the
+    /// command can print it the way it likes.
+    ///
+    /// Commands should use this for everything they list, so scripts can rely on the structured
+    /// formats instead of parsing text.
+    pub fn output<T, F>(&self, records: &[T], text: F) -> Result<(), RuntimeError>
+        where T: Serialize,
+              F: Fn(&mut OutputProxy, &T) -> ::std::io::Result<()>
+    {
+        write_records(&mut self.stdout(), self.output_format(), records, text)
+    }
+
+    /// Whether ids should be read from stdin, because stdin is a pipe
+    ///
+    /// Commands which take ids should read them with `Runtime::ids_from_stdin()` if none are
+    /// passed on the commandline and this returns true.
+    pub fn ids_from_pipe(&self) -> bool {
+        !self.cli().is_present(Runtime::arg_ignore_ids_name()) && !::atty::is(::atty::Stream::Stdin)
+    }
+
+    /// Whether stdout is a pipe, so the ids of altered entries should be printed
+    pub fn output_is_pipe(&self) -> bool {
+        !self.cli().is_present(Runtime::arg_ignore_ids_name()) && !::atty::is(::atty::Stream::Stdout)
+    }
+
+    /// Read store ids from stdin, in the format described in `libimagrt::pipe`
+    ///
+    /// Fails if stdin contains no ids, so a command which is called without input (for example
+    /// from cron, where stdin is not a terminal) does not silently do nothing.
+    pub fn ids_from_stdin(&self) -> Result<Vec<StoreId>, RuntimeError> {
+        let mut buf = String::new();
+        let stdin   = self.stdin().ok_or_else(|| RuntimeError::from_kind(RuntimeErrorKind::IOError))?;
+        let _       = stdin.lock().read_to_string(&mut buf)?;
+
+        let ids = parse_ids(&buf, self.store().path())?;
+        if ids.is_empty() {
+            return Err(RuntimeError::from_kind(RuntimeErrorKind::NoIdsPassed))
+        }
+
+        Ok(ids)
+    }
+
+    /// Get the ids a command works on
+    ///
+    /// These are the values of the argument `arg` in `matches`. If there are none, the ids are read
+    /// from stdin if the flag `stdin_flag` is present in `matches` or stdin is a pipe. Fails if no
+    /// ids are passed at all.
+    pub fn ids<'b>(&self, matches: &ArgMatches<'b>, arg: &str, stdin_flag: Option<&str>)
+        -> Result<Vec<StoreId>, RuntimeError>
+    {
+        if let Some(ids) = matches.values_of(arg) {
+            return ids
+                .map(|id| StoreId::new_baseless(PathBuf::from(id)).map_err(RuntimeError::from))
+                .collect()
+        }
+
+        let from_stdin = stdin_flag.map(|flag| matches.is_present(flag)).unwrap_or(false);
+        if from_stdin || self.ids_from_pipe() {
+            self.ids_from_stdin()
+        } else {
+            Err(RuntimeError::from_kind(RuntimeErrorKind::NoIdsPassed))
+        }
+    }
+
+    /// Report that `entry` was altered by the command
+    ///
+    /// If stdout is a pipe, the id of the entry is printed, so the next command in the pipe can
+    /// work on the entry. With `--format json`, a JSON record with the id and the header of the
+    /// entry is printed.
+    pub fn report_touched(&self, entry: &Entry) -> Result<(), RuntimeError> {
+        if !self.output_is_pipe() {
+            return Ok(())
+        }
+
+        if self.output_format() == OutputFormat::Json {
+            write_record(&mut self.stdout(), entry.get_location(), Some(entry.get_header()))
+        } else {
+            write_id(&mut self.stdout(), entry.get_location())
+        }
+    }
+
     /// Helper for handling subcommands which are not available.
     ///
     /// # Example
@@ -524,6 +627,54 @@ pub fn get_rtp_match<'a>(matches: &ArgMatches<'a>) -> PathBuf {
         }, PathBuf::from)
 }
 
+/// Get the store path from the commandline, defaulting to the "store" directory in the `rtp`
+pub fn get_storepath_match<'a>(matches: &ArgMatches<'a>, rtp: &PathBuf) -> PathBuf {
+    matches
+        .value_of(Runtime::arg_storepath_name())
+        .map_or_else(|| rtp.join("store"), PathBuf::from)
+}
+
+/// Load the layered configuration for the commandline in `matches`
+///
+/// The user configuration is searched in the path passed with `--config` or the runtimepath,
+/// the per-store configuration in the store path. Afterwards, overrides from the environment and
+/// `--override-config` are applied. Failing overrides are reported but do not fail the loading.
+///
+/// Returns `None` if no configuration file was found.
+pub fn get_layered_config<'a>(matches: &ArgMatches<'a>) -> Result<Option<LayeredConfig>, RuntimeError> {
+    let rtp        = get_rtp_match(matches);
+    let storepath  = get_storepath_match(matches, &rtp);
+    let configpath = matches
+        .value_of(Runtime::arg_config_name())
+        .map_or_else(|| rtp.clone(), PathBuf::from);
+
+    debug!("Config path = {:?}", configpath);
+
+    let workdir    = env::current_dir().ok();
+    let mut config = match LayeredConfig::load(&configpath, &storepath, workdir.as_ref())? {
+        Some(config) => config,
+        None         => return Ok(None),
+    };
+
+    let vars = env::vars_os().filter_map(|(key, value)| -> Option<(String, String)> {
+        Some((key.into_string().ok()?, value.into_string().ok()?))
+    });
+
+    if let Err(e) = config.apply_env(vars) {
+        error!("Could not apply config overrides from the environment");
+        trace_error(&e);
+    }
+
+    if let Err(e) = config.apply_overrides(get_override_specs(matches)) {
+        error!("Could not apply config overrides");
+        trace_error(&e);
+
+        // TODO: continue question (interactive)
+    }
+
+    Ok(Some(config))
+}
+
 fn get_override_specs(matches: &ArgMatches) -> Vec<String> {
     matches
         .values_of("config-override")
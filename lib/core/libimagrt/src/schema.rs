@@ -0,0 +1,348 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Checking the configuration against the keys the crates declare
+//!
+//! Every crate which reads configuration declares the keys it reads (see
+//! `libimagutil::config_schema`). Commands report the keys of the crates they use with their
+//! plugin info (see the `plugin` module), so the keys of all commands can be collected with
+//! `discover_keys()`.
+//!
+//! `validate()` reports values of the wrong type and unknown keys. A key is only reported as
+//! unknown if it is in a section (the first part of the path) some of the passed keys are in, so
+//! sections of commands which were not asked are left alone.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::path::Path;
+
+use toml::Value;
+use toml_query::read::TomlValueReadTypeExt;
+
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
+
+use plugin::discover;
+use plugin::plugin_info;
+
+/// The setting which enables checking the configuration when a command starts
+pub const VALIDATE_CONFIG_KEY : &'static str = "imag.validate-config";
+
+/// A declared configuration key, in the form it is passed between commands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigKeyInfo {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub default: Option<String>,
+    pub doc: String,
+}
+
+impl ConfigKeyInfo {
+
+    /// The type of the value, `ValueType::Any` if the type name is not known
+    pub fn value_type(&self) -> ValueType {
+        ValueType::from_name(&self.kind).unwrap_or(ValueType::Any)
+    }
+
+}
+
+impl<'a> From<&'a ConfigKey> for ConfigKeyInfo {
+
+    fn from(key: &'a ConfigKey) -> ConfigKeyInfo {
+        ConfigKeyInfo {
+            path: String::from(key.path),
+            kind: String::from(key.kind.name()),
+            default: key.default.map(String::from),
+            doc: String::from(key.doc),
+        }
+    }
+
+}
+
+/// A problem found in the configuration
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "issue", rename_all = "kebab-case")]
+pub enum ConfigIssue {
+    UnknownKey { path: String },
+    WrongType { path: String, expected: String },
+}
+
+impl ConfigIssue {
+
+    pub fn path(&self) -> &str {
+        match *self {
+            ConfigIssue::UnknownKey { ref path }    => path,
+            ConfigIssue::WrongType { ref path, .. } => path,
+        }
+    }
+
+}
+
+impl Display for ConfigIssue {
+
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            ConfigIssue::UnknownKey { ref path } => {
+                write!(f, "Unknown configuration key '{}'", path)
+            },
+            ConfigIssue::WrongType { ref path, ref expected } => {
+                write!(f, "Configuration key '{}' should be of type '{}'", path, expected)
+            },
+        }
+    }
+
+}
+
+/// Convert the keys declared by crates
+pub fn key_infos(keys: &[&[ConfigKey]]) -> Vec<ConfigKeyInfo> {
+    keys.iter()
+        .flat_map(|keys| keys.iter())
+        .map(ConfigKeyInfo::from)
+        .collect()
+}
+
+/// The keys of the crates every command uses: libimagrt, libimagstore and libimaginteraction
+pub fn core_keys() -> Vec<ConfigKeyInfo> {
+    key_infos(&[
+        ::configuration::CONFIG_KEYS,
+        ::libimagstore::configuration::CONFIG_KEYS,
+        ::libimaginteraction::readline::CONFIG_KEYS,
+    ])
+}
+
+/// Collect the keys of the core crates and of all imag commands
+///
+/// The plugin info of every command found by `plugin::discover()` is read (see
+/// `plugin::plugin_info()`), which takes a moment if commands have to be asked for it.
+/// The keys are sorted by path, keys declared by several commands are returned once.
+pub fn discover_keys(rtp: &Path) -> Vec<ConfigKeyInfo> {
+    let mut keys = core_keys();
+
+    for (name, executable) in discover(rtp) {
+        match plugin_info(rtp, &name, &executable) {
+            Ok(info) => keys.extend(info.config),
+            Err(e)   => debug!("No plugin info from '{}': {:?}", name, e),
+        }
+    }
+
+    sorted_keys(keys)
+}
+
+/// Sort `keys` by path and remove duplicates
+pub fn sorted_keys(mut keys: Vec<ConfigKeyInfo>) -> Vec<ConfigKeyInfo> {
+    keys.sort_by(|a, b| a.path.cmp(&b.path));
+    keys.dedup_by(|a, b| a.path == b.path);
+    keys
+}
+
+/// Whether the configuration asks for checking it when a command starts
+pub fn validate_on_startup(config: Option<&Value>) -> bool {
+    config
+        .and_then(|config| config.read_bool(VALIDATE_CONFIG_KEY).ok())
+        .and_then(|b| b)
+        .unwrap_or(false)
+}
+
+/// Check `config` against `keys`
+pub fn validate(config: &Value, keys: &[ConfigKeyInfo]) -> Vec<ConfigIssue> {
+    let patterns = keys
+        .iter()
+        .map(|key| (key.path.split('.').collect::<Vec<&str>>(), key))
+        .collect::<Vec<_>>();
+
+    let mut issues = vec![];
+    check(config, &mut vec![], &patterns, &mut issues);
+    issues
+}
+
+fn check<'a>(value: &'a Value,
+             path: &mut Vec<&'a str>,
+             patterns: &[(Vec<&str>, &ConfigKeyInfo)],
+             issues: &mut Vec<ConfigIssue>)
+{
+    let table = match *value {
+        Value::Table(ref table) => table,
+        _                       => return,
+    };
+
+    for (key, value) in table.iter() {
+        path.push(key);
+
+        let (declared, is_inner, in_section) = {
+            let current : &[&str] = &path[..];
+
+            let declared = patterns
+                .iter()
+                .find(|&&(ref pattern, _)| segments_match(pattern, current))
+                .map(|&(_, info)| info);
+
+            let is_inner   = patterns.iter().any(|&(ref pattern, _)| is_below(pattern, current));
+            let in_section = patterns.iter().any(|&(ref pattern, _)| pattern[0] == current[0]);
+
+            (declared, is_inner, in_section)
+        };
+
+        if let Some(info) = declared {
+            if !accepts(info.value_type(), value) {
+                issues.push(ConfigIssue::WrongType {
+                    path: path.join("."),
+                    expected: info.kind.clone(),
+                });
+            }
+        } else if is_inner {
+            if is_match!(*value, Value::Table(_)) {
+                check(value, path, patterns, issues);
+            } else {
+                issues.push(ConfigIssue::WrongType {
+                    path: path.join("."),
+                    expected: String::from(ValueType::Table.name()),
+                });
+            }
+        } else if in_section {
+            issues.push(ConfigIssue::UnknownKey { path: path.join(".") });
+        }
+
+        let _ = path.pop();
+    }
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    pattern.len() == path.len() &&
+        pattern.iter().zip(path.iter()).all(|(p, s)| *p == "*" || p == s)
+}
+
+/// Whether the key `pattern` is inside the table at `path`
+fn is_below(pattern: &[&str], path: &[&str]) -> bool {
+    pattern.len() > path.len() && segments_match(&pattern[..path.len()], path)
+}
+
+fn accepts(kind: ValueType, value: &Value) -> bool {
+    match (kind, value) {
+        (ValueType::Any,      _)                   => true,
+        (ValueType::String,   &Value::String(_))   => true,
+        (ValueType::Integer,  &Value::Integer(_))  => true,
+        (ValueType::Float,    &Value::Float(_))    => true,
+        (ValueType::Boolean,  &Value::Boolean(_))  => true,
+        (ValueType::Datetime, &Value::Datetime(_)) => true,
+        (ValueType::Array,    &Value::Array(_))    => true,
+        (ValueType::Table,    &Value::Table(_))    => true,
+        _                                          => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use toml::de::from_str;
+
+    use super::*;
+
+    const KEYS : &'static [ConfigKey] = &[
+        ConfigKey {
+            path: "diary.default_diary",
+            kind: ValueType::String,
+            default: None,
+            doc: "",
+        },
+        ConfigKey {
+            path: "diary.diaries.*.timed",
+            kind: ValueType::String,
+            default: None,
+            doc: "",
+        },
+        ConfigKey {
+            path: "imag.aliases",
+            kind: ValueType::Table,
+            default: None,
+            doc: "",
+        },
+    ];
+
+    fn issues(config: &str) -> Vec<ConfigIssue> {
+        validate(&from_str::<Value>(config).unwrap(), &key_infos(&[KEYS]))
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = r#"
+            [diary]
+            default_diary = "default"
+
+            [diary.diaries.default]
+            timed = "minutely"
+
+            [imag.aliases]
+            store = ["s", "st"]
+
+            [imag.aliases.sc]
+            command = "store create"
+
+            [contact]
+            list_format = "{{id}}"
+        "#;
+
+        assert!(issues(config).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let config = r#"
+            [diary.diaries.default]
+            timd = "minutely"
+
+            [imag]
+            unknown = 1
+        "#;
+
+        assert_eq!(issues(config), vec![
+            ConfigIssue::UnknownKey { path: String::from("diary.diaries.default.timd") },
+            ConfigIssue::UnknownKey { path: String::from("imag.unknown") },
+        ]);
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let config = r#"
+            [diary]
+            default_diary = 1
+            diaries = "default"
+        "#;
+
+        assert_eq!(issues(config), vec![
+            ConfigIssue::WrongType {
+                path: String::from("diary.default_diary"),
+                expected: String::from("string"),
+            },
+            ConfigIssue::WrongType {
+                path: String::from("diary.diaries"),
+                expected: String::from("table"),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_sorted_keys() {
+        let mut keys = key_infos(&[KEYS, KEYS]);
+        keys.reverse();
+
+        let paths = sorted_keys(keys).into_iter().map(|key| key.path).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["diary.default_diary", "diary.diaries.*.timed", "imag.aliases"]);
+    }
+}
@@ -19,7 +19,12 @@
 
 use clap::App;
 
+use libimagutil::config_schema::ConfigKey;
+
 use runtime::Runtime;
+use plugin::PluginInfo;
+use plugin::PLUGIN_INFO_FLAG;
+use schema;
 
 pub type Name          = &'static str;
 pub type Version<'a>   = &'a str;
@@ -32,15 +37,78 @@ pub type About         = &'static str;
 pub fn generate_runtime_setup<'a, B>(name: Name, version: Version<'a>, about: About, builder: B)
     -> Runtime<'a>
     where B: FnOnce(App<'a, 'a>) -> App<'a, 'a>
+{
+    generate_runtime_setup_with_collections(name, version, about, &[], builder)
+}
+
+/// Helper to generate the Runtime object for a command which stores its entries in `collections`
+///
+/// If the command is called with `--imag-plugin-info`, it prints its plugin info (see the `plugin`
+/// module) and exits instead.
+pub fn generate_runtime_setup_with_collections<'a, B>(name: Name,
+                                                      version: Version<'a>,
+                                                      about: About,
+                                                      collections: &[&str],
+                                                      builder: B)
+    -> Runtime<'a>
+    where B: FnOnce(App<'a, 'a>) -> App<'a, 'a>
+{
+    generate_runtime_setup_with_config(name, version, about, collections, &[], builder)
+}
+
+/// Helper to generate the Runtime object for a command which stores its entries in `collections`
+/// and reads the configuration keys `config_keys`
+///
+/// `config_keys` are the keys the command and the crates it uses read, apart from the ones of
+/// libimagrt itself. They are part of the plugin info and the configuration is checked against
+/// them at startup if `imag.validate-config` is set.
+pub fn generate_runtime_setup_with_config<'a, B>(name: Name,
+                                                 version: Version<'a>,
+                                                 about: About,
+                                                 collections: &[&str],
+                                                 config_keys: &[&[ConfigKey]],
+                                                 builder: B)
+    -> Runtime<'a>
+    where B: FnOnce(App<'a, 'a>) -> App<'a, 'a>
 {
     use std::process::exit;
+    use libimagerror::trace::trace_error;
     use libimagerror::trace::trace_error_dbg;
 
-    Runtime::new(builder(Runtime::get_default_cli_builder(name, version, about)))
+    if ::std::env::args().nth(1).map(|arg| arg == PLUGIN_INFO_FLAG).unwrap_or(false) {
+        let info = PluginInfo::new(name, version, about, collections)
+            .with_config(schema::key_infos(config_keys));
+
+        match info.to_toml() {
+            Ok(info) => {
+                print!("{}", info);
+                exit(0)
+            },
+            Err(e) => {
+                trace_error(&e);
+                exit(1)
+            },
+        }
+    }
+
+    let rt = Runtime::new(builder(Runtime::get_default_cli_builder(name, version, about)))
         .unwrap_or_else(|e| {
             println!("Could not set up Runtime");
             println!("{:?}", e);
             trace_error_dbg(&e);
             exit(1);
-        })
+        });
+
+    if let Some(config) = rt.config() {
+        if schema::validate_on_startup(Some(config)) {
+            let mut keys = schema::core_keys();
+            keys.extend(schema::key_infos(config_keys));
+
+            for issue in schema::validate(config, &keys) {
+                warn!("{}", issue);
+            }
+        }
+    }
+
+    rt
 }
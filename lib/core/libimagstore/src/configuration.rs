@@ -19,10 +19,29 @@
 
 use toml::Value;
 
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
+
 use store::Result;
 use error::StoreError as SE;
 use error::StoreErrorKind as SEK;
 
+/// The configuration keys the store reads
+pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "store.implicit-create",
+        kind: ValueType::Boolean,
+        default: Some("false"),
+        doc: "Whether the store directory is created if it does not exist",
+    },
+    ConfigKey {
+        path: "imag.version",
+        kind: ValueType::String,
+        default: None,
+        doc: "The version of imag the configuration was written for",
+    },
+];
+
 /// Checks whether the store configuration has a key "implicit-create" which maps to a boolean
 /// value. If that key is present, the boolean is returned, otherwise false is returned.
 pub fn config_implicit_store_create_allowed(config: &Option<Value>) -> Result<bool> {
@@ -136,6 +136,10 @@ impl FileAbstraction for FSFileAbstraction {
     fn pathes_recursively(&self, basepath: PathBuf) -> Result<PathIterator, SE> {
         use walkdir::WalkDir;
 
+        if !basepath.exists() {
+            return Ok(PathIterator::new(Box::new(::std::iter::empty())));
+        }
+
         let i = WalkDir::new(basepath)
             .min_depth(1)
             .max_open(100)
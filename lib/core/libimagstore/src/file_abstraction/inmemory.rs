@@ -181,14 +181,15 @@ impl FileAbstraction for InMemoryFileAbstraction {
         Ok(())
     }
 
-    fn pathes_recursively(&self, _basepath: PathBuf) -> Result<PathIterator, SE> {
-        debug!("Getting all pathes");
+    fn pathes_recursively(&self, basepath: PathBuf) -> Result<PathIterator, SE> {
+        debug!("Getting all pathes below {:?}", basepath);
         let keys : Vec<Result<PathBuf, SE>> = self
             .backend()
             .lock()
             .map_err(|_| SE::from_kind(SEK::FileError))?
             .get_mut()
             .keys()
+            .filter(|path| path.starts_with(&basepath))
             .map(PathBuf::from)
             .map(Ok)
             .collect(); // we have to collect() because of the lock() above.
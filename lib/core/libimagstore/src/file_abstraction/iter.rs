@@ -23,6 +23,7 @@ use std::sync::Arc;
 use error::Result;
 use storeid::StoreId;
 use file_abstraction::FileAbstraction;
+use store::STORE_CONFIG_FILE_NAME;
 
 /// A wrapper for an iterator over `PathBuf`s
 pub struct PathIterator(Box<Iterator<Item = Result<PathBuf>>>);
@@ -53,7 +54,7 @@ impl Iterator for PathIterator {
 
 /// Helper type for constructing StoreIds from a PathIterator.
 ///
-/// Automatically ignores non-files.
+/// Automatically ignores non-files and the per-store configuration file.
 pub struct StoreIdConstructingIterator(PathIterator, PathBuf, Arc<FileAbstraction>);
 
 impl Iterator for StoreIdConstructingIterator {
@@ -63,6 +64,7 @@ impl Iterator for StoreIdConstructingIterator {
         while let Some(next) = self.0.next() {
             match next {
                 Err(e)  => return Some(Err(e)),
+                Ok(ref next) if is_store_config(&self.1, next) => continue,
                 Ok(next) => match self.2.is_file(&next) {
                     Err(e)    => return Some(Err(e)),
                     Ok(true)  => return Some(StoreId::from_full_path(&self.1, next)),
@@ -76,3 +78,8 @@ impl Iterator for StoreIdConstructingIterator {
 
 }
 
+fn is_store_config(storepath: &PathBuf, path: &PathBuf) -> bool {
+    path.parent() == Some(storepath.as_path()) &&
+        path.file_name().map(|n| n == STORE_CONFIG_FILE_NAME).unwrap_or(false)
+}
+
@@ -0,0 +1,89 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Attach the id of the entry an operation works on to the log records of that operation
+//!
+//! The store calls `enter_id()` when it works on an entry. Until the returned guard is dropped,
+//! `current_id()` returns the id, so a logger can add it to every log record. This way a log shows
+//! which entry a command touched, even for messages which do not mention the id.
+//!
+//! Commands may use `enter_id()` as well, to attach an id to their own messages.
+
+use std::cell::RefCell;
+
+use storeid::StoreId;
+
+thread_local! {
+    static CURRENT_IDS: RefCell<Vec<StoreId>> = RefCell::new(Vec::new());
+}
+
+/// Guard for an id entered with `enter_id()`, leaves the id when dropped
+#[must_use]
+pub struct IdGuard {
+    _private: (),
+}
+
+impl Drop for IdGuard {
+
+    fn drop(&mut self) {
+        CURRENT_IDS.with(|ids| {
+            ids.borrow_mut().pop();
+        })
+    }
+
+}
+
+/// Attach `id` to the log records of the current thread, until the returned guard is dropped
+///
+/// Calls can be nested, the id entered last is the current one.
+pub fn enter_id(id: &StoreId) -> IdGuard {
+    CURRENT_IDS.with(|ids| ids.borrow_mut().push(id.clone()));
+    IdGuard { _private: () }
+}
+
+/// The id which was entered last on the current thread, if any
+pub fn current_id() -> Option<StoreId> {
+    CURRENT_IDS.with(|ids| ids.borrow().last().cloned())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+    use storeid::StoreId;
+
+    #[test]
+    fn test_nested_ids() {
+        let a = StoreId::new_baseless(PathBuf::from("test/a")).unwrap();
+        let b = StoreId::new_baseless(PathBuf::from("test/b")).unwrap();
+
+        assert_eq!(current_id(), None);
+        {
+            let _outer = enter_id(&a);
+            {
+                let _inner = enter_id(&b);
+                assert_eq!(current_id(), Some(b.clone()));
+            }
+            assert_eq!(current_id(), Some(a.clone()));
+        }
+        assert_eq!(current_id(), None);
+    }
+
+}
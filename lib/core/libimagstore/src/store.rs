@@ -50,6 +50,12 @@ use libimagutil::debug_result::*;
 /// The Result Type returned by any interaction with the store that could fail
 pub type Result<T> = RResult<T, SE>;
 
+/// The name of the per-store configuration file
+///
+/// A file with this name directly inside the store directory is not an entry, it holds
+/// configuration which only applies to this store.
+pub const STORE_CONFIG_FILE_NAME : &'static str = ".imagrc.toml";
+
 
 #[derive(Debug, PartialEq)]
 enum StoreEntryStatus {
@@ -224,7 +230,8 @@ impl Store {
     ///  - CreateCallError(EntryAlreadyExists()) if the entry exists already.
     ///
     pub fn create<'a, S: IntoStoreId>(&'a self, id: S) -> Result<FileLockEntry<'a>> {
-        let id = id.into_storeid()?.with_base(self.path().clone());
+        let id     = id.into_storeid()?.with_base(self.path().clone());
+        let _guard = ::logging::enter_id(&id);
 
         debug!("Creating id: '{}'", id);
 
@@ -279,7 +286,8 @@ impl Store {
     ///  - RetrieveCallError(LockPoisoned()) if the internal lock is poisened.
     ///
     pub fn retrieve<'a, S: IntoStoreId>(&'a self, id: S) -> Result<FileLockEntry<'a>> {
-        let id = id.into_storeid()?.with_base(self.path().clone());
+        let id     = id.into_storeid()?.with_base(self.path().clone());
+        let _guard = ::logging::enter_id(&id);
         debug!("Retrieving id: '{}'", id);
         let entry = self
             .entries
@@ -309,7 +317,8 @@ impl Store {
     ///  - Errors Store::retrieve() might return
     ///
     pub fn get<'a, S: IntoStoreId + Clone>(&'a self, id: S) -> Result<Option<FileLockEntry<'a>>> {
-        let id = id.into_storeid()?.with_base(self.path().clone());
+        let id     = id.into_storeid()?.with_base(self.path().clone());
+        let _guard = ::logging::enter_id(&id);
 
         debug!("Getting id: '{}'", id);
 
@@ -352,6 +361,7 @@ impl Store {
     /// it is not public.
     ///
     fn _update<'a>(&'a self, entry: &mut FileLockEntry<'a>, modify_presence: bool) -> Result<()> {
+        let _guard    = ::logging::enter_id(&entry.location);
         let mut hsmap = self.entries.write().map_err(|_| SE::from_kind(SEK::LockPoisoned))?;
 
         let se = hsmap.get_mut(&entry.location).ok_or_else(|| {
@@ -430,7 +440,8 @@ impl Store {
     ///  - Errors StoreEntry::new() might return
     ///
     pub fn get_copy<S: IntoStoreId>(&self, id: S) -> Result<Entry> {
-        let id = id.into_storeid()?.with_base(self.path().clone());
+        let id     = id.into_storeid()?.with_base(self.path().clone());
+        let _guard = ::logging::enter_id(&id);
         debug!("Retrieving copy of '{}'", id);
         let entries = self.entries.write()
             .map_err(|_| SE::from_kind(SEK::LockPoisoned))
@@ -456,7 +467,8 @@ impl Store {
     ///  - DeleteCallError(FileError()) if the internals failed to remove the file.
     ///
     pub fn delete<S: IntoStoreId>(&self, id: S) -> Result<()> {
-        let id = id.into_storeid()?.with_base(self.path().clone());
+        let id     = id.into_storeid()?.with_base(self.path().clone());
+        let _guard = ::logging::enter_id(&id);
 
         debug!("Deleting id: '{}'", id);
 
@@ -528,6 +540,8 @@ impl Store {
     fn save_to_other_location(&self, entry: &FileLockEntry, new_id: StoreId, remove_old: bool)
         -> Result<()>
     {
+        let _guard = ::logging::enter_id(entry.get_location());
+
         let new_id = new_id.with_base(self.path().clone());
         let hsmap = self
             .entries
@@ -592,6 +606,7 @@ impl Store {
     pub fn move_by_id(&self, old_id: StoreId, new_id: StoreId) -> Result<()> {
         let new_id = new_id.with_base(self.path().clone());
         let old_id = old_id.with_base(self.path().clone());
+        let _guard = ::logging::enter_id(&old_id);
 
         debug!("Moving '{}' to '{}'", old_id, new_id);
 
@@ -650,6 +665,21 @@ impl Store {
             .map(|it| StoreIdIteratorWithStore::new(it, self))
     }
 
+    /// Get all entries in the store below `prefix` (by id as iterator)
+    ///
+    /// Only the subtree of the store denoted by `prefix` is traversed, which is a lot cheaper than
+    /// filtering the result of `Store::entries()` for large stores. A `prefix` which does not
+    /// exist yields no entries.
+    pub fn entries_below(&self, prefix: StoreId) -> Result<StoreIdIteratorWithStore> {
+        let path = prefix.with_base(self.path().clone()).into_pathbuf()?;
+
+        self.backend
+            .pathes_recursively(path)
+            .map(|i| i.store_id_constructing(self.path().clone(), self.backend.clone()))
+            .map(Box::new)
+            .map(|it| StoreIdIteratorWithStore::new(it, self))
+    }
+
     /// Gets the path where this store is on the disk
     pub fn path(&self) -> &PathBuf {
         &self.location
@@ -1204,5 +1234,28 @@ mod store_tests {
         }
     }
 
+    #[test]
+    fn test_store_entries_below() {
+        use storeid::StoreId;
+
+        let store = get_store();
+
+        for id in &["coll/2000/01/a", "coll/2000/01/b", "coll/2000/10/a", "other/2000/01/a"] {
+            let _ = store.create(PathBuf::from(id)).unwrap();
+        }
+
+        let mut ids = store
+            .entries_below(StoreId::new_baseless(PathBuf::from("coll/2000/01")).unwrap())
+            .unwrap()
+            .map(|id| id.unwrap().without_base().to_str().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort();
+
+        assert_eq!(vec!["coll/2000/01/a", "coll/2000/01/b"], ids);
+
+        let nonexistent = StoreId::new_baseless(PathBuf::from("coll/1999")).unwrap();
+        assert_eq!(0, store.entries_below(nonexistent).unwrap().count());
+    }
+
 }
 
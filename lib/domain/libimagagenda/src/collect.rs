@@ -0,0 +1,648 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Collectors for the dated items of the individual domains
+//!
+//! All ranges are wall clock time in the timezone which is passed alongside. Entries are only
+//! read, never written back.
+
+use std::collections::BTreeSet;
+
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagentrydatetime::datetime::EntryDate;
+use libimagentrydatetime::range::DateTimeRange;
+use libimagentrydatetime::recurrence::EntryRecurrence;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagcontact::contact::Contact;
+use libimagcontact::store::ContactStore;
+use libimagdiary::diary::Diary;
+use libimagdiary::entry::DiaryEntry;
+use libimaghabit::habit::HabitTemplate;
+use libimaghabit::store::HabitStore;
+use libimagtimetrack::timetracking::TimeTracking;
+use libimagtimetrack::timetrackingstore::TimeTrackStore;
+use libimagtodo::task::Task;
+use libimagtodo::taskstore::TaskStore;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
+
+use error::AgendaError as AE;
+use error::AgendaErrorKind as AEK;
+use error::Result;
+use item::AgendaItem;
+use item::AgendaItemKind;
+
+/// Collect the items of all `kinds` in `range`, in chronological order
+///
+/// `collections` are the collections which are searched for dated entries, see
+/// `dated_entry_items()`.
+pub fn agenda(store: &Store,
+              range: &DateTimeRange,
+              zone: Tz,
+              kinds: &[AgendaItemKind],
+              collections: Option<&[String]>)
+    -> Result<Vec<AgendaItem>>
+{
+    let wants     = |kind: AgendaItemKind| kinds.contains(&kind);
+    let mut items = vec![];
+
+    if wants(AgendaItemKind::Diary) {
+        items.extend(diary_items(store, range, zone)?);
+    }
+
+    if wants(AgendaItemKind::Habit) {
+        items.extend(habit_items(store, range, zone)?);
+    }
+
+    if wants(AgendaItemKind::TimeTracking) {
+        items.extend(timetrack_items(store, range, zone)?);
+    }
+
+    if wants(AgendaItemKind::Todo) {
+        items.extend(todo_items(store, range, zone)?);
+    }
+
+    if wants(AgendaItemKind::Birthday) || wants(AgendaItemKind::Anniversary) {
+        let contact_items = contact_items(store, range, zone)?
+            .into_iter()
+            .filter(|item| wants(item.kind()));
+
+        items.extend(contact_items);
+    }
+
+    if wants(AgendaItemKind::Date) {
+        items.extend(dated_entry_items(store, range, zone, collections)?);
+    }
+
+    items.sort_by(|a, b| a.chronological_cmp(b));
+    Ok(items)
+}
+
+/// Diary entries written in `range`
+///
+/// The title is the name of the diary and the first line of the entry.
+pub fn diary_items(store: &Store, range: &DateTimeRange, zone: Tz) -> Result<Vec<AgendaItem>> {
+    let mut names = BTreeSet::new();
+    for name in store.diary_names()? {
+        let _ = names.insert(name?);
+    }
+
+    let mut items = vec![];
+    for name in names {
+        for id in store.entries_in_range(&name, range)? {
+            let entry = store.get_copy(id?)?;
+
+            let dt = entry.datetime_zoned(zone)?;
+            if !in_range(range, zone, &dt) {
+                continue
+            }
+
+            let title = match first_line(entry.get_content()) {
+                Some(line) => format!("{}: {}", name, line),
+                None       => name.clone(),
+            };
+
+            items.push(AgendaItem::new(AgendaItemKind::Diary, dt, title, entry.get_location().clone()));
+        }
+    }
+
+    Ok(items)
+}
+
+/// Habit instances which are due in `range`
+///
+/// Instances which are already done are marked as such in the title.
+pub fn habit_items(store: &Store, range: &DateTimeRange, zone: Tz) -> Result<Vec<AgendaItem>> {
+    let mut items = vec![];
+
+    for id in store.all_habit_templates()? {
+        let entry = store.get_copy(id?)?;
+
+        let name     = entry.habit_name()?;
+        let mut base = range.start().date().and_hms(0, 0, 0);
+
+        while let Some(date) = entry.next_instance_date_after(&base)? {
+            if date > range.end().date() {
+                break
+            }
+
+            let title = if entry.instance_exists_for_date(&date)? {
+                format!("{} (done)", name)
+            } else {
+                name.clone()
+            };
+
+            let start = start_of_day(&date, zone)?;
+            let item  = AgendaItem::new(AgendaItemKind::Habit, start, title, entry.get_location().clone())
+                .with_all_day(true);
+
+            items.push(item);
+            base = (date + Duration::days(1)).and_hms(0, 0, 0);
+        }
+    }
+
+    Ok(items)
+}
+
+/// Timetrackings which were started in `range`
+pub fn timetrack_items(store: &Store, range: &DateTimeRange, zone: Tz) -> Result<Vec<AgendaItem>> {
+    let mut items = vec![];
+
+    for id in store.timetracking_ids_in_range(range)? {
+        let entry = store.get_copy(id?)?;
+        let start = match entry.get_start_datetime_zoned(zone)? {
+            Some(start) => start,
+            None        => continue,
+        };
+
+        if !in_range(range, zone, &start) {
+            continue
+        }
+
+        let title    = String::from(entry.get_timetrack_tag()?.as_str());
+        let mut item = AgendaItem::new(AgendaItemKind::TimeTracking, start, title, entry.get_location().clone());
+
+        if let Some(end) = entry.get_end_datetime_zoned(zone)? {
+            item = item.with_end(end);
+        }
+
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+/// Todos which are due in `range`
+pub fn todo_items(store: &Store, range: &DateTimeRange, zone: Tz) -> Result<Vec<AgendaItem>> {
+    let mut items = vec![];
+
+    for id in store.all_tasks()? {
+        let entry = store.get_copy(id?)?;
+
+        let due = match entry.get_due()? {
+            Some(due) => ZonedDateTime::from_zoned(zone.from_utc_datetime(&due)),
+            None      => continue,
+        };
+
+        if !in_range(range, zone, &due) {
+            continue
+        }
+
+        let title = format!("{}", entry.get_uuid()?);
+        items.push(AgendaItem::new(AgendaItemKind::Todo, due, title, entry.get_location().clone()));
+    }
+
+    Ok(items)
+}
+
+/// Birthdays and anniversaries of contacts in `range`
+///
+/// If the year of the date is known, the title contains the number of years.
+pub fn contact_items(store: &Store, range: &DateTimeRange, zone: Tz) -> Result<Vec<AgendaItem>> {
+    let mut items = vec![];
+
+    for id in store.all_contacts()? {
+        let entry = store.get_copy(id?)?;
+
+        let card = entry.deser()?;
+        let name = card
+            .fullname()
+            .first()
+            .cloned()
+            .unwrap_or_else(|| entry.get_location().local().display().to_string());
+
+        let dates = vec![
+            (AgendaItemKind::Birthday, card.bday()),
+            (AgendaItemKind::Anniversary, card.anniversary()),
+        ];
+
+        for (kind, date) in dates {
+            let (year, month, day) = match date.and_then(|d| parse_vcard_date(d)) {
+                Some(parsed) => parsed,
+                None         => continue,
+            };
+
+            for y in range.start().year()..(range.end().year() + 1) {
+                let date = match NaiveDate::from_ymd_opt(y, month, day) {
+                    Some(date) => date,
+                    None       => continue, // February 29th in a non-leap year
+                };
+
+                if date < range.start().date() || date > range.end().date() {
+                    continue
+                }
+
+                let title = match year {
+                    Some(since) if since <= y => format!("{} ({})", name, y - since),
+                    _                         => name.clone(),
+                };
+
+                let start = start_of_day(&date, zone)?;
+                let item  = AgendaItem::new(kind, start, title, entry.get_location().clone())
+                    .with_all_day(true);
+
+                items.push(item);
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+/// Collections which have their own collector, so their entries are not searched for dates
+const COLLECTED_ELSEWHERE : &'static [&'static str] = &["diary", "habit", "timetrack", "todo", "contact"];
+
+/// Path of the collections which are searched for dated entries in the imag configuration
+pub const DATED_COLLECTIONS_CONFIG_PATH : &'static str = "agenda.collections";
+
+/// The configuration keys the agenda reads
+pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: DATED_COLLECTIONS_CONFIG_PATH,
+        kind: ValueType::Array,
+        default: None,
+        doc: "The collections which are searched for entries with a date, defaults to all collections",
+    },
+];
+
+/// Get the collections which are searched for dated entries from the configuration
+///
+/// Returns None if nothing is configured, which means that all collections are searched.
+pub fn dated_collections(config: Option<&Value>) -> Result<Option<Vec<String>>> {
+    let value = match config {
+        Some(cfg) => cfg.read(DATED_COLLECTIONS_CONFIG_PATH)?,
+        None      => None,
+    };
+
+    match value {
+        None                         => Ok(None),
+        Some(&Value::Array(ref ary)) => ary
+            .iter()
+            .map(|v| match *v {
+                Value::String(ref s) => Ok(s.clone()),
+                _ => Err(AE::from_kind(AEK::ConfigTypeError(String::from(DATED_COLLECTIONS_CONFIG_PATH)))),
+            })
+            .collect::<Result<Vec<String>>>()
+            .map(Some),
+        Some(_) => Err(AE::from_kind(AEK::ConfigTypeError(String::from(DATED_COLLECTIONS_CONFIG_PATH)))),
+    }
+}
+
+/// Entries with a date, a date range or a recurrence (see `libimagentrydatetime`) in `range`
+///
+/// Only the entries in `collections` (like "notes" or "wiki/main") are searched, or all entries if
+/// no collections are passed. Entries of the collections which have their own collector (like the
+/// diary) are always skipped. Entries with a date which cannot be parsed are skipped with a
+/// warning.
+pub fn dated_entry_items(store: &Store, range: &DateTimeRange, zone: Tz, collections: Option<&[String]>)
+    -> Result<Vec<AgendaItem>>
+{
+    let collections = collections.map(|colls| {
+        colls
+            .iter()
+            .map(|c| c.split('/').filter(|s| !s.is_empty()).collect::<Vec<&str>>())
+            .collect::<Vec<_>>()
+    });
+
+    let mut items = vec![];
+
+    for id in store.entries()?.without_store() {
+        let id = id?;
+        if COLLECTED_ELSEWHERE.iter().any(|c| id.is_in_collection(&[c])) {
+            continue
+        }
+
+        if let Some(ref colls) = collections {
+            if !colls.iter().any(|c| id.is_in_collection(c)) {
+                continue
+            }
+        }
+
+        let entry = store.get_copy(id)?;
+        match entry_date_items(&entry, range, zone) {
+            Ok(found) => items.extend(found),
+            Err(e)    => warn!("Ignoring the date of {}: {}", entry.get_location(), e),
+        }
+    }
+
+    Ok(items)
+}
+
+/// The dates, date ranges and recurrences of `entry` in `range`
+fn entry_date_items(entry: &Entry, range: &DateTimeRange, zone: Tz) -> Result<Vec<AgendaItem>> {
+    let mut items = vec![];
+
+    // Entries imported from iCalendar have a summary
+    let title = match entry.get_header().read("agenda.summary")? {
+        Some(&Value::String(ref summary)) => summary.clone(),
+        _ => entry.get_location().local().display().to_string(),
+    };
+    let location = entry.get_location().clone();
+
+    // Entries imported from iCalendar can last whole days
+    let all_day = match entry.get_header().read("agenda.all_day")? {
+        Some(&Value::Boolean(b)) => b,
+        _                        => false,
+    };
+
+    let item = |start: ZonedDateTime, end: Option<ZonedDateTime>| {
+        let item = AgendaItem::new(AgendaItemKind::Date, start, title.clone(), location.clone())
+            .with_all_day(all_day);
+
+        match end {
+            Some(end) => item.with_end(end),
+            None      => item,
+        }
+    };
+
+    if entry.get_header().read("datetime.value")?.is_some() {
+        let dt = entry.read_date_zoned(zone)?;
+
+        if in_range(range, zone, &dt) {
+            items.push(item(dt, None));
+        }
+    }
+
+    if entry.get_header().read("datetime.range")?.is_some() {
+        let span = entry.read_date_range_zoned(zone)?;
+
+        if overlaps(range, zone, span.start(), span.end()) {
+            items.push(item(span.start().clone(), Some(span.end().clone())));
+        }
+    }
+
+    if let Some(recurrence) = entry.get_recurrence()? {
+        let event_zone = recurrence.timezone().unwrap_or(zone);
+        let duration   = recurrence.duration().unwrap_or_else(Duration::zero);
+
+        // The occurrences are in the timezone of the recurrence, so the window is a day bigger on
+        // both sides and includes the occurrences which started before but last into the range
+        let window = DateTimeRange::new(*range.start() - Duration::days(1) - duration,
+                                        *range.end() + Duration::days(1))?;
+
+        for occurrence in recurrence.occurrences_in(&window) {
+            // Occurrences which fall into a gap of a DST change do not happen
+            let start = match ZonedDateTime::from_local(&occurrence, event_zone) {
+                Ok(start) => start,
+                Err(_)    => continue,
+            };
+
+            let end = match recurrence.duration() {
+                Some(duration) => start
+                    .datetime()
+                    .checked_add_signed(duration)
+                    .map(|end| ZonedDateTime::new(end, start.zone())),
+                None => None,
+            };
+
+            if overlaps(range, zone, &start, end.as_ref().unwrap_or(&start)) {
+                items.push(item(start, end));
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+/// Check whether `dt` lies in `range`
+fn in_range(range: &DateTimeRange, zone: Tz, dt: &ZonedDateTime) -> bool {
+    range.contains(&dt.in_zone(zone).naive_local())
+}
+
+/// Check whether the span from `start` to `end` overlaps `range`
+fn overlaps(range: &DateTimeRange, zone: Tz, start: &ZonedDateTime, end: &ZonedDateTime) -> bool {
+    start.in_zone(zone).naive_local() <= *range.end() && end.in_zone(zone).naive_local() >= *range.start()
+}
+
+/// The start of `date` in `zone`, which is 01:00 on days where midnight does not exist
+fn start_of_day(date: &NaiveDate, zone: Tz) -> Result<ZonedDateTime> {
+    ZonedDateTime::from_local(&date.and_hms(0, 0, 0), zone)
+        .or_else(|_| ZonedDateTime::from_local(&date.and_hms(1, 0, 0), zone))
+        .map_err(From::from)
+}
+
+fn first_line(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+}
+
+/// Parse a vcard date ("19960415", "1996-04-15", "--0415" or "--04-15"), ignoring a time part
+///
+/// Returns the year, if known, the month and the day.
+fn parse_vcard_date(s: &str) -> Option<(Option<i32>, u32, u32)> {
+    let date = s.split('T').next().unwrap_or("");
+    if !date.is_ascii() {
+        return None;
+    }
+
+    let (year, rest) = if date.starts_with("--") {
+        (None, date[2..].replace("-", ""))
+    } else {
+        let digits = date.replace("-", "");
+        if digits.len() != 8 {
+            return None;
+        }
+
+        (Some(digits[..4].parse::<i32>().ok()?), String::from(&digits[4..]))
+    };
+
+    if rest.len() != 4 {
+        return None;
+    }
+
+    let month = rest[..2].parse::<u32>().ok()?;
+    let day   = rest[2..].parse::<u32>().ok()?;
+    Some((year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use chrono::NaiveDate;
+
+    use libimagstore::store::Store;
+    use libimagentrydatetime::datetime::EntryDate;
+    use libimagentrydatetime::range::DateTimeRange;
+    use libimagentrydatetime::timezone::Tz;
+
+    use super::*;
+
+    fn get_store() -> Store {
+        use libimagstore::store::InMemoryFileAbstraction;
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    fn week() -> DateTimeRange {
+        let start = NaiveDate::from_ymd(2018, 5, 14).and_hms(0, 0, 0);
+        let end   = NaiveDate::from_ymd(2018, 5, 20).and_hms(23, 59, 59);
+        DateTimeRange::new(start, end).unwrap()
+    }
+
+    #[test]
+    fn test_parse_vcard_date() {
+        assert_eq!(Some((Some(1996), 4, 15)), parse_vcard_date("19960415"));
+        assert_eq!(Some((Some(1996), 4, 15)), parse_vcard_date("1996-04-15"));
+        assert_eq!(Some((Some(1996), 4, 15)), parse_vcard_date("1996-04-15T10:00:00Z"));
+        assert_eq!(Some((None, 4, 15)), parse_vcard_date("--0415"));
+        assert_eq!(Some((None, 4, 15)), parse_vcard_date("--04-15"));
+        assert_eq!(None, parse_vcard_date("1996"));
+        assert_eq!(None, parse_vcard_date("sometime"));
+    }
+
+    #[test]
+    fn test_dated_entries_in_range() {
+        let store = get_store();
+
+        {
+            let mut entry = store.create(PathBuf::from("in")).unwrap();
+            let _ = entry.set_date(NaiveDate::from_ymd(2018, 5, 16).and_hms(12, 0, 0)).unwrap();
+        }
+
+        {
+            let mut entry = store.create(PathBuf::from("out")).unwrap();
+            let _ = entry.set_date(NaiveDate::from_ymd(2018, 5, 21).and_hms(12, 0, 0)).unwrap();
+        }
+
+        {
+            let start     = NaiveDate::from_ymd(2018, 5, 1).and_hms(0, 0, 0);
+            let end       = NaiveDate::from_ymd(2018, 5, 14).and_hms(8, 0, 0);
+            let mut entry = store.create(PathBuf::from("span")).unwrap();
+            let _ = entry.set_date_range(start, end).unwrap();
+        }
+
+        let _ = store.create(PathBuf::from("undated")).unwrap();
+
+        let items = agenda(&store, &week(), Tz::UTC, &[AgendaItemKind::Date], None).unwrap();
+        let names = items.iter().map(|i| i.title().clone()).collect::<Vec<_>>();
+
+        assert_eq!(vec!["span", "in"], names);
+        assert!(items[0].end().is_some());
+        assert!(items[1].end().is_none());
+    }
+
+    #[test]
+    fn test_agenda_filters_kinds() {
+        let store = get_store();
+
+        {
+            let mut entry = store.create(PathBuf::from("in")).unwrap();
+            let _ = entry.set_date(NaiveDate::from_ymd(2018, 5, 16).and_hms(12, 0, 0)).unwrap();
+        }
+
+        let items = agenda(&store, &week(), Tz::UTC, &[AgendaItemKind::Birthday], None).unwrap();
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_dates_are_skipped() {
+        use toml::Value;
+        use toml_query::insert::TomlValueInsertExt;
+
+        let store = get_store();
+
+        {
+            let mut entry = store.create(PathBuf::from("in")).unwrap();
+            let _ = entry.set_date(NaiveDate::from_ymd(2018, 5, 16).and_hms(12, 0, 0)).unwrap();
+        }
+
+        {
+            let mut entry = store.create(PathBuf::from("broken")).unwrap();
+            let value     = Value::String(String::from("sometime"));
+            let _ = entry.get_header_mut().insert("datetime.value", value).unwrap();
+        }
+
+        let items = agenda(&store, &week(), Tz::UTC, &[AgendaItemKind::Date], None).unwrap();
+        let names = items.iter().map(|i| i.title().clone()).collect::<Vec<_>>();
+        assert_eq!(vec!["in"], names);
+    }
+
+    #[test]
+    fn test_recurrence_in_its_timezone() {
+        use std::str::FromStr;
+        use chrono::Duration;
+        use libimagentrydatetime::recurrence::EntryRecurrence;
+        use libimagentrydatetime::recurrence::RRule;
+        use libimagentrydatetime::recurrence::Recurrence;
+        use libimagentrydatetime::timezone::parse_timezone;
+
+        let store    = get_store();
+        let new_york = parse_timezone("America/New_York").unwrap();
+
+        {
+            // 2018-03-05 is a monday, DST starts on 2018-03-11 in New York
+            let start      = NaiveDate::from_ymd(2018, 3, 5).and_hms(9, 0, 0);
+            let rule       = RRule::from_str("FREQ=WEEKLY;COUNT=2").unwrap();
+            let recurrence = Recurrence::new(start, rule)
+                .unwrap()
+                .with_timezone(new_york)
+                .with_duration(Duration::hours(1));
+
+            let mut entry = store.create(PathBuf::from("weekly")).unwrap();
+            let _ = entry.set_recurrence(&recurrence).unwrap();
+        }
+
+        let start = NaiveDate::from_ymd(2018, 3, 1).and_hms(0, 0, 0);
+        let end   = NaiveDate::from_ymd(2018, 3, 31).and_hms(23, 59, 59);
+        let month = DateTimeRange::new(start, end).unwrap();
+        let items = agenda(&store, &month, Tz::UTC, &[AgendaItemKind::Date], None).unwrap();
+        let times = items
+            .iter()
+            .map(|i| (i.start().in_zone(Tz::UTC).to_rfc3339(), i.end().map(|e| e.in_zone(Tz::UTC).to_rfc3339())))
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![
+            (String::from("2018-03-05T14:00:00+00:00"), Some(String::from("2018-03-05T15:00:00+00:00"))),
+            (String::from("2018-03-12T13:00:00+00:00"), Some(String::from("2018-03-12T14:00:00+00:00"))),
+        ], times);
+    }
+
+    #[test]
+    fn test_dated_entries_only_in_collections() {
+        let store = get_store();
+
+        for name in &["notes/a", "wiki/main/b", "other/c"] {
+            let mut entry = store.create(PathBuf::from(name)).unwrap();
+            let _ = entry.set_date(NaiveDate::from_ymd(2018, 5, 16).and_hms(12, 0, 0)).unwrap();
+        }
+
+        let collections = vec![String::from("notes"), String::from("wiki/main")];
+        let items = dated_entry_items(&store, &week(), Tz::UTC, Some(&collections)).unwrap();
+        let mut names = items.iter().map(|i| i.title().clone()).collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(vec!["notes/a", "wiki/main/b"], names);
+    }
+
+}
+
@@ -0,0 +1,59 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+error_chain! {
+    types {
+        AgendaError, AgendaErrorKind, ResultExt, Result;
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+        TomlQueryError(::toml_query::error::Error);
+    }
+
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+        DateError(::libimagentrydatetime::error::DateError, ::libimagentrydatetime::error::DateErrorKind);
+        ContactError(::libimagcontact::error::ContactError, ::libimagcontact::error::ContactErrorKind);
+        DiaryError(::libimagdiary::error::DiaryError, ::libimagdiary::error::DiaryErrorKind);
+        HabitError(::libimaghabit::error::HabitError, ::libimaghabit::error::HabitErrorKind);
+        TimeTrackError(::libimagtimetrack::error::TimeTrackError, ::libimagtimetrack::error::TimeTrackErrorKind);
+        TodoError(::libimagtodo::error::TodoError, ::libimagtodo::error::TodoErrorKind);
+        TagError(::libimagentrytag::error::TagError, ::libimagentrytag::error::TagErrorKind);
+        VObjectError(::vobject::error::VObjectError, ::vobject::error::VObjectErrorKind);
+    }
+
+    errors {
+        UnknownItemKind(s: String) {
+            description("Unknown agenda item kind")
+            display("Unknown agenda item kind: '{}'", s)
+        }
+
+        MalformedICalendar(s: String) {
+            description("Malformed iCalendar data")
+            display("Malformed iCalendar data: '{}'", s)
+        }
+
+        ConfigTypeError(path: String) {
+            description("Configuration value has the wrong type")
+            display("Configuration value at '{}' has the wrong type", path)
+        }
+    }
+}
+
@@ -0,0 +1,129 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use chrono::Duration;
+
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
+
+use item::AgendaItem;
+use item::AgendaItemKind;
+use ical::escape_text;
+use ical::fold_line;
+use ical::format_date;
+use ical::format_utc;
+
+/// Render agenda items as an iCalendar document
+///
+/// Todos become VTODO components with their due date, all other items become VEVENT components.
+/// All day items are exported as dates in `zone`, all other times in UTC. `stamp` is the time
+/// the document is created at.
+pub fn to_icalendar(items: &[AgendaItem], zone: Tz, stamp: &ZonedDateTime) -> String {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//imag//imag-agenda//EN"),
+    ];
+
+    for item in items {
+        let component = match item.kind() {
+            AgendaItemKind::Todo => "VTODO",
+            _                    => "VEVENT",
+        };
+
+        let uid = format!("{}-{}-{}@imag",
+                          item.kind(),
+                          format_utc(item.start()),
+                          item.id().local().display());
+
+        lines.push(format!("BEGIN:{}", component));
+        lines.push(format!("UID:{}", escape_text(&uid)));
+        lines.push(format!("DTSTAMP:{}", format_utc(stamp)));
+
+        if item.kind() == AgendaItemKind::Todo {
+            lines.push(format!("DUE:{}", format_utc(item.start())));
+        } else if item.is_all_day() {
+            let first = item.start().in_zone(zone).naive_local().date();
+            let last  = item
+                .end()
+                .map(|end| end.in_zone(zone).naive_local().date())
+                .unwrap_or(first);
+
+            lines.push(format!("DTSTART;VALUE=DATE:{}", format_date(&first)));
+            lines.push(format!("DTEND;VALUE=DATE:{}", format_date(&(last + Duration::days(1)))));
+        } else {
+            lines.push(format!("DTSTART:{}", format_utc(item.start())));
+            if let Some(end) = item.end() {
+                lines.push(format!("DTEND:{}", format_utc(end)));
+            }
+        }
+
+        lines.push(format!("SUMMARY:{}", escape_text(item.title())));
+        lines.push(format!("CATEGORIES:{}", item.kind()));
+        lines.push(format!("END:{}", component));
+    }
+
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.into_iter()
+        .map(|line| fold_line(&line) + "\r\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::NaiveDate;
+
+    use libimagstore::storeid::StoreId;
+    use libimagentrydatetime::timezone::parse_timezone;
+
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let at    = |d, h| ZonedDateTime::from_local(&NaiveDate::from_ymd(2018, 5, d).and_hms(h, 0, 0), zone).unwrap();
+        let id    = |s: &str| StoreId::new_baseless(PathBuf::from(s)).unwrap();
+
+        let items = vec![
+            AgendaItem::new(AgendaItemKind::TimeTracking, at(14, 9), String::from("work"), id("timetrack/work"))
+                .with_end(at(14, 12)),
+            AgendaItem::new(AgendaItemKind::Birthday, at(15, 0), String::from("Jane, 30"), id("contact/jane"))
+                .with_all_day(true),
+            AgendaItem::new(AgendaItemKind::Todo, at(16, 18), String::from("taxes"), id("todo/taxes")),
+        ];
+
+        let ics   = to_icalendar(&items, zone, &at(1, 0));
+        let lines = ics.split("\r\n").collect::<Vec<_>>();
+
+        assert_eq!(Some(&"BEGIN:VCALENDAR"), lines.first());
+        assert!(lines.contains(&"DTSTART:20180514T070000Z"));
+        assert!(lines.contains(&"DTEND:20180514T100000Z"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20180515"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20180516"));
+        assert!(lines.contains(&"SUMMARY:Jane\\, 30"));
+        assert!(lines.contains(&"BEGIN:VTODO"));
+        assert!(lines.contains(&"DUE:20180516T160000Z"));
+        assert_eq!(2, lines.iter().filter(|l| **l == "BEGIN:VEVENT").count());
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
+
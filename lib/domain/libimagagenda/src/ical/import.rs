@@ -0,0 +1,750 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use std::str::FromStr;
+
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::TimeZone;
+use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadExt;
+use vobject::Component;
+use vobject::Property;
+use vobject::parse_component;
+
+use libimagstore::store::Store;
+use libimagstore::store::FileLockEntry;
+use libimagstore::storeid::IntoStoreId;
+use libimagstore::storeid::StoreId;
+use libimagentrydatetime::datetime::EntryDate;
+use libimagentrydatetime::range::ZonedDateTimeRange;
+use libimagentrydatetime::recurrence::EntryRecurrence;
+use libimagentrydatetime::recurrence::RRule;
+use libimagentrydatetime::recurrence::Recurrence;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::parse_timezone;
+use libimagentrytag::tagable::Tagable;
+use libimagutil::tag::normalize_tag;
+
+use error::AgendaError as AE;
+use error::AgendaErrorKind as AEK;
+use error::Result;
+use ical::unescape_text;
+use module_path::ModuleEntryPath;
+
+/// Import all events and todos of an iCalendar document into the store
+///
+/// Each event or todo becomes the entry "agenda/ical/<UID>", so importing an updated document
+/// updates the entries. Dates are stored with the `libimagentrydatetime` headers (recurring
+/// events only with their recurrence, in the timezone of their start and with their duration),
+/// the summary and location in the "agenda" header section and the description as content. All
+/// day events are marked with "agenda.all_day". CATEGORIES become tags
+/// (see `libimagutil::tag::normalize_tag()`).
+///
+/// Components with a RECURRENCE-ID override a single occurrence of a recurring event. They become
+/// the entry "agenda/ical/<UID>_<RECURRENCE-ID>" and the occurrence is excluded from the
+/// recurrence of the event.
+///
+/// Times without timezone are interpreted in `zone`.
+pub fn import_icalendar(store: &Store, ics: &str, zone: Tz) -> Result<Vec<StoreId>> {
+    parse_icalendar(ics, zone)?
+        .into_iter()
+        .map(|item| store_item(store, &item, zone).map(|entry| entry.get_location().clone()))
+        .collect()
+}
+
+/// A date or a datetime
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum IcalTime {
+    Date(NaiveDate),
+    DateTime(ZonedDateTime),
+}
+
+impl IcalTime {
+
+    /// The time in the basic iCalendar format, datetimes in UTC
+    fn to_ical_string(&self) -> String {
+        match *self {
+            IcalTime::Date(ref date)   => date.format("%Y%m%d").to_string(),
+            IcalTime::DateTime(ref dt) => dt.in_zone(Tz::UTC).format("%Y%m%dT%H%M%SZ").to_string(),
+        }
+    }
+
+    /// The point in time, dates start at midnight in `zone`
+    fn start_in(&self, zone: Tz) -> Result<ZonedDateTime> {
+        match *self {
+            IcalTime::Date(ref date)   => ZonedDateTime::from_local(&date.and_hms(0, 0, 0), zone)
+                .map_err(From::from),
+            IcalTime::DateTime(ref dt) => Ok(dt.clone()),
+        }
+    }
+
+}
+
+/// A VEVENT or VTODO component
+#[derive(Clone, Debug)]
+struct CalendarItem {
+    uid: String,
+    is_todo: bool,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    categories: Vec<String>,
+    start: IcalTime,
+    end: Option<IcalTime>,
+    rrule: Option<String>,
+    exdates: Vec<IcalTime>,
+    recurrence_id: Option<IcalTime>,
+}
+
+impl CalendarItem {
+
+    /// Whether the item lasts whole days, which is the case if it starts at a date
+    fn is_all_day(&self) -> bool {
+        match self.start {
+            IcalTime::Date(_)     => true,
+            IcalTime::DateTime(_) => false,
+        }
+    }
+
+}
+
+fn parse_icalendar(ics: &str, zone: Tz) -> Result<Vec<CalendarItem>> {
+    let calendar = parse_component(&String::from(ics))?;
+    if calendar.name != "VCALENDAR" {
+        let msg = format!("Expected VCALENDAR, found {}", calendar.name);
+        return Err(AE::from_kind(AEK::MalformedICalendar(msg)));
+    }
+
+    let mut items = vec![];
+    for component in calendar.subcomponents.iter() {
+        let is_todo = match component.name.as_str() {
+            "VEVENT" => false,
+            "VTODO"  => true,
+            other    => {
+                debug!("Skipping {} component", other);
+                continue
+            },
+        };
+
+        if let Some(item) = parse_item(component, is_todo, zone)? {
+            items.push(item);
+        }
+    }
+
+    // Overridden occurrences do not happen as part of the recurrence
+    let overridden = items
+        .iter()
+        .filter_map(|item| item.recurrence_id.clone().map(|rid| (item.uid.clone(), rid)))
+        .collect::<Vec<_>>();
+
+    for (uid, rid) in overridden {
+        let master = items
+            .iter_mut()
+            .find(|item| item.uid == uid && item.recurrence_id.is_none());
+
+        match master {
+            Some(master) => master.exdates.push(rid),
+            None         => debug!("No recurring event '{}' for overridden occurrence", uid),
+        }
+    }
+
+    Ok(items)
+}
+
+fn parse_item(component: &Component, is_todo: bool, zone: Tz) -> Result<Option<CalendarItem>> {
+    let text = |name: &str| component.get_only(name).map(|p| unescape_text(&p.raw_value));
+
+    let uid = match text("UID") {
+        Some(uid) => uid,
+        None      => {
+            warn!("Skipping {} without UID", component.name);
+            return Ok(None)
+        },
+    };
+
+    let start = if is_todo {
+        component.get_only("DUE").or_else(|| component.get_only("DTSTART"))
+    } else {
+        component.get_only("DTSTART")
+    };
+
+    let start = match start {
+        Some(prop) => parse_time(&prop.raw_value, prop, zone)?,
+        None       => {
+            warn!("Skipping '{}', it has no date", uid);
+            return Ok(None)
+        },
+    };
+
+    let end = if is_todo {
+        None
+    } else if let Some(prop) = component.get_only("DTEND") {
+        Some(parse_time(&prop.raw_value, prop, zone)?)
+    } else if let Some(prop) = component.get_only("DURATION") {
+        let duration = parse_duration(&prop.raw_value).ok_or_else(|| malformed(prop, &prop.raw_value))?;
+        Some(add_duration(&start, duration, zone)?)
+    } else {
+        None
+    };
+
+    let recurrence_id = match component.get_only("RECURRENCE-ID") {
+        Some(prop) => Some(parse_time(&prop.raw_value, prop, zone)?),
+        None       => None,
+    };
+
+    let mut exdates = vec![];
+    for prop in component.get_all("EXDATE") {
+        for value in prop.raw_value.split(',') {
+            exdates.push(parse_time(value, prop, zone)?);
+        }
+    }
+
+    let categories = component
+        .get_all("CATEGORIES")
+        .iter()
+        .flat_map(|prop| split_list(&prop.raw_value))
+        .collect();
+
+    Ok(Some(CalendarItem {
+        uid,
+        is_todo,
+        summary: text("SUMMARY"),
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        categories,
+        start,
+        end,
+        rrule: component.get_only("RRULE").map(|p| p.raw_value.clone()),
+        exdates,
+        recurrence_id,
+    }))
+}
+
+fn malformed(prop: &Property, value: &str) -> AE {
+    AE::from_kind(AEK::MalformedICalendar(format!("{}:{}", prop.name, value)))
+}
+
+/// Parse a DATE or DATE-TIME `value` of `prop`
+///
+/// Times in UTC stay in UTC, floating times (without TZID) are interpreted in `zone`.
+fn parse_time(value: &str, prop: &Property, zone: Tz) -> Result<IcalTime> {
+    let value   = value.trim();
+    let is_date = prop
+        .params
+        .get("VALUE")
+        .map(|v| v.eq_ignore_ascii_case("DATE"))
+        .unwrap_or(false) || value.len() == 8;
+
+    if is_date {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(IcalTime::Date)
+            .map_err(|_| malformed(prop, value));
+    }
+
+    if value.ends_with('Z') {
+        let ndt = NaiveDateTime::parse_from_str(value.trim_right_matches('Z'), "%Y%m%dT%H%M%S")
+            .map_err(|_| malformed(prop, value))?;
+
+        return Ok(IcalTime::DateTime(ZonedDateTime::from_zoned(Tz::UTC.from_utc_datetime(&ndt))));
+    }
+
+    let ndt = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| malformed(prop, value))?;
+    let tz  = match prop.params.get("TZID") {
+        Some(tzid) => parse_timezone(tzid.trim_matches('"')).unwrap_or_else(|_| {
+            warn!("Unknown timezone '{}', using {}", tzid, zone.name());
+            zone
+        }),
+        None => zone,
+    };
+
+    ZonedDateTime::from_local(&ndt, tz)
+        .map(IcalTime::DateTime)
+        .map_err(From::from)
+}
+
+/// Parse a (positive) DURATION value like "P1D", "PT1H30M" or "P2W"
+///
+/// Returns None for malformed values and values which do not fit into a `Duration`.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim().trim_left_matches('+');
+    if !s.starts_with('P') {
+        return None;
+    }
+
+    let mut total   = Duration::zero();
+    let mut number  = String::new();
+    let mut in_time = false;
+
+    for c in s[1..].chars() {
+        match c {
+            '0'...'9' => number.push(c),
+            'T'       => in_time = true,
+            unit      => {
+                let n = number.parse::<i64>().ok()?;
+                number.clear();
+
+                let factor = match (unit, in_time) {
+                    ('W', false) => 7 * 24 * 60 * 60,
+                    ('D', false) => 24 * 60 * 60,
+                    ('H', true)  => 60 * 60,
+                    ('M', true)  => 60,
+                    ('S', true)  => 1,
+                    _            => return None,
+                };
+
+                // Duration::seconds() panics for values which do not fit into milliseconds
+                let secs = n.checked_mul(factor)?;
+                if secs > ::std::i64::MAX / 1000 {
+                    return None;
+                }
+
+                total = total.checked_add(&Duration::seconds(secs))?;
+            },
+        }
+    }
+
+    if number.is_empty() {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+fn add_duration(start: &IcalTime, duration: Duration, zone: Tz) -> Result<IcalTime> {
+    let out_of_range = || {
+        let msg = format!("DURATION {} is out of range", duration);
+        AE::from_kind(AEK::MalformedICalendar(msg))
+    };
+
+    match *start {
+        IcalTime::Date(ref date) if duration.num_seconds() % 86400 == 0 => {
+            date.checked_add_signed(Duration::days(duration.num_days()))
+                .map(IcalTime::Date)
+                .ok_or_else(out_of_range)
+        },
+        _ => {
+            let start = start.start_in(zone)?;
+            let end   = start.datetime().checked_add_signed(duration).ok_or_else(out_of_range)?;
+            Ok(IcalTime::DateTime(ZonedDateTime::new(end, start.zone())))
+        },
+    }
+}
+
+/// The UNTIL part of the RRULE `rule`, if it is a time in UTC
+fn utc_until(rule: &str) -> Option<NaiveDateTime> {
+    rule.split(';')
+        .filter_map(|part| {
+            let mut kv = part.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some(key), Some(value)) if key.trim().eq_ignore_ascii_case("UNTIL") => Some(value.trim()),
+                _ => None,
+            }
+        })
+        .find(|value| value.ends_with('Z') || value.ends_with('z'))
+        .and_then(|value| NaiveDateTime::parse_from_str(&value[..value.len() - 1], "%Y%m%dT%H%M%S").ok())
+}
+
+/// Split a list of TEXT values at the unescaped commas
+fn split_list(s: &str) -> Vec<String> {
+    let mut values  = vec![];
+    let mut current = String::new();
+    let mut escaped = false;
+
+    for c in s.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            values.push(unescape_text(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    values.push(unescape_text(&current));
+
+    values.into_iter().filter(|v| !v.trim().is_empty()).collect()
+}
+
+fn store_item<'a>(store: &'a Store, item: &CalendarItem, zone: Tz) -> Result<FileLockEntry<'a>> {
+    let name = match item.recurrence_id {
+        Some(ref rid) => format!("ical/{}_{}", item.uid.replace('/', "_"), rid.to_ical_string()),
+        None          => format!("ical/{}", item.uid.replace('/', "_")),
+    };
+
+    let mut entry = ModuleEntryPath::new(name).into_storeid().and_then(|id| store.retrieve(id))?;
+
+    // The tags a previous import added for the categories
+    let old_categories = match entry.get_header().read("agenda.categories")? {
+        Some(&Value::Array(ref ary)) => ary.iter().filter_map(Value::as_str).map(String::from).collect(),
+        _                            => vec![],
+    };
+
+    {
+        let header = entry.get_header_mut();
+
+        // Forget what a previous import of this item wrote
+        if let Some(table) = header.as_table_mut() {
+            let _ = table.remove("datetime");
+            let _ = table.remove("agenda");
+        }
+
+        let _ = header.insert("agenda.uid", Value::String(item.uid.clone()))?;
+        let _ = header.insert("agenda.is_todo", Value::Boolean(item.is_todo))?;
+
+        if let Some(ref summary) = item.summary {
+            let _ = header.insert("agenda.summary", Value::String(summary.clone()))?;
+        }
+
+        if let Some(ref location) = item.location {
+            let _ = header.insert("agenda.location", Value::String(location.clone()))?;
+        }
+
+        if let Some(ref rid) = item.recurrence_id {
+            let _ = header.insert("agenda.recurrence_id", Value::String(rid.to_ical_string()))?;
+        }
+
+        if item.is_all_day() {
+            let _ = header.insert("agenda.all_day", Value::Boolean(true))?;
+        }
+    }
+
+    let rule = match item.rrule {
+        Some(ref rule) => match RRule::from_str(rule) {
+            Ok(rule) => Some(rule),
+            Err(e)   => {
+                warn!("Not importing the recurrence of '{}': {}", item.uid, e);
+                None
+            },
+        },
+        None => None,
+    };
+
+    let start = item.start.start_in(zone)?;
+    let end   = match item.end {
+        // The end of an all day event is the day after the last day, all day events are stored
+        // from the start of their first to the start of their last day
+        Some(IcalTime::Date(ref date)) => Some(ZonedDateTime::from_local(&date.pred().and_hms(0, 0, 0), zone)?),
+        Some(IcalTime::DateTime(ref dt)) => Some(dt.clone()),
+        None => None,
+    };
+
+    // Only an end after the start makes a range
+    let end = match end {
+        Some(end) => if end.naive_utc() > start.naive_utc() { Some(end) } else { None },
+        None      => None,
+    };
+
+    if let Some(mut rule) = rule {
+        // The occurrences happen at the wall clock time of the start in its timezone, so they
+        // stay at the same time of the day across DST changes
+        let event_zone = start.zone().unwrap_or(zone);
+        let local      = |t: &IcalTime| t.start_in(zone).map(|dt| dt.in_zone(event_zone).naive_local());
+
+        if let Some(until) = item.rrule.as_ref().and_then(|r| utc_until(r)) {
+            rule = rule.with_until(event_zone.from_utc_datetime(&until).naive_local());
+        }
+
+        let mut recurrence = Recurrence::new(local(&item.start)?, rule)?.with_timezone(event_zone);
+        if let Some(ref end) = end {
+            recurrence = recurrence.with_duration(end.signed_duration_since(&start));
+        }
+
+        let recurrence = item
+            .exdates
+            .iter()
+            .map(&local)
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .fold(recurrence, Recurrence::with_exception);
+
+        let _ = entry.set_recurrence(&recurrence)?;
+    } else {
+        match end {
+            Some(end) => {
+                let _ = entry.set_date_range_zoned(&ZonedDateTimeRange::new(start, end)?)?;
+            },
+            None => {
+                let _ = entry.set_date_zoned(&start)?;
+            },
+        }
+    }
+
+    for tag in old_categories {
+        let _ = entry.remove_tag(tag)?;
+    }
+
+    let mut categories = vec![];
+    for tag in item.categories.iter().filter_map(|c| normalize_tag(c)) {
+        let _ = entry.add_tag(tag.clone())?;
+        categories.push(Value::String(tag));
+    }
+    let _ = entry.get_header_mut().insert("agenda.categories", Value::Array(categories))?;
+
+    *entry.get_content_mut() = item.description.clone().unwrap_or_default();
+
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use toml_query::read::TomlValueReadTypeExt;
+
+    use libimagstore::store::Store;
+    use libimagentrydatetime::timezone::parse_timezone;
+
+    use super::*;
+
+    const ICS : &'static str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//test//test//EN\r
+BEGIN:VEVENT\r
+UID:meeting-1@example.com\r
+DTSTAMP:20180501T120000Z\r
+DTSTART;TZID=America/New_York:20180514T090000\r
+DTEND;TZID=America/New_York:20180514T103000\r
+SUMMARY:Planning\\, with everyone\r
+DESCRIPTION:Bring the\\nnotes\r
+CATEGORIES:Work,Planning\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday@example.com\r
+DTSTART;VALUE=DATE:20180521\r
+DTEND;VALUE=DATE:20180522\r
+SUMMARY:Holiday\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup@example.com\r
+DTSTART:20180514T080000Z\r
+DURATION:PT15M\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE\r
+EXDATE:20180516T080000Z\r
+SUMMARY:Standup\r
+END:VEVENT\r
+BEGIN:VTODO\r
+UID:todo-1@example.com\r
+DUE:20180518T170000Z\r
+SUMMARY:Report\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    fn get_store() -> Store {
+        use libimagstore::store::InMemoryFileAbstraction;
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Some(Duration::minutes(90)), parse_duration("PT1H30M"));
+        assert_eq!(Some(Duration::days(15)), parse_duration("P2W1D"));
+        assert_eq!(None, parse_duration("1H"));
+        assert_eq!(None, parse_duration("PT1D"));
+        assert_eq!(None, parse_duration("P9223372036854775807W"));
+        assert_eq!(None, parse_duration("PT9223372036854775807S"));
+        assert_eq!(None, parse_duration("P9007199254740991DT9007199254740991H"));
+    }
+
+    #[test]
+    fn test_duration_out_of_range() {
+        let ics = ICS.replace("DURATION:PT15M", "DURATION:P1000000000D");
+        assert!(parse_icalendar(&ics, Tz::UTC).is_err());
+    }
+
+    #[test]
+    fn test_split_list() {
+        assert_eq!(vec!["a", "b,c", "d"], split_list("a,b\\,c,,d"));
+    }
+
+    #[test]
+    fn test_parse_icalendar() {
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let items = parse_icalendar(ICS, zone).unwrap();
+
+        assert_eq!(4, items.len());
+
+        let meeting = &items[0];
+        assert_eq!("meeting-1@example.com", meeting.uid);
+        assert_eq!(Some(String::from("Planning, with everyone")), meeting.summary);
+        assert_eq!(Some(String::from("Bring the\nnotes")), meeting.description);
+        assert_eq!(vec!["Work", "Planning"], meeting.categories);
+        match meeting.start {
+            IcalTime::DateTime(ref dt) => assert_eq!("2018-05-14T13:00:00+00:00",
+                                                     dt.in_zone(Tz::UTC).to_rfc3339()),
+            _ => assert!(false, "Expected a datetime"),
+        }
+
+        assert_eq!(IcalTime::Date(NaiveDate::from_ymd(2018, 5, 21)), items[1].start);
+
+        let standup = &items[2];
+        assert_eq!(Some(String::from("FREQ=WEEKLY;BYDAY=MO,WE")), standup.rrule);
+        assert_eq!(1, standup.exdates.len());
+        match standup.end {
+            Some(IcalTime::DateTime(ref dt)) => assert_eq!("2018-05-14T08:15:00+00:00",
+                                                           dt.in_zone(Tz::UTC).to_rfc3339()),
+            _ => assert!(false, "Expected a datetime"),
+        }
+
+        assert!(items[3].is_todo);
+    }
+
+    #[test]
+    fn test_import() {
+        let store = get_store();
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let ids   = import_icalendar(&store, ICS, zone).unwrap();
+
+        assert_eq!(4, ids.len());
+
+        let meeting = store.get(ids[0].clone()).unwrap().unwrap();
+        let range   = meeting.read_date_range_zoned(zone).unwrap();
+        assert_eq!("2018-05-14T15:00:00+02:00", range.start().in_zone(zone).to_rfc3339());
+        assert_eq!("2018-05-14T16:30:00+02:00", range.end().in_zone(zone).to_rfc3339());
+        assert_eq!(Some(String::from("Planning, with everyone")),
+                   meeting.get_header().read_string("agenda.summary").unwrap());
+        assert_eq!(vec!["work", "planning"], meeting.get_tags().unwrap());
+        assert_eq!("Bring the\nnotes", meeting.get_content());
+
+        let holiday = store.get(ids[1].clone()).unwrap().unwrap();
+        assert_eq!(Some(true), holiday.get_header().read_bool("agenda.all_day").unwrap());
+        assert_eq!("2018-05-21T00:00:00+02:00", holiday.read_date_zoned(zone).unwrap().to_rfc3339());
+        assert!(holiday.read_date_range_zoned(zone).is_err());
+
+        let standup    = store.get(ids[2].clone()).unwrap().unwrap();
+        let recurrence = standup.get_recurrence().unwrap().unwrap();
+        assert_eq!(NaiveDate::from_ymd(2018, 5, 14).and_hms(8, 0, 0), *recurrence.start());
+        assert_eq!(Some(Tz::UTC), recurrence.timezone());
+        assert_eq!(Some(Duration::minutes(15)), recurrence.duration());
+        assert_eq!(1, recurrence.exceptions().len());
+        assert!(standup.read_date().is_err());
+        assert_eq!(None, standup.get_header().read_bool("agenda.all_day").unwrap());
+
+        let todo = store.get(ids[3].clone()).unwrap().unwrap();
+        assert_eq!("2018-05-18T19:00:00+02:00", todo.read_date_zoned(zone).unwrap().in_zone(zone).to_rfc3339());
+    }
+
+    #[test]
+    fn test_import_recurrence_in_event_timezone() {
+        let store = get_store();
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let ics   = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:weekly@example.com\r
+DTSTART;TZID=America/New_York:20180305T090000\r
+DTEND;TZID=America/New_York:20180305T100000\r
+RRULE:FREQ=WEEKLY;UNTIL=20180319T130000Z\r
+EXDATE:20180312T130000Z\r
+SUMMARY:Weekly\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+        let ids = import_icalendar(&store, ics, zone).unwrap();
+
+        let weekly     = store.get(ids[0].clone()).unwrap().unwrap();
+        let recurrence = weekly.get_recurrence().unwrap().unwrap();
+        let local      = |d| NaiveDate::from_ymd(2018, 3, d).and_hms(9, 0, 0);
+
+        // 13:00 UTC is 09:00 in New York after the DST change on March 11th
+        assert_eq!(local(5), *recurrence.start());
+        assert_eq!(Some(parse_timezone("America/New_York").unwrap()), recurrence.timezone());
+        assert_eq!(Some(Duration::hours(1)), recurrence.duration());
+        assert_eq!(&vec![local(12)], recurrence.exceptions());
+        assert_eq!(Some(&local(19)), recurrence.rule().until());
+    }
+
+    #[test]
+    fn test_reimport_updates_entry() {
+        let store = get_store();
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let _     = import_icalendar(&store, ICS, zone).unwrap();
+
+        let updated = ICS.replace("SUMMARY:Holiday", "SUMMARY:Vacation");
+        let ids     = import_icalendar(&store, &updated, zone).unwrap();
+
+        let holiday = store.get(ids[1].clone()).unwrap().unwrap();
+        assert_eq!(Some(String::from("Vacation")),
+                   holiday.get_header().read_string("agenda.summary").unwrap());
+        assert_eq!(4, store.entries().unwrap().count());
+    }
+
+    #[test]
+    fn test_reimport_replaces_categories() {
+        let store = get_store();
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let ids   = import_icalendar(&store, ICS, zone).unwrap();
+
+        {
+            let mut meeting = store.get(ids[0].clone()).unwrap().unwrap();
+            let _ = meeting.add_tag(String::from("mine")).unwrap();
+        }
+
+        let updated = ICS.replace("CATEGORIES:Work,Planning", "CATEGORIES:Work,Review");
+        let ids     = import_icalendar(&store, &updated, zone).unwrap();
+
+        let meeting = store.get(ids[0].clone()).unwrap().unwrap();
+        let mut tags = meeting.get_tags().unwrap();
+        tags.sort();
+        assert_eq!(vec!["mine", "review", "work"], tags);
+    }
+
+    #[test]
+    fn test_import_overridden_occurrence() {
+        let store = get_store();
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+
+        let overridden = "BEGIN:VEVENT\r
+UID:standup@example.com\r
+RECURRENCE-ID:20180521T080000Z\r
+DTSTART:20180521T090000Z\r
+DURATION:PT15M\r
+SUMMARY:Late standup\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+        let ics = ICS.replace("END:VCALENDAR\r\n", overridden);
+        let ids = import_icalendar(&store, &ics, zone).unwrap();
+
+        assert_eq!(5, ids.len());
+        assert_eq!("agenda/ical/standup@example.com_20180521T080000Z",
+                   format!("{}", ids[4].without_base()));
+
+        let standup = store.get(ids[2].clone()).unwrap().unwrap();
+        assert_eq!(Some(String::from("Standup")),
+                   standup.get_header().read_string("agenda.summary").unwrap());
+        assert_eq!(2, standup.get_recurrence().unwrap().unwrap().exceptions().len());
+
+        let late = store.get(ids[4].clone()).unwrap().unwrap();
+        assert_eq!(Some(String::from("Late standup")),
+                   late.get_header().read_string("agenda.summary").unwrap());
+        assert!(late.read_date_range_zoned(zone).is_ok());
+    }
+}
+
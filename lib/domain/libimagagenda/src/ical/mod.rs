@@ -0,0 +1,107 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Export to and import from iCalendar (RFC 5545)
+
+use chrono::NaiveDate;
+
+use libimagentrydatetime::timezone::ZonedDateTime;
+
+pub mod export;
+pub mod import;
+
+/// Escape a TEXT value
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Unescape a TEXT value
+fn unescape_text(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars  = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other)           => result.push(other),
+            None                  => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+/// Fold a content line, so no line is longer than 75 octets
+fn fold_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut len    = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            result.push_str("\r\n ");
+            len = 1;
+        }
+
+        result.push(c);
+        len += c.len_utf8();
+    }
+
+    result
+}
+
+fn format_utc(dt: &ZonedDateTime) -> String {
+    dt.naive_utc().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_date(date: &NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_roundtrip() {
+        let text = "Meeting; with, \\ backslash\nand a second line";
+        let esc  = escape_text(text);
+
+        assert_eq!("Meeting\\; with\\, \\\\ backslash\\nand a second line", esc);
+        assert_eq!(text, unescape_text(&esc));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line   = format!("SUMMARY:{}", "ä".repeat(60));
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(line, folded.replace("\r\n ", ""));
+    }
+}
+
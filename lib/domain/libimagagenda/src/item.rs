@@ -0,0 +1,174 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Error as FmtError;
+use std::str::FromStr;
+
+use libimagstore::storeid::StoreId;
+use libimagentrydatetime::timezone::ZonedDateTime;
+
+use error::AgendaError as AE;
+use error::AgendaErrorKind as AEK;
+
+/// The domain an agenda item was collected from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AgendaItemKind {
+    Diary,
+    Habit,
+    TimeTracking,
+    Todo,
+    Birthday,
+    Anniversary,
+    Date,
+}
+
+impl AgendaItemKind {
+
+    pub fn all() -> Vec<AgendaItemKind> {
+        vec![
+            AgendaItemKind::Diary,
+            AgendaItemKind::Habit,
+            AgendaItemKind::TimeTracking,
+            AgendaItemKind::Todo,
+            AgendaItemKind::Birthday,
+            AgendaItemKind::Anniversary,
+            AgendaItemKind::Date,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AgendaItemKind::Diary        => "diary",
+            AgendaItemKind::Habit        => "habit",
+            AgendaItemKind::TimeTracking => "timetrack",
+            AgendaItemKind::Todo         => "todo",
+            AgendaItemKind::Birthday     => "birthday",
+            AgendaItemKind::Anniversary  => "anniversary",
+            AgendaItemKind::Date         => "date",
+        }
+    }
+
+}
+
+impl Display for AgendaItemKind {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), FmtError> {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AgendaItemKind {
+    type Err = AE;
+
+    fn from_str(s: &str) -> Result<AgendaItemKind, AE> {
+        AgendaItemKind::all()
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| AE::from_kind(AEK::UnknownItemKind(String::from(s))))
+    }
+}
+
+/// Something that happens at a point in time (or during a span of time)
+///
+/// Items which are "all day" (birthdays, habits, ...) start at midnight.
+#[derive(Clone, Debug)]
+pub struct AgendaItem {
+    kind: AgendaItemKind,
+    start: ZonedDateTime,
+    end: Option<ZonedDateTime>,
+    all_day: bool,
+    title: String,
+    id: StoreId,
+}
+
+impl AgendaItem {
+
+    pub fn new(kind: AgendaItemKind, start: ZonedDateTime, title: String, id: StoreId) -> AgendaItem {
+        AgendaItem {
+            kind,
+            start,
+            end: None,
+            all_day: false,
+            title,
+            id,
+        }
+    }
+
+    pub fn with_end(mut self, end: ZonedDateTime) -> AgendaItem {
+        self.end = Some(end);
+        self
+    }
+
+    pub fn with_all_day(mut self, all_day: bool) -> AgendaItem {
+        self.all_day = all_day;
+        self
+    }
+
+    pub fn kind(&self) -> AgendaItemKind {
+        self.kind
+    }
+
+    pub fn start(&self) -> &ZonedDateTime {
+        &self.start
+    }
+
+    pub fn end(&self) -> Option<&ZonedDateTime> {
+        self.end.as_ref()
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        self.all_day
+    }
+
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn id(&self) -> &StoreId {
+        &self.id
+    }
+
+    /// Chronological order, items at the same time ordered by kind and title
+    pub fn chronological_cmp(&self, other: &AgendaItem) -> Ordering {
+        self.start
+            .naive_utc()
+            .cmp(&other.start.naive_utc())
+            .then_with(|| other.all_day.cmp(&self.all_day))
+            .then_with(|| self.kind.cmp(&other.kind))
+            .then_with(|| self.title.cmp(&other.title))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_roundtrip() {
+        for kind in AgendaItemKind::all() {
+            assert_eq!(kind, kind.as_str().parse::<AgendaItemKind>().unwrap());
+        }
+
+        assert!("meeting".parse::<AgendaItemKind>().is_err());
+    }
+}
+
@@ -20,10 +20,28 @@
 use toml::Value;
 
 use libimagrt::runtime::Runtime;
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 
 use toml_query::read::TomlValueReadExt;
 use toml_query::read::TomlValueReadTypeExt;
 
+/// The configuration keys of the diary
+pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "diary.default_diary",
+        kind: ValueType::String,
+        default: None,
+        doc: "The diary which is used if no diary is passed",
+    },
+    ConfigKey {
+        path: "diary.diaries.*.timed",
+        kind: ValueType::String,
+        default: None,
+        doc: "Whether entries of the diary are 'daily', 'hourly', 'minutely' or 'secondly'",
+    },
+];
+
 pub fn get_default_diary_name(rt: &Runtime) -> Option<String> {
     get_diary_config_section(rt)
         .and_then(|config| {
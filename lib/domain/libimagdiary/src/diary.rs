@@ -23,6 +23,13 @@ use libimagstore::store::FileLockEntry;
 use libimagstore::store::Store;
 use libimagerror::trace::trace_error;
 use libimagentryutil::isa::Is;
+use libimagentrydatetime::datetime::EntryDate;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::datepath::accuracy::Accuracy;
+use libimagentrydatetime::datepath::compiler::DatePathCompiler;
+use libimagentrydatetime::datepath::format::Format;
+use libimagentrydatetime::datepath::query::DatePathQuery;
+use libimagentrydatetime::range::DateTimeRange;
 
 use chrono::offset::Local;
 use chrono::Datelike;
@@ -49,9 +56,17 @@ pub trait Diary {
 
     fn new_entry_at(&self, diary_name: &str, ndt: &NaiveDateTime) -> Result<FileLockEntry>;
 
+    /// Create or get an entry for the local time of `zdt`, and remember the timezone in the entry
+    fn new_entry_at_zoned(&self, diary_name: &str, zdt: &ZonedDateTime) -> Result<FileLockEntry>;
+
     // Get an iterator for iterating over all entries of a Diary
     fn entries(&self, diary_name: &str) -> Result<DiaryEntryIterator>;
 
+    /// Get an iterator over the entries of a Diary in `range`
+    ///
+    /// Only the parts of the store which can contain these entries are traversed.
+    fn entries_in_range(&self, diary_name: &str, range: &DateTimeRange) -> Result<DiaryEntryIterator>;
+
     fn get_youngest_entry_id(&self, diary_name: &str) -> Option<Result<DiaryId>>;
 
     /// Get all diary names
@@ -92,6 +107,12 @@ impl Diary for Store {
         Ok(entry)
     }
 
+    fn new_entry_at_zoned(&self, diary_name: &str, zdt: &ZonedDateTime) -> Result<FileLockEntry> {
+        let mut entry = self.new_entry_at(diary_name, &zdt.naive_local())?;
+        let _         = entry.set_date_zoned(zdt)?;
+        Ok(entry)
+    }
+
     // Get an iterator for iterating over all entries
     fn entries(&self, diary_name: &str) -> Result<DiaryEntryIterator> {
         debug!("Building iterator for module 'diary' with diary name = '{}'", diary_name);
@@ -100,6 +121,19 @@ impl Diary for Store {
             .chain_err(|| DEK::StoreReadError)
     }
 
+    fn entries_in_range(&self, diary_name: &str, range: &DateTimeRange) -> Result<DiaryEntryIterator> {
+        debug!("Building iterator for diary '{}' in range {:?}", diary_name, range);
+
+        // Diary entries are stored as <year>/<month>/<day>/<hour>:<minute>:<second>, which has the
+        // same folders as the DaysAreFolder format
+        let compiler = DatePathCompiler::new(Accuracy::Second, Format::DaysAreFolder);
+        let module   = format!("diary/{}", diary_name);
+
+        self.ids_in_range(&module, &compiler, range)
+            .map(|iter| DiaryEntryIterator::new(String::from(diary_name), iter).range(range.clone()))
+            .chain_err(|| DEK::StoreReadError)
+    }
+
     /// get the id of the youngest entry
     ///
     /// TODO: We collect internally here. We shouldn't do that. Solution unclear.
@@ -17,9 +17,14 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+use chrono::NaiveDateTime;
+
 use libimagstore::store::Entry;
 use libimagentryutil::isa::Is;
 use libimagentryutil::isa::IsKindHeaderPathProvider;
+use libimagentrydatetime::datetime::EntryDate;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
 
 use diaryid::DiaryId;
 use diaryid::FromStoreId;
@@ -30,6 +35,7 @@ provide_kindflag_path!(pub IsDiaryEntry, "diary.is_diary_entry");
 pub trait DiaryEntry {
     fn is_diary_entry(&self) -> Result<bool>;
     fn diary_id(&self) -> Result<DiaryId>;
+    fn datetime_zoned(&self, fallback: Tz) -> Result<ZonedDateTime>;
 }
 
 impl DiaryEntry for Entry {
@@ -46,5 +52,18 @@ impl DiaryEntry for Entry {
         DiaryId::from_storeid(&self.get_location().clone())
     }
 
+    /// Get the point in time of this entry, with the timezone it was written in
+    ///
+    /// Entries which were created without timezone information are interpreted as local time in
+    /// `fallback`.
+    fn datetime_zoned(&self, fallback: Tz) -> Result<ZonedDateTime> {
+        if self.read_date().is_ok() {
+            return self.read_date_zoned(fallback).map_err(From::from);
+        }
+
+        let ndt : NaiveDateTime = self.diary_id()?.into();
+        ZonedDateTime::from_local(&ndt, fallback).map_err(From::from)
+    }
+
 }
 
@@ -29,6 +29,8 @@ error_chain! {
     links {
         StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
         EntryUtilError(::libimagentryutil::error::EntryUtilError, ::libimagentryutil::error::EntryUtilErrorKind);
+        DateError(::libimagentrydatetime::error::DateError, ::libimagentrydatetime::error::DateErrorKind);
+        DatePathError(::libimagentrydatetime::datepath::error::DatePathCompilerError, ::libimagentrydatetime::datepath::error::DatePathCompilerErrorKind);
     }
 
     errors {
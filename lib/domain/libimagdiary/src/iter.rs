@@ -25,7 +25,11 @@ use filters::filter::Filter;
 use libimagstore::storeid::StoreIdIterator;
 use libimagstore::storeid::StoreId;
 
+use libimagentrydatetime::range::DateTimeRange;
+
 use is_in_diary::IsInDiary;
+use diaryid::DiaryId;
+use diaryid::FromStoreId;
 use error::DiaryErrorKind as DEK;
 use error::DiaryError as DE;
 use error::ResultExt;
@@ -39,13 +43,14 @@ pub struct DiaryEntryIterator {
     year: Option<i32>,
     month: Option<u32>,
     day: Option<u32>,
+    range: Option<DateTimeRange>,
 }
 
 impl Debug for DiaryEntryIterator {
 
     fn fmt(&self, fmt: &mut Formatter) -> RResult<(), FmtError> {
-        write!(fmt, "DiaryEntryIterator<name = {}, year = {:?}, month = {:?}, day = {:?}>",
-               self.name, self.year, self.month, self.day)
+        write!(fmt, "DiaryEntryIterator<name = {}, year = {:?}, month = {:?}, day = {:?}, range = {:?}>",
+               self.name, self.year, self.month, self.day, self.range)
     }
 
 }
@@ -60,6 +65,7 @@ impl DiaryEntryIterator {
             year: None,
             month: None,
             day: None,
+            range: None,
         }
     }
 
@@ -81,10 +87,26 @@ impl DiaryEntryIterator {
         self
     }
 
+    // Filter by range, get all diary entries in this range (start and end included)
+    pub fn range(mut self, range: DateTimeRange) -> DiaryEntryIterator {
+        self.range = Some(range);
+        self
+    }
+
 }
 
 impl Filter<StoreId> for DiaryEntryIterator {
     fn filter(&self, id: &StoreId) -> bool {
+        if let Some(ref range) = self.range {
+            let in_range = DiaryId::from_storeid(id)
+                .map(|diary_id| range.contains(&diary_id.into()))
+                .unwrap_or(false);
+
+            if !in_range {
+                return false;
+            }
+        }
+
         if id.is_in_diary(&self.name) {
             match (self.year, self.month, self.day) {
                 (None    , None    , None)    => true,
@@ -48,7 +48,9 @@ extern crate filters;
 extern crate libimagerror;
 extern crate libimagentryedit;
 extern crate libimagentryview;
+extern crate libimagentrydatetime;
 extern crate libimagrt;
+extern crate libimagutil;
 
 module_entry_path_mod!("diary");
 
@@ -21,5 +21,7 @@ pub const CRATE_NAME : &'static str                  = "timetrack";
 pub const DATE_TIME_FORMAT : &'static str            = "%Y-%m-%dT%H:%M:%S";
 pub const DATE_TIME_START_HEADER_PATH : &'static str = "timetrack.start";
 pub const DATE_TIME_END_HEADER_PATH : &'static str   = "timetrack.end";
+pub const DATE_TIME_START_TIMEZONE_HEADER_PATH : &'static str = "timetrack.start_timezone";
+pub const DATE_TIME_END_TIMEZONE_HEADER_PATH : &'static str   = "timetrack.end_timezone";
 pub const DATE_TIME_TAG_HEADER_PATH : &'static str   = "timetrack.tag";
 
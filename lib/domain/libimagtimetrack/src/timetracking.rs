@@ -24,9 +24,16 @@
 //! getting the start and end time and also deleting start and end time.
 //!
 
+use chrono::Duration;
 use chrono::naive::NaiveDateTime;
 
 use libimagstore::store::Entry;
+use libimagentrydatetime::timezone::Tz;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagentrydatetime::timezone::parse_naive;
+use libimagentrydatetime::timezone::read_zoned_datetime;
+use libimagentrydatetime::timezone::write_zoned_datetime;
+use libimagentrydatetime::timezone::delete_if_present;
 
 use tag::TimeTrackingTag as TTT;
 use error::TimeTrackErrorKind as TTEK;
@@ -55,6 +62,25 @@ pub trait TimeTracking {
 
     fn delete_end_datetime(&mut self) -> Result<()>;
 
+    fn set_start_datetime_zoned(&mut self, dt: &ZonedDateTime) -> Result<()>;
+
+    /// Get the start time with its timezone
+    ///
+    /// Start times without timezone information are interpreted as local time in `fallback`.
+    fn get_start_datetime_zoned(&self, fallback: Tz) -> Result<Option<ZonedDateTime>>;
+
+    fn set_end_datetime_zoned(&mut self, dt: &ZonedDateTime) -> Result<()>;
+
+    /// Get the end time with its timezone
+    ///
+    /// End times without timezone information are interpreted as local time in `fallback`.
+    fn get_end_datetime_zoned(&self, fallback: Tz) -> Result<Option<ZonedDateTime>>;
+
+    /// Get the tracked duration, or None if the timetracking is not ended yet
+    ///
+    /// The duration respects timezones and daylight saving time changes.
+    fn duration(&self, fallback: Tz) -> Result<Option<Duration>>;
+
     fn valid(&self) -> Result<bool>;
 
 }
@@ -71,6 +97,7 @@ impl TimeTracking for Entry {
 
     fn set_start_datetime(&mut self, dt: NaiveDateTime) -> Result<()> {
         let s = dt.format(DATE_TIME_FORMAT).to_string();
+        let _ = delete_if_present(self.get_header_mut(), DATE_TIME_START_TIMEZONE_HEADER_PATH)?;
 
         self.get_header_mut()
             .insert(DATE_TIME_START_HEADER_PATH, Value::String(s))
@@ -86,6 +113,8 @@ impl TimeTracking for Entry {
     }
 
     fn delete_start_datetime(&mut self) -> Result<()> {
+        let _ = delete_if_present(self.get_header_mut(), DATE_TIME_START_TIMEZONE_HEADER_PATH)?;
+
         self.get_header_mut()
             .delete(DATE_TIME_START_HEADER_PATH)
             .map_err(From::from)
@@ -94,6 +123,7 @@ impl TimeTracking for Entry {
 
     fn set_end_datetime(&mut self, dt: NaiveDateTime) -> Result<()> {
         let s = dt.format(DATE_TIME_FORMAT).to_string();
+        let _ = delete_if_present(self.get_header_mut(), DATE_TIME_END_TIMEZONE_HEADER_PATH)?;
 
         self.get_header_mut()
             .insert(DATE_TIME_END_HEADER_PATH, Value::String(s))
@@ -109,12 +139,56 @@ impl TimeTracking for Entry {
     }
 
     fn delete_end_datetime(&mut self) -> Result<()> {
+        let _ = delete_if_present(self.get_header_mut(), DATE_TIME_END_TIMEZONE_HEADER_PATH)?;
+
         self.get_header_mut()
             .delete(DATE_TIME_END_HEADER_PATH)
             .map_err(From::from)
             .map(|_| ())
     }
 
+    fn set_start_datetime_zoned(&mut self, dt: &ZonedDateTime) -> Result<()> {
+        write_zoned_datetime(self.get_header_mut(),
+                             DATE_TIME_START_HEADER_PATH,
+                             DATE_TIME_START_TIMEZONE_HEADER_PATH,
+                             dt)
+            .map_err(From::from)
+    }
+
+    fn get_start_datetime_zoned(&self, fallback: Tz) -> Result<Option<ZonedDateTime>> {
+        read_zoned_datetime(self.get_header(),
+                            DATE_TIME_START_HEADER_PATH,
+                            DATE_TIME_START_TIMEZONE_HEADER_PATH,
+                            fallback)
+            .map_err(From::from)
+    }
+
+    fn set_end_datetime_zoned(&mut self, dt: &ZonedDateTime) -> Result<()> {
+        write_zoned_datetime(self.get_header_mut(),
+                             DATE_TIME_END_HEADER_PATH,
+                             DATE_TIME_END_TIMEZONE_HEADER_PATH,
+                             dt)
+            .map_err(From::from)
+    }
+
+    fn get_end_datetime_zoned(&self, fallback: Tz) -> Result<Option<ZonedDateTime>> {
+        read_zoned_datetime(self.get_header(),
+                            DATE_TIME_END_HEADER_PATH,
+                            DATE_TIME_END_TIMEZONE_HEADER_PATH,
+                            fallback)
+            .map_err(From::from)
+    }
+
+    fn duration(&self, fallback: Tz) -> Result<Option<Duration>> {
+        let start = self.get_start_datetime_zoned(fallback)?;
+        let end   = self.get_end_datetime_zoned(fallback)?;
+
+        Ok(match (start, end) {
+            (Some(start), Some(end)) => Some(end.signed_duration_since(&start)),
+            _ => None,
+        })
+    }
+
     /// Check whether the Event is valid
     ///
     /// That is:
@@ -128,14 +202,18 @@ impl TimeTracking for Entry {
     /// Err(e) if checking validity failed
     ///
     fn valid(&self) -> Result<bool> {
-        self.get_start_datetime().and_then(|st| self.get_end_datetime().map(|et| st <= et))
+        // Comparing the points in time, so the fallback timezone does not matter
+        let start = self.get_start_datetime_zoned(Tz::UTC)?.map(|dt| *dt.datetime());
+        let end   = self.get_end_datetime_zoned(Tz::UTC)?.map(|dt| *dt.datetime());
+
+        Ok(start <= end)
     }
 
 }
 
 fn header_value_to_dt(val: Option<String>) -> Result<Option<NaiveDateTime>> {
     match val {
-        Some(ref s) => NaiveDateTime::parse_from_str(s, DATE_TIME_FORMAT).map_err(TTE::from).map(Some),
+        Some(ref s) => parse_naive(s).map_err(TTE::from).map(Some),
         None => Ok(None),
     }
 }
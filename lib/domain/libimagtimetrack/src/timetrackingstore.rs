@@ -28,11 +28,17 @@ use toml_query::insert::TomlValueInsertExt;
 
 use libimagstore::store::Store;
 use libimagstore::store::FileLockEntry;
+use libimagstore::storeid::StoreIdIterator;
+use libimagstore::storeid::StoreIdIteratorWithStore;
 use libimagentrydatetime::datepath::compiler::DatePathCompiler;
+use libimagentrydatetime::datepath::query::DatePathQuery;
+use libimagentrydatetime::range::DateTimeRange;
+use libimagentrydatetime::timezone::ZonedDateTime;
 
 use error::Result;
 use constants::*;
 use iter::get::TimeTrackingsGetIterator;
+use timetracking::TimeTracking;
 
 use tag::TimeTrackingTag as TTT;
 
@@ -42,7 +48,24 @@ pub trait TimeTrackStore<'a> {
     fn create_timetracking_at(&'a self, start: &NDT, ts: &TTT)         -> Result<FileLockEntry<'a>>;
     fn create_timetracking(&'a self, start: &NDT, end: &NDT, ts: &TTT) -> Result<FileLockEntry<'a>>;
 
+    /// Same as `create_timetracking_at()`, but stores the timezone of the start time
+    fn create_timetracking_at_zoned(&'a self, start: &ZonedDateTime, ts: &TTT) -> Result<FileLockEntry<'a>>;
+
+    /// Same as `create_timetracking()`, but stores the timezones of the start and end time
+    fn create_timetracking_zoned(&'a self, start: &ZonedDateTime, end: &ZonedDateTime, ts: &TTT)
+        -> Result<FileLockEntry<'a>>;
+
     fn get_timetrackings(&'a self) -> Result<TimeTrackingsGetIterator<'a>>;
+
+    /// Get the timetrackings which were started in `range`
+    ///
+    /// Only the parts of the store which can contain these are traversed. The range is only
+    /// resolved to days though, so the start times have to be filtered by the caller.
+    fn get_timetrackings_in_range(&'a self, range: &DateTimeRange) -> Result<TimeTrackingsGetIterator<'a>>;
+
+    /// Same as `get_timetrackings_in_range()`, but only the ids, so the entries can be read
+    /// without being written back
+    fn timetracking_ids_in_range(&'a self, range: &DateTimeRange) -> Result<StoreIdIterator>;
 }
 
 fn now() -> NDT {
@@ -102,9 +125,40 @@ impl<'a> TimeTrackStore<'a> for Store {
             })
     }
 
+    fn create_timetracking_at_zoned(&'a self, start: &ZonedDateTime, ts: &TTT) -> Result<FileLockEntry<'a>> {
+        // The id is built from the wall clock time, so it looks the same as for timetrackings
+        // without timezone information
+        self.create_timetracking_at(&start.naive_local(), ts)
+            .and_then(|mut fle| fle.set_start_datetime_zoned(start).map(|_| fle))
+    }
+
+    fn create_timetracking_zoned(&'a self, start: &ZonedDateTime, end: &ZonedDateTime, ts: &TTT)
+        -> Result<FileLockEntry<'a>>
+    {
+        self.create_timetracking_at_zoned(start, ts)
+            .and_then(|mut fle| fle.set_end_datetime_zoned(end).map(|_| fle))
+    }
+
     fn get_timetrackings(&'a self) -> Result<TimeTrackingsGetIterator<'a>> {
         Ok(TimeTrackingsGetIterator::new(self.entries()?, self))
     }
 
+    fn get_timetrackings_in_range(&'a self, range: &DateTimeRange) -> Result<TimeTrackingsGetIterator<'a>> {
+        let ids = self.timetracking_ids_in_range(range)?;
+        let ids = StoreIdIteratorWithStore::new(Box::new(ids), self);
+        Ok(TimeTrackingsGetIterator::new(ids, self))
+    }
+
+    fn timetracking_ids_in_range(&'a self, range: &DateTimeRange) -> Result<StoreIdIterator> {
+        let ids = self
+            .ids_in_range(CRATE_NAME, &COMPILER, range)?
+            .filter(|id| match *id {
+                Ok(ref id) => id.is_in_collection(&[CRATE_NAME]),
+                Err(_)     => true,
+            });
+
+        Ok(StoreIdIterator::new(Box::new(ids)))
+    }
+
 }
 
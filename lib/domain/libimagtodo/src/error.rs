@@ -65,6 +65,11 @@ error_chain! {
             description("Uuid parser error")
             display("Uuid parser error")
         }
+
+        DateParserError {
+            description("Date parser error")
+            display("Date parser error")
+        }
     }
 }
 
@@ -36,6 +36,7 @@
 )]
 
 extern crate uuid;
+extern crate chrono;
 extern crate toml;
 extern crate toml_query;
 #[macro_use] extern crate log;
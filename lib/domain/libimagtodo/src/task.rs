@@ -24,11 +24,20 @@ use error::Result;
 
 use libimagstore::store::Entry;
 
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 use toml_query::read::TomlValueReadTypeExt;
 
+/// Format of the due date in the header, which is in UTC like the taskwarrior dates
+pub const DUE_FMT : &'static str = "%Y-%m-%dT%H:%M:%S";
+
 pub trait Task {
     fn get_uuid(&self) -> Result<Uuid>;
+
+    /// Get the due date (in UTC) of the task, if it has one
+    ///
+    /// The due date is copied from taskwarrior when the task is imported.
+    fn get_due(&self) -> Result<Option<NaiveDateTime>>;
 }
 
 impl Task for Entry {
@@ -38,5 +47,14 @@ impl Task for Entry {
             .ok_or(TE::from_kind(TEK::HeaderFieldMissing))
             .and_then(|u| Uuid::parse_str(&u).chain_err(|| TEK::UuidParserError))
     }
+
+    fn get_due(&self) -> Result<Option<NaiveDateTime>> {
+        match self.get_header().read_string("todo.due")? {
+            Some(s) => NaiveDateTime::parse_from_str(&s, DUE_FMT)
+                .chain_err(|| TEK::DateParserError)
+                .map(Some),
+            None    => Ok(None),
+        }
+    }
 }
 
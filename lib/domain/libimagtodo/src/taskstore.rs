@@ -36,6 +36,7 @@ use error::TodoError as TE;
 use error::Result;
 use error::ResultExt;
 use iter::TaskIdIterator;
+use task::DUE_FMT;
 
 /// Task struct containing a `FileLockEntry`
 pub trait TaskStore<'a> {
@@ -177,6 +178,7 @@ impl<'a> TaskStore<'a> for Store {
     fn new_from_twtask(&'a self, task: TTask) -> Result<FileLockEntry<'a>> {
         use toml_query::read::TomlValueReadExt;
         use toml_query::set::TomlValueSetExt;
+        use toml_query::delete::TomlValueDeleteExt;
 
         let uuid     = task.uuid();
         ModuleEntryPath::new(format!("taskwarrior/{}", uuid))
@@ -193,6 +195,16 @@ impl<'a> TaskStore<'a> for Store {
                             }
 
                             hdr.set("todo.uuid", Value::String(format!("{}",uuid)))?;
+
+                            match task.due() {
+                                Some(due) => {
+                                    let due = due.format(DUE_FMT).to_string();
+                                    hdr.set("todo.due", Value::String(due))?;
+                                },
+                                None => if hdr.read("todo.due")?.is_some() {
+                                    let _ = hdr.delete("todo.due")?;
+                                },
+                            }
                         }
 
                         // If none of the errors above have returned the function, everything is fine
@@ -19,11 +19,13 @@
 
 use libimagstore::store::Store;
 use libimagstore::store::Entry;
+use libimagstore::storeid::StoreId;
 use libimagentrymarkdown::processor::LinkProcessor;
 
 use error::Result;
 use error::WikiErrorKind as WEK;
 use error::ResultExt;
+use wiki::Wiki;
 
 pub trait WikiEntry {
     fn autolink(&mut self, store: &Store) -> Result<()>;
@@ -40,6 +42,7 @@ impl WikiEntry for Entry {
     /// * Internal targets creating = true
     /// * External link processing  = true
     /// * Processing of Refs        = true
+    /// * Processing of Wiki links  = true
     ///
     /// This is a convenience function for `WikiEntry::autolink_with_processor()`.
     ///
@@ -58,16 +61,98 @@ impl WikiEntry for Entry {
             .process_internal_links(true)
             .create_internal_targets(true)
             .process_external_links(true)
-            .process_refs(true);
+            .process_refs(true)
+            .process_wiki_links(true);
 
         self.autolink_with_processor(store, processor)
     }
 
     /// Autolink entry to entries linked in content with the passed `LinkProcessor` instance.
     ///
+    /// If the entry is part of a wiki, wiki links (`[[Page Name]]`) are resolved to pages of that
+    /// wiki, via `Wiki::get_entry()` and `Wiki::create_entry()`. For the index page itself, new
+    /// pages are created without linking them to the (already borrowed) index page again, the
+    /// processor links them anyways.
+    ///
     /// See the documentation of `::libimagentrymarkdown::processor::LinkProcessor`.
     fn autolink_with_processor(&mut self, store: &Store, processor: LinkProcessor) -> Result<()> {
-        processor.process(self, store).chain_err(|| WEK::AutoLinkError(self.get_location().clone()))
+        let location = self.get_location().clone();
+        let result   = match wiki_name(&location) {
+            Some(ref name) if !is_index_page(&location) => {
+                let wiki = Wiki::new(store, name);
+                processor.process_with_resolver(self, store, &wiki)
+            },
+            _ => processor.process(self, store),
+        };
+
+        result.chain_err(|| WEK::AutoLinkError(location))
+    }
+
+}
+
+/// Whether the passed id is the index page of a wiki
+fn is_index_page(id: &StoreId) -> bool {
+    id.local().components().count() == 3 && id.local().ends_with("index")
+}
+
+/// Get the name of the wiki an entry with the passed id belongs to, if any
+fn wiki_name(id: &StoreId) -> Option<String> {
+    use std::path::Component;
+
+    let mut components = id.local().components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(coll)), Some(Component::Normal(name))) => {
+            if coll.to_str() == Some("wiki") {
+                name.to_str().map(String::from)
+            } else {
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+    use libimagentrylink::internal::InternalLinker;
+
+    use store::WikiStore;
+    use super::WikiEntry;
+
+    fn get_store() -> Store {
+        let fs = InMemoryFileAbstraction::default();
+        Store::new_with_backend(PathBuf::from("/"), &None, Arc::new(fs)).unwrap()
+    }
+
+    #[test]
+    fn test_autolink_index() {
+        let store = get_store();
+        let wiki  = store.create_wiki("test").unwrap();
+
+        let mut page = wiki.create_entry("page").unwrap();
+        *page.get_content_mut() = String::from("Back to the [[index]], or [[page]] itself");
+        assert!(page.autolink(&store).is_ok());
+
+        let index_id = wiki.entry_id("index").unwrap();
+        assert!(page.get_internal_links().unwrap().any(|link| link.get_store_id() == &index_id));
+    }
+
+    #[test]
+    fn test_autolink_from_index() {
+        let store = get_store();
+        let wiki  = store.create_wiki("test").unwrap();
+
+        let mut index = wiki.retrieve_entry("index").unwrap();
+        *index.get_content_mut() = String::from("See [[index]] and [[New Page]]");
+        assert!(index.autolink(&store).is_ok());
+        drop(index);
+
+        assert!(wiki.get_entry("New Page").unwrap().is_some());
     }
 
 }
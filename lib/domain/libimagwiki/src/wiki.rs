@@ -28,6 +28,10 @@ use libimagstore::storeid::IntoStoreId;
 use libimagstore::storeid::StoreId;
 use libimagstore::storeid::StoreIdIteratorWithStore;
 use libimagentrylink::internal::InternalLinker;
+use libimagentrymarkdown::processor::WikiLinkResolver;
+use libimagentrymarkdown::error::MarkdownErrorKind as MEK;
+use libimagentrymarkdown::error::ResultExt;
+use libimagentrymarkdown::error::Result as MarkdownResult;
 
 use error::WikiError as WE;
 use error::WikiErrorKind as WEK;
@@ -59,38 +63,50 @@ impl<'a, 'b> Wiki<'a, 'b> {
             .map_err(WE::from)
     }
 
+    /// The id of the entry with the name `entry_name` in this wiki
+    pub fn entry_id<EN: AsRef<str>>(&self, entry_name: EN) -> Result<StoreId> {
+        let path = PathBuf::from(format!("{}/{}", self.1, entry_name.as_ref()));
+        ::module_path::ModuleEntryPath::new(path).into_storeid().map_err(WE::from)
+    }
+
     pub fn get_entry<EN: AsRef<str>>(&self, entry_name: EN) -> Result<Option<FileLockEntry<'a>>> {
-        let path  = PathBuf::from(format!("{}/{}", self.1, entry_name.as_ref()));
-        let sid   = ::module_path::ModuleEntryPath::new(path).into_storeid()?;
+        let sid = self.entry_id(entry_name)?;
         self.0.get(sid).map_err(WE::from)
     }
 
     pub fn create_entry<EN: AsRef<str>>(&self, entry_name: EN) -> Result<FileLockEntry<'a>> {
-        let path      = PathBuf::from(format!("{}/{}", self.1, entry_name.as_ref()));
-        let sid       = ::module_path::ModuleEntryPath::new(path).into_storeid()?;
-        let mut index = self
-            .get_entry("index")?
-            .ok_or_else(|| WEK::MissingIndex.into())
-            .map_err(WE::from_kind)?;
+        if entry_name.as_ref() == "index" {
+            return self.create_index_page()
+        }
+
+        let sid       = self.entry_id(entry_name)?;
         let mut entry = self.0.create(sid)?;
 
-        entry.add_internal_link(&mut index)
-            .map_err(WE::from)
-            .map(|_| entry)
+        self.link_to_index(&mut entry).map(|_| entry)
     }
 
     pub fn retrieve_entry<EN: AsRef<str>>(&self, entry_name: EN) -> Result<FileLockEntry<'a>> {
-        let path      = PathBuf::from(format!("{}/{}", self.1, entry_name.as_ref()));
-        let sid       = ::module_path::ModuleEntryPath::new(path).into_storeid()?;
+        let is_index  = entry_name.as_ref() == "index";
+        let sid       = self.entry_id(entry_name)?;
+        let mut entry = self.0.retrieve(sid)?;
+
+        if is_index {
+            return Ok(entry)
+        }
+
+        self.link_to_index(&mut entry).map(|_| entry)
+    }
+
+    /// Link `entry` to the index page
+    ///
+    /// The index page is only borrowed after `entry`, so `entry` must not be the index page.
+    fn link_to_index(&self, entry: &mut FileLockEntry<'a>) -> Result<()> {
         let mut index = self
             .get_entry("index")?
             .ok_or_else(|| WEK::MissingIndex.into())
             .map_err(WE::from_kind)?;
-        let mut entry = self.0.retrieve(sid)?;
 
-        entry.add_internal_link(&mut index)
-            .map_err(WE::from)
-            .map(|_| entry)
+        entry.add_internal_link(&mut index).map_err(WE::from)
     }
 
     pub fn all_ids(&self) -> Result<WikiIdIterator> {
@@ -105,6 +121,23 @@ impl<'a, 'b> Wiki<'a, 'b> {
     }
 }
 
+/// Resolves `[[Page Name]]` links to pages of this wiki
+impl<'a, 'b> WikiLinkResolver<'a> for Wiki<'a, 'b> {
+
+    fn page_id(&self, page: &str) -> MarkdownResult<StoreId> {
+        self.entry_id(page).chain_err(|| MEK::WikiLinkResolveError(String::from(page)))
+    }
+
+    fn get_page(&self, page: &str) -> MarkdownResult<Option<FileLockEntry<'a>>> {
+        self.get_entry(page).chain_err(|| MEK::WikiLinkResolveError(String::from(page)))
+    }
+
+    fn create_page(&self, page: &str) -> MarkdownResult<FileLockEntry<'a>> {
+        self.create_entry(page).chain_err(|| MEK::WikiLinkResolveError(String::from(page)))
+    }
+
+}
+
 pub struct WikiIdIterator<'a>(StoreIdIteratorWithStore<'a>, IdIsInWikiFilter<'a>);
 
 impl<'a> Iterator for WikiIdIterator<'a> {
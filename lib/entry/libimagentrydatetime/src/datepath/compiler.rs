@@ -19,6 +19,7 @@
 
 use std::path::PathBuf;
 
+use chrono::naive::NaiveDate;
 use chrono::naive::NaiveDateTime;
 use chrono::Datelike;
 use chrono::Timelike;
@@ -30,7 +31,9 @@ use datepath::format::Format;
 use datepath::error::Result;
 use datepath::error::DatePathCompilerErrorKind as DPCEK;
 use datepath::error::ResultExt;
+use range::DateTimeRange;
 
+#[derive(Clone, Debug)]
 pub struct DatePathCompiler {
     accuracy : Accuracy,
     format   : Format,
@@ -125,6 +128,108 @@ impl DatePathCompiler {
             .chain_err(|| DPCEK::StoreIdBuildFailed)
     }
 
+    /// The inverse of `DatePathCompiler::compile()`: Get the datetime a StoreId was compiled from
+    ///
+    /// Elements which are not part of the path because of the accuracy are set to their minimum
+    /// (the first month, the first day, midnight).
+    ///
+    /// # Return value
+    ///
+    /// None if the StoreId is not in the module or was not compiled with the settings of this
+    /// compiler.
+    ///
+    pub fn decompile(&self, module_name: &str, id: &StoreId) -> Option<NaiveDateTime> {
+        let path   = id.local().to_str()?;
+        let prefix = format!("{}/", module_name);
+        if !path.starts_with(&prefix) {
+            return None;
+        }
+
+        let elements = path[prefix.len()..]
+            .split(|c| c == '/' || c == '-')
+            .map(|e| e.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        let element = |i: usize, default: u32| elements.get(i).cloned().unwrap_or(default);
+
+        let ndt = NaiveDate::from_ymd_opt(element(0, 0) as i32, element(1, 1), element(2, 1))?
+            .and_hms_opt(element(3, 0), element(4, 0), element(5, 0))?;
+
+        // Make sure the separators and the number of elements match the settings
+        match self.compile(module_name, &ndt) {
+            Ok(ref compiled) if compiled.local() == id.local() => Some(ndt),
+            _ => None,
+        }
+    }
+
+    /// Compile a range of datetimes into the StoreIds of the folders which contain all entries
+    /// compiled from a datetime in this range.
+    ///
+    /// Whole years and months in the range are compiled into one folder each, the remaining days
+    /// into one folder per day (if days are folders with the current settings at all). So the
+    /// number of folders is small even for large ranges.
+    ///
+    /// # Warnings
+    ///
+    /// The folders may contain entries which are outside of the range, because the range is
+    /// only resolved to days. Use `DatePathCompiler::decompile()` for filtering these.
+    ///
+    /// The folders do not necessarily exist in the store.
+    ///
+    pub fn compile_range(&self, module_name: &str, range: &DateTimeRange) -> Result<Vec<StoreId>> {
+        let year_is_folder  = self.accuracy.has_month_accuracy();
+        let month_is_folder = self.accuracy.has_day_accuracy() && self.format != Format::YearIsFolder;
+        let day_is_folder   = self.accuracy.has_hour_accuracy() &&
+            (self.format == Format::ElementIsFolder || self.format == Format::DaysAreFolder);
+
+        if !year_is_folder {
+            // All entries are files directly in the module folder
+            return StoreId::new_baseless(PathBuf::from(module_name))
+                .chain_err(|| DPCEK::StoreIdBuildFailed)
+                .map(|id| vec![id]);
+        }
+
+        let folder = |accuracy: Accuracy, date: &NaiveDate| {
+            DatePathCompiler::new(accuracy, self.format.clone())
+                .compile(module_name, &date.and_hms(0, 0, 0))
+        };
+
+        let end      = range.end().date();
+        let mut date = range.start().date();
+        let mut ids  = vec![];
+
+        while date <= end {
+            let end_of_year  = NaiveDate::from_ymd(date.year(), 12, 31);
+            let end_of_month = last_day_of_month(&date);
+
+            let (id, last_date) = if !month_is_folder || (date.ordinal() == 1 && end_of_year <= end) {
+                (folder(Accuracy::Year, &date)?, end_of_year)
+            } else if !day_is_folder || (date.day() == 1 && end_of_month <= end) {
+                (folder(Accuracy::Month, &date)?, end_of_month)
+            } else {
+                (folder(Accuracy::Day, &date)?, date)
+            };
+
+            ids.push(id);
+            date = match last_date.succ_opt() {
+                Some(d) => d,
+                None    => break,
+            };
+        }
+
+        Ok(ids)
+    }
+
+}
+
+fn last_day_of_month(date: &NaiveDate) -> NaiveDate {
+    let first_of_next_month = if date.month() == 12 {
+        NaiveDate::from_ymd(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd(date.year(), date.month() + 1, 1)
+    };
+
+    first_of_next_month.pred()
 }
 
 #[cfg(test)]
@@ -132,6 +237,9 @@ mod test {
     use super::*;
     use datepath::accuracy::Accuracy;
     use datepath::format::Format;
+    use range::DateTimeRange;
+
+    use std::path::PathBuf;
 
     use chrono::naive::NaiveDate;
     use chrono::naive::NaiveDateTime;
@@ -192,5 +300,88 @@ mod test {
         test_accuracy(Accuracy::Day, dt, "module", "module/0001/01/01");
     }
 
+    #[test]
+    fn test_compiler_decompile() {
+        let dt = NaiveDate::from_ymd(2000, 2, 3).and_hms(4, 5, 6);
+
+        for format in &[Format::ElementIsFolder, Format::DaysAreFolder, Format::MonthIsFolder, Format::YearIsFolder] {
+            let compiler = DatePathCompiler::new(Accuracy::Second, format.clone());
+            let id       = compiler.compile("module", &dt).unwrap();
+
+            assert_eq!(Some(dt), compiler.decompile("module", &id));
+            assert_eq!(None, compiler.decompile("other", &id));
+        }
+    }
+
+    #[test]
+    fn test_compiler_decompile_lower_accuracy() {
+        let compiler = DatePathCompiler::new(Accuracy::Day, Format::default());
+        let id       = StoreId::new_baseless(PathBuf::from("module/2000/02/03")).unwrap();
+        let expected = NaiveDate::from_ymd(2000, 2, 3).and_hms(0, 0, 0);
+
+        assert_eq!(Some(expected), compiler.decompile("module", &id));
+    }
+
+    #[test]
+    fn test_compiler_decompile_wrong_format() {
+        let compiler = DatePathCompiler::new(Accuracy::Second, Format::ElementIsFolder);
+        let id       = StoreId::new_baseless(PathBuf::from("module/2000/02/03/04-05-06")).unwrap();
+        assert_eq!(None, compiler.decompile("module", &id));
+
+        let id = StoreId::new_baseless(PathBuf::from("module/2000/02/03/04/05")).unwrap();
+        assert_eq!(None, compiler.decompile("module", &id));
+
+        let id = StoreId::new_baseless(PathBuf::from("module/2000/02/30/04/05/06")).unwrap();
+        assert_eq!(None, compiler.decompile("module", &id));
+    }
+
+    fn compile_range(compiler: DatePathCompiler, start: NaiveDateTime, end: NaiveDateTime) -> Vec<String> {
+        let range = DateTimeRange::new(start, end).unwrap();
+        compiler
+            .compile_range("module", &range)
+            .unwrap()
+            .into_iter()
+            .map(|id| id.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_compiler_compile_range_days() {
+        let compiler = DatePathCompiler::new(Accuracy::Second, Format::ElementIsFolder);
+        let start    = NaiveDate::from_ymd(2000, 1, 30).and_hms(12, 0, 0);
+        let end      = NaiveDate::from_ymd(2000, 2, 2).and_hms(1, 0, 0);
+
+        assert_eq!(vec!["module/2000/01/30", "module/2000/01/31", "module/2000/02/01", "module/2000/02/02"],
+                   compile_range(compiler, start, end));
+    }
+
+    #[test]
+    fn test_compiler_compile_range_collapses_months_and_years() {
+        let compiler = DatePathCompiler::new(Accuracy::Second, Format::DaysAreFolder);
+        let start    = NaiveDate::from_ymd(1999, 11, 30).and_hms(0, 0, 0);
+        let end      = NaiveDate::from_ymd(2001, 2, 1).and_hms(0, 0, 0);
+
+        assert_eq!(vec!["module/1999/11/30", "module/1999/12", "module/2000", "module/2001/01", "module/2001/02/01"],
+                   compile_range(compiler, start, end));
+    }
+
+    #[test]
+    fn test_compiler_compile_range_month_is_folder() {
+        let compiler = DatePathCompiler::new(Accuracy::Second, Format::MonthIsFolder);
+        let start    = NaiveDate::from_ymd(2000, 1, 30).and_hms(0, 0, 0);
+        let end      = NaiveDate::from_ymd(2000, 2, 2).and_hms(0, 0, 0);
+
+        assert_eq!(vec!["module/2000/01", "module/2000/02"], compile_range(compiler, start, end));
+    }
+
+    #[test]
+    fn test_compiler_compile_range_year_accuracy() {
+        let compiler = DatePathCompiler::new(Accuracy::Year, Format::ElementIsFolder);
+        let start    = NaiveDate::from_ymd(2000, 1, 30).and_hms(0, 0, 0);
+        let end      = NaiveDate::from_ymd(2003, 2, 2).and_hms(0, 0, 0);
+
+        assert_eq!(vec!["module"], compile_range(compiler, start, end));
+    }
+
 }
 
@@ -22,6 +22,10 @@ error_chain! {
         DatePathCompilerError, DatePathCompilerErrorKind, ResultExt, Result;
     }
 
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+    }
+
     errors {
         UnknownDatePathCompilerError {
             description("Unknown DatePathCompiler error")
@@ -21,5 +21,6 @@ pub mod accuracy;
 pub mod compiler;
 pub mod error;
 pub mod format;
+pub mod query;
 pub mod to_store_id;
 
@@ -0,0 +1,119 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreIdIterator;
+
+use datepath::compiler::DatePathCompiler;
+use datepath::error::Result;
+use range::DateTimeRange;
+
+/// Extension for the Store to query entries which were created with the `DatePathCompiler` by
+/// date range
+pub trait DatePathQuery {
+
+    /// Get the ids of all entries of `module_name` in the folders which contain the entries for
+    /// `range`
+    ///
+    /// Only these folders are traversed, not the whole module (see
+    /// `DatePathCompiler::compile_range()`).
+    ///
+    /// # Warning
+    ///
+    /// The range is only resolved to days, so ids from before the start or after the end of the
+    /// range may be returned. Filter them either with `DatePathCompiler::decompile()` or by the
+    /// data in the entries. Modules which append something to the compiled path (like
+    /// timetracking does with the tag) can only use the latter.
+    ///
+    fn ids_in_range(&self, module_name: &str, compiler: &DatePathCompiler, range: &DateTimeRange)
+        -> Result<StoreIdIterator>;
+
+}
+
+impl DatePathQuery for Store {
+
+    fn ids_in_range(&self, module_name: &str, compiler: &DatePathCompiler, range: &DateTimeRange)
+        -> Result<StoreIdIterator>
+    {
+        let iters = compiler
+            .compile_range(module_name, range)?
+            .into_iter()
+            .map(|folder| self.entries_below(folder).map(|iter| iter.without_store()))
+            .collect::<::std::result::Result<Vec<StoreIdIterator>, _>>()?;
+
+        Ok(StoreIdIterator::new(Box::new(iters.into_iter().flat_map(|iter| iter))))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use chrono::naive::NaiveDate;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+
+    use super::*;
+    use datepath::accuracy::Accuracy;
+    use datepath::format::Format;
+
+    fn get_store() -> Store {
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    #[test]
+    fn test_ids_in_range() {
+        let store    = get_store();
+        let compiler = DatePathCompiler::new(Accuracy::Second, Format::ElementIsFolder);
+
+        for &(d, h) in &[(1, 12), (2, 8), (3, 12), (4, 12), (10, 12)] {
+            let dt = NaiveDate::from_ymd(2000, 1, d).and_hms(h, 0, 0);
+            let id = compiler.compile("module", &dt).unwrap();
+            let _  = store.create(id).unwrap();
+        }
+
+        let range = DateTimeRange::new(NaiveDate::from_ymd(2000, 1, 2).and_hms(12, 0, 0),
+                                       NaiveDate::from_ymd(2000, 1, 3).and_hms(12, 0, 0))
+            .unwrap();
+
+        let mut ids = store
+            .ids_in_range("module", &compiler, &range)
+            .unwrap()
+            .map(|id| id.unwrap().without_base().to_str().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort();
+
+        assert_eq!(vec!["module/2000/01/02/08/00/00", "module/2000/01/03/12/00/00"], ids);
+
+        // Exact filtering
+        let ids = store
+            .ids_in_range("module", &compiler, &range)
+            .unwrap()
+            .map(|id| id.unwrap())
+            .filter(|id| compiler.decompile("module", id).map(|dt| range.contains(&dt)).unwrap_or(false))
+            .count();
+
+        assert_eq!(1, ids);
+    }
+
+}
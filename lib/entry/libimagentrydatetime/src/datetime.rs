@@ -24,11 +24,19 @@ use toml_query::read::TomlValueReadTypeExt;
 use toml::Value;
 
 use libimagstore::store::Entry;
+use libimagtimeui::natural::parse_datetime;
 
 use error::DateErrorKind as DEK;
 use error::DateError as DE;
 use error::*;
 use range::DateTimeRange;
+use range::ZonedDateTimeRange;
+use timezone::Tz;
+use timezone::ZonedDateTime;
+use timezone::parse_naive;
+use timezone::read_zoned_datetime;
+use timezone::write_zoned_datetime;
+use timezone::delete_if_present;
 
 pub trait EntryDate {
 
@@ -40,31 +48,56 @@ pub trait EntryDate {
     fn read_date_range(&self) -> Result<DateTimeRange>;
     fn set_date_range(&mut self, start: NaiveDateTime, end: NaiveDateTime) -> Result<Option<Result<DateTimeRange>>>;
 
+    /// Read the date with its timezone
+    ///
+    /// Dates without timezone information are interpreted as local time in `fallback`.
+    fn read_date_zoned(&self, fallback: Tz) -> Result<ZonedDateTime>;
+    fn set_date_zoned(&mut self, d: &ZonedDateTime) -> Result<()>;
+
+    /// Set the date from a string as passed by the user
+    ///
+    /// The string is either in the "YYYY-MM-DD[THH[:mm[:ss]]]" format or a natural language
+    /// expression like "tomorrow 9am" (see `libimagtimeui::natural`), relative to now in `zone`.
+    fn set_date_from_str(&mut self, s: &str, zone: Tz) -> Result<ZonedDateTime>;
+
+    /// Read the date range with its timezones
+    ///
+    /// Dates without timezone information are interpreted as local time in `fallback`.
+    fn read_date_range_zoned(&self, fallback: Tz) -> Result<ZonedDateTimeRange>;
+    fn set_date_range_zoned(&mut self, range: &ZonedDateTimeRange) -> Result<()>;
+
 }
 
 lazy_static! {
     static ref DATE_HEADER_LOCATION : &'static str              = "datetime.value";
+    static ref DATE_TIMEZONE_HEADER_LOCATION : &'static str     = "datetime.timezone";
     static ref DATE_RANGE_START_HEADER_LOCATION : &'static str  = "datetime.range.start";
+    static ref DATE_RANGE_START_TIMEZONE_HEADER_LOCATION : &'static str = "datetime.range.start_timezone";
     static ref DATE_RANGE_END_HEADER_LOCATION : &'static str    = "datetime.range.end";
+    static ref DATE_RANGE_END_TIMEZONE_HEADER_LOCATION : &'static str   = "datetime.range.end_timezone";
     static ref DATE_FMT : &'static str                          = "%Y-%m-%dT%H:%M:%S";
 }
 
 impl EntryDate for Entry {
 
     fn delete_date(&mut self) -> Result<()> {
+        let _ = delete_if_present(self.get_header_mut(), &DATE_TIMEZONE_HEADER_LOCATION)?;
+
         self.get_header_mut()
             .delete(&DATE_HEADER_LOCATION)
             .map(|_| ())
             .chain_err(|| DEK::DeleteDateError)
     }
 
+    /// Read the date as local (wall clock) time
+    ///
+    /// Use `read_date_zoned()` if the timezone is relevant.
     fn read_date(&self) -> Result<NaiveDateTime> {
         self.get_header()
             .read_string(&DATE_HEADER_LOCATION)
             .chain_err(|| DEK::ReadDateError)?
-            .ok_or(DE::from_kind(DEK::ReadDateError))?
-            .parse::<NaiveDateTime>()
-            .chain_err(|| DEK::DateTimeParsingError)
+            .ok_or(DE::from_kind(DEK::ReadDateError))
+            .and_then(|s| parse_naive(&s))
     }
 
     /// Set a Date for this entry
@@ -83,17 +116,16 @@ impl EntryDate for Entry {
     /// - Ok(Some(Err(_))) if the inserting succeeded, but replaced an existing value which then
     /// got parsed into a NaiveDateTime object, where the parsing failed for some reason.
     ///
+    /// Setting a naive date removes the timezone of a previously set date, as the new date is not
+    /// known to be in that timezone.
+    ///
     fn set_date(&mut self, d: NaiveDateTime) -> Result<Option<Result<NaiveDateTime>>> {
         let date = d.format(&DATE_FMT).to_string();
+        let _    = delete_if_present(self.get_header_mut(), &DATE_TIMEZONE_HEADER_LOCATION)?;
 
         self.get_header_mut()
             .insert(&DATE_HEADER_LOCATION, Value::String(date))
-            .map(|opt| opt.map(|stri| {
-                stri.as_str()
-                    .ok_or(DE::from_kind(DEK::DateHeaderFieldTypeError))?
-                    .parse::<NaiveDateTime>()
-                    .chain_err(|| DEK::DateTimeParsingError)
-            }))
+            .map(|opt| opt.as_ref().map(val_to_ndt))
             .chain_err(|| DEK::SetDateError)
     }
 
@@ -106,6 +138,9 @@ impl EntryDate for Entry {
     /// header in an inconsistent state.
     ///
     fn delete_date_range(&mut self) -> Result<()> {
+        let _ = delete_if_present(self.get_header_mut(), &DATE_RANGE_START_TIMEZONE_HEADER_LOCATION)?;
+        let _ = delete_if_present(self.get_header_mut(), &DATE_RANGE_END_TIMEZONE_HEADER_LOCATION)?;
+
         let _ = self
              .get_header_mut()
             .delete(&DATE_RANGE_START_HEADER_LOCATION)
@@ -128,7 +163,7 @@ impl EntryDate for Entry {
 
         let end = self
             .get_header()
-            .read_string(&DATE_RANGE_START_HEADER_LOCATION)
+            .read_string(&DATE_RANGE_END_HEADER_LOCATION)
             .chain_err(|| DEK::ReadDateTimeRangeError)?
             .ok_or_else(|| DE::from_kind(DEK::ReadDateError))
             .and_then(str_to_ndt)?;
@@ -149,6 +184,9 @@ impl EntryDate for Entry {
         let start = start.format(&DATE_FMT).to_string();
         let end   = end.format(&DATE_FMT).to_string();
 
+        let _ = delete_if_present(self.get_header_mut(), &DATE_RANGE_START_TIMEZONE_HEADER_LOCATION)?;
+        let _ = delete_if_present(self.get_header_mut(), &DATE_RANGE_END_TIMEZONE_HEADER_LOCATION)?;
+
         let opt_old_start = self
             .get_header_mut()
             .insert(&DATE_RANGE_START_HEADER_LOCATION, Value::String(start))
@@ -177,19 +215,81 @@ impl EntryDate for Entry {
         }
     }
 
+    fn read_date_zoned(&self, fallback: Tz) -> Result<ZonedDateTime> {
+        read_zoned_datetime(self.get_header(),
+                            &DATE_HEADER_LOCATION,
+                            &DATE_TIMEZONE_HEADER_LOCATION,
+                            fallback)?
+            .ok_or_else(|| DE::from_kind(DEK::ReadDateError))
+    }
+
+    fn set_date_zoned(&mut self, d: &ZonedDateTime) -> Result<()> {
+        write_zoned_datetime(self.get_header_mut(),
+                             &DATE_HEADER_LOCATION,
+                             &DATE_TIMEZONE_HEADER_LOCATION,
+                             d)
+    }
+
+    fn set_date_from_str(&mut self, s: &str, zone: Tz) -> Result<ZonedDateTime> {
+        let now = ZonedDateTime::now(zone).naive_local();
+        let ndt = parse_datetime(s, &now)
+            .ok_or_else(|| DE::from_kind(DEK::UnparsableDateTime(String::from(s))))?;
+        let zdt = ZonedDateTime::from_local(&ndt, zone)?;
+
+        self.set_date_zoned(&zdt).map(|_| zdt)
+    }
+
+    fn read_date_range_zoned(&self, fallback: Tz) -> Result<ZonedDateTimeRange> {
+        let start = read_zoned_datetime(self.get_header(),
+                                        &DATE_RANGE_START_HEADER_LOCATION,
+                                        &DATE_RANGE_START_TIMEZONE_HEADER_LOCATION,
+                                        fallback)
+            .chain_err(|| DEK::ReadDateTimeRangeError)?
+            .ok_or_else(|| DE::from_kind(DEK::ReadDateError))?;
+
+        let end = read_zoned_datetime(self.get_header(),
+                                      &DATE_RANGE_END_HEADER_LOCATION,
+                                      &DATE_RANGE_END_TIMEZONE_HEADER_LOCATION,
+                                      fallback)
+            .chain_err(|| DEK::ReadDateTimeRangeError)?
+            .ok_or_else(|| DE::from_kind(DEK::ReadDateError))?;
+
+        ZonedDateTimeRange::new(start, end).chain_err(|| DEK::DateTimeRangeError)
+    }
+
+    /// Set the date range
+    ///
+    /// # Warning
+    ///
+    /// This first sets the start, then the end. If the first operation fails, this might leave the
+    /// header in an inconsistent state.
+    ///
+    fn set_date_range_zoned(&mut self, range: &ZonedDateTimeRange) -> Result<()> {
+        let _ = write_zoned_datetime(self.get_header_mut(),
+                                     &DATE_RANGE_START_HEADER_LOCATION,
+                                     &DATE_RANGE_START_TIMEZONE_HEADER_LOCATION,
+                                     range.start())
+            .chain_err(|| DEK::SetDateTimeRangeError)?;
+
+        write_zoned_datetime(self.get_header_mut(),
+                             &DATE_RANGE_END_HEADER_LOCATION,
+                             &DATE_RANGE_END_TIMEZONE_HEADER_LOCATION,
+                             range.end())
+            .chain_err(|| DEK::SetDateTimeRangeError)
+    }
+
 }
 
 #[inline]
 fn str_to_ndt(v: String) -> Result<NaiveDateTime> {
-    v.parse::<NaiveDateTime>().chain_err(|| DEK::DateTimeParsingError)
+    parse_naive(&v)
 }
 
 #[inline]
 fn val_to_ndt(v: &Value) -> Result<NaiveDateTime> {
     v.as_str()
-        .ok_or(DE::from_kind(DEK::DateHeaderFieldTypeError))?
-        .parse::<NaiveDateTime>()
-        .chain_err(|| DEK::DateTimeParsingError)
+        .ok_or(DE::from_kind(DEK::DateHeaderFieldTypeError))
+        .and_then(parse_naive)
 }
 
 #[cfg(test)]
@@ -311,5 +411,58 @@ mod tests {
 
         assert!(hdr_field.is_none());
     }
+
+    #[test]
+    fn test_set_and_read_date_zoned() {
+        use timezone::parse_timezone;
+
+        let store = get_store();
+        let zone  = parse_timezone("America/Los_Angeles").unwrap();
+        let date  = NaiveDateTime::new(NaiveDate::from_ymd(2000, 01, 02), NaiveTime::from_hms(03, 04, 05));
+        let date  = ZonedDateTime::from_local(&date, zone).unwrap();
+
+        let mut entry = store.create(PathBuf::from("test")).unwrap();
+        assert!(entry.set_date_zoned(&date).is_ok());
+
+        match entry.get_header().read(&DATE_HEADER_LOCATION).unwrap() {
+            Some(&Value::String(ref s)) => assert_eq!("2000-01-02T03:04:05-08:00", s),
+            _ => assert!(false, "Wrong header type"),
+        }
+
+        assert_eq!(date, entry.read_date_zoned(Tz::UTC).unwrap());
+
+        // Reading the date without timezone still yields the local time
+        assert_eq!(date.naive_local(), entry.read_date().unwrap());
+    }
+
+    #[test]
+    fn test_set_date_from_str() {
+        let store     = get_store();
+        let mut entry = store.create(PathBuf::from("test")).unwrap();
+
+        let date = entry.set_date_from_str("2018-03-04 9am", Tz::UTC).unwrap();
+        assert_eq!("2018-03-04T09:00:00+00:00", date.to_rfc3339());
+        assert_eq!(date, entry.read_date_zoned(Tz::UTC).unwrap());
+
+        assert!(entry.set_date_from_str("someday", Tz::UTC).is_err());
+        assert_eq!(date, entry.read_date_zoned(Tz::UTC).unwrap());
+    }
+
+    #[test]
+    fn test_read_naive_date_zoned() {
+        use timezone::parse_timezone;
+
+        let store = get_store();
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let date  = NaiveDateTime::new(NaiveDate::from_ymd(2000, 01, 02), NaiveTime::from_hms(03, 04, 05));
+
+        let mut entry = store.create(PathBuf::from("test")).unwrap();
+        assert!(entry.set_date(date).is_ok());
+
+        let zoned = entry.read_date_zoned(zone).unwrap();
+        assert_eq!(Some(zone), zoned.zone());
+        assert_eq!(date, zoned.naive_local());
+        assert_eq!("2000-01-02T03:04:05+01:00", zoned.to_rfc3339());
+    }
 }
 
@@ -68,10 +68,50 @@ error_chain! {
             display("Error parsing DateTime")
         }
 
+        UnparsableDateTime(s: String) {
+            description("Cannot parse date-time")
+            display("Cannot parse date-time: '{}'", s)
+        }
+
         EndDateTimeBeforeStartDateTime {
             description("End datetime is before start datetime")
             display("End datetime is before start datetime")
         }
+
+        ReadTimezoneConfigError {
+            description("Error reading timezone from configuration")
+            display("Error reading timezone from configuration")
+        }
+
+        UnknownTimezone(name: String) {
+            description("Unknown timezone")
+            display("Unknown timezone: '{}'", name)
+        }
+
+        MalformedRecurrenceRule(part: String) {
+            description("Malformed recurrence rule")
+            display("Malformed recurrence rule: {}", part)
+        }
+
+        UnsupportedRecurrenceRule(part: String) {
+            description("Unsupported recurrence rule")
+            display("Unsupported recurrence rule: {}", part)
+        }
+
+        ReadRecurrenceError {
+            description("Error reading recurrence")
+            display("Error reading recurrence")
+        }
+
+        SetRecurrenceError {
+            description("Error setting recurrence")
+            display("Error setting recurrence")
+        }
+
+        NonexistentLocalTime(time: String) {
+            description("Local time does not exist in this timezone")
+            display("Local time does not exist in this timezone: {}", time)
+        }
     }
 }
 
@@ -37,15 +37,20 @@
 
 #[macro_use] extern crate lazy_static;
 extern crate chrono;
+extern crate chrono_tz;
 extern crate toml_query;
 extern crate toml;
 #[macro_use] extern crate error_chain;
 
 extern crate libimagerror;
 extern crate libimagstore;
+extern crate libimagtimeui;
+extern crate libimagutil;
 
 pub mod datepath;
 pub mod datetime;
 pub mod error;
 pub mod range;
+pub mod recurrence;
+pub mod timezone;
 
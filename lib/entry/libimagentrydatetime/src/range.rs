@@ -17,11 +17,13 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+use chrono::Duration;
 use chrono::naive::NaiveDateTime;
 
 use error::DateErrorKind as DEK;
 use error::DateError as DE;
 use error::Result;
+use timezone::ZonedDateTime;
 
 /// A Range between two dates
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -44,6 +46,58 @@ impl DateTimeRange {
         }
     }
 
+    pub fn start(&self) -> &NaiveDateTime {
+        &self.0
+    }
+
+    pub fn end(&self) -> &NaiveDateTime {
+        &self.1
+    }
+
+    /// Check whether `dt` is in the range, start and end included
+    pub fn contains(&self, dt: &NaiveDateTime) -> bool {
+        self.0 <= *dt && *dt <= self.1
+    }
+
+}
+
+/// A Range between two timezone aware dates
+///
+/// Start and end may be in different timezones, which is why the range is ordered by the points
+/// in time and not by the wall clock times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZonedDateTimeRange(ZonedDateTime, ZonedDateTime);
+
+impl ZonedDateTimeRange {
+
+    /// Create a new ZonedDateTimeRange object
+    ///
+    /// # Return value
+    ///
+    /// Ok(ZonedDateTimeRange) if start is before end,
+    /// else Err(DateTimeRangeError)
+    ///
+    pub fn new(start: ZonedDateTime, end: ZonedDateTime) -> Result<ZonedDateTimeRange> {
+        if start.datetime() < end.datetime() {
+            Ok(ZonedDateTimeRange(start, end))
+        } else {
+            Err(DE::from_kind(DEK::EndDateTimeBeforeStartDateTime))
+        }
+    }
+
+    pub fn start(&self) -> &ZonedDateTime {
+        &self.0
+    }
+
+    pub fn end(&self) -> &ZonedDateTime {
+        &self.1
+    }
+
+    /// The time between start and end, respecting timezones and daylight saving time changes
+    pub fn duration(&self) -> Duration {
+        self.1.signed_duration_since(&self.0)
+    }
+
 }
 
 #[cfg(test)]
@@ -88,4 +142,24 @@ mod tests {
 
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn test_zoned_range_between_timezones() {
+        use timezone::ZonedDateTime;
+        use timezone::parse_timezone;
+        use super::ZonedDateTimeRange;
+
+        // Departure in Berlin at 10:00, arrival in New York at 13:00 local time
+        let berlin   = parse_timezone("Europe/Berlin").unwrap();
+        let new_york = parse_timezone("America/New_York").unwrap();
+
+        let start = NaiveDateTime::new(NaiveDate::from_ymd(2018, 06, 01), NaiveTime::from_hms(10, 00, 00));
+        let end   = NaiveDateTime::new(NaiveDate::from_ymd(2018, 06, 01), NaiveTime::from_hms(13, 00, 00));
+
+        let start = ZonedDateTime::from_local(&start, berlin).unwrap();
+        let end   = ZonedDateTime::from_local(&end, new_york).unwrap();
+
+        let range = ZonedDateTimeRange::new(start, end).unwrap();
+        assert_eq!(9, range.duration().num_hours());
+    }
 }
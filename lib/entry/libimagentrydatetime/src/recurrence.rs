@@ -0,0 +1,1034 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Recurring dates, following the recurrence rules (RRULE) of RFC 5545 (iCalendar)
+//!
+//! A `Recurrence` is a start datetime, a `RRule` and a list of exceptions. Supported parts of a
+//! rule are `FREQ` (`MINUTELY` up to `YEARLY`), `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY` (with
+//! ordinals like `1MO` or `-1FR` for monthly and yearly rules), `BYMONTHDAY`, `BYMONTH` and
+//! `WKST`. All datetimes are naive, an `UNTIL` in UTC is treated like a local datetime.
+//!
+//! Recurrences can be attached to any entry with the `EntryRecurrence` trait.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fmt::Error as FmtError;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::Weekday;
+use chrono::naive::NaiveDate;
+use chrono::naive::NaiveDateTime;
+use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadExt;
+use toml_query::read::TomlValueReadTypeExt;
+
+use libimagstore::store::Entry;
+
+use error::DateErrorKind as DEK;
+use error::DateError as DE;
+use error::ResultExt;
+use error::Result;
+use range::DateTimeRange;
+use timezone::Tz;
+use timezone::delete_if_present;
+use timezone::parse_naive;
+use timezone::parse_timezone;
+
+lazy_static! {
+    static ref RECURRENCE_START_HEADER_LOCATION : &'static str      = "datetime.recurrence.start";
+    static ref RECURRENCE_RULE_HEADER_LOCATION : &'static str       = "datetime.recurrence.rule";
+    static ref RECURRENCE_EXCEPTIONS_HEADER_LOCATION : &'static str = "datetime.recurrence.exceptions";
+    static ref RECURRENCE_TIMEZONE_HEADER_LOCATION : &'static str   = "datetime.recurrence.timezone";
+    static ref RECURRENCE_DURATION_HEADER_LOCATION : &'static str   = "datetime.recurrence.duration";
+    static ref DATE_FMT : &'static str                              = "%Y-%m-%dT%H:%M:%S";
+    static ref UNTIL_FMT : &'static str                             = "%Y%m%dT%H%M%S";
+}
+
+/// Stop generating occurrences after this many periods without an occurrence, so rules which
+/// never match (like the 30th of February) do not loop forever
+const MAX_EMPTY_PERIODS : u32 = 1_000_000;
+
+/// Do not generate occurrences after this year
+const MAX_YEAR : i32 = 9999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Frequency::Minutely => "MINUTELY",
+            Frequency::Hourly   => "HOURLY",
+            Frequency::Daily    => "DAILY",
+            Frequency::Weekly   => "WEEKLY",
+            Frequency::Monthly  => "MONTHLY",
+            Frequency::Yearly   => "YEARLY",
+        }
+    }
+
+}
+
+impl FromStr for Frequency {
+    type Err = DE;
+
+    fn from_str(s: &str) -> Result<Frequency> {
+        match s {
+            "MINUTELY" => Ok(Frequency::Minutely),
+            "HOURLY"   => Ok(Frequency::Hourly),
+            "DAILY"    => Ok(Frequency::Daily),
+            "WEEKLY"   => Ok(Frequency::Weekly),
+            "MONTHLY"  => Ok(Frequency::Monthly),
+            "YEARLY"   => Ok(Frequency::Yearly),
+            other      => Err(DE::from_kind(DEK::UnsupportedRecurrenceRule(format!("FREQ={}", other)))),
+        }
+    }
+}
+
+/// A weekday in a `BYDAY` rule part, optionally with an ordinal ("the second monday")
+///
+/// Negative ordinals count from the end of the month (or year): -1 is the last such weekday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+impl WeekdayNum {
+
+    pub fn every(weekday: Weekday) -> WeekdayNum {
+        WeekdayNum { ordinal: None, weekday }
+    }
+
+    pub fn nth(ordinal: i32, weekday: Weekday) -> WeekdayNum {
+        WeekdayNum { ordinal: Some(ordinal), weekday }
+    }
+
+}
+
+impl Display for WeekdayNum {
+
+    fn fmt(&self, fmt: &mut Formatter) -> ::std::result::Result<(), FmtError> {
+        if let Some(ordinal) = self.ordinal {
+            write!(fmt, "{}", ordinal)?;
+        }
+        write!(fmt, "{}", weekday_to_str(self.weekday))
+    }
+
+}
+
+impl FromStr for WeekdayNum {
+    type Err = DE;
+
+    fn from_str(s: &str) -> Result<WeekdayNum> {
+        let malformed = || DE::from_kind(DEK::MalformedRecurrenceRule(format!("BYDAY={}", s)));
+
+        if s.len() < 2 || !s.is_char_boundary(s.len() - 2) {
+            return Err(malformed());
+        }
+
+        let (ordinal, weekday) = s.split_at(s.len() - 2);
+        let weekday = weekday_from_str(weekday).ok_or_else(&malformed)?;
+        let ordinal = match ordinal {
+            "" => None,
+            o  => Some(o.trim_left_matches('+').parse::<i32>().map_err(|_| malformed())?),
+        };
+
+        Ok(WeekdayNum { ordinal, weekday })
+    }
+}
+
+fn weekday_to_str(wd: Weekday) -> &'static str {
+    match wd {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn weekday_from_str(s: &str) -> Option<Weekday> {
+    match s {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _    => None,
+    }
+}
+
+/// A recurrence rule (the RRULE property of RFC 5545)
+///
+/// # Example
+///
+/// ```
+/// # extern crate libimagentrydatetime;
+/// use std::str::FromStr;
+/// use libimagentrydatetime::recurrence::RRule;
+///
+/// let rule = RRule::from_str("FREQ=MONTHLY;BYDAY=-1FR;COUNT=12").unwrap();
+/// assert_eq!("FREQ=MONTHLY;COUNT=12;BYDAY=-1FR", rule.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    freq         : Frequency,
+    interval     : u32,
+    count        : Option<u32>,
+    until        : Option<NaiveDateTime>,
+    by_day       : Vec<WeekdayNum>,
+    by_month_day : Vec<i32>,
+    by_month     : Vec<u32>,
+    week_start   : Weekday,
+}
+
+impl RRule {
+
+    pub fn new(freq: Frequency) -> RRule {
+        RRule {
+            freq         : freq,
+            interval     : 1,
+            count        : None,
+            until        : None,
+            by_day       : vec![],
+            by_month_day : vec![],
+            by_month     : vec![],
+            week_start   : Weekday::Mon,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: u32) -> RRule {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_count(mut self, count: u32) -> RRule {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn with_until(mut self, until: NaiveDateTime) -> RRule {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn with_by_day(mut self, by_day: Vec<WeekdayNum>) -> RRule {
+        self.by_day = by_day;
+        self
+    }
+
+    pub fn with_by_month_day(mut self, by_month_day: Vec<i32>) -> RRule {
+        self.by_month_day = by_month_day;
+        self
+    }
+
+    pub fn with_by_month(mut self, by_month: Vec<u32>) -> RRule {
+        self.by_month = by_month;
+        self
+    }
+
+    pub fn with_week_start(mut self, week_start: Weekday) -> RRule {
+        self.week_start = week_start;
+        self
+    }
+
+    pub fn freq(&self) -> Frequency {
+        self.freq
+    }
+
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    pub fn until(&self) -> Option<&NaiveDateTime> {
+        self.until.as_ref()
+    }
+
+    pub fn by_day(&self) -> &Vec<WeekdayNum> {
+        &self.by_day
+    }
+
+    pub fn by_month_day(&self) -> &Vec<i32> {
+        &self.by_month_day
+    }
+
+    pub fn by_month(&self) -> &Vec<u32> {
+        &self.by_month
+    }
+
+    pub fn week_start(&self) -> Weekday {
+        self.week_start
+    }
+
+    /// Check whether the rule is valid and supported
+    pub fn validate(&self) -> Result<()> {
+        let malformed = |part: &str| Err(DE::from_kind(DEK::MalformedRecurrenceRule(String::from(part))));
+
+        if self.interval == 0 {
+            return malformed("INTERVAL=0");
+        }
+
+        if self.count.is_some() && self.until.is_some() {
+            return malformed("COUNT and UNTIL must not be used together");
+        }
+
+        if self.by_month.iter().any(|m| *m < 1 || *m > 12) {
+            return malformed("BYMONTH out of range");
+        }
+
+        if self.by_month_day.iter().any(|d| *d == 0 || *d < -31 || *d > 31) {
+            return malformed("BYMONTHDAY out of range");
+        }
+
+        if self.freq == Frequency::Weekly && !self.by_month_day.is_empty() {
+            return malformed("BYMONTHDAY must not be used with FREQ=WEEKLY");
+        }
+
+        for wdn in self.by_day.iter() {
+            if let Some(ordinal) = wdn.ordinal {
+                match self.freq {
+                    Frequency::Monthly => {},
+                    Frequency::Yearly if !self.by_month.is_empty() => {},
+                    Frequency::Yearly => {
+                        let part = String::from("BYDAY with ordinal in a yearly rule without BYMONTH");
+                        return Err(DE::from_kind(DEK::UnsupportedRecurrenceRule(part)));
+                    },
+                    _ => return malformed("BYDAY with ordinal is only allowed in monthly and yearly rules"),
+                }
+
+                if ordinal == 0 || ordinal < -5 || ordinal > 5 {
+                    return malformed("BYDAY ordinal out of range");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether `date` passes the BYMONTH, BYMONTHDAY and BYDAY (without ordinal) filters
+    fn matches(&self, date: &NaiveDate) -> bool {
+        let month_ok = self.by_month.is_empty() || self.by_month.contains(&date.month());
+
+        let month_day_ok = self.by_month_day.is_empty() || {
+            let last = days_in_month(date.year(), date.month());
+            self.by_month_day
+                .iter()
+                .any(|d| resolve_month_day(*d, last) == Some(date.day()))
+        };
+
+        let day_ok = self.by_day.is_empty() || self.by_day.iter().any(|wdn| wdn.weekday == date.weekday());
+
+        month_ok && month_day_ok && day_ok
+    }
+
+    /// The days of `month` in `year` which are selected by the rule
+    ///
+    /// `default_day` is used if there is neither BYDAY nor BYMONTHDAY.
+    fn expand_month(&self, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+        let last = days_in_month(year, month);
+
+        let mut days : Vec<u32> = if !self.by_month_day.is_empty() {
+            self.by_month_day.iter().filter_map(|d| resolve_month_day(*d, last)).collect()
+        } else if !self.by_day.is_empty() {
+            (1..(last + 1)).collect()
+        } else if default_day <= last {
+            vec![default_day]
+        } else {
+            vec![]
+        };
+
+        if !self.by_day.is_empty() {
+            days.retain(|day| {
+                let date = NaiveDate::from_ymd(year, month, *day);
+                self.by_day.iter().any(|wdn| {
+                    wdn.weekday == date.weekday() && match wdn.ordinal {
+                        None                => true,
+                        Some(n) if n > 0    => ((*day - 1) / 7 + 1) as i32 == n,
+                        Some(n)             => ((last - *day) / 7 + 1) as i32 == -n,
+                    }
+                })
+            });
+        }
+
+        days.sort();
+        days.dedup();
+        days.into_iter().map(|d| NaiveDate::from_ymd(year, month, d)).collect()
+    }
+
+}
+
+impl Display for RRule {
+
+    fn fmt(&self, fmt: &mut Formatter) -> ::std::result::Result<(), FmtError> {
+        fn join<T: Display>(v: &[T]) -> String {
+            v.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(",")
+        }
+
+        write!(fmt, "FREQ={}", self.freq.as_str())?;
+
+        if self.interval != 1 {
+            write!(fmt, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(fmt, ";COUNT={}", count)?;
+        }
+        if let Some(ref until) = self.until {
+            write!(fmt, ";UNTIL={}", until.format(&UNTIL_FMT))?;
+        }
+        if !self.by_day.is_empty() {
+            write!(fmt, ";BYDAY={}", join(&self.by_day))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(fmt, ";BYMONTHDAY={}", join(&self.by_month_day))?;
+        }
+        if !self.by_month.is_empty() {
+            write!(fmt, ";BYMONTH={}", join(&self.by_month))?;
+        }
+        if self.week_start != Weekday::Mon {
+            write!(fmt, ";WKST={}", weekday_to_str(self.week_start))?;
+        }
+
+        Ok(())
+    }
+
+}
+
+impl FromStr for RRule {
+    type Err = DE;
+
+    /// Parse a rule like "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE", optionally prefixed with "RRULE:"
+    fn from_str(s: &str) -> Result<RRule> {
+        let s = s.trim();
+        let s = if s.starts_with("RRULE:") { &s[6..] } else { s };
+
+        let malformed = |part: &str| DE::from_kind(DEK::MalformedRecurrenceRule(String::from(part)));
+
+        fn list<T: FromStr>(value: &str) -> Option<Vec<T>> {
+            value.split(',').map(|e| e.trim_left_matches('+').parse::<T>().ok()).collect()
+        }
+
+        let mut freq = None;
+        let mut rule = RRule::new(Frequency::Daily);
+
+        for part in s.split(';').filter(|p| !p.is_empty()) {
+            let mut kv    = part.splitn(2, '=');
+            let key       = kv.next().unwrap_or("").to_uppercase();
+            let value     = kv.next().ok_or_else(|| malformed(part))?.to_uppercase();
+
+            match key.as_str() {
+                "FREQ"       => freq = Some(Frequency::from_str(&value)?),
+                "INTERVAL"   => rule.interval = value.parse().map_err(|_| malformed(part))?,
+                "COUNT"      => rule.count = Some(value.parse().map_err(|_| malformed(part))?),
+                "UNTIL"      => rule.until = Some(parse_until(&value).ok_or_else(|| malformed(part))?),
+                "BYMONTHDAY" => rule.by_month_day = list(&value).ok_or_else(|| malformed(part))?,
+                "BYMONTH"    => rule.by_month = list(&value).ok_or_else(|| malformed(part))?,
+                "WKST"       => rule.week_start = weekday_from_str(&value).ok_or_else(|| malformed(part))?,
+                "BYDAY"      => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(WeekdayNum::from_str)
+                        .collect::<Result<Vec<_>>>()?;
+                },
+                _ => return Err(DE::from_kind(DEK::UnsupportedRecurrenceRule(String::from(part)))),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| malformed("FREQ is missing"))?;
+        rule.validate().map(|_| rule)
+    }
+}
+
+fn parse_until(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim_right_matches('Z');
+    NaiveDateTime::parse_from_str(s, &UNTIL_FMT)
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y%m%d").ok().map(|d| d.and_hms(23, 59, 59)))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let first_of_next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+
+    first_of_next
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(31)
+}
+
+/// Resolve a BYMONTHDAY value (negative values count from the end) in a month with `last` days
+fn resolve_month_day(day: i32, last: u32) -> Option<u32> {
+    let last = last as i32;
+    let day  = if day < 0 { last + 1 + day } else { day };
+
+    if day >= 1 && day <= last {
+        Some(day as u32)
+    } else {
+        None
+    }
+}
+
+/// A recurring date: the first occurrence, the rule and the occurrences which are left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    start      : NaiveDateTime,
+    rule       : RRule,
+    exceptions : Vec<NaiveDateTime>,
+    zone       : Option<Tz>,
+    duration   : Option<Duration>,
+}
+
+impl Recurrence {
+
+    /// Create a new Recurrence starting at `start`
+    ///
+    /// `start` is only an occurrence if it matches the rule. The time of all occurrences is the
+    /// time of `start`.
+    pub fn new(start: NaiveDateTime, rule: RRule) -> Result<Recurrence> {
+        rule.validate().map(|_| Recurrence { start, rule, exceptions: vec![], zone: None, duration: None })
+    }
+
+    /// Let the occurrences happen in `zone`
+    ///
+    /// All times of the recurrence are wall clock times in this zone, so the occurrences stay at
+    /// the same time of the day across DST changes. Without a zone, they are in the timezone of
+    /// the user.
+    pub fn with_timezone(mut self, zone: Tz) -> Recurrence {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Let each occurrence last `duration`
+    pub fn with_duration(mut self, duration: Duration) -> Recurrence {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Leave out the occurrence at `dt`
+    ///
+    /// Occurrences which are left out still count for the COUNT part of the rule.
+    pub fn with_exception(mut self, dt: NaiveDateTime) -> Recurrence {
+        if !self.exceptions.contains(&dt) {
+            self.exceptions.push(dt);
+            self.exceptions.sort();
+        }
+        self
+    }
+
+    pub fn start(&self) -> &NaiveDateTime {
+        &self.start
+    }
+
+    pub fn rule(&self) -> &RRule {
+        &self.rule
+    }
+
+    pub fn exceptions(&self) -> &Vec<NaiveDateTime> {
+        &self.exceptions
+    }
+
+    pub fn timezone(&self) -> Option<Tz> {
+        self.zone
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Iterate over all occurrences
+    ///
+    /// The iterator is infinite if the rule has neither COUNT nor UNTIL.
+    pub fn occurrences(&self) -> Occurrences {
+        Occurrences {
+            recurrence    : self,
+            period        : 0,
+            buffer        : VecDeque::new(),
+            counted       : 0,
+            empty_periods : 0,
+            done          : false,
+        }
+    }
+
+    /// Iterate over the occurrences in `window` (start and end included)
+    pub fn occurrences_in(&self, window: &DateTimeRange) -> OccurrencesIn {
+        OccurrencesIn(self.occurrences(), window.clone())
+    }
+
+    /// The dates of the `n`th period of the rule, which may include dates before `start`
+    ///
+    /// None if the period is out of the supported range of dates.
+    fn period(&self, n: u32) -> Option<Vec<NaiveDateTime>> {
+        let steps = i64::from(n) * i64::from(self.rule.interval);
+        let start = &self.start;
+
+        let dates : Vec<NaiveDate> = match self.rule.freq {
+            Frequency::Minutely | Frequency::Hourly | Frequency::Daily => {
+                let step = match self.rule.freq {
+                    Frequency::Minutely => Duration::minutes(steps),
+                    Frequency::Hourly   => Duration::hours(steps),
+                    _                   => Duration::days(steps),
+                };
+                let dt = start.checked_add_signed(step)?;
+                if dt.year() > MAX_YEAR {
+                    return None;
+                }
+
+                // Sub-daily rules keep their time, so they are handled here
+                return Some(if self.rule.matches(&dt.date()) { vec![dt] } else { vec![] });
+            },
+
+            Frequency::Weekly => {
+                let wkst       = i64::from(self.rule.week_start.num_days_from_monday());
+                let offset     = |wd: Weekday| (i64::from(wd.num_days_from_monday()) + 7 - wkst) % 7;
+                let week_start = start.date()
+                    .checked_sub_signed(Duration::days(offset(start.weekday())))?
+                    .checked_add_signed(Duration::weeks(steps))?;
+
+                if week_start.year() > MAX_YEAR {
+                    return None;
+                }
+
+                let mut dates = if self.rule.by_day.is_empty() {
+                    vec![week_start + Duration::days(offset(start.weekday()))]
+                } else {
+                    self.rule
+                        .by_day
+                        .iter()
+                        .map(|wdn| week_start + Duration::days(offset(wdn.weekday)))
+                        .collect()
+                };
+
+                dates.sort();
+                dates.dedup();
+                dates.retain(|d| self.rule.by_month.is_empty() || self.rule.by_month.contains(&d.month()));
+                dates
+            },
+
+            Frequency::Monthly => {
+                let index = i64::from(start.year()) * 12 + i64::from(start.month0()) + steps;
+                let year  = index / 12;
+                let month = (index % 12) as u32 + 1;
+
+                if year > i64::from(MAX_YEAR) {
+                    return None;
+                }
+
+                if self.rule.by_month.is_empty() || self.rule.by_month.contains(&month) {
+                    self.rule.expand_month(year as i32, month, start.day())
+                } else {
+                    vec![]
+                }
+            },
+
+            Frequency::Yearly => {
+                let year = i64::from(start.year()) + steps;
+                if year > i64::from(MAX_YEAR) {
+                    return None;
+                }
+
+                let months = if !self.rule.by_month.is_empty() {
+                    let mut months = self.rule.by_month.clone();
+                    months.sort();
+                    months.dedup();
+                    months
+                } else if !self.rule.by_day.is_empty() || !self.rule.by_month_day.is_empty() {
+                    (1..13).collect()
+                } else {
+                    vec![start.month()]
+                };
+
+                months
+                    .into_iter()
+                    .flat_map(|m| self.rule.expand_month(year as i32, m, start.day()))
+                    .collect()
+            },
+        };
+
+        Some(dates.into_iter().map(|d| d.and_time(start.time())).collect())
+    }
+
+}
+
+/// Iterator over the occurrences of a `Recurrence`, in chronological order
+pub struct Occurrences<'a> {
+    recurrence    : &'a Recurrence,
+    period        : u32,
+    buffer        : VecDeque<NaiveDateTime>,
+    counted       : u32,
+    empty_periods : u32,
+    done          : bool,
+}
+
+impl<'a> Iterator for Occurrences<'a> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let recurrence = self.recurrence;
+        let rule       = &recurrence.rule;
+
+        loop {
+            if let Some(dt) = self.buffer.pop_front() {
+                let after_until = rule.until.map(|until| dt > until).unwrap_or(false);
+                let count_hit   = rule.count.map(|count| self.counted >= count).unwrap_or(false);
+
+                if after_until || count_hit {
+                    self.done = true;
+                    self.buffer.clear();
+                    return None;
+                }
+
+                self.counted += 1;
+
+                if recurrence.exceptions.contains(&dt) {
+                    continue;
+                }
+
+                return Some(dt);
+            }
+
+            if self.done || self.empty_periods >= MAX_EMPTY_PERIODS {
+                return None;
+            }
+
+            match recurrence.period(self.period) {
+                None        => self.done = true,
+                Some(dates) => {
+                    let start = recurrence.start;
+                    let len   = self.buffer.len();
+                    self.buffer.extend(dates.into_iter().filter(|dt| *dt >= start));
+
+                    if self.buffer.len() == len {
+                        self.empty_periods += 1;
+                    } else {
+                        self.empty_periods = 0;
+                    }
+                },
+            }
+
+            self.period += 1;
+        }
+    }
+}
+
+/// Iterator over the occurrences of a `Recurrence` in a window
+pub struct OccurrencesIn<'a>(Occurrences<'a>, DateTimeRange);
+
+impl<'a> Iterator for OccurrencesIn<'a> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(dt) = self.0.next() {
+            if dt < *self.1.start() {
+                continue;
+            }
+
+            if dt > *self.1.end() {
+                return None;
+            }
+
+            return Some(dt);
+        }
+
+        None
+    }
+}
+
+/// Extension for entries to attach a `Recurrence`
+///
+/// The recurrence is stored in the header at `datetime.recurrence`, with the rule in the RRULE
+/// format of RFC 5545, the timezone as its name and the duration in seconds.
+pub trait EntryRecurrence {
+
+    fn get_recurrence(&self) -> Result<Option<Recurrence>>;
+    fn set_recurrence(&mut self, recurrence: &Recurrence) -> Result<()>;
+    fn delete_recurrence(&mut self) -> Result<()>;
+
+}
+
+impl EntryRecurrence for Entry {
+
+    fn get_recurrence(&self) -> Result<Option<Recurrence>> {
+        let header = self.get_header();
+
+        let rule = match header.read_string(&RECURRENCE_RULE_HEADER_LOCATION).chain_err(|| DEK::ReadRecurrenceError)? {
+            Some(rule) => RRule::from_str(&rule)?,
+            None       => return Ok(None),
+        };
+
+        let start = header
+            .read_string(&RECURRENCE_START_HEADER_LOCATION)
+            .chain_err(|| DEK::ReadRecurrenceError)?
+            .ok_or_else(|| DE::from_kind(DEK::ReadRecurrenceError))
+            .and_then(|s| parse_naive(&s))?;
+
+        let exceptions = match header.read(&RECURRENCE_EXCEPTIONS_HEADER_LOCATION).chain_err(|| DEK::ReadRecurrenceError)? {
+            Some(&Value::Array(ref exceptions)) => exceptions
+                .iter()
+                .map(|v| match *v {
+                    Value::String(ref s) => parse_naive(s),
+                    _ => Err(DE::from_kind(DEK::DateHeaderFieldTypeError)),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => return Err(DE::from_kind(DEK::DateHeaderFieldTypeError)),
+            None    => vec![],
+        };
+
+        let mut recurrence = Recurrence::new(start, rule)?;
+
+        if let Some(zone) = header.read_string(&RECURRENCE_TIMEZONE_HEADER_LOCATION).chain_err(|| DEK::ReadRecurrenceError)? {
+            recurrence = recurrence.with_timezone(parse_timezone(&zone)?);
+        }
+
+        if let Some(secs) = header.read_int(&RECURRENCE_DURATION_HEADER_LOCATION).chain_err(|| DEK::ReadRecurrenceError)? {
+            recurrence = recurrence.with_duration(Duration::seconds(secs));
+        }
+
+        Ok(Some(exceptions.into_iter().fold(recurrence, Recurrence::with_exception)))
+    }
+
+    fn set_recurrence(&mut self, recurrence: &Recurrence) -> Result<()> {
+        let header = self.get_header_mut();
+
+        let start = Value::String(recurrence.start.format(&DATE_FMT).to_string());
+        let _ = header
+            .insert(&RECURRENCE_START_HEADER_LOCATION, start)
+            .chain_err(|| DEK::SetRecurrenceError)?;
+
+        let rule = Value::String(recurrence.rule.to_string());
+        let _ = header
+            .insert(&RECURRENCE_RULE_HEADER_LOCATION, rule)
+            .chain_err(|| DEK::SetRecurrenceError)?;
+
+        match recurrence.zone {
+            Some(zone) => {
+                let _ = header
+                    .insert(&RECURRENCE_TIMEZONE_HEADER_LOCATION, Value::String(String::from(zone.name())))
+                    .chain_err(|| DEK::SetRecurrenceError)?;
+            },
+            None => delete_if_present(header, &RECURRENCE_TIMEZONE_HEADER_LOCATION)?,
+        }
+
+        match recurrence.duration {
+            Some(duration) => {
+                let _ = header
+                    .insert(&RECURRENCE_DURATION_HEADER_LOCATION, Value::Integer(duration.num_seconds()))
+                    .chain_err(|| DEK::SetRecurrenceError)?;
+            },
+            None => delete_if_present(header, &RECURRENCE_DURATION_HEADER_LOCATION)?,
+        }
+
+        if recurrence.exceptions.is_empty() {
+            delete_if_present(header, &RECURRENCE_EXCEPTIONS_HEADER_LOCATION)
+        } else {
+            let exceptions = recurrence
+                .exceptions
+                .iter()
+                .map(|dt| Value::String(dt.format(&DATE_FMT).to_string()))
+                .collect();
+
+            header
+                .insert(&RECURRENCE_EXCEPTIONS_HEADER_LOCATION, Value::Array(exceptions))
+                .map(|_| ())
+                .chain_err(|| DEK::SetRecurrenceError)
+        }
+    }
+
+    fn delete_recurrence(&mut self) -> Result<()> {
+        let header = self.get_header_mut();
+        let _ = delete_if_present(header, &RECURRENCE_START_HEADER_LOCATION)?;
+        let _ = delete_if_present(header, &RECURRENCE_RULE_HEADER_LOCATION)?;
+        let _ = delete_if_present(header, &RECURRENCE_TIMEZONE_HEADER_LOCATION)?;
+        let _ = delete_if_present(header, &RECURRENCE_DURATION_HEADER_LOCATION)?;
+        delete_if_present(header, &RECURRENCE_EXCEPTIONS_HEADER_LOCATION)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use chrono::naive::NaiveDate;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+
+    use super::*;
+
+    fn dt(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(y, m, d).and_hms(10, 0, 0)
+    }
+
+    fn occurrences(start: NaiveDateTime, rule: &str, n: usize) -> Vec<NaiveDateTime> {
+        let rule = RRule::from_str(rule).unwrap();
+        Recurrence::new(start, rule).unwrap().occurrences().take(n).collect()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let rule = RRule::from_str("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20181231T235959Z").unwrap();
+        assert_eq!(Frequency::Weekly, rule.freq());
+        assert_eq!(2, rule.interval());
+        assert_eq!(&vec![WeekdayNum::every(Weekday::Mon), WeekdayNum::every(Weekday::Wed)], rule.by_day());
+        assert_eq!("FREQ=WEEKLY;INTERVAL=2;UNTIL=20181231T235959;BYDAY=MO,WE", rule.to_string());
+
+        let rule = RRule::from_str("freq=monthly;byday=+2TU,-1FR").unwrap();
+        assert_eq!(&vec![WeekdayNum::nth(2, Weekday::Tue), WeekdayNum::nth(-1, Weekday::Fri)], rule.by_day());
+        assert_eq!(rule, RRule::from_str(&rule.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(RRule::from_str("").is_err());
+        assert!(RRule::from_str("INTERVAL=2").is_err());
+        assert!(RRule::from_str("FREQ=FORTNIGHTLY").is_err());
+        assert!(RRule::from_str("FREQ=DAILY;INTERVAL=0").is_err());
+        assert!(RRule::from_str("FREQ=DAILY;COUNT=2;UNTIL=20180101").is_err());
+        assert!(RRule::from_str("FREQ=DAILY;BYDAY=1MO").is_err());
+        assert!(RRule::from_str("FREQ=DAILY;BYSETPOS=1").is_err());
+        assert!(RRule::from_str("FREQ=MONTHLY;BYMONTHDAY=32").is_err());
+    }
+
+    #[test]
+    fn test_daily() {
+        let occ = occurrences(dt(2018, 1, 30), "FREQ=DAILY;INTERVAL=2", 3);
+        assert_eq!(vec![dt(2018, 1, 30), dt(2018, 2, 1), dt(2018, 2, 3)], occ);
+    }
+
+    #[test]
+    fn test_daily_count_and_until() {
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=DAILY;COUNT=3", 10);
+        assert_eq!(vec![dt(2018, 1, 1), dt(2018, 1, 2), dt(2018, 1, 3)], occ);
+
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=DAILY;UNTIL=20180102", 10);
+        assert_eq!(vec![dt(2018, 1, 1), dt(2018, 1, 2)], occ);
+    }
+
+    #[test]
+    fn test_weekly_by_day() {
+        // 2018-01-03 is a wednesday
+        let occ = occurrences(dt(2018, 1, 3), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE,FR", 5);
+        assert_eq!(vec![dt(2018, 1, 3), dt(2018, 1, 5), dt(2018, 1, 15), dt(2018, 1, 17), dt(2018, 1, 19)], occ);
+    }
+
+    #[test]
+    fn test_monthly_by_day_ordinal() {
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=MONTHLY;BYDAY=-1FR", 3);
+        assert_eq!(vec![dt(2018, 1, 26), dt(2018, 2, 23), dt(2018, 3, 30)], occ);
+
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=MONTHLY;BYDAY=1MO", 2);
+        assert_eq!(vec![dt(2018, 1, 1), dt(2018, 2, 5)], occ);
+    }
+
+    #[test]
+    fn test_monthly_skips_short_months() {
+        let occ = occurrences(dt(2018, 1, 31), "FREQ=MONTHLY", 3);
+        assert_eq!(vec![dt(2018, 1, 31), dt(2018, 3, 31), dt(2018, 5, 31)], occ);
+
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=MONTHLY;BYMONTHDAY=-1", 2);
+        assert_eq!(vec![dt(2018, 1, 31), dt(2018, 2, 28)], occ);
+    }
+
+    #[test]
+    fn test_yearly_anniversary() {
+        let occ = occurrences(dt(2016, 2, 29), "FREQ=YEARLY", 2);
+        assert_eq!(vec![dt(2016, 2, 29), dt(2020, 2, 29)], occ);
+
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", 2);
+        assert_eq!(vec![dt(2018, 11, 22), dt(2019, 11, 28)], occ);
+    }
+
+    #[test]
+    fn test_impossible_rule_terminates() {
+        let occ = occurrences(dt(2018, 1, 1), "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30", 1);
+        assert!(occ.is_empty());
+    }
+
+    #[test]
+    fn test_exceptions_count() {
+        let rule = RRule::from_str("FREQ=DAILY;COUNT=3").unwrap();
+        let occ  = Recurrence::new(dt(2018, 1, 1), rule)
+            .unwrap()
+            .with_exception(dt(2018, 1, 2))
+            .occurrences()
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![dt(2018, 1, 1), dt(2018, 1, 3)], occ);
+    }
+
+    #[test]
+    fn test_occurrences_in_window() {
+        let rule   = RRule::from_str("FREQ=WEEKLY").unwrap();
+        let rec    = Recurrence::new(dt(2018, 1, 1), rule).unwrap();
+        let window = DateTimeRange::new(dt(2018, 3, 1), dt(2018, 3, 19)).unwrap();
+
+        let occ = rec.occurrences_in(&window).collect::<Vec<_>>();
+        assert_eq!(vec![dt(2018, 3, 5), dt(2018, 3, 12), dt(2018, 3, 19)], occ);
+    }
+
+    #[test]
+    fn test_entry_recurrence() {
+        let store = Store::new_with_backend(PathBuf::from("/"),
+                                            &None,
+                                            Arc::new(InMemoryFileAbstraction::default())).unwrap();
+        let mut entry = store.create(PathBuf::from("test")).unwrap();
+
+        assert!(entry.get_recurrence().unwrap().is_none());
+
+        let rule = RRule::from_str("FREQ=YEARLY;BYMONTH=5;BYMONTHDAY=1").unwrap();
+        let rec  = Recurrence::new(dt(2018, 5, 1), rule).unwrap().with_exception(dt(2019, 5, 1));
+
+        assert!(entry.set_recurrence(&rec).is_ok());
+        assert_eq!(Some(rec.clone()), entry.get_recurrence().unwrap());
+
+        let zone = parse_timezone("America/New_York").unwrap();
+        let rec  = rec.with_timezone(zone).with_duration(Duration::minutes(90));
+
+        assert!(entry.set_recurrence(&rec).is_ok());
+        assert_eq!(Some(rec), entry.get_recurrence().unwrap());
+
+        assert!(entry.delete_recurrence().is_ok());
+        assert!(entry.get_recurrence().unwrap().is_none());
+        assert!(entry.get_header().read("datetime.recurrence.timezone").unwrap().is_none());
+    }
+
+}
@@ -0,0 +1,382 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Timezone aware datetimes
+//!
+//! Datetimes are stored in the header as RFC 3339 strings (which include the UTC offset), next
+//! to the name of the IANA timezone the datetime was recorded in, for example:
+//!
+//! ```toml
+//! [datetime]
+//! value = "2018-03-25T01:30:00+01:00"
+//! timezone = "Europe/Berlin"
+//! ```
+//!
+//! Values which were written by older versions of imag do not have an offset. These are
+//! interpreted as local time in the timezone passed as fallback, which is normally the configured
+//! default timezone (see `default_timezone()`).
+
+use std::env;
+use std::fs::File;
+use std::fs::read_link;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::FixedOffset;
+use chrono::LocalResult;
+use chrono::Offset;
+use chrono::TimeZone;
+use chrono::Utc;
+use chrono::naive::NaiveDateTime;
+use toml::Value;
+use toml_query::delete::TomlValueDeleteExt;
+use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadExt;
+use toml_query::read::TomlValueReadTypeExt;
+
+pub use chrono_tz::Tz;
+
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
+
+use error::DateErrorKind as DEK;
+use error::DateError as DE;
+use error::ResultExt;
+use error::Result;
+
+/// Path of the default timezone in the imag configuration
+pub const TIMEZONE_CONFIG_PATH : &'static str = "datetime.timezone";
+
+/// The configuration keys the datetime handling reads
+pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: TIMEZONE_CONFIG_PATH,
+        kind: ValueType::String,
+        default: None,
+        doc: "The IANA timezone datetimes are recorded in, defaults to $TZ or the timezone of the system",
+    },
+];
+
+/// Get the default timezone
+///
+/// The timezone is read from the configuration (`datetime.timezone`), then from the `TZ`
+/// environment variable. If neither is set (or `TZ` does not name an IANA timezone), the timezone
+/// of the system is used (see `system_timezone()`) and if that is unknown, UTC.
+pub fn default_timezone(config: Option<&Value>) -> Result<Tz> {
+    let configured = match config {
+        Some(cfg) => cfg
+            .read_string(TIMEZONE_CONFIG_PATH)
+            .chain_err(|| DEK::ReadTimezoneConfigError)?,
+        None => None,
+    };
+
+    match configured {
+        Some(name) => parse_timezone(&name),
+        None => Ok(env::var("TZ")
+            .ok()
+            .and_then(|name| parse_timezone(name.trim_left_matches(':')).ok())
+            .or_else(system_timezone)
+            .unwrap_or(Tz::UTC)),
+    }
+}
+
+/// Get the timezone of the system
+///
+/// This is the timezone `/etc/localtime` links to or, if it is not a link, the one named in
+/// `/etc/timezone`.
+pub fn system_timezone() -> Option<Tz> {
+    read_link("/etc/localtime")
+        .ok()
+        .and_then(|target| zone_from_zoneinfo_path(&target))
+        .or_else(|| {
+            let mut name = String::new();
+            File::open("/etc/timezone")
+                .and_then(|mut f| f.read_to_string(&mut name))
+                .ok()
+                .and_then(|_| parse_timezone(name.trim()).ok())
+        })
+}
+
+/// Get the timezone from a path into the zoneinfo database, like
+/// "/usr/share/zoneinfo/Europe/Berlin"
+fn zone_from_zoneinfo_path(path: &Path) -> Option<Tz> {
+    let path = path.to_str()?;
+    let name = &path[path.find("zoneinfo/")? + "zoneinfo/".len()..];
+
+    // the "posix" and "right" subdirectories contain the same timezones
+    let name = if name.starts_with("posix/") {
+        &name["posix/".len()..]
+    } else if name.starts_with("right/") {
+        &name["right/".len()..]
+    } else {
+        name
+    };
+
+    parse_timezone(name).ok()
+}
+
+/// Parse an IANA timezone name, like "Europe/Berlin"
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.parse::<Tz>()
+        .map_err(|_| DE::from_kind(DEK::UnknownTimezone(String::from(name))))
+}
+
+/// Parse a datetime from the header into its local (wall clock) time
+///
+/// Accepts both RFC 3339 strings and datetimes without offset, so code which works with naive
+/// datetimes can read values written with and without timezone information.
+pub fn parse_naive(s: &str) -> Result<NaiveDateTime> {
+    match DateTime::parse_from_rfc3339(s) {
+        Ok(dt) => Ok(dt.naive_local()),
+        Err(_) => s.parse::<NaiveDateTime>().chain_err(|| DEK::DateTimeParsingError),
+    }
+}
+
+/// A point in time, together with the timezone it was recorded in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZonedDateTime {
+    datetime: DateTime<FixedOffset>,
+    zone: Option<Tz>,
+}
+
+impl ZonedDateTime {
+
+    pub fn new(datetime: DateTime<FixedOffset>, zone: Option<Tz>) -> ZonedDateTime {
+        ZonedDateTime { datetime, zone }
+    }
+
+    pub fn from_zoned(datetime: DateTime<Tz>) -> ZonedDateTime {
+        let zone   = datetime.timezone();
+        let offset = datetime.offset().fix();
+
+        ZonedDateTime {
+            datetime: datetime.with_timezone(&offset),
+            zone: Some(zone),
+        }
+    }
+
+    /// Interpret a naive datetime as local time in `zone`
+    ///
+    /// If the local time is ambiguous (because clocks were set back), the earlier point in time is
+    /// used. If the local time does not exist (because clocks were set forward), an error is
+    /// returned.
+    pub fn from_local(ndt: &NaiveDateTime, zone: Tz) -> Result<ZonedDateTime> {
+        match zone.from_local_datetime(ndt) {
+            LocalResult::Single(dt)       => Ok(ZonedDateTime::from_zoned(dt)),
+            LocalResult::Ambiguous(dt, _) => Ok(ZonedDateTime::from_zoned(dt)),
+            LocalResult::None => {
+                let s = format!("{} in {}", ndt, zone.name());
+                Err(DE::from_kind(DEK::NonexistentLocalTime(s)))
+            },
+        }
+    }
+
+    /// The current time in `zone`
+    pub fn now(zone: Tz) -> ZonedDateTime {
+        ZonedDateTime::from_zoned(Utc::now().with_timezone(&zone))
+    }
+
+    /// Parse a datetime string and an optional timezone, as stored in the header
+    ///
+    /// Strings without offset are interpreted as local time in `zone` or, if no zone is passed, in
+    /// `fallback`.
+    pub fn parse(s: &str, zone: Option<Tz>, fallback: Tz) -> Result<ZonedDateTime> {
+        match DateTime::parse_from_rfc3339(s) {
+            Ok(dt) => Ok(ZonedDateTime::new(dt, zone)),
+            Err(_) => {
+                let ndt = s.parse::<NaiveDateTime>().chain_err(|| DEK::DateTimeParsingError)?;
+                ZonedDateTime::from_local(&ndt, zone.unwrap_or(fallback))
+            },
+        }
+    }
+
+    pub fn datetime(&self) -> &DateTime<FixedOffset> {
+        &self.datetime
+    }
+
+    pub fn zone(&self) -> Option<Tz> {
+        self.zone
+    }
+
+    /// The wall clock time in the timezone the datetime was recorded in
+    pub fn naive_local(&self) -> NaiveDateTime {
+        self.datetime.naive_local()
+    }
+
+    pub fn naive_utc(&self) -> NaiveDateTime {
+        self.datetime.naive_utc()
+    }
+
+    /// Convert to another timezone
+    pub fn in_zone(&self, zone: Tz) -> ZonedDateTime {
+        ZonedDateTime::from_zoned(self.datetime.with_timezone(&zone))
+    }
+
+    /// The time between `other` and `self`, independent of the timezones
+    pub fn signed_duration_since(&self, other: &ZonedDateTime) -> Duration {
+        self.datetime.signed_duration_since(other.datetime)
+    }
+
+    pub fn to_rfc3339(&self) -> String {
+        self.datetime.to_rfc3339()
+    }
+
+}
+
+/// Read a zoned datetime from `path` in the header, and its timezone from `zone_path`
+pub fn read_zoned_datetime(header: &Value, path: &str, zone_path: &str, fallback: Tz)
+    -> Result<Option<ZonedDateTime>>
+{
+    let zone = match header.read_string(zone_path).chain_err(|| DEK::ReadDateError)? {
+        Some(name) => Some(parse_timezone(&name)?),
+        None       => None,
+    };
+
+    match header.read_string(path).chain_err(|| DEK::ReadDateError)? {
+        Some(s) => ZonedDateTime::parse(&s, zone, fallback).map(Some),
+        None    => Ok(None),
+    }
+}
+
+/// Write a zoned datetime to `path` in the header, and its timezone (if known) to `zone_path`
+pub fn write_zoned_datetime(header: &mut Value, path: &str, zone_path: &str, dt: &ZonedDateTime)
+    -> Result<()>
+{
+    let _ = header
+        .insert(path, Value::String(dt.to_rfc3339()))
+        .chain_err(|| DEK::SetDateError)?;
+
+    match dt.zone() {
+        Some(zone) => header
+            .insert(zone_path, Value::String(String::from(zone.name())))
+            .map(|_| ())
+            .chain_err(|| DEK::SetDateError),
+        None => delete_if_present(header, zone_path),
+    }
+}
+
+/// Delete `path` from the header, if it exists
+pub fn delete_if_present(header: &mut Value, path: &str) -> Result<()> {
+    let present = header.read(path).chain_err(|| DEK::DeleteDateError)?.is_some();
+    if present {
+        let _ = header.delete(path).chain_err(|| DEK::DeleteDateError)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::naive::NaiveDate;
+    use toml::Value;
+
+    use super::*;
+
+    fn ndt(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(y, m, d).and_hms(h, min, 0)
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert!(parse_timezone("Europe/Berlin").is_ok());
+        assert!(parse_timezone("Nowhere/Special").is_err());
+    }
+
+    #[test]
+    fn test_default_timezone_from_config() {
+        let mut config = Value::Table(Default::default());
+        let _ = config.insert(TIMEZONE_CONFIG_PATH, Value::String(String::from("America/New_York"))).unwrap();
+
+        let tz = default_timezone(Some(&config)).unwrap();
+        assert_eq!("America/New_York", tz.name());
+    }
+
+    #[test]
+    fn test_default_timezone_without_tz_and_config() {
+        env::remove_var("TZ");
+
+        let tz = default_timezone(None).unwrap();
+        assert_eq!(system_timezone().unwrap_or(Tz::UTC), tz);
+    }
+
+    #[test]
+    fn test_zone_from_zoneinfo_path() {
+        let zone = |p: &str| zone_from_zoneinfo_path(Path::new(p)).map(|tz| tz.name());
+
+        assert_eq!(Some("Europe/Berlin"), zone("/usr/share/zoneinfo/Europe/Berlin"));
+        assert_eq!(Some("Europe/Berlin"), zone("../usr/share/zoneinfo/posix/Europe/Berlin"));
+        assert_eq!(Some("UTC"), zone("/usr/share/zoneinfo/UTC"));
+        assert_eq!(None, zone("/usr/share/zoneinfo/Nowhere/Special"));
+        assert_eq!(None, zone("/etc/localtime"));
+    }
+
+    #[test]
+    fn test_from_local_nonexistent() {
+        // clocks were set forward from 02:00 to 03:00 in Berlin on this day
+        let zone = parse_timezone("Europe/Berlin").unwrap();
+        assert!(ZonedDateTime::from_local(&ndt(2018, 3, 25, 2, 30), zone).is_err());
+    }
+
+    #[test]
+    fn test_duration_over_dst_change() {
+        let zone  = parse_timezone("Europe/Berlin").unwrap();
+        let start = ZonedDateTime::from_local(&ndt(2018, 3, 25, 1, 0), zone).unwrap();
+        let end   = ZonedDateTime::from_local(&ndt(2018, 3, 25, 4, 0), zone).unwrap();
+
+        assert_eq!(2, end.signed_duration_since(&start).num_hours());
+    }
+
+    #[test]
+    fn test_parse_naive_and_rfc3339() {
+        let expected = ndt(2018, 1, 2, 3, 4);
+        assert_eq!(expected, parse_naive("2018-01-02T03:04:00").unwrap());
+        assert_eq!(expected, parse_naive("2018-01-02T03:04:00-05:00").unwrap());
+    }
+
+    #[test]
+    fn test_parse_uses_fallback_for_naive_values() {
+        let zone = parse_timezone("America/New_York").unwrap();
+        let dt   = ZonedDateTime::parse("2018-01-02T03:04:00", None, zone).unwrap();
+
+        assert_eq!("2018-01-02T03:04:00-05:00", dt.to_rfc3339());
+        assert_eq!(Some(zone), dt.zone());
+    }
+
+    #[test]
+    fn test_header_roundtrip() {
+        let zone   = parse_timezone("Asia/Tokyo").unwrap();
+        let dt     = ZonedDateTime::from_local(&ndt(2018, 6, 1, 9, 0), zone).unwrap();
+        let mut hd = Value::Table(Default::default());
+
+        write_zoned_datetime(&mut hd, "a.value", "a.timezone", &dt).unwrap();
+        let read = read_zoned_datetime(&hd, "a.value", "a.timezone", Tz::UTC).unwrap();
+
+        assert_eq!(Some(dt), read);
+    }
+
+    #[test]
+    fn test_in_zone() {
+        let berlin = parse_timezone("Europe/Berlin").unwrap();
+        let utc    = ZonedDateTime::from_local(&ndt(2018, 6, 1, 9, 0), Tz::UTC).unwrap();
+
+        assert_eq!(ndt(2018, 6, 1, 11, 0), utc.in_zone(berlin).naive_local());
+    }
+
+}
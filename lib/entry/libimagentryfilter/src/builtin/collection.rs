@@ -0,0 +1,50 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+use libimagstore::store::Entry;
+
+use filters::filter::Filter;
+
+/// Check whether an Entry is in a certain collection
+pub struct IsInCollection {
+    collection: Vec<String>,
+}
+
+impl IsInCollection {
+
+    /// Create the filter for a collection. Nested collections are separated by a slash, as in
+    /// "diary/work".
+    pub fn new(collection: &str) -> IsInCollection {
+        IsInCollection {
+            collection: collection
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+}
+
+impl Filter<Entry> for IsInCollection {
+
+    fn filter(&self, e: &Entry) -> bool {
+        e.get_location().is_in_collection(&self.collection)
+    }
+
+}
@@ -0,0 +1,86 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! Helpers for accepting queries on the commandline
+//!
+//! All binaries which filter entries should use these helpers, so the `--where` and `--saved`
+//! flags behave the same everywhere.
+
+use clap::Arg;
+use clap::ArgMatches;
+
+use libimagstore::store::Store;
+
+use query::Query;
+use saved::SavedQueryStore;
+use error::FilterError as FE;
+use error::FilterErrorKind as FEK;
+use error::Result;
+
+/// Name of the argument built by `where_arg()`
+pub const WHERE_ARG_NAME : &'static str = "where";
+
+/// Name of the argument built by `saved_arg()`
+pub const SAVED_ARG_NAME : &'static str = "saved";
+
+/// Build the `--where QUERY` argument
+pub fn where_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(WHERE_ARG_NAME)
+        .long("where")
+        .takes_value(true)
+        .required(false)
+        .multiple(false)
+        .value_name("QUERY")
+        .help("Only use entries matching QUERY, e.g. 'tag(\"work\") and not collection(\"diary\")'")
+}
+
+/// Build the `--saved NAME` argument
+pub fn saved_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SAVED_ARG_NAME)
+        .long("saved")
+        .takes_value(true)
+        .required(false)
+        .multiple(false)
+        .value_name("NAME")
+        .help("Only use entries matching the saved query NAME")
+}
+
+/// Get the compiled query from the `--where` and `--saved` arguments, if they were passed
+///
+/// If both are passed, an entry has to match both queries.
+pub fn query_from_matches(store: &Store, matches: &ArgMatches) -> Result<Option<Query>> {
+    let query = match matches.value_of(WHERE_ARG_NAME) {
+        Some(q) => Some(Query::parse(q)?),
+        None    => None,
+    };
+
+    let saved = match matches.value_of(SAVED_ARG_NAME) {
+        Some(name) => match store.get_saved_query(name)? {
+            Some(q) => Some(q),
+            None    => return Err(FE::from_kind(FEK::SavedQueryNotFound(String::from(name)))),
+        },
+        None => None,
+    };
+
+    Ok(match (query, saved) {
+        (Some(q), Some(s)) => Some(Query::And(Box::new(q), Box::new(s))),
+        (Some(q), None)    => Some(q),
+        (None, Some(s))    => Some(s),
+        (None, None)       => None,
+    })
+}
@@ -0,0 +1,109 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+use std::cmp::Ordering;
+
+use chrono::NaiveDateTime;
+use toml_query::read::TomlValueReadExt;
+
+use libimagstore::store::Entry;
+use libimagentrydatetime::datetime::EntryDate;
+
+use filters::failable::filter::FailableFilter;
+use error::Result;
+use error::FilterError as FE;
+
+/// The header location libimagentrydatetime stores the date of an entry at
+const DATE_HEADER_LOCATION : &'static str = "datetime.value";
+
+/// How the date of an entry should compare to a date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateComparison {
+    Before,
+    BeforeOrAt,
+    At,
+    NotAt,
+    AtOrAfter,
+    After,
+}
+
+impl DateComparison {
+
+    fn matches(&self, ord: Ordering) -> bool {
+        match *self {
+            DateComparison::Before     => ord == Ordering::Less,
+            DateComparison::BeforeOrAt => ord != Ordering::Greater,
+            DateComparison::At         => ord == Ordering::Equal,
+            DateComparison::NotAt      => ord != Ordering::Equal,
+            DateComparison::AtOrAfter  => ord != Ordering::Less,
+            DateComparison::After      => ord == Ordering::Greater,
+        }
+    }
+
+}
+
+/// Compare the date of an entry (as set by libimagentrydatetime) to a date
+///
+/// # Notice
+///
+/// Returns false if the entry has no date.
+pub struct DateCompare {
+    comparison: DateComparison,
+    date: NaiveDateTime,
+    date_only: bool,
+}
+
+impl DateCompare {
+
+    pub fn new(comparison: DateComparison, date: NaiveDateTime) -> DateCompare {
+        DateCompare {
+            comparison: comparison,
+            date: date,
+            date_only: false,
+        }
+    }
+
+    /// Only compare the day of the dates, ignoring the time
+    ///
+    /// This way, an entry from 2018-01-01T12:00:00 is `At` 2018-01-01T00:00:00.
+    pub fn date_only(mut self, b: bool) -> Self {
+        self.date_only = b;
+        self
+    }
+
+}
+
+impl FailableFilter<Entry> for DateCompare {
+    type Error = FE;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        if e.get_header().read(DATE_HEADER_LOCATION)?.is_none() {
+            return Ok(false);
+        }
+
+        let date = e.read_date()?;
+        let ord  = if self.date_only {
+            date.date().cmp(&self.date.date())
+        } else {
+            date.cmp(&self.date)
+        };
+
+        Ok(self.comparison.matches(ord))
+    }
+
+}
@@ -16,19 +16,49 @@
 // License along with this library; if not, write to the Free Software
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
-
 error_chain! {
 
     types {
         FilterError, FilterErrorKind, ResultExt, Result;
     }
 
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+        CategoryError(::libimagentrycategory::error::CategoryError, ::libimagentrycategory::error::CategoryErrorKind);
+        DateError(::libimagentrydatetime::error::DateError, ::libimagentrydatetime::error::DateErrorKind);
+        LinkError(::libimagentrylink::error::LinkError, ::libimagentrylink::error::LinkErrorKind);
+    }
+
     foreign_links {
         TomlQueryError(::toml_query::error::Error);
+        RegexError(::regex::Error);
     }
 
     errors {
+        QueryParseError(reason: String) {
+            description("Failed to parse query")
+            display("Failed to parse query: {}", reason)
+        }
+
+        QueryCompileError(reason: String) {
+            description("Invalid query")
+            display("Invalid query: {}", reason)
+        }
+
+        SavedQueryNotFound(name: String) {
+            description("Saved query not found")
+            display("Saved query not found: {}", name)
+        }
+
+        SavedQueryMalformed(name: String) {
+            description("Saved query is malformed")
+            display("Saved query is malformed: {}", name)
+        }
+
+        InvalidSavedQueryName(name: String) {
+            description("Invalid name for a saved query")
+            display("Invalid name for a saved query, it must not contain '/' or be '.' or '..': {}", name)
+        }
     }
 
 }
-
@@ -17,6 +17,8 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+#![recursion_limit="256"]
+
 #![deny(
     dead_code,
     non_camel_case_types,
@@ -33,15 +35,22 @@
     while_true,
 )]
 
+extern crate chrono;
+extern crate clap;
 extern crate filters;
+#[macro_use] extern crate log;
+#[macro_use] extern crate nom;
 extern crate regex;
 extern crate semver;
 extern crate toml;
 extern crate toml_query;
 #[macro_use] extern crate error_chain;
 
-extern crate libimagstore;
+#[macro_use] extern crate libimagstore;
 extern crate libimagentrytag;
+extern crate libimagentrycategory;
+extern crate libimagentrydatetime;
+extern crate libimagentrylink;
 
 // core functionality modules of the crate,
 // these depend only on libimagstore
@@ -53,4 +62,15 @@ pub mod error;
 // these depend on other internal libraries than libimagstore and use the upper core modules for
 // their functionality
 
+pub mod category;
+pub mod datetime;
+pub mod link;
 pub mod tags;
+
+// The query language, which compiles to the filters from above
+
+pub mod cli;
+pub mod query;
+pub mod saved;
+
+module_entry_path_mod!("query");
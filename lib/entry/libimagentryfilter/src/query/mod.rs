@@ -0,0 +1,508 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! The imag query language
+//!
+//! A query is parsed into an abstract syntax tree (see the `parser` module) and then compiled into
+//! the filters of this crate, so every binary which accepts a query filters entries the same way.
+//!
+//! See `QUERY_LANGUAGE_DOC` for a description of the language.
+
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use toml::Value as TomlValue;
+
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+use filters::filter::Filter;
+use filters::failable::filter::FailableFilter;
+
+use builtin::collection::IsInCollection;
+use builtin::content::grep::ContentGrep;
+use builtin::header::field_eq::FieldEq;
+use builtin::header::field_grep::FieldGrep;
+use builtin::header::field_istype::FieldIsType;
+use builtin::header::field_istype::Type;
+use builtin::header::field_predicate::FieldPredicate;
+use builtin::header::field_predicate::Predicate;
+use category::HasCategory;
+use datetime::DateCompare;
+use datetime::DateComparison;
+use link::IsLinkedTo;
+use saved::is_saved_query_id;
+use tags::HasTag;
+use error::FilterError as FE;
+use error::FilterErrorKind as FEK;
+use error::Result;
+
+pub mod parser;
+
+use self::parser::Atom;
+use self::parser::CompareOp;
+use self::parser::CompareValue;
+use self::parser::Comparison;
+use self::parser::Function;
+use self::parser::Operator;
+use self::parser::PredicateKind;
+use self::parser::Selector;
+use self::parser::Value;
+
+/// Documentation of the query language, for showing it in the `--help` output of binaries
+pub const QUERY_LANGUAGE_DOC : &'static str = include_str!("../../static/query-language.md");
+
+/// A compiled query, which can be used to filter entries
+pub enum Query {
+    Filter(Box<FailableFilter<Entry, Error = FE>>),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Xor(Box<Query>, Box<Query>),
+}
+
+impl Query {
+
+    /// Parse and compile a query
+    pub fn parse(s: &str) -> Result<Query> {
+        parser::parse(s).and_then(compile_query)
+    }
+
+    /// Check whether an entry matches the query
+    ///
+    /// Same as `FailableFilter::filter()`, except that saved queries never match. Callers do not
+    /// need to depend on the `filters` crate for this.
+    pub fn matches(&self, e: &Entry) -> Result<bool> {
+        if is_saved_query_id(e.get_location()) {
+            return Ok(false);
+        }
+
+        self.filter(e)
+    }
+
+    /// Get the ids of all entries in the store which match the query
+    ///
+    /// Saved queries are not loaded and never match.
+    pub fn matching_ids(&self, store: &Store) -> Result<Vec<StoreId>> {
+        let mut ids = Vec::new();
+
+        for id in store.entries()?.without_store() {
+            let id = id?;
+            if is_saved_query_id(&id) {
+                continue;
+            }
+
+            let matches = match store.get(id.clone())? {
+                Some(entry) => self.matches(&entry)?,
+                None        => false,
+            };
+
+            if matches {
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+}
+
+impl FromStr for Query {
+    type Err = FE;
+
+    fn from_str(s: &str) -> Result<Query> {
+        Query::parse(s)
+    }
+}
+
+impl FailableFilter<Entry> for Query {
+    type Error = FE;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        match *self {
+            Query::Filter(ref f) => f.filter(e),
+            Query::Not(ref q)    => q.filter(e).map(|b| !b),
+            Query::And(ref a, ref b) => Ok(a.filter(e)? && b.filter(e)?),
+            Query::Or(ref a, ref b)  => Ok(a.filter(e)? || b.filter(e)?),
+            Query::Xor(ref a, ref b) => Ok(a.filter(e)? != b.filter(e)?),
+        }
+    }
+}
+
+/// Helper to use the non-failing filters of this crate in a `Query`
+struct Infallible<F: Filter<Entry>>(F);
+
+impl<F: Filter<Entry>> FailableFilter<Entry> for Infallible<F> {
+    type Error = FE;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        Ok(self.0.filter(e))
+    }
+}
+
+fn boxed<F: FailableFilter<Entry, Error = FE> + 'static>(f: F) -> Query {
+    Query::Filter(Box::new(f))
+}
+
+fn query_error<T>(s: String) -> Result<T> {
+    Err(FEK::QueryCompileError(s).into())
+}
+
+fn compile_query(q: parser::Query) -> Result<Query> {
+    let mut query = compile_term(q.first)?;
+
+    for (op, term) in q.rest {
+        let next = Box::new(compile_term(term)?);
+        query = match op {
+            Operator::And    => Query::And(Box::new(query), next),
+            Operator::AndNot => Query::And(Box::new(query), Box::new(Query::Not(next))),
+            Operator::Or     => Query::Or(Box::new(query), next),
+            Operator::OrNot  => Query::Or(Box::new(query), Box::new(Query::Not(next))),
+            Operator::Xor    => Query::Xor(Box::new(query), next),
+        };
+    }
+
+    Ok(query)
+}
+
+fn compile_term(t: parser::Term) -> Result<Query> {
+    let q = match t.atom {
+        Atom::Group(q)               => compile_query(*q)?,
+        Atom::Predicate(kind, arg)   => compile_predicate(kind, arg)?,
+        Atom::Date(op, date)         => compile_date(op, &date)?,
+        Atom::Comparison(comparison) => compile_comparison(comparison)?,
+    };
+
+    if t.negated {
+        Ok(Query::Not(Box::new(q)))
+    } else {
+        Ok(q)
+    }
+}
+
+fn compile_predicate(kind: PredicateKind, arg: String) -> Result<Query> {
+    debug!("Compiling predicate {:?}({:?})", kind, arg);
+    match kind {
+        PredicateKind::Tag        => Ok(boxed(Infallible(HasTag::new(arg)))),
+        PredicateKind::Category   => Ok(boxed(HasCategory::new(arg))),
+        PredicateKind::Collection => Ok(boxed(Infallible(IsInCollection::new(&arg)))),
+        PredicateKind::Content    => Ok(boxed(Infallible(ContentGrep::new(arg.as_str())?))),
+        PredicateKind::Linked     => {
+            let id = StoreId::new_baseless(PathBuf::from(arg))?;
+            Ok(boxed(IsLinkedTo::new(id)))
+        },
+    }
+}
+
+fn compile_date(op: CompareOp, date: &str) -> Result<Query> {
+    let comparison = match op {
+        CompareOp::Lt  => DateComparison::Before,
+        CompareOp::Lte => DateComparison::BeforeOrAt,
+        CompareOp::Eq  => DateComparison::At,
+        CompareOp::Neq => DateComparison::NotAt,
+        CompareOp::Gte => DateComparison::AtOrAfter,
+        CompareOp::Gt  => DateComparison::After,
+        other          => return query_error(format!("Cannot compare dates with {:?}", other)),
+    };
+
+    let (date, date_only) = parse_date(date)?;
+    Ok(boxed(DateCompare::new(comparison, date).date_only(date_only)))
+}
+
+/// Parse "2018-01-01" or "2018-01-01T12:00:00", returns whether only the date was given
+fn parse_date(s: &str) -> Result<(NaiveDateTime, bool)> {
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Ok((dt, false));
+    }
+
+    match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        Ok(d)  => Ok((d.and_hms(0, 0, 0), true)),
+        Err(_) => query_error(format!("Cannot parse '{}' as date, expected YYYY-MM-DD[THH:MM:SS]", s)),
+    }
+}
+
+fn compile_comparison(c: Comparison) -> Result<Query> {
+    debug!("Compiling comparison {:?}", c);
+    let (function, path) = match c.selector {
+        Selector::Direct(path)         => (None, path),
+        Selector::Function(fun, path)  => (Some(fun), path),
+    };
+
+    match (function, c.op, c.value) {
+        (None, CompareOp::Eq, CompareValue::Value(v)) => {
+            Ok(boxed(FieldEq::new(path, to_toml(v))))
+        },
+
+        (None, CompareOp::Matches, CompareValue::Value(Value::String(s))) => {
+            Ok(boxed(FieldGrep::new(path, Regex::new(&s)?)))
+        },
+
+        (_, CompareOp::Matches, _) => {
+            query_error(String::from("=~ can only be used with a string on a header field"))
+        },
+
+        (None, CompareOp::Is, CompareValue::Value(v)) => {
+            let ty = match v {
+                Value::Boolean(_) => Type::Boolean,
+                Value::Integer(_) => Type::Integer,
+                Value::String(_)  => Type::String,
+            };
+            Ok(boxed(FieldIsType::new(path, ty)))
+        },
+
+        (_, CompareOp::In, value)  |
+        (_, CompareOp::Any, value) |
+        (_, CompareOp::All, value) |
+        (_, _, value @ CompareValue::Value(_)) => {
+            if c.op == CompareOp::Is {
+                return query_error(String::from("Cannot check the type of the result of a function"));
+            }
+
+            let pred = HeaderPredicate { function: function, op: c.op, value: value };
+            Ok(boxed(FieldPredicate::new(path, Box::new(pred))))
+        },
+
+        (_, op, CompareValue::Values(_)) => {
+            query_error(format!("Cannot compare a header field with multiple values using {:?}", op))
+        },
+    }
+}
+
+fn to_toml(v: Value) -> TomlValue {
+    match v {
+        Value::Boolean(b) => TomlValue::Boolean(b),
+        Value::Integer(i) => TomlValue::Integer(i),
+        Value::String(s)  => TomlValue::String(s),
+    }
+}
+
+/// The predicate for all comparisons which are not covered by the builtin header filters
+struct HeaderPredicate {
+    function: Option<Function>,
+    op: CompareOp,
+    value: CompareValue,
+}
+
+impl Predicate for HeaderPredicate {
+
+    fn evaluate(&self, v: &TomlValue) -> bool {
+        let applied;
+        let v = match self.function {
+            None => v,
+            Some(ref fun) => {
+                applied = match apply_function(fun, v) {
+                    Some(applied) => applied,
+                    None          => return false,
+                };
+                &applied
+            },
+        };
+
+        match (self.op, &self.value) {
+            (CompareOp::In, &CompareValue::Values(ref list)) => {
+                let list = list.iter().cloned().map(to_toml).collect::<Vec<_>>();
+                match *v {
+                    TomlValue::Array(ref a) => a.iter().any(|e| list.contains(e)),
+                    ref other               => list.contains(other),
+                }
+            },
+
+            (CompareOp::In, &CompareValue::Value(ref val)) => {
+                match (to_toml(val.clone()), v) {
+                    (ref val, &TomlValue::Array(ref a))                => a.contains(val),
+                    (TomlValue::String(ref s), &TomlValue::String(ref h)) => s.contains(h.as_str()),
+                    (ref val, other)                                   => val == other,
+                }
+            },
+
+            (CompareOp::Any, value) | (CompareOp::All, value) => {
+                let list = match *value {
+                    CompareValue::Value(ref val)   => vec![to_toml(val.clone())],
+                    CompareValue::Values(ref list) => list.iter().cloned().map(to_toml).collect(),
+                };
+
+                let contains = |e: &TomlValue| match *v {
+                    TomlValue::Array(ref a) => a.contains(e),
+                    ref other               => other == e,
+                };
+
+                if self.op == CompareOp::Any {
+                    list.iter().any(contains)
+                } else {
+                    list.iter().all(contains)
+                }
+            },
+
+            (op, &CompareValue::Value(ref val)) => {
+                let ord = match compare(v, &to_toml(val.clone())) {
+                    Some(ord) => ord,
+                    None      => return false,
+                };
+
+                match op {
+                    CompareOp::Eq  => ord == Ordering::Equal,
+                    CompareOp::Neq => ord != Ordering::Equal,
+                    CompareOp::Gte => ord != Ordering::Less,
+                    CompareOp::Lte => ord != Ordering::Greater,
+                    CompareOp::Lt  => ord == Ordering::Less,
+                    CompareOp::Gt  => ord == Ordering::Greater,
+                    _              => false, // ruled out when compiling
+                }
+            },
+
+            _ => false, // ruled out when compiling
+        }
+    }
+
+}
+
+fn apply_function(fun: &Function, v: &TomlValue) -> Option<TomlValue> {
+    match *fun {
+        Function::Length => Some(TomlValue::Integer(match *v {
+            TomlValue::Array(ref a)  => a.len() as i64,
+            TomlValue::String(ref s) => s.len() as i64,
+            TomlValue::Table(ref t)  => t.len() as i64,
+            _                        => 1,
+        })),
+        Function::Keys => match *v {
+            TomlValue::Table(ref t) => Some(TomlValue::Array(t.keys().cloned().map(TomlValue::String).collect())),
+            _                       => None,
+        },
+        Function::Values => match *v {
+            TomlValue::Table(ref t) => Some(TomlValue::Array(t.values().cloned().collect())),
+            _                       => None,
+        },
+    }
+}
+
+/// Compare a header value to a query value, None if they cannot be compared
+///
+/// Strings are compared lexicographically, which also works for ISO 8601 dates.
+fn compare(header: &TomlValue, value: &TomlValue) -> Option<Ordering> {
+    match (header, value) {
+        (&TomlValue::Integer(a), &TomlValue::Integer(b))       => Some(a.cmp(&b)),
+        (&TomlValue::Float(a), &TomlValue::Integer(b))         => a.partial_cmp(&(b as f64)),
+        (&TomlValue::Boolean(a), &TomlValue::Boolean(b))       => Some(a.cmp(&b)),
+        (&TomlValue::String(ref a), &TomlValue::String(ref b)) => Some(a.cmp(b)),
+        (&TomlValue::Datetime(ref a), &TomlValue::String(ref b)) => Some(a.to_string().cmp(b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use toml::Value;
+    use toml_query::insert::TomlValueInsertExt;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+
+    use super::*;
+
+    fn get_store() -> Store {
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    fn setup(store: &Store) {
+        {
+            let mut e = store.create(PathBuf::from("notes/a")).unwrap();
+            e.get_header_mut().insert("notes.count", Value::Integer(5)).unwrap();
+            e.get_header_mut().insert("tag.values", Value::Array(vec![Value::String(String::from("work"))])).unwrap();
+            e.get_header_mut().insert("datetime.value", Value::String(String::from("2018-03-04T12:00:00"))).unwrap();
+            *e.get_content_mut() = String::from("Meeting with Bob");
+        }
+        {
+            let mut e = store.create(PathBuf::from("diary/b")).unwrap();
+            e.get_header_mut().insert("notes.count", Value::Integer(10)).unwrap();
+            *e.get_content_mut() = String::from("Nothing happened");
+        }
+    }
+
+    fn matching(store: &Store, q: &str) -> Vec<String> {
+        let mut ids : Vec<String> = Query::parse(q)
+            .unwrap()
+            .matching_ids(store)
+            .unwrap()
+            .into_iter()
+            .map(|id| format!("{}", id.without_base()))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_header_comparisons() {
+        let store = get_store();
+        setup(&store);
+
+        assert_eq!(matching(&store, "notes.count == 5"), vec!["notes/a"]);
+        assert_eq!(matching(&store, "notes.count > 5"), vec!["diary/b"]);
+        assert_eq!(matching(&store, "notes.count <= 10"), vec!["diary/b", "notes/a"]);
+        assert_eq!(matching(&store, "notes.count in [1, 10]"), vec!["diary/b"]);
+        assert_eq!(matching(&store, "length(tag.values) == 1"), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"tag.values in "work""#), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"tag.values any ["work", "home"]"#), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"tag.values all ["work", "home"]"#), Vec::<String>::new());
+        assert_eq!(matching(&store, r#"tag.values all ["work"]"#), vec!["notes/a"]);
+        assert_eq!(matching(&store, "notes.count any [5, 6]"), vec!["notes/a"]);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let store = get_store();
+        setup(&store);
+
+        assert_eq!(matching(&store, r#"tag("work")"#), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"collection("diary")"#), vec!["diary/b"]);
+        assert_eq!(matching(&store, r#"content("[Bb]ob")"#), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"date == "2018-03-04""#), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"date < "2018-03-04T11:00:00""#), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_operators() {
+        let store = get_store();
+        setup(&store);
+
+        assert_eq!(matching(&store, r#"not tag("work")"#), vec!["diary/b"]);
+        assert_eq!(matching(&store, r#"tag("work") or collection("diary")"#), vec!["diary/b", "notes/a"]);
+        assert_eq!(matching(&store, r#"tag("work") and (collection("diary") or notes.count == 5)"#), vec!["notes/a"]);
+        assert_eq!(matching(&store, r#"notes.count > 1 xor tag("work")"#), vec!["diary/b"]);
+        assert_eq!(matching(&store, r#"notes.count > 1 and_not tag("work")"#), vec!["diary/b"]);
+        assert_eq!(matching(&store, r#"tag("work") or_not notes.count == 5"#), vec!["diary/b", "notes/a"]);
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert!(Query::parse(r#"date is "2018-01-01""#).is_err());
+        assert!(Query::parse(r#"date == "yesterday""#).is_err());
+        assert!(Query::parse(r#"content("(")"#).is_err());
+        assert!(Query::parse("a.b == [1, 2]").is_err());
+        assert!(Query::parse("a.b =~ 1").is_err());
+    }
+
+}
@@ -0,0 +1,448 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! The parser for the query language
+//!
+//! Parses a query string into an abstract syntax tree, which is compiled into filters by
+//! `query::Query::parse()`.
+
+use std::str;
+use std::str::FromStr;
+
+use nom::IResult;
+use nom::ErrorKind;
+use nom::digit;
+use nom::multispace;
+
+use error::FilterErrorKind as FEK;
+use error::Result;
+
+fn is_word_char(c: u8) -> bool {
+    (c as char).is_alphanumeric() || c == b'_' || c == b'.'
+}
+
+/// Succeeds if the input does not continue a word
+///
+/// Keywords have to be followed by this, so "order.x" is not parsed as the "or" operator followed
+/// by "der.x".
+fn keyword_end(i: &[u8]) -> IResult<&[u8], ()> {
+    match i.first() {
+        Some(c) if is_word_char(*c) => IResult::Error(error_position!(ErrorKind::Custom(0), i)),
+        _                           => IResult::Done(i, ()),
+    }
+}
+
+macro_rules! keyword {
+    ($i:expr, $kw:expr) => { terminated!($i, tag!($kw), call!(keyword_end)) };
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompareOp {
+    Is,
+    In,
+    Eq,
+    Neq,
+    Matches, // =~
+    Gte,     // >=
+    Lte,     // <=
+    Lt,      // <
+    Gt,      // >
+    Any,
+    All,
+}
+
+named!(compare_op<CompareOp>, alt_complete!(
+    keyword!("is" ) => { |_| CompareOp::Is      } |
+    keyword!("in" ) => { |_| CompareOp::In      } |
+    tag!("==" )     => { |_| CompareOp::Eq      } |
+    keyword!("eq" ) => { |_| CompareOp::Eq      } |
+    tag!("!=" )     => { |_| CompareOp::Neq     } |
+    keyword!("neq") => { |_| CompareOp::Neq     } |
+    tag!("=~" )     => { |_| CompareOp::Matches } |
+    tag!(">=" )     => { |_| CompareOp::Gte     } |
+    tag!("<=" )     => { |_| CompareOp::Lte     } |
+    tag!("<"  )     => { |_| CompareOp::Lt      } |
+    tag!(">"  )     => { |_| CompareOp::Gt      } |
+    keyword!("any") => { |_| CompareOp::Any     } |
+    keyword!("all") => { |_| CompareOp::All     }
+));
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operator {
+    Or,
+    OrNot,
+    And,
+    AndNot,
+    Xor,
+}
+
+named!(operator<Operator>, alt_complete!(
+    keyword!("or_not")  => { |_| Operator::OrNot  } |
+    keyword!("or")      => { |_| Operator::Or     } |
+    keyword!("and_not") => { |_| Operator::AndNot } |
+    keyword!("and")     => { |_| Operator::And    } |
+    keyword!("xor")     => { |_| Operator::Xor    }
+));
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Function {
+    Length,
+    Keys,
+    Values,
+}
+
+named!(function<Function>, alt_complete!(
+    tag!("length") => { |_| Function::Length } |
+    tag!("keys")   => { |_| Function::Keys   } |
+    tag!("values") => { |_| Function::Values }
+));
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Value {
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+}
+
+named!(int64<i64>, map_res!(map_res!(digit, str::from_utf8), i64::from_str));
+
+named!(integer<i64>, do_parse!(
+    sign: opt!(alt_complete!(char!('+') | char!('-'))) >>
+    num: int64 >>
+    (if sign == Some('-') { -num } else { num })
+));
+
+named!(boolean<bool>, alt_complete!(
+    keyword!("false") => { |_| false } |
+    keyword!("true")  => { |_| true  }
+));
+
+named!(string<String>, map!(
+    map_res!(delimited!(char!('"'), take_until!("\""), char!('"')), str::from_utf8),
+    String::from
+));
+
+named!(val<Value>, alt_complete!(
+    boolean => { |b| Value::Boolean(b) } |
+    integer => { |i| Value::Integer(i) } |
+    string  => { |s| Value::String(s)  }
+));
+
+named!(list_of_val<Vec<Value>>, do_parse!(
+        char!('[') >>
+        opt!(multispace) >>
+        list: many0!(
+            do_parse!(
+                list: terminated!(val, opt!(char!(','))) >>
+                opt!(multispace) >>
+                (list)
+        )) >>
+        char!(']') >> (list)
+));
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CompareValue {
+    Value(Value),
+    Values(Vec<Value>)
+}
+
+named!(compare_value<CompareValue>, alt_complete!(
+    list_of_val => { |l| CompareValue::Values(l) } |
+    val         => { |v| CompareValue::Value(v)  }
+));
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Selector {
+    Direct(String),
+    Function(Function, String)
+}
+
+fn is_selector_char(c: u8) -> bool {
+    (c as char).is_alphanumeric() || b"._-[]".contains(&c)
+}
+
+named!(selector_str<String>, map!(
+    map_res!(take_while1!(is_selector_char), str::from_utf8),
+    String::from
+));
+
+named!(selector<Selector>, alt_complete!(
+    do_parse!(
+        fun: function >>
+        char!('(') >> opt!(multispace) >>
+        sel: selector_str >>
+        opt!(multispace) >> char!(')') >>
+        (Selector::Function(fun, sel))
+    ) |
+    selector_str => { |s| Selector::Direct(s) }
+));
+
+/// A comparison of a value in the header of an entry
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Comparison {
+    pub selector: Selector,
+    pub op: CompareOp,
+    pub value: CompareValue,
+}
+
+named!(comparison<Comparison>, do_parse!(
+        sel: selector >> opt!(multispace) >>
+        op: compare_op >> opt!(multispace) >>
+        value: compare_value >>
+        (Comparison { selector: sel, op: op, value: value })
+));
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PredicateKind {
+    Tag,
+    Category,
+    Collection,
+    Linked,
+    Content,
+}
+
+named!(predicate_kind<PredicateKind>, alt_complete!(
+    tag!("tag")        => { |_| PredicateKind::Tag        } |
+    tag!("category")   => { |_| PredicateKind::Category   } |
+    tag!("collection") => { |_| PredicateKind::Collection } |
+    tag!("linked")     => { |_| PredicateKind::Linked     } |
+    tag!("content")    => { |_| PredicateKind::Content    }
+));
+
+named!(predicate<(PredicateKind, String)>, do_parse!(
+        kind: predicate_kind >> opt!(multispace) >>
+        char!('(') >> opt!(multispace) >>
+        arg: string >>
+        opt!(multispace) >> char!(')') >>
+        ((kind, arg))
+));
+
+named!(date<(CompareOp, String)>, do_parse!(
+        keyword!("date") >> opt!(multispace) >>
+        op: compare_op >> opt!(multispace) >>
+        d: string >>
+        ((op, d))
+));
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Atom {
+    Group(Box<Query>),
+    Predicate(PredicateKind, String),
+    Date(CompareOp, String),
+    Comparison(Comparison),
+}
+
+named!(group<Box<Query>>, do_parse!(
+        char!('(') >> opt!(multispace) >>
+        q: query >>
+        opt!(multispace) >> char!(')') >>
+        (Box::new(q))
+));
+
+named!(atom<Atom>, alt_complete!(
+    group      => { |q| Atom::Group(q) } |
+    predicate  => { |(k, a)| Atom::Predicate(k, a) } |
+    date       => { |(op, d)| Atom::Date(op, d) } |
+    comparison => { |c| Atom::Comparison(c) }
+));
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Term {
+    pub negated: bool,
+    pub atom: Atom,
+}
+
+named!(term<Term>, do_parse!(
+        neg: opt!(terminated!(tag!("not"), multispace)) >>
+        atom: atom >>
+        (Term { negated: neg.is_some(), atom: atom })
+));
+
+/// A query is a chain of terms, which are combined from left to right
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Query {
+    pub first: Term,
+    pub rest: Vec<(Operator, Term)>,
+}
+
+named!(query<Query>, do_parse!(
+        first: term >>
+        rest: many0!(do_parse!(opt!(multispace) >> op: operator >> opt!(multispace) >> t: term >> ((op, t)))) >>
+        (Query { first: first, rest: rest })
+));
+
+pub fn parse(s: &str) -> Result<Query> {
+    match query(s.trim().as_bytes()) {
+        IResult::Done(rest, q) => {
+            if rest.iter().all(|c| (*c as char).is_whitespace()) {
+                trace!("Parsed query: {:?}", q);
+                Ok(q)
+            } else {
+                let msg = format!("Unexpected input: '{}'", String::from_utf8_lossy(rest));
+                Err(FEK::QueryParseError(msg).into())
+            }
+        },
+        IResult::Error(e) => {
+            Err(FEK::QueryParseError(format!("{:?}", e)).into())
+        },
+        IResult::Incomplete(needed) => {
+            Err(FEK::QueryParseError(format!("Incomplete input, needed: {:?}", needed)).into())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(s: &str) -> String {
+        String::from(s)
+    }
+
+    fn single(atom: Atom) -> Query {
+        Query { first: Term { negated: false, atom: atom }, rest: vec![] }
+    }
+
+    #[test]
+    fn test_compare_op() {
+        assert_eq!(compare_op(b"is"  ).unwrap().1, CompareOp::Is     );
+        assert_eq!(compare_op(b"in"  ).unwrap().1, CompareOp::In     );
+        assert_eq!(compare_op(b"=="  ).unwrap().1, CompareOp::Eq     );
+        assert_eq!(compare_op(b"eq"  ).unwrap().1, CompareOp::Eq     );
+        assert_eq!(compare_op(b"!="  ).unwrap().1, CompareOp::Neq    );
+        assert_eq!(compare_op(b"neq" ).unwrap().1, CompareOp::Neq    );
+        assert_eq!(compare_op(b"=~"  ).unwrap().1, CompareOp::Matches);
+        assert_eq!(compare_op(b">="  ).unwrap().1, CompareOp::Gte    );
+        assert_eq!(compare_op(b"<="  ).unwrap().1, CompareOp::Lte    );
+        assert_eq!(compare_op(b"<"   ).unwrap().1, CompareOp::Lt     );
+        assert_eq!(compare_op(b">"   ).unwrap().1, CompareOp::Gt     );
+        assert_eq!(compare_op(b"any" ).unwrap().1, CompareOp::Any    );
+        assert_eq!(compare_op(b"all" ).unwrap().1, CompareOp::All    );
+    }
+
+    #[test]
+    fn test_operator() {
+        assert_eq!(operator(b"or").unwrap().1, Operator::Or );
+        assert_eq!(operator(b"or_not").unwrap().1, Operator::OrNot);
+        assert_eq!(operator(b"and").unwrap().1, Operator::And);
+        assert_eq!(operator(b"and_not").unwrap().1, Operator::AndNot);
+        assert_eq!(operator(b"xor").unwrap().1, Operator::Xor);
+    }
+
+    #[test]
+    fn test_keywords_need_word_boundary() {
+        assert!(operator(b"order.x").is_err());
+        assert!(compare_op(b"isx").is_err());
+        assert!(boolean(b"trueish").is_err());
+
+        assert!(parse(r#"tag("a") order.x == 1"#).is_err());
+        assert!(parse(r#"tag("a") andx.y == 1"#).is_err());
+        assert!(parse(r#"tag("a") or(tag("b"))"#).is_ok());
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(integer(b"12 ").unwrap().1, 12);
+        assert_eq!(integer(b"-12 ").unwrap().1, -12);
+        assert_eq!(string(b"\"foo\"").unwrap().1, "foo");
+        assert_eq!(val(b"true ").unwrap().1, Value::Boolean(true));
+        assert_eq!(val(b"\"foobar\"").unwrap().1, Value::String(s("foobar")));
+    }
+
+    #[test]
+    fn test_list_of_val() {
+        assert_eq!(list_of_val(b"[]").unwrap().1, vec![]);
+        assert_eq!(list_of_val(b"[12, 13]").unwrap().1, vec![Value::Integer(12), Value::Integer(13)]);
+        assert_eq!(list_of_val(b"[\"1\", \"2\"]").unwrap().1,
+                   vec![Value::String(s("1")), Value::String(s("2"))]);
+    }
+
+    #[test]
+    fn test_selector() {
+        assert_eq!(selector(b"foo.bar baz").unwrap().1, Selector::Direct(s("foo.bar")));
+        assert_eq!(selector(b"foo.bar==1").unwrap().1, Selector::Direct(s("foo.bar")));
+        assert_eq!(selector(b"length(foo.bar) ").unwrap().1, Selector::Function(Function::Length, s("foo.bar")));
+    }
+
+    #[test]
+    fn test_comparison() {
+        let exp = single(Atom::Comparison(Comparison {
+            selector: Selector::Function(Function::Length, s("imag.header")),
+            op: CompareOp::Gt,
+            value: CompareValue::Value(Value::Integer(12)),
+        }));
+        assert_eq!(parse("length(imag.header) > 12").unwrap(), exp);
+
+        let exp = single(Atom::Comparison(Comparison {
+            selector: Selector::Direct(s("imag.version")),
+            op: CompareOp::Eq,
+            value: CompareValue::Value(Value::String(s("0.7.0"))),
+        }));
+        assert_eq!(parse(r#"imag.version == "0.7.0""#).unwrap(), exp);
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert_eq!(parse(r#"tag("work")"#).unwrap(), single(Atom::Predicate(PredicateKind::Tag, s("work"))));
+        assert_eq!(parse(r#"collection( "diary" )"#).unwrap(),
+                   single(Atom::Predicate(PredicateKind::Collection, s("diary"))));
+        assert_eq!(parse(r#"date >= "2018-01-01""#).unwrap(),
+                   single(Atom::Date(CompareOp::Gte, s("2018-01-01"))));
+
+        // not a predicate, but a header path starting with a predicate name
+        let exp = single(Atom::Comparison(Comparison {
+            selector: Selector::Direct(s("datetime.value")),
+            op: CompareOp::Eq,
+            value: CompareValue::Value(Value::String(s("x"))),
+        }));
+        assert_eq!(parse(r#"datetime.value == "x""#).unwrap(), exp);
+    }
+
+    #[test]
+    fn test_chain_and_group() {
+        let q = parse(r#"not tag("a") and (tag("b") or tag("c"))"#).unwrap();
+        assert!(q.first.negated);
+        assert_eq!(q.rest.len(), 1);
+        assert_eq!(q.rest[0].0, Operator::And);
+
+        match q.rest[0].1.atom {
+            Atom::Group(ref inner) => {
+                assert_eq!(inner.first.atom, Atom::Predicate(PredicateKind::Tag, s("b")));
+                assert_eq!(inner.rest, vec![(Operator::Or, Term {
+                    negated: false,
+                    atom: Atom::Predicate(PredicateKind::Tag, s("c")),
+                })]);
+            },
+            ref other => panic!("Expected group, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_not_prefix_of_selector() {
+        let q = parse("notes.count == 1").unwrap();
+        assert!(!q.first.negated);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("").is_err());
+        assert!(parse(r#"tag("a") garbage"#).is_err());
+        assert!(parse(r#"(tag("a")"#).is_err());
+    }
+
+}
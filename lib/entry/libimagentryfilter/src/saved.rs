@@ -0,0 +1,254 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! Saved queries
+//!
+//! A query can be saved under a name, so it can be used like a (virtual) collection later on.
+//! Saved queries are stored as entries in the "query" collection, so they are versioned and
+//! synced with the rest of the store.
+
+use std::ops::DerefMut;
+use std::path::Component;
+use std::path::Path;
+
+use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadExt;
+use toml_query::read::TomlValueReadTypeExt;
+
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::IntoStoreId;
+use libimagstore::storeid::StoreId;
+
+use module_path::ModuleEntryPath;
+use query::Query;
+use error::FilterError as FE;
+use error::FilterErrorKind as FEK;
+use error::Result;
+
+/// The collection saved queries are stored in
+pub const SAVED_QUERY_COLLECTION : &'static str = "query";
+
+/// Check whether `id` is the id of a saved query
+///
+/// Saved queries are no user data, so they never match a query and are not listed by `imag ids`.
+pub fn is_saved_query_id(id: &StoreId) -> bool {
+    id.is_in_collection(&[SAVED_QUERY_COLLECTION])
+}
+
+/// The id of the saved query `name`
+///
+/// The name must be a single path component, so a query cannot be saved outside of the
+/// collection.
+fn saved_query_id(name: &str) -> Result<StoreId> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !name.contains('/') => {
+            ModuleEntryPath::new(name).into_storeid().map_err(FE::from)
+        },
+        _ => Err(FE::from_kind(FEK::InvalidSavedQueryName(String::from(name)))),
+    }
+}
+
+pub trait SavedQueryStore<'a> {
+
+    /// Save `query` under `name`, overwriting an existing query with the same name
+    ///
+    /// The query is validated before it is saved.
+    fn save_query(&'a self, name: &str, query: &str) -> Result<FileLockEntry<'a>>;
+
+    /// Get the expression of the saved query `name`
+    fn get_saved_query_expression(&'a self, name: &str) -> Result<Option<String>>;
+
+    /// Get the compiled saved query `name`
+    fn get_saved_query(&'a self, name: &str) -> Result<Option<Query>>;
+
+    /// Delete the saved query `name`
+    fn delete_saved_query(&'a self, name: &str) -> Result<()>;
+
+    /// Get the names of all saved queries
+    fn all_saved_query_names(&'a self) -> Result<Vec<String>>;
+
+}
+
+impl<'a> SavedQueryStore<'a> for Store {
+
+    fn save_query(&'a self, name: &str, query: &str) -> Result<FileLockEntry<'a>> {
+        let _ = Query::parse(query)?;
+
+        debug!("Saving query '{}': {}", name, query);
+        let mut entry = self.retrieve(saved_query_id(name)?)?;
+
+        {
+            let entry = entry.deref_mut();
+            let _ = entry.get_header_mut().insert("query.name", Value::String(String::from(name)))?;
+            let _ = entry.get_header_mut().insert("query.expression", Value::String(String::from(query)))?;
+        }
+
+        Ok(entry)
+    }
+
+    fn get_saved_query_expression(&'a self, name: &str) -> Result<Option<String>> {
+        // The copy is not written back, and it is empty if there is no such query
+        let entry = self.get_copy(saved_query_id(name)?)?;
+
+        if entry.get_header().read("query")?.is_none() {
+            return Ok(None)
+        }
+
+        entry
+            .get_header()
+            .read_string("query.expression")?
+            .ok_or_else(|| FE::from_kind(FEK::SavedQueryMalformed(String::from(name))))
+            .map(Some)
+    }
+
+    fn get_saved_query(&'a self, name: &str) -> Result<Option<Query>> {
+        match self.get_saved_query_expression(name)? {
+            Some(expr) => Query::parse(&expr).map(Some),
+            None       => Ok(None),
+        }
+    }
+
+    fn delete_saved_query(&'a self, name: &str) -> Result<()> {
+        self.delete(saved_query_id(name)?).map_err(FE::from)
+    }
+
+    fn all_saved_query_names(&'a self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+
+        for id in self.entries()?.without_store() {
+            let id = id?;
+            if !is_saved_query_id(&id) {
+                continue;
+            }
+
+            if let Some(name) = id.local().file_name().and_then(|n| n.to_str()) {
+                names.push(String::from(name));
+            }
+        }
+
+        names.sort();
+        Ok(names)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use toml::Value;
+    use toml_query::insert::TomlValueInsertExt;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+
+    use super::*;
+
+    fn get_store() -> Store {
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    #[test]
+    fn test_save_and_get() {
+        let store = get_store();
+        let _ = store.save_query("work", "tag(\"work\")").unwrap();
+
+        let expr = store.get_saved_query_expression("work").unwrap();
+        assert_eq!(Some(String::from("tag(\"work\")")), expr);
+        assert!(store.get_saved_query_expression("private").unwrap().is_none());
+        assert_eq!(vec![String::from("work")], store.all_saved_query_names().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_names_fail() {
+        let store = get_store();
+
+        for name in &["../work", "a/b", "..", ".", "/work", ""] {
+            assert!(store.save_query(name, "tag(\"work\")").is_err(), "Saved '{}'", name);
+            assert!(store.get_saved_query_expression(name).is_err(), "Read '{}'", name);
+            assert!(store.delete_saved_query(name).is_err(), "Deleted '{}'", name);
+        }
+
+        assert!(store.entries().unwrap().count() == 0);
+    }
+
+    #[test]
+    fn test_save_invalid_query_fails() {
+        let store = get_store();
+        assert!(store.save_query("broken", "tag(\"work\"").is_err());
+        assert!(store.all_saved_query_names().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_saved_query_matches() {
+        let store = get_store();
+        {
+            let mut e = store.create(PathBuf::from("notes/a")).unwrap();
+            let tags  = Value::Array(vec![Value::String(String::from("work"))]);
+            e.get_header_mut().insert("tag.values", tags).unwrap();
+        }
+        let _ = store.create(PathBuf::from("notes/b")).unwrap();
+        let _ = store.save_query("work", "tag(\"work\")").unwrap();
+
+        let ids : Vec<String> = store
+            .get_saved_query("work")
+            .unwrap()
+            .unwrap()
+            .matching_ids(&store)
+            .unwrap()
+            .into_iter()
+            .map(|id| format!("{}", id.without_base()))
+            .collect();
+
+        assert_eq!(vec![String::from("notes/a")], ids);
+    }
+
+    #[test]
+    fn test_saved_queries_never_match() {
+        let store = get_store();
+        let _ = store.create(PathBuf::from("notes/a")).unwrap();
+        let _ = store.save_query("everything", "not tag(\"work\")").unwrap();
+
+        let query = store.get_saved_query("everything").unwrap().unwrap();
+        let ids : Vec<String> = query
+            .matching_ids(&store)
+            .unwrap()
+            .into_iter()
+            .map(|id| format!("{}", id.without_base()))
+            .collect();
+
+        assert_eq!(vec![String::from("notes/a")], ids);
+
+        let entry = store.get(PathBuf::from("query/everything")).unwrap().unwrap();
+        assert!(!query.matches(&entry).unwrap());
+    }
+
+    #[test]
+    fn test_delete() {
+        let store = get_store();
+        let _ = store.save_query("work", "tag(\"work\")").unwrap();
+        let _ = store.delete_saved_query("work").unwrap();
+        assert!(store.get_saved_query("work").unwrap().is_none());
+    }
+
+}
@@ -0,0 +1,67 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+error_chain! {
+    types {
+        FrontMatterError, FrontMatterErrorKind, ResultExt, Result;
+    }
+
+    links {
+        StoreError(::libimagstore::error::StoreError, ::libimagstore::error::StoreErrorKind);
+        TagError(::libimagentrytag::error::TagError, ::libimagentrytag::error::TagErrorKind);
+        DateError(::libimagentrydatetime::error::DateError, ::libimagentrydatetime::error::DateErrorKind);
+    }
+
+    foreign_links {
+        Io(::std::io::Error);
+        YamlError(::serde_yaml::Error);
+        TomlDeError(::toml::de::Error);
+        TomlSerError(::toml::ser::Error);
+        TomlQueryError(::toml_query::error::Error);
+    }
+
+    errors {
+        UnterminatedFrontMatter {
+            description("Front matter is not terminated")
+            display("Front matter is not terminated")
+        }
+
+        FrontMatterNotATable {
+            description("Front matter is not a key-value mapping")
+            display("Front matter is not a key-value mapping")
+        }
+
+        UnsupportedYamlValue(key: String) {
+            description("Unsupported value in YAML front matter")
+            display("The YAML value of '{}' cannot be represented in imag", key)
+        }
+
+        DateParseError(s: String) {
+            description("Failed to parse date")
+            display("Failed to parse date: '{}'", s)
+        }
+
+        NoEntryName(p: ::std::path::PathBuf) {
+            description("Cannot find name for entry")
+            display("Cannot find a name for the entry imported from {}", p.display())
+        }
+    }
+}
+
@@ -0,0 +1,149 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Export of entries to markdown files with front matter
+//!
+//! This is the inverse of the `import` module: The `frontmatter` header table, the tags and the
+//! date of an entry are written as front matter. If there is no title, the last component of the
+//! entry id is used.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+use libimagstore::store::Entry;
+use libimagentrytag::tagable::Tagable;
+use libimagentrydatetime::datetime::EntryDate;
+
+use error::FrontMatterErrorKind as FMEK;
+use error::FrontMatterError as FME;
+use error::Result;
+use frontmatter::Document;
+use frontmatter::FrontMatterFormat;
+use import::FRONTMATTER_HEADER_LOCATION;
+
+/// Build the front matter document for an entry
+pub fn entry_to_document(entry: &Entry) -> Result<Document> {
+    let mut header = match entry.get_header().read(FRONTMATTER_HEADER_LOCATION)? {
+        Some(&Value::Table(ref t)) => t.clone(),
+        _                          => ::toml::value::Table::new(),
+    };
+
+    if !header.contains_key("title") {
+        let name = entry_file_name(entry)?;
+        let _    = header.insert(String::from("title"), Value::String(name));
+    }
+
+    let tags = entry.get_tags()?;
+    if !tags.is_empty() {
+        let tags = tags.into_iter().map(Value::String).collect();
+        let _    = header.insert(String::from("tags"), Value::Array(tags));
+    }
+
+    let has_date = entry.get_header().read("datetime.value")?.is_some();
+    if has_date {
+        let date = entry.read_date()?.format("%Y-%m-%dT%H:%M:%S").to_string();
+        let _    = header.insert(String::from("date"), Value::String(date));
+    }
+
+    Ok(Document {
+        header: header,
+        content: entry.get_content().clone(),
+    })
+}
+
+/// Render an entry as markdown text with front matter in the passed format
+pub fn export_entry(entry: &Entry, format: FrontMatterFormat) -> Result<String> {
+    entry_to_document(entry)?.render(format)
+}
+
+/// Export an entry to a markdown file in `dir`
+///
+/// The file is named after the last component of the entry id, with `.md` appended.
+///
+/// # Return value
+///
+/// The path of the written file.
+pub fn export_entry_to_dir(entry: &Entry, dir: &Path, format: FrontMatterFormat) -> Result<PathBuf> {
+    let text = export_entry(entry, format)?;
+    let path = dir.join(format!("{}.md", entry_file_name(entry)?));
+
+    let _ = File::create(&path)?.write_all(text.as_bytes())?;
+    Ok(path)
+}
+
+fn entry_file_name(entry: &Entry) -> Result<String> {
+    entry
+        .get_location()
+        .local()
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(String::from)
+        .ok_or_else(|| FME::from_kind(FMEK::NoEntryName(entry.get_location().local().clone())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use libimagstore::store::Store;
+
+    use import::import_str;
+
+    fn get_store() -> Store {
+        use libimagstore::file_abstraction::InMemoryFileAbstraction;
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    #[test]
+    fn test_export_imported_entry() {
+        let store = get_store();
+        let text  = "---\ntitle: Hello World\ntags: [foo]\ndate: 2018-03-04\n---\nContent\n";
+        let entry = import_str(&store, &PathBuf::from("notes"), "fallback", text).unwrap();
+
+        let exported = export_entry(&entry, FrontMatterFormat::Yaml).unwrap();
+        let doc      = Document::parse(&exported).unwrap();
+
+        assert_eq!(Some(&Value::String(String::from("Hello World"))), doc.header.get("title"));
+        assert_eq!(Some(&Value::Array(vec![Value::String(String::from("foo"))])), doc.header.get("tags"));
+        assert_eq!(Some(&Value::String(String::from("2018-03-04T00:00:00"))), doc.header.get("date"));
+        assert_eq!("Content\n", doc.content);
+    }
+
+    #[test]
+    fn test_export_plain_entry() {
+        let store     = get_store();
+        let mut entry = store.create(PathBuf::from("notes/plain")).unwrap();
+        *entry.get_content_mut() = String::from("Content");
+
+        let doc = entry_to_document(&entry).unwrap();
+        assert_eq!(Some(&Value::String(String::from("plain"))), doc.header.get("title"));
+        assert_eq!(1, doc.header.len());
+    }
+
+}
+
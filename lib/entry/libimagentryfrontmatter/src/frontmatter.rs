@@ -0,0 +1,273 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Parsing and rendering of markdown files with front matter
+//!
+//! Front matter is either YAML, delimited by `---` lines (as used by Jekyll, Hugo and Obsidian),
+//! or TOML, delimited by `+++` lines (as used by Hugo).
+
+use std::str::FromStr;
+
+use toml::Value;
+use toml::value::Table;
+use serde_yaml::Value as YamlValue;
+use serde_yaml::Mapping;
+
+use error::FrontMatterErrorKind as FMEK;
+use error::FrontMatterError as FME;
+use error::Result;
+
+/// The format of front matter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontMatterFormat {
+    Yaml,
+    Toml,
+}
+
+impl FrontMatterFormat {
+
+    fn delimiter(&self) -> &'static str {
+        match *self {
+            FrontMatterFormat::Yaml => "---",
+            FrontMatterFormat::Toml => "+++",
+        }
+    }
+
+}
+
+impl FromStr for FrontMatterFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "yaml" | "yml" => Ok(FrontMatterFormat::Yaml),
+            "toml"         => Ok(FrontMatterFormat::Toml),
+            other          => Err(format!("Unknown front matter format: '{}'", other)),
+        }
+    }
+}
+
+/// A markdown document with its front matter
+///
+/// YAML front matter is converted to TOML values when parsing, so both formats are represented
+/// the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub header: Table,
+    pub content: String,
+}
+
+impl Document {
+
+    /// Parse a document
+    ///
+    /// A document without front matter results in an empty header.
+    pub fn parse(text: &str) -> Result<Document> {
+        let format = match text.lines().next().map(str::trim_right) {
+            Some("---") => FrontMatterFormat::Yaml,
+            Some("+++") => FrontMatterFormat::Toml,
+            _           => return Ok(Document { header: Table::new(), content: String::from(text) }),
+        };
+
+        let delimiter = format.delimiter();
+        let mut lines = text.lines().skip(1);
+        let mut front = vec![];
+
+        loop {
+            match lines.next() {
+                Some(line) if line.trim_right() == delimiter => break,
+                Some(line) if format == FrontMatterFormat::Yaml && line.trim_right() == "..." => break,
+                Some(line) => front.push(line),
+                None       => return Err(FME::from_kind(FMEK::UnterminatedFrontMatter)),
+            }
+        }
+
+        let mut content = lines.collect::<Vec<_>>().join("\n");
+        if text.ends_with('\n') && !content.is_empty() {
+            content.push('\n');
+        }
+
+        let front  = front.join("\n");
+        let header = match format {
+            FrontMatterFormat::Toml => match ::toml::de::from_str::<Value>(&front)? {
+                Value::Table(t) => t,
+                _               => return Err(FME::from_kind(FMEK::FrontMatterNotATable)),
+            },
+            FrontMatterFormat::Yaml => if front.trim().is_empty() {
+                Table::new()
+            } else {
+                match yaml_to_toml("", ::serde_yaml::from_str(&front)?)? {
+                    Some(Value::Table(t)) => t,
+                    _                     => return Err(FME::from_kind(FMEK::FrontMatterNotATable)),
+                }
+            },
+        };
+
+        Ok(Document { header: header, content: content })
+    }
+
+    /// Render the document with front matter in the passed format
+    ///
+    /// If the header is empty, no front matter is rendered at all.
+    pub fn render(&self, format: FrontMatterFormat) -> Result<String> {
+        if self.header.is_empty() {
+            return Ok(self.content.clone())
+        }
+
+        let front = match format {
+            FrontMatterFormat::Toml => ::toml::ser::to_string(&Value::Table(self.header.clone()))?,
+            FrontMatterFormat::Yaml => {
+                let yaml = ::serde_yaml::to_string(&toml_to_yaml(Value::Table(self.header.clone())))?;
+
+                // serde_yaml starts the document with a "---" line, which we write ourselves
+                if yaml.starts_with("---\n") {
+                    String::from(&yaml[4..])
+                } else {
+                    yaml
+                }
+            },
+        };
+
+        let delimiter = format.delimiter();
+        let newline   = if front.ends_with('\n') { "" } else { "\n" };
+
+        Ok(format!("{}\n{}{}{}\n{}", delimiter, front, newline, delimiter, self.content))
+    }
+
+}
+
+/// Convert a YAML value to a TOML value
+///
+/// TOML has no null value, so null values (and null values in sequences and mappings) are
+/// dropped. Mapping keys which are not strings are converted to strings if they are numbers or
+/// booleans.
+fn yaml_to_toml(key: &str, v: YamlValue) -> Result<Option<Value>> {
+    match v {
+        YamlValue::Null      => Ok(None),
+        YamlValue::Bool(b)   => Ok(Some(Value::Boolean(b))),
+        YamlValue::String(s) => Ok(Some(Value::String(s))),
+        YamlValue::Number(n) => if let Some(i) = n.as_i64() {
+            Ok(Some(Value::Integer(i)))
+        } else if let Some(f) = n.as_f64() {
+            Ok(Some(Value::Float(f)))
+        } else {
+            Err(FME::from_kind(FMEK::UnsupportedYamlValue(String::from(key))))
+        },
+        YamlValue::Sequence(seq) => {
+            let mut array = vec![];
+            for elem in seq {
+                if let Some(v) = yaml_to_toml(key, elem)? {
+                    array.push(v);
+                }
+            }
+            Ok(Some(Value::Array(array)))
+        },
+        YamlValue::Mapping(map) => {
+            let mut table = Table::new();
+            for (k, v) in map {
+                let k = match k {
+                    YamlValue::String(s) => s,
+                    YamlValue::Number(n) => format!("{}", n),
+                    YamlValue::Bool(b)   => format!("{}", b),
+                    _ => return Err(FME::from_kind(FMEK::UnsupportedYamlValue(String::from(key)))),
+                };
+
+                if let Some(v) = yaml_to_toml(&k, v)? {
+                    table.insert(k, v);
+                }
+            }
+            Ok(Some(Value::Table(table)))
+        },
+    }
+}
+
+/// Convert a TOML value to a YAML value
+///
+/// Datetimes are converted to strings.
+fn toml_to_yaml(v: Value) -> YamlValue {
+    match v {
+        Value::String(s)   => YamlValue::String(s),
+        Value::Integer(i)  => YamlValue::Number(i.into()),
+        Value::Float(f)    => YamlValue::Number(f.into()),
+        Value::Boolean(b)  => YamlValue::Bool(b),
+        Value::Datetime(d) => YamlValue::String(d.to_string()),
+        Value::Array(a)    => YamlValue::Sequence(a.into_iter().map(toml_to_yaml).collect()),
+        Value::Table(t)    => {
+            let mut map = Mapping::new();
+            for (k, v) in t {
+                map.insert(YamlValue::String(k), toml_to_yaml(v));
+            }
+            YamlValue::Mapping(map)
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_yaml() {
+        let text = "---\ntitle: Hello World\ntags:\n  - foo\n  - bar\ndraft: null\n---\nContent\n";
+        let doc  = Document::parse(text).unwrap();
+
+        assert_eq!(Some(&Value::String(String::from("Hello World"))), doc.header.get("title"));
+        assert_eq!(Some(&Value::Array(vec![
+            Value::String(String::from("foo")),
+            Value::String(String::from("bar")),
+        ])), doc.header.get("tags"));
+        assert!(doc.header.get("draft").is_none());
+        assert_eq!("Content\n", doc.content);
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let text = "+++\ntitle = \"Hello World\"\n+++\nContent";
+        let doc  = Document::parse(text).unwrap();
+
+        assert_eq!(Some(&Value::String(String::from("Hello World"))), doc.header.get("title"));
+        assert_eq!("Content", doc.content);
+    }
+
+    #[test]
+    fn test_parse_without_front_matter() {
+        let doc = Document::parse("# Heading\n").unwrap();
+        assert!(doc.header.is_empty());
+        assert_eq!("# Heading\n", doc.content);
+    }
+
+    #[test]
+    fn test_parse_unterminated() {
+        assert!(Document::parse("---\ntitle: foo\n").is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let text = "---\ntitle: Hello World\ntags:\n  - foo\n---\nContent\n";
+        let doc  = Document::parse(text).unwrap();
+
+        for format in &[FrontMatterFormat::Yaml, FrontMatterFormat::Toml] {
+            let rendered = doc.render(*format).unwrap();
+            assert_eq!(doc, Document::parse(&rendered).unwrap());
+        }
+    }
+
+}
+
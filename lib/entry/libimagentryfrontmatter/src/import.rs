@@ -0,0 +1,293 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Import of markdown files with front matter into the store
+//!
+//! The following front matter fields are mapped onto imag functionality:
+//!
+//! * `title` is used to build the name of the entry (see `libimagutil::slug::slugify()`)
+//! * `tags` (a list or a whitespace/comma separated string) are set via `libimagentrytag`. Tags
+//!   are normalized with `libimagutil::tag::normalize_tag()`, which warns about altered tags.
+//! * `date` is set via `libimagentrydatetime`, with its UTC offset if it has one
+//!
+//! All other fields, including `title`, are kept in the `frontmatter` table of the entry header,
+//! so they can be exported again.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
+
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+use libimagentrytag::tagable::Tagable;
+use libimagentrytag::tag::Tag;
+use libimagentrydatetime::datetime::EntryDate;
+use libimagentrydatetime::timezone::ZonedDateTime;
+use libimagutil::slug::slugify;
+use libimagutil::tag::normalize_tag;
+
+use error::FrontMatterErrorKind as FMEK;
+use error::FrontMatterError as FME;
+use error::Result;
+use frontmatter::Document;
+
+/// The header location where unmapped front matter fields are stored
+pub const FRONTMATTER_HEADER_LOCATION : &'static str = "frontmatter";
+
+/// Import a markdown text with front matter as a new entry in `collection`
+///
+/// The `name` is used as entry name if the front matter does not contain a title.
+pub fn import_str<'a>(store: &'a Store, collection: &Path, name: &str, text: &str)
+    -> Result<FileLockEntry<'a>>
+{
+    let mut doc = Document::parse(text)?;
+
+    let name = doc
+        .header
+        .get("title")
+        .and_then(Value::as_str)
+        .map(slugify)
+        .and_then(|n| if n.is_empty() { None } else { Some(n) })
+        .unwrap_or_else(|| String::from(name));
+
+    let tags = match doc.header.remove("tags") {
+        Some(v) => Some(tags_from_value(&v)),
+        None    => None,
+    };
+
+    let date = match doc.header.remove("date") {
+        Some(v) => Some(date_from_value(&v)?),
+        None    => None,
+    };
+
+    let id        = StoreId::new(Some(store.path().clone()), collection.join(name))?;
+    let mut entry = store.create(id)?;
+
+    if let Some(tags) = tags {
+        let _ = entry.set_tags(&tags)?;
+    }
+
+    match date {
+        Some(FrontMatterDate::Local(date)) => {
+            let _ = entry.set_date(date)?;
+        },
+        Some(FrontMatterDate::WithOffset(date)) => {
+            let _ = entry.set_date_zoned(&ZonedDateTime::new(date, None))?;
+        },
+        None => {},
+    }
+
+    if !doc.header.is_empty() {
+        let _ = entry
+            .get_header_mut()
+            .insert(FRONTMATTER_HEADER_LOCATION, Value::Table(doc.header))?;
+    }
+
+    *entry.get_content_mut() = doc.content;
+    Ok(entry)
+}
+
+/// Import a markdown file with front matter as a new entry in `collection`
+///
+/// If the front matter does not contain a title, the file stem is used as entry name.
+pub fn import_file<'a>(store: &'a Store, collection: &Path, path: &Path) -> Result<FileLockEntry<'a>> {
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(String::from)
+        .ok_or_else(|| FME::from_kind(FMEK::NoEntryName(path.to_path_buf())))?;
+
+    let mut text = String::new();
+    let _        = File::open(path)?.read_to_string(&mut text)?;
+
+    import_str(store, collection, &name, &text)
+}
+
+/// Import all markdown files (`*.md` and `*.markdown`) of a directory into `collection`
+///
+/// Subdirectories are not traversed.
+pub fn import_directory(store: &Store, collection: &Path, dir: &Path) -> Result<Vec<StoreId>> {
+    let mut paths = vec![];
+    for entry in dir.read_dir()? {
+        let path = entry?.path();
+        let is_markdown = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e == "md" || e == "markdown")
+            .unwrap_or(false);
+
+        if path.is_file() && is_markdown {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths.into_iter()
+        .map(|path: PathBuf| import_file(store, collection, &path).map(|e| e.get_location().clone()))
+        .collect()
+}
+
+fn tags_from_value(v: &Value) -> Vec<Tag> {
+    let raw : Vec<String> = match *v {
+        Value::String(ref s) => s
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .map(String::from)
+            .collect(),
+        Value::Array(ref a) => a
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => vec![],
+    };
+
+    raw.iter().filter_map(|t| normalize_tag(t)).collect()
+}
+
+/// A date of the front matter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontMatterDate {
+    /// A date without UTC offset, which is local time
+    Local(NaiveDateTime),
+
+    /// A date with UTC offset
+    WithOffset(DateTime<FixedOffset>),
+}
+
+fn date_from_value(v: &Value) -> Result<FrontMatterDate> {
+    let s = match *v {
+        Value::String(ref s)   => s.clone(),
+        Value::Datetime(ref d) => d.to_string(),
+        _ => return Err(FME::from_kind(FMEK::DateParseError(format!("{}", v)))),
+    };
+
+    parse_date(&s)
+}
+
+/// Parse the date formats which are commonly used in front matter
+///
+/// Dates with UTC offset keep it, so they are stored as the point in time they describe.
+pub fn parse_date(s: &str) -> Result<FrontMatterDate> {
+    let s = s.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(FrontMatterDate::WithOffset(dt))
+    }
+
+    if let Ok(dt) = DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S %z") {
+        return Ok(FrontMatterDate::WithOffset(dt))
+    }
+
+    for fmt in &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(FrontMatterDate::Local(ndt))
+        }
+    }
+
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|d| FrontMatterDate::Local(d.and_hms(0, 0, 0)))
+        .map_err(|_| FME::from_kind(FMEK::DateParseError(String::from(s))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use toml_query::read::TomlValueReadTypeExt;
+
+    fn get_store() -> Store {
+        use libimagstore::file_abstraction::InMemoryFileAbstraction;
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    #[test]
+    fn test_tags_from_value() {
+        let tags = tags_from_value(&Value::String(String::from("Foo, c++ Straße 2018")));
+        assert_eq!(vec![String::from("foo"), String::from("c"), String::from("strae")], tags);
+        assert!(tags.iter().all(|t| ::libimagentrytag::tag::is_tag_str(t).is_ok()));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let exp = FrontMatterDate::Local(NaiveDate::from_ymd(2018, 3, 4).and_hms(12, 30, 0));
+        assert_eq!(exp, parse_date("2018-03-04 12:30:00").unwrap());
+        assert_eq!(exp, parse_date("2018-03-04T12:30:00").unwrap());
+        assert_eq!(FrontMatterDate::Local(NaiveDate::from_ymd(2018, 3, 4).and_hms(0, 0, 0)),
+                   parse_date("2018-03-04").unwrap());
+        assert!(parse_date("yesterday").is_err());
+
+        for s in &["2018-03-04 12:30:00 +0100", "2018-03-04T12:30:00+01:00"] {
+            match parse_date(s).unwrap() {
+                FrontMatterDate::WithOffset(dt) => {
+                    assert_eq!(NaiveDate::from_ymd(2018, 3, 4).and_hms(11, 30, 0), dt.naive_utc())
+                },
+                other => assert!(false, "Expected a date with offset, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_str() {
+        let store = get_store();
+        let text  = "---\ntitle: Hello World\ntags: [Foo, bar]\ndate: 2018-03-04\nlayout: post\n---\nContent\n";
+
+        let entry = import_str(&store, &PathBuf::from("notes"), "fallback", text).unwrap();
+
+        assert_eq!("notes/hello-world", entry.get_location().to_str().unwrap());
+        assert_eq!(vec![String::from("foo"), String::from("bar")], entry.get_tags().unwrap());
+        assert_eq!(NaiveDate::from_ymd(2018, 3, 4).and_hms(0, 0, 0), entry.read_date().unwrap());
+        assert_eq!(Some(String::from("post")),
+                   entry.get_header().read_string("frontmatter.layout").unwrap());
+        assert_eq!("Content\n", entry.get_content());
+    }
+
+    #[test]
+    fn test_import_str_with_offset() {
+        use libimagentrydatetime::timezone::Tz;
+
+        let store = get_store();
+        let text  = "---\ndate: \"2018-03-04 12:30:00 +0100\"\n---\nContent\n";
+        let entry = import_str(&store, &PathBuf::from("notes"), "offset", text).unwrap();
+
+        assert_eq!(NaiveDate::from_ymd(2018, 3, 4).and_hms(11, 30, 0),
+                   entry.read_date_zoned(Tz::UTC).unwrap().naive_utc());
+    }
+
+    #[test]
+    fn test_import_str_without_title() {
+        let store = get_store();
+        let entry = import_str(&store, &PathBuf::from("notes"), "fallback", "Content").unwrap();
+
+        assert_eq!("notes/fallback", entry.get_location().to_str().unwrap());
+    }
+
+}
+
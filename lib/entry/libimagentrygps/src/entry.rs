@@ -33,6 +33,12 @@ pub trait GPSEntry {
     fn set_coordinates(&mut self, c: Coordinates) -> Result<()>;
     fn get_coordinates(&self) -> Result<Option<Coordinates>>;
 
+    /// Set the position, keeping the decimal degrees as well as altitude and accuracy
+    fn set_position(&mut self, p: Position) -> Result<()>;
+
+    /// Get the position, which can also be read from plain coordinates
+    fn get_position(&self) -> Result<Option<Position>>;
+
     /// Remove the coordinates from the entry
     ///
     /// # Returns
@@ -70,6 +76,20 @@ impl GPSEntry for Entry {
         }
     }
 
+    fn set_position(&mut self, p: Position) -> Result<()> {
+        self.get_header_mut()
+            .insert("gps.coordinates", p.into())
+            .map(|_| ())
+            .chain_err(|| GPSEK::HeaderWriteError)
+    }
+
+    fn get_position(&self) -> Result<Option<Position>> {
+        match self.get_header().read("gps.coordinates").chain_err(|| GPSEK::HeaderWriteError)? {
+            Some(hdr) => Position::from_value(hdr).map(Some),
+            None      => Ok(None),
+        }
+    }
+
     fn remove_coordinates(&mut self) -> Result<Option<Result<Coordinates>>> {
         let coordinates = self.get_coordinates();
 
@@ -77,9 +97,13 @@ impl GPSEntry for Entry {
             "gps.coordinates.latitude.degree",
             "gps.coordinates.latitude.minutes",
             "gps.coordinates.latitude.seconds",
+            "gps.coordinates.latitude.decimal",
             "gps.coordinates.longitude.degree",
             "gps.coordinates.longitude.minutes",
             "gps.coordinates.longitude.seconds",
+            "gps.coordinates.longitude.decimal",
+            "gps.coordinates.altitude",
+            "gps.coordinates.accuracy",
             "gps.coordinates.latitude",
             "gps.coordinates.longitude",
             "gps.coordinates",
@@ -167,5 +191,19 @@ mod tests {
         assert_eq!(0, coordinates.latitude.minutes);
         assert_eq!(0, coordinates.latitude.seconds);
     }
-}
 
+    #[test]
+    fn test_setget_position() {
+        setup_logging();
+
+        let store = get_store();
+
+        let mut entry = store.create(PathBuf::from("test_setget_position")).unwrap();
+
+        let position = Position::new(11.575382, 48.137154).with_altitude(519.0);
+        assert!(entry.set_position(position.clone()).is_ok());
+
+        assert_eq!(Some(position.clone()), entry.get_position().unwrap());
+        assert_eq!(Some(position.coordinates()), entry.get_coordinates().unwrap());
+    }
+}
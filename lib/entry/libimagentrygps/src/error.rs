@@ -22,6 +22,10 @@ error_chain! {
         GPSError, GPSErrorKind, ResultExt, Result;
     }
 
+    foreign_links {
+        XmlError(::xml::reader::Error);
+    }
+
     errors {
         StoreReadError {
             description("Store read error")
@@ -82,6 +86,31 @@ error_chain! {
             description("Cannot convert number to fit into variable")
             display("Cannot convert number to fit into variable")
         }
+
+        DistanceParseError(s: String) {
+            description("Cannot parse distance")
+            display("Cannot parse distance: '{}'", s)
+        }
+
+        PointParseError(s: String) {
+            description("Cannot parse point")
+            display("Cannot parse point, expected '<latitude>,<longitude>': '{}'", s)
+        }
+
+        TimeParseError(s: String) {
+            description("Cannot parse time")
+            display("Cannot parse time: '{}'", s)
+        }
+
+        GpxAttributeMissing(attr: String) {
+            description("Attribute missing in GPX point")
+            display("Attribute '{}' missing in GPX point", attr)
+        }
+
+        KmlCoordinatesError(s: String) {
+            description("Cannot parse KML coordinates")
+            display("Cannot parse KML coordinates: '{}'", s)
+        }
     }
 }
 
@@ -0,0 +1,115 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Filtering entries by their location
+
+use filters::failable::filter::FailableFilter;
+
+use libimagstore::store::Entry;
+
+use entry::GPSEntry;
+use error::GPSError as GPSE;
+use error::GPSErrorKind as GPSEK;
+use error::Result;
+use types::Position;
+
+/// Filter for entries which have coordinates within a radius around a point
+///
+/// Entries without coordinates do not match.
+pub struct WithinRadius {
+    center: Position,
+    radius: f64,
+}
+
+impl WithinRadius {
+
+    /// Create the filter, the radius is in meters
+    pub fn new(center: Position, radius: f64) -> WithinRadius {
+        WithinRadius {
+            center: center,
+            radius: radius,
+        }
+    }
+
+}
+
+impl FailableFilter<Entry> for WithinRadius {
+    type Error = GPSE;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        Ok(e.get_position()?
+           .map(|p| p.distance_to(&self.center) <= self.radius)
+           .unwrap_or(false))
+    }
+
+}
+
+/// Parse a distance like "5km", "500m" or "500" (meters) into meters
+pub fn parse_distance(s: &str) -> Result<f64> {
+    let s = s.trim();
+    let (number, factor) = if s.ends_with("km") {
+        (&s[..(s.len() - 2)], 1000.0)
+    } else if s.ends_with('m') {
+        (&s[..(s.len() - 1)], 1.0)
+    } else {
+        (s, 1.0)
+    };
+
+    number
+        .trim()
+        .parse::<f64>()
+        .map(|n| n * factor)
+        .map_err(|_| GPSE::from_kind(GPSEK::DistanceParseError(String::from(s))))
+}
+
+/// Parse a point like "48.137154,11.575382" (latitude first, as in most map applications) into a
+/// Position
+pub fn parse_point(s: &str) -> Result<Position> {
+    let mut split = s.split(',').map(str::trim).map(str::parse::<f64>);
+
+    match (split.next(), split.next(), split.next()) {
+        (Some(Ok(lat)), Some(Ok(long)), None) => Ok(Position::new(long, lat)),
+        _ => Err(GPSE::from_kind(GPSEK::PointParseError(String::from(s)))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_distance() {
+        assert_eq!(5000.0, parse_distance("5km").unwrap());
+        assert_eq!(500.0, parse_distance("500m").unwrap());
+        assert_eq!(500.0, parse_distance("500").unwrap());
+        assert!(parse_distance("far").is_err());
+    }
+
+    #[test]
+    fn test_parse_point() {
+        let p = parse_point("48.137154, 11.575382").unwrap();
+        assert_eq!(48.137154, p.latitude);
+        assert_eq!(11.575382, p.longitude);
+
+        assert!(parse_point("48.137154").is_err());
+    }
+
+}
+
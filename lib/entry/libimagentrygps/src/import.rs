@@ -0,0 +1,211 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Import of track points from GPX and KML files
+
+use std::io::Read;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use xml::reader::EventReader;
+use xml::reader::XmlEvent;
+
+use error::GPSError as GPSE;
+use error::GPSErrorKind as GPSEK;
+use error::Result;
+use types::Position;
+
+/// A point of a track, with the time it was recorded, if known
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackPoint {
+    pub position: Position,
+    pub time: Option<DateTime<FixedOffset>>,
+}
+
+/// Read all track points (`trkpt`), route points (`rtept`) and waypoints (`wpt`) from a GPX
+/// document
+pub fn read_gpx<R: Read>(r: R) -> Result<Vec<TrackPoint>> {
+    let mut points  = vec![];
+    let mut current : Option<TrackPoint> = None;
+    let mut text    = String::new();
+
+    for event in EventReader::new(r) {
+        match event? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                text.clear();
+
+                let is_point = ["trkpt", "rtept", "wpt"].contains(&name.local_name.as_str());
+                if is_point {
+                    let attr = |n: &str| -> Result<f64> {
+                        attributes
+                            .iter()
+                            .find(|a| a.name.local_name == n)
+                            .ok_or_else(|| GPSE::from_kind(GPSEK::GpxAttributeMissing(String::from(n))))
+                            .and_then(|a| parse_float(&a.value))
+                    };
+
+                    let position = Position::new(attr("lon")?, attr("lat")?);
+                    current = Some(TrackPoint { position: position, time: None });
+                }
+            },
+
+            XmlEvent::Characters(s) => text.push_str(&s),
+
+            XmlEvent::EndElement { name } => {
+                match (name.local_name.as_str(), current.as_mut()) {
+                    ("ele", Some(point)) => {
+                        point.position.altitude = Some(parse_float(&text)?);
+                    },
+                    ("time", Some(point)) => {
+                        point.time = Some(parse_time(&text)?);
+                    },
+                    ("trkpt", _) | ("rtept", _) | ("wpt", _) => {
+                        if let Some(point) = current.take() {
+                            points.push(point);
+                        }
+                    },
+                    _ => {},
+                }
+            },
+
+            _ => {},
+        }
+    }
+
+    Ok(points)
+}
+
+/// Read all coordinates from a KML document
+///
+/// KML `coordinates` elements contain whitespace separated `long,lat[,alt]` tuples. The time of a
+/// point is read from the `when` element (as in `<TimeStamp><when>...</when></TimeStamp>`) of its
+/// `Placemark`, if it precedes the coordinates.
+pub fn read_kml<R: Read>(r: R) -> Result<Vec<TrackPoint>> {
+    let mut points = vec![];
+    let mut text   = String::new();
+    let mut time   = None;
+
+    for event in EventReader::new(r) {
+        match event? {
+            XmlEvent::StartElement { name, .. } => {
+                if name.local_name == "Placemark" {
+                    time = None;
+                }
+                text.clear();
+            },
+
+            XmlEvent::Characters(s) => text.push_str(&s),
+
+            XmlEvent::EndElement { name } => {
+                match name.local_name.as_str() {
+                    "when" => time = Some(parse_time(&text)?),
+                    "coordinates" => {
+                        for tuple in text.split_whitespace() {
+                            let mut values = tuple.split(',');
+                            let long = values.next().ok_or_else(|| kml_error(tuple)).and_then(parse_float)?;
+                            let lat  = values.next().ok_or_else(|| kml_error(tuple)).and_then(parse_float)?;
+                            let alt  = values.next().map(parse_float);
+
+                            let mut position = Position::new(long, lat);
+                            if let Some(alt) = alt {
+                                position.altitude = Some(alt?);
+                            }
+
+                            points.push(TrackPoint { position: position, time: time });
+                        }
+                    },
+                    _ => {},
+                }
+            },
+
+            _ => {},
+        }
+    }
+
+    Ok(points)
+}
+
+fn kml_error(tuple: &str) -> GPSE {
+    GPSE::from_kind(GPSEK::KmlCoordinatesError(String::from(tuple)))
+}
+
+fn parse_float(s: &str) -> Result<f64> {
+    s.trim()
+        .parse::<f64>()
+        .map_err(|_| GPSE::from_kind(GPSEK::NumberConversionError))
+}
+
+fn parse_time(s: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s.trim())
+        .map_err(|_| GPSE::from_kind(GPSEK::TimeParseError(String::from(s))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_gpx() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk><trkseg>
+    <trkpt lat="48.137154" lon="11.575382">
+      <ele>519.5</ele>
+      <time>2018-03-04T12:30:00Z</time>
+    </trkpt>
+    <trkpt lat="48.1" lon="11.5"></trkpt>
+  </trkseg></trk>
+</gpx>"#;
+
+        let points = read_gpx(gpx.as_bytes()).unwrap();
+        assert_eq!(2, points.len());
+
+        assert_eq!(48.137154, points[0].position.latitude);
+        assert_eq!(11.575382, points[0].position.longitude);
+        assert_eq!(Some(519.5), points[0].position.altitude);
+        assert!(points[0].time.is_some());
+
+        assert_eq!(None, points[1].position.altitude);
+        assert!(points[1].time.is_none());
+    }
+
+    #[test]
+    fn test_read_kml() {
+        let kml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Placemark>
+    <TimeStamp><when>2018-03-04T12:30:00+01:00</when></TimeStamp>
+    <Point><coordinates>11.575382,48.137154,519</coordinates></Point>
+  </Placemark>
+  <Placemark>
+    <LineString><coordinates>11.5,48.1 11.6,48.2</coordinates></LineString>
+  </Placemark>
+</kml>"#;
+
+        let points = read_kml(kml.as_bytes()).unwrap();
+        assert_eq!(3, points.len());
+        assert_eq!(Some(519.0), points[0].position.altitude);
+        assert!(points[0].time.is_some());
+        assert!(points[1].time.is_none());
+        assert_eq!(48.2, points[2].position.latitude);
+    }
+
+}
+
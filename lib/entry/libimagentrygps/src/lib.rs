@@ -35,6 +35,9 @@
 
 extern crate toml;
 extern crate toml_query;
+extern crate chrono;
+extern crate filters;
+extern crate xml;
 #[macro_use] extern crate serde_derive;
 #[macro_use] extern crate error_chain;
 
@@ -46,5 +49,7 @@ extern crate env_logger;
 
 pub mod entry;
 pub mod error;
+pub mod filter;
+pub mod import;
 pub mod types;
 
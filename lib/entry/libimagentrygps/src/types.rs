@@ -28,6 +28,9 @@ use error::GPSErrorKind as GPSEK;
 use error::GPSError as GPSE;
 use error::Result;
 
+/// Mean earth radius in meters, as used for distance calculations
+pub const EARTH_RADIUS : f64 = 6_371_008.8;
+
 pub trait FromValue : Sized {
     fn from_value(v: &Value) -> Result<Self>;
 }
@@ -49,6 +52,52 @@ impl GPSValue {
         }
     }
 
+    /// Create a GPSValue from decimal degrees
+    ///
+    /// The seconds are rounded, use `Position` to keep the full precision. For negative values,
+    /// the sign is put on the first component which is not zero.
+    pub fn from_decimal(value: f64) -> GPSValue {
+        let abs         = value.abs();
+        let fraction    = abs - abs.trunc();
+        let mut degree  = abs.trunc() as i64;
+        let mut minutes = (fraction * 60.0).trunc() as i64;
+        let mut seconds = (fraction * 3600.0 - (minutes as f64) * 60.0).round() as i64;
+
+        if seconds == 60 {
+            seconds  = 0;
+            minutes += 1;
+        }
+
+        if minutes == 60 {
+            minutes  = 0;
+            degree  += 1;
+        }
+
+        if value < 0.0 {
+            if degree != 0 {
+                degree = -degree;
+            } else if minutes != 0 {
+                minutes = -minutes;
+            } else {
+                seconds = -seconds;
+            }
+        }
+
+        GPSValue::new(degree, minutes, seconds)
+    }
+
+    /// Get the value in decimal degrees
+    ///
+    /// The value is negative if any of the components is negative.
+    pub fn to_decimal(&self) -> f64 {
+        let negative = self.degree < 0 || self.minutes < 0 || self.seconds < 0;
+        let abs      = (self.degree.abs() as f64)
+            + (self.minutes.abs() as f64) / 60.0
+            + (self.seconds.abs() as f64) / 3600.0;
+
+        if negative { -abs } else { abs }
+    }
+
     pub fn degree(&self) -> i64 {
         self.degree
     }
@@ -117,17 +166,22 @@ impl Display for GPSValue {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Coordinates {
     pub longitude: GPSValue,
-    pub latitude:  GPSValue,
+    pub latitude: GPSValue,
 }
 
 impl Coordinates {
     pub fn new(long: GPSValue, lat: GPSValue) -> Coordinates {
         Coordinates {
             longitude: long,
-            latitude:  lat,
+            latitude: lat,
         }
     }
 
+    /// Create Coordinates from decimal degrees
+    pub fn from_decimal(long: f64, lat: f64) -> Coordinates {
+        Coordinates::new(GPSValue::from_decimal(long), GPSValue::from_decimal(lat))
+    }
+
     pub fn longitude(&self) -> &GPSValue {
         &self.longitude
     }
@@ -172,4 +226,234 @@ impl Display for Coordinates {
     }
 }
 
+/// A position in decimal degrees, with optional altitude and accuracy
+///
+/// `Coordinates` only hold integer seconds, which loses the precision of GPS devices. A `Position`
+/// is stored in the same header location as `Coordinates`, with the DMS values and an additional
+/// `decimal` key per axis, so it can still be read as `Coordinates`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Position {
+    pub longitude: f64,
+    pub latitude:  f64,
+
+    /// Altitude in meters
+    pub altitude:  Option<f64>,
+
+    /// Accuracy of the position in meters
+    pub accuracy:  Option<f64>,
+}
+
+impl Position {
+    pub fn new(long: f64, lat: f64) -> Position {
+        Position {
+            longitude: long,
+            latitude:  lat,
+            altitude:  None,
+            accuracy:  None,
+        }
+    }
+
+    pub fn with_altitude(mut self, altitude: f64) -> Position {
+        self.altitude = Some(altitude);
+        self
+    }
+
+    pub fn with_accuracy(mut self, accuracy: f64) -> Position {
+        self.accuracy = Some(accuracy);
+        self
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn altitude(&self) -> Option<f64> {
+        self.altitude
+    }
+
+    pub fn accuracy(&self) -> Option<f64> {
+        self.accuracy
+    }
+
+    /// The position as (rounded) coordinates
+    pub fn coordinates(&self) -> Coordinates {
+        Coordinates::from_decimal(self.longitude, self.latitude)
+    }
+
+    /// The great-circle distance to another position in meters
+    ///
+    /// Calculated with the haversine formula, altitudes are ignored.
+    pub fn distance_to(&self, other: &Position) -> f64 {
+        let lat1  = self.latitude.to_radians();
+        let lat2  = other.latitude.to_radians();
+        let dlat  = lat2 - lat1;
+        let dlong = (other.longitude - self.longitude).to_radians();
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlong / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().atan2((1.0 - a).sqrt())
+    }
+
+    /// The initial bearing towards another position in degrees (0 = north, 90 = east)
+    pub fn bearing_to(&self, other: &Position) -> f64 {
+        let lat1  = self.latitude.to_radians();
+        let lat2  = other.latitude.to_radians();
+        let dlong = (other.longitude - self.longitude).to_radians();
+
+        let y = dlong.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlong.cos();
+
+        (y.atan2(x).to_degrees() + 360.0) % 360.0
+    }
+}
+
+impl From<Coordinates> for Position {
+    fn from(c: Coordinates) -> Position {
+        Position::new(c.longitude.to_decimal(), c.latitude.to_decimal())
+    }
+}
 
+impl Into<Value> for Position {
+
+    fn into(self) -> Value {
+        let axis = |decimal: f64| -> Value {
+            let mut value : Value = GPSValue::from_decimal(decimal).into();
+            if let Value::Table(ref mut map) = value {
+                let _ = map.insert("decimal".to_owned(), Value::Float(decimal));
+            }
+            value
+        };
+
+        let mut map = BTreeMap::new();
+        let _ = map.insert("longitude".to_owned(), axis(self.longitude));
+        let _ = map.insert("latitude".to_owned(), axis(self.latitude));
+        if let Some(altitude) = self.altitude {
+            let _ = map.insert("altitude".to_owned(), Value::Float(altitude));
+        }
+        if let Some(accuracy) = self.accuracy {
+            let _ = map.insert("accuracy".to_owned(), Value::Float(accuracy));
+        }
+        Value::Table(map)
+    }
+
+}
+
+impl FromValue for Position {
+    fn from_value(v: &Value) -> Result<Self> {
+        v.as_table()
+            .ok_or(GPSE::from_kind(GPSEK::TypeError))
+            .and_then(|t| {
+                // The decimal value is preferred, plain coordinates only have the DMS values
+                let get = |m: &BTreeMap<_, _>, what: &'static str, ek| -> Result<f64> {
+                    let value = m.get(what).ok_or(GPSE::from_kind(ek))?;
+                    match value.as_table().and_then(|axis| axis.get("decimal")) {
+                        Some(decimal) => float_from_value(decimal),
+                        None          => GPSValue::from_value(value).map(|v| v.to_decimal()),
+                    }
+                };
+
+                let mut p = Position::new(
+                    get(t, "longitude", GPSEK::LongitudeMissing)?,
+                    get(t, "latitude", GPSEK::LatitudeMissing)?
+                );
+
+                p.altitude = t.get("altitude").map(float_from_value).map_or(Ok(None), |r| r.map(Some))?;
+                p.accuracy = t.get("accuracy").map(float_from_value).map_or(Ok(None), |r| r.map(Some))?;
+
+                Ok(p)
+            })
+    }
+
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "longitude = {}\nlatitude = {}", self.longitude, self.latitude)?;
+        if let Some(altitude) = self.altitude {
+            write!(f, "\naltitude = {}", altitude)?;
+        }
+        if let Some(accuracy) = self.accuracy {
+            write!(f, "\naccuracy = {}", accuracy)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a float from a header value, integers are accepted as well
+fn float_from_value(v: &Value) -> Result<f64> {
+    match *v {
+        Value::Float(f)   => Ok(f),
+        Value::Integer(i) => Ok(i as f64),
+        _                 => Err(GPSE::from_kind(GPSEK::HeaderTypeError)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_decimal() {
+        assert_eq!(GPSValue::new(48, 8, 14), GPSValue::from_decimal(48.137154));
+        assert_eq!(GPSValue::new(-11, 30, 0), GPSValue::from_decimal(-11.5));
+    }
+
+    #[test]
+    fn test_from_decimal_keeps_sign_below_one_degree() {
+        let v = GPSValue::from_decimal(-0.5);
+        assert_eq!(GPSValue::new(0, -30, 0), v);
+        assert_eq!(-0.5, v.to_decimal());
+
+        let v = GPSValue::from_decimal(-0.005);
+        assert_eq!(GPSValue::new(0, 0, -18), v);
+        assert_eq!(-0.005, v.to_decimal());
+    }
+
+    #[test]
+    fn test_from_decimal_carries_rounded_seconds() {
+        assert_eq!(GPSValue::new(10, 1, 0), GPSValue::from_decimal(10.0166666));
+        assert_eq!(GPSValue::new(11, 0, 0), GPSValue::from_decimal(10.9999999));
+        assert_eq!(GPSValue::new(-11, 0, 0), GPSValue::from_decimal(-10.9999999));
+    }
+
+    #[test]
+    fn test_dms_to_decimal() {
+        assert_eq!(48.5, GPSValue::new(48, 30, 0).to_decimal());
+        assert_eq!(-48.5, GPSValue::new(-48, 30, 0).to_decimal());
+    }
+
+    #[test]
+    fn test_position_value_roundtrip() {
+        let p = Position::new(11.575382, 48.137154)
+            .with_altitude(519.0)
+            .with_accuracy(5.0);
+
+        let v : Value = p.clone().into();
+        assert_eq!(p, Position::from_value(&v).unwrap());
+        assert_eq!(p.coordinates(), Coordinates::from_value(&v).unwrap());
+    }
+
+    #[test]
+    fn test_position_from_coordinates_value() {
+        let c = Coordinates::new(GPSValue::new(11, 30, 0), GPSValue::new(-48, 15, 0));
+        let v : Value = c.into();
+        assert_eq!(Position::new(11.5, -48.25), Position::from_value(&v).unwrap());
+    }
+
+    #[test]
+    fn test_distance_and_bearing() {
+        let munich = Position::new(11.575382, 48.137154);
+        let berlin = Position::new(13.404954, 52.520008);
+
+        let distance = munich.distance_to(&berlin);
+        assert!(distance > 500_000.0 && distance < 510_000.0, "distance = {}", distance);
+        assert_eq!(0.0, munich.distance_to(&munich));
+
+        let bearing = munich.bearing_to(&berlin);
+        assert!(bearing > 10.0 && bearing < 20.0, "bearing = {}", bearing);
+    }
+
+}
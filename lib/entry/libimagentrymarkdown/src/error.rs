@@ -57,6 +57,26 @@ error_chain! {
             display("The Type of the link '{}' cannot be recognized", s)
         }
 
+        WikiPageMissing(page: String) {
+            description("Wiki page does not exist")
+            display("The wiki page '{}' does not exist", page)
+        }
+
+        WikiLinkResolveError(page: String) {
+            description("Failed to resolve wiki link")
+            display("The wiki link to '{}' could not be resolved", page)
+        }
+
+        SectionNotFound(name: String) {
+            description("Section not found")
+            display("The section '{}' could not be found", name)
+        }
+
+        NoTaskListItem(line: usize) {
+            description("No task list item")
+            display("Line {} is not a task list item", line)
+        }
+
         UrlProcessingError(u: Url) {
             description("Failed to properly processing URL")
             display("The URL '{:?}' could not be processed properly", u)
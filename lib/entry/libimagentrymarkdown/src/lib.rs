@@ -53,4 +53,6 @@ pub mod error;
 pub mod html;
 pub mod link;
 pub mod processor;
+pub mod section;
+pub mod tasklist;
 
@@ -103,9 +103,103 @@ pub fn extract_links(buf: &str) -> Vec<Link> {
     le.links()
 }
 
+/// Split a link into the link target and the section it points to, if any
+///
+/// `"foo/bar#some-heading"` is split into `("foo/bar", Some("some-heading"))`. A link to a section
+/// in the same entry, like `"#some-heading"`, yields an empty target.
+pub fn split_section_link(link: &str) -> (&str, Option<&str>) {
+    match link.find('#') {
+        Some(pos) => (&link[..pos], Some(&link[(pos + 1)..])),
+        None      => (link, None),
+    }
+}
+
+/// A wiki-style link, as in `[[Page Name]]` or `[[Page Name|label]]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    pub page: String,
+    pub label: Option<String>,
+}
+
+impl WikiLink {
+
+    /// The text which should be displayed for this link
+    ///
+    /// This is the label, if there is one, or the page name otherwise.
+    pub fn title(&self) -> &str {
+        self.label.as_ref().map(String::as_str).unwrap_or(&self.page)
+    }
+
+    fn parse(inner: &str) -> Option<WikiLink> {
+        let mut split = inner.splitn(2, '|');
+        let page      = split.next().map(str::trim).unwrap_or("");
+        let label     = split
+            .next()
+            .map(str::trim)
+            .and_then(|l| if l.is_empty() { None } else { Some(String::from(l)) });
+
+        if page.is_empty() {
+            None
+        } else {
+            Some(WikiLink { page: String::from(page), label: label })
+        }
+    }
+
+}
+
+/// Extract all wiki-style `[[Page Name]]` and `[[Page Name|label]]` links from a text
+///
+/// Links inside of fenced code blocks and inline code spans are ignored, as are links which
+/// span multiple lines or have an empty page name.
+pub fn extract_wiki_links(buf: &str) -> Vec<WikiLink> {
+    let mut links    = vec![];
+    let mut in_fence = false;
+
+    for line in buf.lines() {
+        if line.trim_left().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut rest    = line;
+
+        while !rest.is_empty() {
+            if rest.starts_with('`') {
+                in_code = !in_code;
+                rest    = &rest[1..];
+                continue;
+            }
+
+            if !in_code && rest.starts_with("[[") {
+                if let Some(end) = rest[2..].find("]]") {
+                    let inner = &rest[2..(2 + end)];
+                    if !inner.contains('[') {
+                        if let Some(link) = WikiLink::parse(inner) {
+                            links.push(link);
+                        }
+                        rest = &rest[(4 + end)..];
+                        continue;
+                    }
+                }
+            }
+
+            let next = rest.chars().next().map(char::len_utf8).unwrap_or(1);
+            rest = &rest[next..];
+        }
+    }
+
+    trace!("Extracted wiki links: {:?}", links);
+    links
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Link, extract_links};
+    use super::{Link, WikiLink, extract_links, extract_wiki_links, split_section_link};
 
     #[test]
     fn test_one_link() {
@@ -162,5 +256,40 @@ Some more [foo](http://example.com/foo).
         assert_eq!(exp1, links.pop().unwrap());
     }
 
-}
+    #[test]
+    fn test_wiki_links() {
+        let testtext = r#"
+Some [[Page Name]] and [[Other Page|a label]].
+Not a [[]] link, neither is `[[code]]`.
+```
+[[fenced]]
+```
+        "#;
 
+        let links = extract_wiki_links(testtext);
+        assert_eq!(2, links.len());
+
+        assert_eq!(WikiLink { page: String::from("Page Name"), label: None }, links[0]);
+        assert_eq!(WikiLink {
+            page: String::from("Other Page"),
+            label: Some(String::from("a label")),
+        }, links[1]);
+        assert_eq!("a label", links[1].title());
+    }
+
+    #[test]
+    fn test_wiki_links_do_not_interfere_with_markdown_links() {
+        let testtext = "Some [example text](http://example.com) and [[Page]].";
+
+        assert_eq!(1, extract_links(testtext).len());
+        assert_eq!(1, extract_wiki_links(testtext).len());
+    }
+
+    #[test]
+    fn test_split_section_link() {
+        assert_eq!(("foo/bar", Some("baz")), split_section_link("foo/bar#baz"));
+        assert_eq!(("", Some("baz")), split_section_link("#baz"));
+        assert_eq!(("foo/bar", None), split_section_link("foo/bar"));
+    }
+
+}
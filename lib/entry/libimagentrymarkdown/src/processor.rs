@@ -24,6 +24,11 @@ use error::MarkdownError as ME;
 use error::MarkdownErrorKind as MEK;
 use error::*;
 use link::extract_links;
+use link::extract_wiki_links;
+use link::split_section_link;
+use section::SectionName;
+use section::find_section;
+use section::sections;
 
 use libimagentrylink::external::ExternalLinker;
 use libimagentrylink::internal::InternalLinker;
@@ -31,6 +36,7 @@ use libimagentryref::refstore::RefStore;
 use libimagentryref::refstore::UniqueRefPathGenerator;
 use libimagentryref::generators::sha512::Sha512;
 use libimagstore::store::Entry;
+use libimagstore::store::FileLockEntry;
 use libimagstore::store::Store;
 use libimagstore::storeid::StoreId;
 
@@ -69,6 +75,8 @@ impl UniqueRefPathGenerator for UniqueMarkdownRefGenerator {
 ///  * Process external links (from store entry to URL)
 ///  * Process refs (from store entry to files on the filesystem and outside of the store)
 ///  (default: false)
+///  * Process wiki-style links (`[[Page Name]]` or `[[Page Name|label]]`), which are resolved
+///    with a `WikiLinkResolver` (default: false)
 ///
 ///  # Note
 ///
@@ -78,7 +86,8 @@ pub struct LinkProcessor {
     process_internal_links: bool,
     create_internal_targets: bool,
     process_external_links: bool,
-    process_refs: bool
+    process_refs: bool,
+    process_wiki_links: bool,
 }
 
 impl LinkProcessor {
@@ -87,6 +96,9 @@ impl LinkProcessor {
     ///
     /// Internal links are links which are simply `dirctory/file`, but not `/directory/file`, as
     /// beginning an id with `/` is discouraged in imag.
+    ///
+    /// Links to a section of an entry (`directory/file#heading`) link the entry. Links to a
+    /// section of the processed entry itself (`#heading`) are ignored.
     pub fn process_internal_links(mut self, b: bool) -> Self {
         self.process_internal_links = b;
         self
@@ -118,14 +130,50 @@ impl LinkProcessor {
         self
     }
 
+    /// Switch wiki link processing on/off
+    ///
+    /// A wiki link is written as `[[Page Name]]` or `[[Page Name|label]]`. The page name is
+    /// resolved to an entry by a `WikiLinkResolver`. If the target does not exist, it is created
+    /// if `LinkProcessor::create_internal_targets()` is set, else processing fails.
+    pub fn process_wiki_links(mut self, b: bool) -> Self {
+        self.process_wiki_links = b;
+        self
+    }
+
     /// Process an Entry for its links
     ///
+    /// Wiki links are resolved relative to the collection the processed entry lives in, so
+    /// `[[bar]]` in `foo/baz` links to `foo/bar`. Use `LinkProcessor::process_with_resolver()` to
+    /// resolve them differently.
+    ///
     /// # Warning
     ///
     /// When `LinkProcessor::create_internal_targets()` was called to set the setting to true, this
     /// function returns all errors returned by the Store.
     ///
     pub fn process<'a>(&self, entry: &mut Entry, store: &'a Store) -> Result<()> {
+        let collection = entry
+            .get_location()
+            .local()
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_else(PathBuf::new);
+        let resolver = CollectionWikiLinkResolver::new(store, collection);
+
+        self.process_with_resolver(entry, store, &resolver)
+    }
+
+    /// Process an Entry for its links, using the passed `WikiLinkResolver` for wiki links
+    ///
+    /// # Warning
+    ///
+    /// When `LinkProcessor::create_internal_targets()` was called to set the setting to true, this
+    /// function returns all errors returned by the Store or the resolver.
+    ///
+    pub fn process_with_resolver<'a, R>(&self, entry: &mut Entry, store: &'a Store, resolver: &R)
+        -> Result<()>
+        where R: WikiLinkResolver<'a>
+    {
         let text = entry.to_str()?;
         trace!("Processing: {:?}", entry.get_location());
         for link in extract_links(&text).into_iter() {
@@ -136,8 +184,14 @@ impl LinkProcessor {
                         continue
                     }
 
+                    let (path, section) = split_section_link(&link.link);
+                    if path.is_empty() {
+                        trace!("Link to section in same entry, nothing to link");
+                        continue
+                    }
+
                     let spath      = Some(store.path().clone());
-                    let id         = StoreId::new(spath, PathBuf::from(&link.link))?;
+                    let id         = StoreId::new(spath, PathBuf::from(path))?;
                     let mut target = if self.create_internal_targets {
                         store.retrieve(id)?
                     } else {
@@ -145,6 +199,7 @@ impl LinkProcessor {
                             .ok_or(ME::from_kind(MEK::StoreGetError(id)))?
                     };
 
+                    check_section_exists(&target, section);
                     let _ = entry.add_internal_link(&mut target)?;
                 },
                 LinkQualification::ExternalLink(url) => {
@@ -175,11 +230,104 @@ impl LinkProcessor {
             }
         }
 
+        if self.process_wiki_links {
+            for link in extract_wiki_links(&text).into_iter() {
+                trace!("Processing {:?}", link);
+                let (page, section) = split_section_link(&link.page);
+                if page.is_empty() {
+                    trace!("Link to section in same entry, nothing to link");
+                    continue
+                }
+
+                if resolver.page_id(page)? == *entry.get_location() {
+                    trace!("Link to the entry itself, nothing to link");
+                    continue
+                }
+
+                let mut target = match resolver.get_page(page)? {
+                    Some(target) => target,
+                    None => if self.create_internal_targets {
+                        resolver.create_page(page)?
+                    } else {
+                        return Err(ME::from_kind(MEK::WikiPageMissing(String::from(page))))
+                    },
+                };
+
+                check_section_exists(&target, section);
+                let _ = entry.add_internal_link(&mut target)?;
+            }
+        }
+
         Ok(())
     }
 
 }
 
+/// Warn if a link points to a section of an entry which does not exist (anymore)
+///
+/// The link to the entry is created anyways, as sections are not tracked by `libimagentrylink`.
+fn check_section_exists(target: &Entry, section: Option<&str>) {
+    if let Some(section) = section {
+        let name = SectionName::parse(section);
+        if find_section(&sections(target.get_content()), &name).is_none() {
+            warn!("Section '{}' not found in {}", section, target.get_location());
+        }
+    }
+}
+
+/// Resolves the page names of wiki-style links to store entries
+///
+/// `libimagwiki` implements this for its `Wiki` type, so that links are resolved inside the wiki
+/// the processed entry belongs to.
+pub trait WikiLinkResolver<'a> {
+
+    /// The id of the entry for the page
+    fn page_id(&self, page: &str) -> Result<StoreId>;
+
+    /// Get the entry for the page, if it exists
+    fn get_page(&self, page: &str) -> Result<Option<FileLockEntry<'a>>>;
+
+    /// Create the entry for the page, which does not exist yet
+    fn create_page(&self, page: &str) -> Result<FileLockEntry<'a>>;
+
+}
+
+/// A `WikiLinkResolver` which resolves page names relative to a collection in the store
+pub struct CollectionWikiLinkResolver<'a> {
+    store: &'a Store,
+    collection: PathBuf,
+}
+
+impl<'a> CollectionWikiLinkResolver<'a> {
+
+    pub fn new(store: &'a Store, collection: PathBuf) -> Self {
+        CollectionWikiLinkResolver {
+            store: store,
+            collection: collection,
+        }
+    }
+
+}
+
+impl<'a> WikiLinkResolver<'a> for CollectionWikiLinkResolver<'a> {
+
+    fn page_id(&self, page: &str) -> Result<StoreId> {
+        let spath = Some(self.store.path().clone());
+        StoreId::new(spath, self.collection.join(page)).map_err(ME::from)
+    }
+
+    fn get_page(&self, page: &str) -> Result<Option<FileLockEntry<'a>>> {
+        let id = self.page_id(page)?;
+        self.store.get(id).map_err(ME::from)
+    }
+
+    fn create_page(&self, page: &str) -> Result<FileLockEntry<'a>> {
+        let id = self.page_id(page)?;
+        self.store.create(id).map_err(ME::from)
+    }
+
+}
+
 /// Enum to tell what kind of link a string of text is
 enum LinkQualification {
     InternalLink,
@@ -223,7 +371,8 @@ impl Default for LinkProcessor {
             process_internal_links: true,
             create_internal_targets: false,
             process_external_links: true,
-            process_refs: false
+            process_refs: false,
+            process_wiki_links: false,
         }
     }
 }
@@ -566,5 +715,76 @@ mod tests {
         assert_eq!(2, store.entries().unwrap().collect::<Vec<_>>().len());
     }
 
-}
+    #[test]
+    fn test_process_one_wiki_link() {
+        setup_logging();
+        let store = get_store();
+
+        let mut base = store.create(PathBuf::from("wiki/test-6.1")).unwrap();
+        *base.get_content_mut() = format!("This links to [[test-6.2|another page]].");
+
+        let update = store.update(&mut base);
+        assert!(update.is_ok());
+
+        let processor = LinkProcessor::default()
+            .process_internal_links(false)
+            .create_internal_targets(true)
+            .process_external_links(false)
+            .process_refs(false)
+            .process_wiki_links(true);
+
+        let result = processor.process(&mut base, &store);
+        assert!(result.is_ok(), "Should be Ok(()): {:?}", result);
+
+        let base_links : Vec<_> = base.get_internal_links().unwrap().collect();
+        assert_eq!(1, base_links.len());
+        assert_eq!("wiki/test-6.2", base_links[0].to_str().unwrap());
+    }
+
+    #[test]
+    fn test_process_nonexisting_wiki_link_without_target_creation() {
+        setup_logging();
+        let store = get_store();
+
+        let mut base = store.create(PathBuf::from("wiki/test-6.1")).unwrap();
+        *base.get_content_mut() = format!("This links to [[test-6.2]].");
+
+        let update = store.update(&mut base);
+        assert!(update.is_ok());
+
+        let processor = LinkProcessor::default()
+            .create_internal_targets(false)
+            .process_wiki_links(true);
+
+        let result = processor.process(&mut base, &store);
+        assert!(result.is_err(), "Should be Err(_), but is Ok(())");
+    }
+
+    #[test]
+    fn test_process_section_link() {
+        setup_logging();
+        let store = get_store();
+
+        let mut base = store.create(PathBuf::from("test-7.1")).unwrap();
+        *base.get_content_mut() = format!("See [notes](test-7.2#meeting-notes) and [here](#foo).");
+
+        let update = store.update(&mut base);
+        assert!(update.is_ok());
 
+        let _ = store.create(PathBuf::from("test-7.2")).unwrap();
+
+        let processor = LinkProcessor::default()
+            .process_internal_links(true)
+            .create_internal_targets(false)
+            .process_external_links(false)
+            .process_refs(false);
+
+        let result = processor.process(&mut base, &store);
+        assert!(result.is_ok(), "Should be Ok(()): {:?}", result);
+
+        let base_links : Vec<_> = base.get_internal_links().unwrap().collect();
+        assert_eq!(1, base_links.len());
+        assert_eq!("test-7.2", base_links[0].to_str().unwrap());
+    }
+
+}
@@ -0,0 +1,405 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Heading/section aware operations on markdown text
+//!
+//! Only ATX-style headings (`# Heading`, `## Heading`, ...) are recognized. Headings inside of
+//! fenced code blocks are ignored. A section spans from its heading to the next heading of the
+//! same or a higher level, so it contains all of its subsections.
+
+use libimagstore::store::Entry;
+
+pub use libimagutil::slug::slugify;
+
+use error::MarkdownErrorKind as MEK;
+use error::Result;
+
+/// A section of a markdown text, identified by its heading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// The heading level, 1 for `#`, 2 for `##`, ...
+    pub level: usize,
+
+    /// The heading text, without the leading `#`s
+    pub title: String,
+
+    /// The line number (starting at 0) of the heading
+    pub heading_line: usize,
+
+    /// The line number (exclusive) where the section ends
+    pub end_line: usize,
+
+    /// Subsections of this section
+    pub children: Vec<Section>,
+}
+
+impl Section {
+
+    /// The anchor of this section, as used in links like `entry#anchor`
+    pub fn anchor(&self) -> String {
+        slugify(&self.title)
+    }
+
+    /// Check whether this section is meant by the passed name
+    ///
+    /// See `SectionName`.
+    pub fn matches(&self, name: &SectionName) -> bool {
+        let level_matches = name.level.map(|l| l == self.level).unwrap_or(true);
+        let title_matches = self.title.to_lowercase() == name.title.to_lowercase()
+            || self.anchor() == slugify(&name.title);
+
+        level_matches && title_matches
+    }
+
+}
+
+/// A name which refers to a section
+///
+/// Parsed from strings like `"Meeting notes"`, `"meeting-notes"` (the anchor of the section) or
+/// `"## Meeting notes"`. In the latter form, only sections with the given level match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionName {
+    pub level: Option<usize>,
+    pub title: String,
+}
+
+impl SectionName {
+
+    pub fn parse(s: &str) -> SectionName {
+        let s      = s.trim();
+        let hashes = s.chars().take_while(|c| *c == '#').count();
+        let title  = s[hashes..].trim();
+
+        SectionName {
+            level: if hashes == 0 { None } else { Some(hashes) },
+            title: String::from(title),
+        }
+    }
+
+    /// The heading line which is used when a section with this name is created
+    pub fn heading(&self) -> String {
+        let level = self.level.unwrap_or(1);
+        format!("{} {}", "#".repeat(level), self.title)
+    }
+
+}
+
+
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None
+    }
+
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None
+    }
+
+    let title = rest.trim().trim_right_matches('#').trim();
+    Some((level, String::from(title)))
+}
+
+fn headings(lines: &[&str]) -> Vec<(usize, usize, String)> {
+    let mut in_fence = false;
+
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            if line.trim_left().starts_with("```") {
+                in_fence = !in_fence;
+                return None
+            }
+
+            if in_fence {
+                None
+            } else {
+                parse_heading(line).map(|(level, title)| (i, level, title))
+            }
+        })
+        .collect()
+}
+
+fn build_tree(headings: &[(usize, usize, String)], nlines: usize) -> Vec<Section> {
+    let mut sections = vec![];
+    let mut i        = 0;
+
+    while i < headings.len() {
+        let (line, level, ref title) = headings[i];
+
+        let next = headings[(i + 1)..]
+            .iter()
+            .position(|&(_, l, _)| l <= level)
+            .map(|p| p + i + 1)
+            .unwrap_or(headings.len());
+
+        let end_line = headings.get(next).map(|&(l, _, _)| l).unwrap_or(nlines);
+
+        sections.push(Section {
+            level: level,
+            title: title.clone(),
+            heading_line: line,
+            end_line: end_line,
+            children: build_tree(&headings[(i + 1)..next], end_line),
+        });
+
+        i = next;
+    }
+
+    sections
+}
+
+/// Parse the heading tree of a markdown text
+pub fn sections(text: &str) -> Vec<Section> {
+    let lines = text.lines().collect::<Vec<_>>();
+    build_tree(&headings(&lines), lines.len())
+}
+
+/// Find a section by name in a heading tree, depth-first
+pub fn find_section<'a>(sections: &'a [Section], name: &SectionName) -> Option<&'a Section> {
+    for section in sections {
+        if section.matches(name) {
+            return Some(section)
+        }
+
+        if let Some(found) = find_section(&section.children, name) {
+            return Some(found)
+        }
+    }
+
+    None
+}
+
+fn join(lines: Vec<&str>, trailing_newline: bool) -> String {
+    let mut s = lines.join("\n");
+    if trailing_newline {
+        s.push('\n');
+    }
+    s
+}
+
+fn ends_with_newline(text: &str) -> bool {
+    text.is_empty() || text.ends_with('\n')
+}
+
+/// Get the body of a section, without its heading, but including its subsections
+///
+/// Leading and trailing empty lines are removed.
+pub fn get_section(text: &str, name: &str) -> Option<String> {
+    let name  = SectionName::parse(name);
+    let lines = text.lines().collect::<Vec<_>>();
+    let tree  = build_tree(&headings(&lines), lines.len());
+
+    find_section(&tree, &name).map(|section| {
+        let body = &lines[(section.heading_line + 1)..section.end_line];
+        let body = body
+            .iter()
+            .skip_while(|l| l.trim().is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        let nonempty = body.iter().rposition(|l| !l.trim().is_empty()).map(|p| p + 1).unwrap_or(0);
+
+        body[..nonempty].join("\n")
+    })
+}
+
+/// Replace the body of a section, keeping its heading
+///
+/// Fails if there is no section with this name.
+pub fn replace_section(text: &str, name: &str, body: &str) -> Result<String> {
+    let sname = SectionName::parse(name);
+    let lines = text.lines().collect::<Vec<_>>();
+    let tree  = build_tree(&headings(&lines), lines.len());
+
+    let (start, end) = find_section(&tree, &sname)
+        .map(|section| (section.heading_line + 1, section.end_line))
+        .ok_or_else(|| MEK::SectionNotFound(String::from(name)))?;
+
+    let mut new = lines[..start].to_vec();
+    new.extend(body.lines());
+    if end < lines.len() {
+        new.push("");
+    }
+    new.extend(&lines[end..]);
+
+    Ok(join(new, ends_with_newline(text)))
+}
+
+/// Append text to the end of a section
+///
+/// If there is no section with this name, it is created at the end of the text. The level of the
+/// created heading can be set by using the `"## Heading"` form of the name, it defaults to 1.
+pub fn append_to_section(text: &str, name: &str, addition: &str) -> String {
+    let sname = SectionName::parse(name);
+    let lines = text.lines().collect::<Vec<_>>();
+    let tree  = build_tree(&headings(&lines), lines.len());
+
+    match find_section(&tree, &sname).map(|s| (s.heading_line, s.end_line)) {
+        Some((heading, end)) => {
+            let mut pos = end;
+            while pos > heading + 1 && lines[pos - 1].trim().is_empty() {
+                pos -= 1;
+            }
+
+            let mut new = lines[..pos].to_vec();
+            if pos > heading + 1 {
+                new.push("");
+            }
+            new.extend(addition.lines());
+            if end < lines.len() {
+                new.push("");
+            }
+            new.extend(&lines[end..]);
+
+            join(new, ends_with_newline(text))
+        },
+        None => {
+            let heading = sname.heading();
+            let nonempty = lines.iter().rposition(|l| !l.trim().is_empty()).map(|p| p + 1).unwrap_or(0);
+
+            let mut new = lines[..nonempty].to_vec();
+            if nonempty != 0 {
+                new.push("");
+            }
+            new.push(&heading);
+            new.push("");
+            new.extend(addition.lines());
+
+            join(new, true)
+        },
+    }
+}
+
+/// Section operations on the content of an `Entry`
+pub trait MarkdownSections {
+
+    /// Get the heading tree of the content
+    fn sections(&self) -> Vec<Section>;
+
+    /// Get the body of a section, see `get_section()`
+    fn get_section(&self, name: &str) -> Option<String>;
+
+    /// Replace the body of a section, see `replace_section()`
+    fn replace_section(&mut self, name: &str, body: &str) -> Result<()>;
+
+    /// Append to a section, see `append_to_section()`
+    fn append_to_section(&mut self, name: &str, addition: &str);
+
+}
+
+impl MarkdownSections for Entry {
+
+    fn sections(&self) -> Vec<Section> {
+        sections(self.get_content())
+    }
+
+    fn get_section(&self, name: &str) -> Option<String> {
+        get_section(self.get_content(), name)
+    }
+
+    fn replace_section(&mut self, name: &str, body: &str) -> Result<()> {
+        let new = replace_section(self.get_content(), name, body)?;
+        *self.get_content_mut() = new;
+        Ok(())
+    }
+
+    fn append_to_section(&mut self, name: &str, addition: &str) {
+        let new = append_to_section(self.get_content(), name, addition);
+        *self.get_content_mut() = new;
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT : &'static str = r#"# Title
+
+Intro
+
+## Meeting notes
+
+Some notes
+
+### Details
+
+Detail text
+
+## Todo
+
+- foo
+"#;
+
+    #[test]
+    fn test_section_tree() {
+        let tree = sections(TEXT);
+
+        assert_eq!(1, tree.len());
+        assert_eq!("Title", tree[0].title);
+        assert_eq!(2, tree[0].children.len());
+        assert_eq!("Meeting notes", tree[0].children[0].title);
+        assert_eq!("meeting-notes", tree[0].children[0].anchor());
+        assert_eq!(1, tree[0].children[0].children.len());
+        assert_eq!("Todo", tree[0].children[1].title);
+    }
+
+    #[test]
+    fn test_get_section() {
+        let section = get_section(TEXT, "meeting-notes").unwrap();
+        assert_eq!("Some notes\n\n### Details\n\nDetail text", section);
+
+        assert_eq!(Some(String::from("- foo")), get_section(TEXT, "## Todo"));
+        assert_eq!(None, get_section(TEXT, "### Todo"));
+        assert_eq!(None, get_section(TEXT, "Nonexistent"));
+    }
+
+    #[test]
+    fn test_headings_in_code_are_ignored() {
+        let text = "# A\n\n```\n# not a heading\n```\n";
+        assert_eq!(1, sections(text).len());
+    }
+
+    #[test]
+    fn test_replace_section() {
+        let new = replace_section(TEXT, "Todo", "- bar").unwrap();
+        assert_eq!(Some(String::from("- bar")), get_section(&new, "Todo"));
+        assert!(new.contains("Some notes"));
+
+        assert!(replace_section(TEXT, "Nonexistent", "").is_err());
+    }
+
+    #[test]
+    fn test_append_to_section() {
+        let new = append_to_section(TEXT, "Meeting notes", "More notes");
+        assert_eq!("Some notes\n\n### Details\n\nDetail text\n\nMore notes",
+                   get_section(&new, "Meeting notes").unwrap());
+        assert_eq!(Some(String::from("- foo")), get_section(&new, "Todo"));
+    }
+
+    #[test]
+    fn test_append_to_missing_section() {
+        let new = append_to_section(TEXT, "## Done", "- baz");
+        assert!(new.ends_with("## Done\n\n- baz\n"));
+        assert_eq!(Some(String::from("- baz")), get_section(&new, "done"));
+    }
+
+}
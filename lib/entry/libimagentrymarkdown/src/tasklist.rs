@@ -0,0 +1,217 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! GitHub-style task lists in markdown text
+//!
+//! A task list item is a list item which starts with `[ ]` (open) or `[x]` (done), for example
+//! `- [ ] write documentation`. Items inside of fenced code blocks are ignored.
+
+use libimagstore::store::Entry;
+
+use error::MarkdownErrorKind as MEK;
+use error::Result;
+
+/// An item of a task list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskListItem {
+    /// The line number (starting at 1) of the item
+    pub line: usize,
+
+    /// Whether the item is checked
+    pub checked: bool,
+
+    /// The text of the item, without list marker and checkbox
+    pub text: String,
+}
+
+/// Parse a line as task list item
+///
+/// Returns the byte offset of the checkbox character (the space or `x` between the brackets), the
+/// state and the text of the item.
+fn parse_item(line: &str) -> Option<(usize, bool, &str)> {
+    let trimmed = line.trim_left();
+    let indent  = line.len() - trimmed.len();
+
+    let marker_len = if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+        2
+    } else {
+        let digits = trimmed.chars().take_while(|c| c.is_digit(10)).count();
+        if digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") ")) {
+            digits + 2
+        } else {
+            return None
+        }
+    };
+
+    let rest = &trimmed[marker_len..];
+    let checked = if rest.starts_with("[ ]") {
+        false
+    } else if rest.starts_with("[x]") || rest.starts_with("[X]") {
+        true
+    } else {
+        return None
+    };
+
+    let text = &rest[3..];
+    if !text.is_empty() && !text.starts_with(' ') && !text.starts_with('\t') {
+        return None
+    }
+
+    Some((indent + marker_len + 1, checked, text.trim()))
+}
+
+/// Extract all task list items from a text
+pub fn extract_tasks(text: &str) -> Vec<TaskListItem> {
+    let mut in_fence = false;
+
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            if line.trim_left().starts_with("```") {
+                in_fence = !in_fence;
+                return None
+            }
+
+            if in_fence {
+                return None
+            }
+
+            parse_item(line).map(|(_, checked, text)| TaskListItem {
+                line: i + 1,
+                checked: checked,
+                text: String::from(text),
+            })
+        })
+        .collect()
+}
+
+/// Set the state of the task list item in the passed line (starting at 1)
+///
+/// Fails if there is no task list item in that line.
+pub fn set_task_state(text: &str, line: usize, checked: bool) -> Result<String> {
+    let is_task = extract_tasks(text).iter().any(|item| item.line == line);
+    if !is_task {
+        return Err(MEK::NoTaskListItem(line).into())
+    }
+
+    let new = text
+        .lines()
+        .enumerate()
+        .map(|(i, l)| if i + 1 == line {
+            // safe, because we checked that this line is an item
+            let (pos, _, _) = parse_item(l).unwrap();
+            let mark        = if checked { "x" } else { " " };
+            format!("{}{}{}", &l[..pos], mark, &l[(pos + 1)..])
+        } else {
+            String::from(l)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if text.ends_with('\n') {
+        Ok(new + "\n")
+    } else {
+        Ok(new)
+    }
+}
+
+/// Task list operations on the content of an `Entry`
+pub trait MarkdownTaskList {
+
+    /// Get all task list items of the content
+    fn tasks(&self) -> Vec<TaskListItem>;
+
+    /// Check or uncheck the task list item in the passed line (starting at 1)
+    fn set_task_state(&mut self, line: usize, checked: bool) -> Result<()>;
+
+    /// Toggle the task list item in the passed line (starting at 1)
+    ///
+    /// Returns the new state of the item.
+    fn toggle_task(&mut self, line: usize) -> Result<bool>;
+
+}
+
+impl MarkdownTaskList for Entry {
+
+    fn tasks(&self) -> Vec<TaskListItem> {
+        extract_tasks(self.get_content())
+    }
+
+    fn set_task_state(&mut self, line: usize, checked: bool) -> Result<()> {
+        let new = set_task_state(self.get_content(), line, checked)?;
+        *self.get_content_mut() = new;
+        Ok(())
+    }
+
+    fn toggle_task(&mut self, line: usize) -> Result<bool> {
+        let checked = self
+            .tasks()
+            .into_iter()
+            .find(|item| item.line == line)
+            .map(|item| !item.checked)
+            .ok_or_else(|| MEK::NoTaskListItem(line))?;
+
+        MarkdownTaskList::set_task_state(self, line, checked).map(|_| checked)
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT : &'static str = r#"# Todo
+
+- [ ] open item
+- [x] done item
+  * [X] nested done
+1. [ ] numbered
+- [] no item
+- not an item
+
+```
+- [ ] in code
+```
+"#;
+
+    #[test]
+    fn test_extract_tasks() {
+        let tasks = extract_tasks(TEXT);
+
+        assert_eq!(4, tasks.len());
+        assert_eq!(TaskListItem { line: 3, checked: false, text: String::from("open item") }, tasks[0]);
+        assert_eq!(TaskListItem { line: 4, checked: true, text: String::from("done item") }, tasks[1]);
+        assert_eq!(TaskListItem { line: 5, checked: true, text: String::from("nested done") }, tasks[2]);
+        assert_eq!(TaskListItem { line: 6, checked: false, text: String::from("numbered") }, tasks[3]);
+    }
+
+    #[test]
+    fn test_set_task_state() {
+        let new = set_task_state(TEXT, 3, true).unwrap();
+        assert!(new.contains("- [x] open item\n"));
+
+        let new = set_task_state(&new, 5, false).unwrap();
+        assert!(new.contains("  * [ ] nested done\n"));
+
+        assert!(set_task_state(TEXT, 7, true).is_err());
+        assert!(set_task_state(TEXT, 11, true).is_err());
+    }
+
+}
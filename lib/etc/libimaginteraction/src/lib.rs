@@ -41,16 +41,20 @@ extern crate ansi_term;
 extern crate regex;
 extern crate clap;
 extern crate toml;
+extern crate toml_query;
+extern crate rustyline;
 extern crate handlebars;
 extern crate serde_json;
 #[macro_use] extern crate error_chain;
 
 extern crate libimagstore;
 extern crate libimagerror;
+extern crate libimagutil;
 
 pub mod ask;
 pub mod error;
 pub mod filter;
 pub mod format;
+pub mod readline;
 pub mod ui;
 
@@ -17,76 +17,171 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+//! Line editing with history and completion, configured by the `ui.cli.readline_*` settings
+
+use std::fs::File;
+use std::path::PathBuf;
+
 use error::InteractionError as IE;
 use error::InteractionErrorKind as IEK;
 use error::ResultExt;
+use error::Result;
 
 use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+use libimagutil::config_schema::ConfigKey;
+use libimagutil::config_schema::ValueType;
 
 use rustyline::{Config, Editor};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+
+/// The configuration keys the line editor reads
+pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+    ConfigKey {
+        path: "ui.cli.readline_history_file",
+        kind: ValueType::String,
+        default: None,
+        doc: "The file the history is kept in, no history is kept if it is not set",
+    },
+    ConfigKey {
+        path: "ui.cli.readline_history_size",
+        kind: ValueType::Integer,
+        default: Some("100"),
+        doc: "The number of lines kept in the history",
+    },
+    ConfigKey {
+        path: "ui.cli.readline_history_ignore_dups",
+        kind: ValueType::Boolean,
+        default: Some("true"),
+        doc: "Whether a line which equals the previous one is not added to the history",
+    },
+    ConfigKey {
+        path: "ui.cli.readline_history_ignore_space",
+        kind: ValueType::Boolean,
+        default: Some("true"),
+        doc: "Whether lines which start with a space are not added to the history",
+    },
+    ConfigKey {
+        path: "ui.cli.readline_prompt",
+        kind: ValueType::String,
+        default: Some("\">> \""),
+        doc: "The prompt",
+    },
+];
+
+/// Something which knows the completions for a word on the line
+pub trait Completion {
+
+    /// Get the candidates for `word`, which is the `index`th word on the line (starting with 0)
+    ///
+    /// The candidates replace the word, so they have to start with it to be useful.
+    fn candidates(&self, word: &str, index: usize) -> Vec<String>;
+
+}
+
+/// No completion at all
+impl Completion for () {
+    fn candidates(&self, _: &str, _: usize) -> Vec<String> {
+        vec![]
+    }
+}
 
-pub struct Readline {
-    editor: Editor,
-    history_file: PathBuf,
+/// Adapter from `Completion` to the completer of the line editor
+pub struct CompletionAdapter<C: Completion>(C);
+
+impl<C: Completion> Completer for CompletionAdapter<C> {
+    fn complete(&self, line: &str, pos: usize) -> ::rustyline::Result<(usize, Vec<String>)> {
+        let (start, index) = current_word(line, pos);
+        Ok((start, self.0.candidates(&line[start..pos], index)))
+    }
+}
+
+/// Find the start of the word which ends at `pos` and the index of the word on the line
+fn current_word(line: &str, pos: usize) -> (usize, usize) {
+    let before = &line[..pos];
+    let start  = before
+        .rfind(char::is_whitespace)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let index  = before[..start].split_whitespace().count();
+
+    (start, index)
+}
+
+pub struct Readline<C: Completion> {
+    editor: Editor<CompletionAdapter<C>>,
+    history_file: Option<PathBuf>,
     prompt: String,
 }
 
-impl Readline {
-
-    pub fn new(rt: &Runtime) -> Result<Readline> {
-        let c = rt.config().ok_or(IEK::NoConfigError)?;
-
-        let histfile     = c.lookup("ui.cli.readline_history_file").ok_or(IEK::ConfigError)?;
-        let histsize     = c.lookup("ui.cli.readline_history_size").ok_or(IEK::ConfigError)?;
-        let histigndups  = c.lookup("ui.cli.readline_history_ignore_dups").ok_or(IEK::ConfigError)?;
-        let histignspace = c.lookup("ui.cli.readline_history_ignore_space").ok_or(IEK::ConfigError)?;
-        let prompt       = c.lookup("ui.cli.readline_prompt").ok_or(IEK::ConfigError)?;
-
-        let histfile = histfile
-            .as_str()
-            .map(PathBuf::from)
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .chain_err(|| IEK::ConfigError)
-            .chain_err(|| IEK::ReadlineError)?;
-
-        let histsize = histsize
-            .as_int()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .chain_err(|| IEK::ConfigError)
-            .chain_err(|| IEK::ReadlineError)?;
-
-        let histigndups = histigndups
-            .as_bool()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .chain_err(|| IEK::ConfigError)
-            .chain_err(|| IEK::ReadlineError)?;
-
-        let histignspace = histignspace
-            .as_bool()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .chain_err(|| IEK::ConfigError)
-            .chain_err(|| IEK::ReadlineError)?;
-
-        let prompt = prompt
-            .as_str()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .chain_err(|| IEK::ConfigError)
-            .chain_err(|| IEK::ReadlineError)?;
-
-        let config = Config::builder().
+impl<C: Completion> Readline<C> {
+
+    /// Create a line editor from the `ui.cli.readline_*` settings in `config`
+    ///
+    /// Settings which are not present fall back to defaults. If no history file is configured, the
+    /// history is not persisted.
+    pub fn new(config: Option<&Value>, completion: C) -> Result<Readline<C>> {
+        let read_setting = |name: &str| -> Result<Option<Value>> {
+            match config {
+                Some(c) => c
+                    .read(&format!("ui.cli.{}", name))
+                    .map(|v| v.cloned())
+                    .chain_err(|| IEK::ConfigError),
+                None    => Ok(None),
+            }
+        };
+
+        let type_error = || IE::from_kind(IEK::ConfigTypeError);
+
+        let histfile = match read_setting("readline_history_file")? {
+            Some(Value::String(s)) => Some(PathBuf::from(s)),
+            Some(_)                => return Err(type_error()),
+            None                   => None,
+        };
+
+        let histsize = match read_setting("readline_history_size")? {
+            Some(Value::Integer(i)) if i >= 0 => i as usize,
+            Some(_)                           => return Err(type_error()),
+            None                              => 100,
+        };
+
+        let histigndups = match read_setting("readline_history_ignore_dups")? {
+            Some(Value::Boolean(b)) => b,
+            Some(_)                 => return Err(type_error()),
+            None                    => true,
+        };
+
+        let histignspace = match read_setting("readline_history_ignore_space")? {
+            Some(Value::Boolean(b)) => b,
+            Some(_)                 => return Err(type_error()),
+            None                    => true,
+        };
+
+        let prompt = match read_setting("readline_prompt")? {
+            Some(Value::String(s)) => s,
+            Some(_)                => return Err(type_error()),
+            None                   => String::from(">> "),
+        };
+
+        let config = Config::builder()
             .max_history_size(histsize)
             .history_ignore_dups(histigndups)
             .history_ignore_space(histignspace)
             .build();
 
-        let mut editor = Editor::new(config);
+        let mut editor = Editor::with_config(config);
+        editor.set_completer(Some(CompletionAdapter(completion)));
 
-        if !histfile.exists() {
-            let _ = File::create(histfile.clone())
-                         .chain_err(|| IEK::ReadlineHistoryFileCreationError)?;
-        }
+        if let Some(ref histfile) = histfile {
+            if !histfile.exists() {
+                let _ = File::create(histfile.clone())
+                    .chain_err(|| IEK::ReadlineHistoryFileCreationError)?;
+            }
 
-        let _ = editor.load_history(&histfile).chain_err(|| ReadlineError)?;
+            let _ = editor.load_history(histfile).chain_err(|| IEK::ReadlineError)?;
+        }
 
         Ok(Readline {
             editor: editor,
@@ -95,31 +190,59 @@ impl Readline {
         })
     }
 
-    pub fn read_line(&mut self) -> Result<Option<String>> {
-        use rustyline::ReadlineError;
-        use libimagutil::warn_result::*;
+    pub fn set_prompt(&mut self, prompt: String) {
+        self.prompt = prompt;
+    }
 
+    /// Read a line, `None` means that the user is done (CTRL-D)
+    ///
+    /// CTRL-C cancels the current line, which results in an empty line.
+    pub fn read_line(&mut self) -> Result<Option<String>> {
         match self.editor.readline(&self.prompt) {
             Ok(line) => {
-                self.editor.add_history_line(&line);
-                self.editor
-                    .save_history(&self.history_file)
-                    .map_warn_err_str(|e| format!("Could not save history file {} -> {:?}",
-                                                  self.history_file.display(), e));
-                return line;
+                let _ = self.editor.add_history_entry(line.as_str());
+                if let Some(ref histfile) = self.history_file {
+                    let _ = self.editor
+                        .save_history(histfile)
+                        .chain_err(|| IEK::ReadlineError)?;
+                }
+                Ok(Some(line))
             },
-            Err(ReadlineError::Interrupted) => {
-                info!("CTRL-C");
-                Ok(None)
-            },
-            Err(ReadlineError::Eof) => {
-                info!("CTRL-D");
-                Ok(None)
-            },
-            Err(err) => Err(err).map_err_into(ReadlineError),
-
+            Err(ReadlineError::Interrupted) => Ok(Some(String::new())),
+            Err(ReadlineError::Eof)         => Ok(None),
+            Err(e)                          => Err(e).chain_err(|| IEK::ReadlineError),
         }
     }
 
 }
 
+#[cfg(test)]
+mod test {
+    use super::current_word;
+    use super::Completer;
+    use super::Completion;
+    use super::CompletionAdapter;
+
+    struct Words;
+
+    impl Completion for Words {
+        fn candidates(&self, word: &str, index: usize) -> Vec<String> {
+            vec![format!("{}:{}", index, word)]
+        }
+    }
+
+    #[test]
+    fn test_current_word() {
+        assert_eq!((0, 0), current_word("", 0));
+        assert_eq!((0, 0), current_word("ta", 2));
+        assert_eq!((4, 1), current_word("tag ", 4));
+        assert_eq!((4, 1), current_word("tag no", 6));
+        assert_eq!((13, 2), current_word("tag  notes/a fo", 15));
+    }
+
+    #[test]
+    fn test_completion_adapter() {
+        let adapter = CompletionAdapter(Words);
+        assert_eq!((4, vec![String::from("1:no")]), adapter.complete("tag notes", 6).unwrap());
+    }
+}
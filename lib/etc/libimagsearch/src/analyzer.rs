@@ -0,0 +1,132 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! Splitting text into normalized terms
+
+use rust_stemmers::Algorithm;
+use rust_stemmers::Stemmer;
+
+/// A term found in a text, with the byte range it was found at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The normalized (lowercased and stemmed) term
+    pub term: String,
+
+    /// Byte offset of the first character of the token in the original text
+    pub start: usize,
+
+    /// Byte offset right after the last character of the token in the original text
+    pub end: usize,
+}
+
+/// Splits text into words and normalizes them, so that "Running" and "runs" end up as the same
+/// term
+pub struct Analyzer {
+    stemmer: Stemmer,
+}
+
+impl Analyzer {
+
+    pub fn new() -> Analyzer {
+        Analyzer {
+            stemmer: Stemmer::create(Algorithm::English),
+        }
+    }
+
+    /// Normalize a single word
+    pub fn normalize(&self, word: &str) -> String {
+        let lower = word.to_lowercase();
+        self.stemmer.stem(&lower).into_owned()
+    }
+
+    /// Get all tokens of a text, in order
+    ///
+    /// Words are maximal runs of alphanumeric characters, everything else is treated as
+    /// separator.
+    pub fn tokens(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut start  = None;
+
+        for (idx, ch) in text.char_indices() {
+            match (ch.is_alphanumeric(), start) {
+                (true, None)        => start = Some(idx),
+                (false, Some(s))    => {
+                    tokens.push(self.make_token(text, s, idx));
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+
+        if let Some(s) = start {
+            tokens.push(self.make_token(text, s, text.len()));
+        }
+
+        tokens
+    }
+
+    /// Get only the normalized terms of a text, in order
+    pub fn terms(&self, text: &str) -> Vec<String> {
+        self.tokens(text).into_iter().map(|t| t.term).collect()
+    }
+
+    fn make_token(&self, text: &str, start: usize, end: usize) -> Token {
+        Token {
+            term: self.normalize(&text[start..end]),
+            start: start,
+            end: end,
+        }
+    }
+
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Analyzer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_offsets() {
+        let text   = "Hello, wörld!";
+        let tokens = Analyzer::new().tokens(text);
+
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(&text[tokens[0].start..tokens[0].end], "Hello");
+        assert_eq!(&text[tokens[1].start..tokens[1].end], "wörld");
+        assert_eq!(tokens[0].term, "hello");
+    }
+
+    #[test]
+    fn test_stemming() {
+        let a = Analyzer::new();
+        assert_eq!(a.normalize("Running"), a.normalize("runs"));
+        assert_eq!(a.normalize("meetings"), a.normalize("meeting"));
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(Analyzer::new().tokens(" -- ").is_empty());
+    }
+
+}
+
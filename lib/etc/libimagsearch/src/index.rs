@@ -0,0 +1,496 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! The inverted index
+//!
+//! The index maps every term to the documents (store entries) it occurs in, together with the
+//! positions of the term in the document, which is what phrase queries need.
+//!
+//! Header values are indexed as "field terms": the value of `diary.name` is indexed under
+//! `diary.name:<term>`, so it can be searched with `diary.name:<term>` but does not show up when
+//! searching for `<term>` alone.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use toml::Value;
+use serde_json;
+
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+use analyzer::Analyzer;
+use error::Result;
+
+/// Increment whenever the on-disk format or the analysis changes, so old indexes get rebuilt
+const INDEX_VERSION: u32 = 1;
+
+/// Name of the index file in the runtime path
+pub const INDEX_FILE_NAME: &'static str = ".search-index.json";
+
+/// Get the default path of the index file for a runtime path
+pub fn default_index_path(rtp: &Path) -> PathBuf {
+    rtp.join(INDEX_FILE_NAME)
+}
+
+/// Build the key under which a (possibly field-scoped) term is stored in the index
+pub fn term_key(field: Option<&str>, term: &str) -> String {
+    match field {
+        Some(f) => format!("{}:{}", f, term),
+        None    => String::from(term),
+    }
+}
+
+/// Positions of one term in one document
+pub type Positions = Vec<usize>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Document {
+    /// Modification time of the file when it was indexed, if the backend has one
+    pub(crate) modified: Option<(u64, u32)>,
+
+    /// Hash of the entry, to detect changes if the modification time is not available or changed
+    /// without the entry changing
+    pub(crate) hash: u64,
+
+    /// Number of terms in the content
+    pub(crate) length: usize,
+
+    /// All keys this document is listed under, for removing it again
+    pub(crate) keys: Vec<String>,
+}
+
+/// Statistics about what happened during an `Index::refresh()`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Index {
+    version: u32,
+    documents: BTreeMap<String, Document>,
+    postings: BTreeMap<String, BTreeMap<String, Positions>>,
+
+    #[serde(skip)]
+    analyzer: Analyzer,
+
+    /// Whether the index was changed since it was loaded
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Index {
+
+    pub fn new() -> Index {
+        Index {
+            version: INDEX_VERSION,
+            documents: BTreeMap::new(),
+            postings: BTreeMap::new(),
+            analyzer: Analyzer::new(),
+            dirty: false,
+        }
+    }
+
+    /// Load the index from `path`
+    ///
+    /// If there is no index file yet or it was written by an incompatible version, an empty index
+    /// is returned, which gets filled by the next `Index::refresh()`.
+    pub fn load(path: &Path) -> Result<Index> {
+        if !path.exists() {
+            debug!("No search index at {}, starting with empty index", path.display());
+            return Ok(Index::new());
+        }
+
+        let index : Index = serde_json::from_reader(File::open(path)?)?;
+        if index.version != INDEX_VERSION {
+            info!("Search index at {} is outdated, rebuilding", path.display());
+            return Ok(Index::new());
+        }
+
+        Ok(index)
+    }
+
+    /// Load the index from `path`, bring it up to date with `store` and write it back
+    pub fn load_refreshed(path: &Path, store: &Store) -> Result<Index> {
+        let mut index = Index::load(path)?;
+        let stats     = index.refresh(store)?;
+        debug!("Refreshed search index: {:?}", stats);
+
+        if index.is_dirty() {
+            index.save(path)?;
+        }
+
+        Ok(index)
+    }
+
+    /// Write the index to `path`
+    ///
+    /// The index is written to a temporary file first which is then moved into place, so an
+    /// interrupted write does not leave a broken index behind.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        {
+            let file = OpenOptions::new().write(true).create(true).truncate(true).open(&tmp)?;
+            serde_json::to_writer(file, self)?;
+        }
+        ::std::fs::rename(&tmp, path).map_err(From::from)
+    }
+
+    /// Number of indexed entries
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn contains(&self, id: &StoreId) -> bool {
+        self.documents.contains_key(&doc_key(id))
+    }
+
+    /// Whether the index was changed since it was loaded and should be saved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn analyzer(&self) -> &Analyzer {
+        &self.analyzer
+    }
+
+    /// Bring the index up to date with the store
+    ///
+    /// Entries which were not modified since they were indexed are not read at all, entries which
+    /// were removed from the store are removed from the index.
+    pub fn refresh(&mut self, store: &Store) -> Result<RefreshStats> {
+        let mut stats = RefreshStats::default();
+        let mut seen  = BTreeSet::new();
+
+        for id in store.entries()?.without_store() {
+            let id  = id?;
+            let key = doc_key(&id);
+            seen.insert(key.clone());
+
+            let modified = modification_time(&id);
+            let is_known = match self.documents.get(&key) {
+                Some(doc) => {
+                    if modified.is_some() && doc.modified == modified {
+                        stats.unchanged += 1;
+                        continue;
+                    }
+                    true
+                },
+                None => false,
+            };
+
+            let changed = {
+                let entry = match store.get(id.clone())? {
+                    Some(entry) => entry,
+                    None        => continue,
+                };
+                self.index_entry(&entry)?
+            };
+
+            // The entry is written back when the FileLockEntry is dropped, so the modification
+            // time has to be read afterwards.
+            if let Some(doc) = self.documents.get_mut(&key) {
+                let modified = modification_time(&id);
+                if doc.modified != modified {
+                    doc.modified = modified;
+                    self.dirty   = true;
+                }
+            }
+
+            match (is_known, changed) {
+                (false, _)    => stats.added += 1,
+                (true, true)  => stats.updated += 1,
+                (true, false) => stats.unchanged += 1,
+            }
+        }
+
+        let removed : Vec<String> = self.documents
+            .keys()
+            .filter(|k| !seen.contains(*k))
+            .cloned()
+            .collect();
+
+        for key in removed {
+            self.remove_key(&key);
+            stats.removed += 1;
+        }
+
+        Ok(stats)
+    }
+
+    /// Add an entry to the index or update it
+    ///
+    /// Returns whether the index was changed, which is not the case if the entry was indexed
+    /// before and did not change since.
+    pub fn index_entry(&mut self, entry: &Entry) -> Result<bool> {
+        let key  = doc_key(entry.get_location());
+        let hash = entry_hash(entry)?;
+
+        if self.documents.get(&key).map(|doc| doc.hash == hash).unwrap_or(false) {
+            return Ok(false);
+        }
+
+        self.remove_key(&key);
+
+        let mut terms : HashMap<String, Positions> = HashMap::new();
+
+        let content_terms = self.analyzer.terms(entry.get_content());
+        let length        = content_terms.len();
+        for (pos, term) in content_terms.into_iter().enumerate() {
+            terms.entry(term).or_insert_with(Vec::new).push(pos);
+        }
+
+        let mut field_positions = HashMap::new();
+        index_header_value(&self.analyzer, entry.get_header(), "", &mut field_positions, &mut terms);
+
+        let mut keys = Vec::with_capacity(terms.len());
+        for (term, positions) in terms {
+            self.postings
+                .entry(term.clone())
+                .or_insert_with(BTreeMap::new)
+                .insert(key.clone(), positions);
+            keys.push(term);
+        }
+
+        self.documents.insert(key, Document {
+            modified: None,
+            hash: hash,
+            length: length,
+            keys: keys,
+        });
+
+        self.dirty = true;
+        Ok(true)
+    }
+
+    /// Remove an entry from the index, returns whether it was indexed
+    pub fn remove(&mut self, id: &StoreId) -> bool {
+        self.remove_key(&doc_key(id))
+    }
+
+    fn remove_key(&mut self, key: &str) -> bool {
+        match self.documents.remove(key) {
+            None      => false,
+            Some(doc) => {
+                for term in doc.keys {
+                    let now_empty = match self.postings.get_mut(&term) {
+                        Some(docs) => {
+                            docs.remove(key);
+                            docs.is_empty()
+                        },
+                        None => false,
+                    };
+
+                    if now_empty {
+                        self.postings.remove(&term);
+                    }
+                }
+                self.dirty = true;
+                true
+            },
+        }
+    }
+
+    pub(crate) fn documents(&self) -> &BTreeMap<String, Document> {
+        &self.documents
+    }
+
+    pub(crate) fn postings(&self, key: &str) -> Option<&BTreeMap<String, Positions>> {
+        self.postings.get(key)
+    }
+
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Index::new()
+    }
+}
+
+pub(crate) fn doc_key(id: &StoreId) -> String {
+    format!("{}", id)
+}
+
+fn modification_time(id: &StoreId) -> Option<(u64, u32)> {
+    id.clone()
+        .into_pathbuf()
+        .ok()
+        .and_then(|path| path.metadata().ok())
+        .and_then(|meta| meta.modified().ok())
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|dur| (dur.as_secs(), dur.subsec_nanos()))
+}
+
+/// Hash of the complete entry
+///
+/// The hash is not guaranteed to be stable between different builds of imag, which only results
+/// in the entry being re-indexed.
+fn entry_hash(entry: &Entry) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    entry.to_str()?.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+fn index_header_value(analyzer: &Analyzer,
+                      value: &Value,
+                      path: &str,
+                      field_positions: &mut HashMap<String, usize>,
+                      terms: &mut HashMap<String, Positions>)
+{
+    let text = match *value {
+        Value::Table(ref t) => {
+            for (k, v) in t.iter() {
+                let subpath = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+                index_header_value(analyzer, v, &subpath, field_positions, terms);
+            }
+            return;
+        },
+        Value::Array(ref a) => {
+            for v in a.iter() {
+                index_header_value(analyzer, v, path, field_positions, terms);
+            }
+            return;
+        },
+        Value::String(ref s)   => s.clone(),
+        Value::Integer(i)      => i.to_string(),
+        Value::Float(f)        => f.to_string(),
+        Value::Boolean(b)      => b.to_string(),
+        Value::Datetime(ref d) => d.to_string(),
+    };
+
+    let next_pos = field_positions.entry(String::from(path)).or_insert(0);
+    for term in analyzer.terms(&text) {
+        terms.entry(term_key(Some(path), &term)).or_insert_with(Vec::new).push(*next_pos);
+        *next_pos += 1;
+    }
+
+    // Leave a gap between values of an array, so a phrase cannot match across two of them
+    *next_pos += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+
+    use super::*;
+
+    fn get_store() -> Store {
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    fn create(store: &Store, name: &str, content: &str) {
+        let mut entry = store.create(PathBuf::from(name)).unwrap();
+        *entry.get_content_mut() = String::from(content);
+    }
+
+    #[test]
+    fn test_refresh_adds_entries() {
+        let store = get_store();
+        create(&store, "a", "The quick brown fox");
+        create(&store, "b", "jumps over the lazy dog");
+
+        let mut index = Index::new();
+        let stats     = index.refresh(&store).unwrap();
+
+        assert_eq!(stats.added, 2);
+        assert_eq!(index.len(), 2);
+        assert!(index.postings("quick").unwrap().contains_key("a"));
+        assert!(index.postings("dog").unwrap().contains_key("b"));
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let store = get_store();
+        create(&store, "a", "first version");
+        create(&store, "b", "unchanged");
+
+        let mut index = Index::new();
+        index.refresh(&store).unwrap();
+
+        {
+            let mut entry = store.get(PathBuf::from("a")).unwrap().unwrap();
+            *entry.get_content_mut() = String::from("second version");
+        }
+        store.delete(PathBuf::from("b")).unwrap();
+
+        let stats = index.refresh(&store).unwrap();
+        assert_eq!(stats, RefreshStats { added: 0, updated: 1, removed: 1, unchanged: 0 });
+        assert!(index.postings("first").is_none());
+        assert!(index.postings("second").unwrap().contains_key("a"));
+        assert!(index.postings("unchang").is_none());
+
+        let stats = index.refresh(&store).unwrap();
+        assert_eq!(stats, RefreshStats { added: 0, updated: 0, removed: 0, unchanged: 1 });
+    }
+
+    #[test]
+    fn test_header_fields_are_indexed() {
+        let store = get_store();
+        {
+            let mut entry = store.create(PathBuf::from("a")).unwrap();
+            let tags = Value::Array(vec![Value::String(String::from("work"))]);
+            let mut tag = ::toml::value::Table::new();
+            tag.insert(String::from("values"), tags);
+            entry.get_header_mut().as_table_mut().unwrap().insert(String::from("tag"), Value::Table(tag));
+        }
+
+        let mut index = Index::new();
+        index.refresh(&store).unwrap();
+
+        assert!(index.postings("tag.values:work").unwrap().contains_key("a"));
+        assert!(index.postings("work").is_none());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let store = get_store();
+        create(&store, "a", "some content");
+
+        let mut index = Index::new();
+        index.refresh(&store).unwrap();
+
+        let s      = serde_json::to_string(&index).unwrap();
+        let loaded : Index = serde_json::from_str(&s).unwrap();
+        assert_eq!(loaded.documents(), index.documents());
+    }
+
+}
+
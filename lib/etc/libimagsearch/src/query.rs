@@ -0,0 +1,344 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! The search query language
+//!
+//! ```text
+//! query  := or
+//! or     := and ("OR" and)*
+//! and    := unary ("AND"? unary)*
+//! unary  := ("NOT" | "-") unary | atom
+//! atom   := "(" query ")" | [field ":"] (word | "\"" phrase "\"")
+//! ```
+//!
+//! Terms which are not joined by an operator are implicitly joined with `AND`. A field is a
+//! dotted path into the header of an entry (`diary.name:work`), `tag` is an alias for the tags of
+//! an entry.
+
+use analyzer::Analyzer;
+use error::SearchErrorKind as SEK;
+use error::Result;
+
+/// A parsed search query
+///
+/// All terms are already normalized by the `Analyzer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    Term {
+        field: Option<String>,
+        term: String,
+    },
+
+    Phrase {
+        field: Option<String>,
+        terms: Vec<String>,
+    },
+
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+
+    pub fn parse(s: &str) -> Result<Query> {
+        Query::parse_with(s, &Analyzer::new())
+    }
+
+    pub fn parse_with(s: &str, analyzer: &Analyzer) -> Result<Query> {
+        let tokens = lex(s)?;
+        if tokens.is_empty() {
+            return Err(SEK::EmptyQuery.into());
+        }
+
+        let mut parser = Parser { tokens: tokens, pos: 0, analyzer: analyzer };
+        let query      = parser.parse_or()?;
+
+        match parser.peek() {
+            None      => Ok(query),
+            Some(tok) => Err(SEK::QueryParseError(format!("Unexpected {:?}", tok)).into()),
+        }
+    }
+
+    /// All terms which contribute to the relevance of a result
+    ///
+    /// These are all terms which are not negated, together with their field.
+    pub fn positive_terms(&self) -> Vec<(Option<String>, String)> {
+        let mut v = Vec::new();
+        self.collect_positive_terms(&mut v, false);
+        v
+    }
+
+    fn collect_positive_terms(&self, v: &mut Vec<(Option<String>, String)>, negated: bool) {
+        match *self {
+            Query::Term { ref field, ref term } => if !negated {
+                v.push((field.clone(), term.clone()));
+            },
+            Query::Phrase { ref field, ref terms } => if !negated {
+                v.extend(terms.iter().map(|t| (field.clone(), t.clone())));
+            },
+            Query::And(ref a, ref b) | Query::Or(ref a, ref b) => {
+                a.collect_positive_terms(v, negated);
+                b.collect_positive_terms(v, negated);
+            },
+            Query::Not(ref q) => q.collect_positive_terms(v, !negated),
+        }
+    }
+
+}
+
+/// Resolve field aliases
+pub fn field_name(field: &str) -> String {
+    match field {
+        "tag" | "tags" => String::from("tag.values"),
+        other          => String::from(other),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tok {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Word(Option<String>, String),
+    Phrase(Option<String>, String),
+}
+
+fn lex(s: &str) -> Result<Vec<Tok>> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens       = Vec::new();
+    let mut i            = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Tok::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Tok::RParen);
+            i += 1;
+        } else if c == '-' {
+            tokens.push(Tok::Not);
+            i += 1;
+        } else if c == '"' {
+            let (phrase, next) = read_phrase(&chars, i)?;
+            tokens.push(Tok::Phrase(None, phrase));
+            i = next;
+        } else {
+            let start = i;
+            while i < chars.len() && !chars[i].is_whitespace() && !"()\"".contains(chars[i]) {
+                if chars[i] == ':' {
+                    break;
+                }
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+
+            if i < chars.len() && chars[i] == ':' {
+                // field-scoped term or phrase
+                let field = Some(field_name(&word));
+                i += 1;
+                if i < chars.len() && chars[i] == '"' {
+                    let (phrase, next) = read_phrase(&chars, i)?;
+                    tokens.push(Tok::Phrase(field, phrase));
+                    i = next;
+                } else {
+                    let vstart = i;
+                    while i < chars.len() && !chars[i].is_whitespace() && !"()\"".contains(chars[i]) {
+                        i += 1;
+                    }
+                    if vstart == i {
+                        let msg = format!("Missing value for field '{}'", word);
+                        return Err(SEK::QueryParseError(msg).into());
+                    }
+                    tokens.push(Tok::Word(field, chars[vstart..i].iter().collect()));
+                }
+            } else {
+                tokens.push(match word.as_ref() {
+                    "AND" => Tok::And,
+                    "OR"  => Tok::Or,
+                    "NOT" => Tok::Not,
+                    _     => Tok::Word(None, word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read a quoted phrase starting at `chars[start] == '"'`, returns the phrase and the index after
+/// the closing quote
+fn read_phrase(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let mut i = start + 1;
+    while i < chars.len() && chars[i] != '"' {
+        i += 1;
+    }
+
+    if i >= chars.len() {
+        return Err(SEK::QueryParseError(String::from("Unterminated phrase")).into());
+    }
+
+    Ok((chars[start + 1..i].iter().collect(), i + 1))
+}
+
+struct Parser<'a> {
+    tokens: Vec<Tok>,
+    pos: usize,
+    analyzer: &'a Analyzer,
+}
+
+impl<'a> Parser<'a> {
+
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn parse_or(&mut self) -> Result<Query> {
+        let mut q = self.parse_and()?;
+        while self.peek() == Some(&Tok::Or) {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            q = Query::Or(Box::new(q), Box::new(rhs));
+        }
+        Ok(q)
+    }
+
+    fn parse_and(&mut self) -> Result<Query> {
+        let mut q = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(&Tok::And) => { self.pos += 1; },
+                Some(&Tok::Or) | Some(&Tok::RParen) | None => break,
+                _ => {}, // implicit AND
+            }
+            let rhs = self.parse_unary()?;
+            q = Query::And(Box::new(q), Box::new(rhs));
+        }
+        Ok(q)
+    }
+
+    fn parse_unary(&mut self) -> Result<Query> {
+        if self.peek() == Some(&Tok::Not) {
+            self.pos += 1;
+            return self.parse_unary().map(Box::new).map(Query::Not);
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Query> {
+        match self.next() {
+            Some(Tok::LParen) => {
+                let q = self.parse_or()?;
+                match self.next() {
+                    Some(Tok::RParen) => Ok(q),
+                    _ => Err(SEK::QueryParseError(String::from("Missing ')'")).into()),
+                }
+            },
+            Some(Tok::Word(field, word))     => self.make_query(field, &word),
+            Some(Tok::Phrase(field, phrase)) => self.make_query(field, &phrase),
+            Some(other) => Err(SEK::QueryParseError(format!("Unexpected {:?}", other)).into()),
+            None        => Err(SEK::QueryParseError(String::from("Unexpected end of query")).into()),
+        }
+    }
+
+    /// Build a term or a phrase query, depending on how many terms the analyzer finds in `text`
+    ///
+    /// This way, `foo-bar` is searched as the phrase "foo bar", just like it was indexed.
+    fn make_query(&self, field: Option<String>, text: &str) -> Result<Query> {
+        let mut terms = self.analyzer.terms(text);
+        match terms.len() {
+            0 => Err(SEK::QueryParseError(format!("No searchable term in '{}'", text)).into()),
+            1 => Ok(Query::Term { field: field, term: terms.remove(0) }),
+            _ => Ok(Query::Phrase { field: field, terms: terms }),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(t: &str) -> Query {
+        Query::Term { field: None, term: String::from(t) }
+    }
+
+    #[test]
+    fn test_implicit_and() {
+        let q = Query::parse("foo bar").unwrap();
+        assert_eq!(q, Query::And(Box::new(term("foo")), Box::new(term("bar"))));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let q = Query::parse("foo OR bar baz").unwrap();
+        let expected = Query::Or(Box::new(term("foo")),
+                                 Box::new(Query::And(Box::new(term("bar")), Box::new(term("baz")))));
+        assert_eq!(q, expected);
+    }
+
+    #[test]
+    fn test_not_and_grouping() {
+        let q = Query::parse("-(foo OR bar)").unwrap();
+        let expected = Query::Not(Box::new(Query::Or(Box::new(term("foo")), Box::new(term("bar")))));
+        assert_eq!(q, expected);
+        assert_eq!(Query::parse("NOT foo").unwrap(), Query::Not(Box::new(term("foo"))));
+    }
+
+    #[test]
+    fn test_phrase_and_field() {
+        let q = Query::parse("tag:work title:\"Meeting notes\"").unwrap();
+        let expected = Query::And(
+            Box::new(Query::Term { field: Some(String::from("tag.values")), term: String::from("work") }),
+            Box::new(Query::Phrase {
+                field: Some(String::from("title")),
+                terms: vec![String::from("meet"), String::from("note")],
+            }));
+        assert_eq!(q, expected);
+    }
+
+    #[test]
+    fn test_positive_terms() {
+        let q = Query::parse("foo -bar").unwrap();
+        assert_eq!(q.positive_terms(), vec![(None, String::from("foo"))]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Query::parse("").is_err());
+        assert!(Query::parse("\"foo").is_err());
+        assert!(Query::parse("(foo").is_err());
+        assert!(Query::parse("foo)").is_err());
+        assert!(Query::parse("tag:").is_err());
+        assert!(Query::parse("foo OR").is_err());
+    }
+
+}
+
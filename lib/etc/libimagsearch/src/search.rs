@@ -0,0 +1,242 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! Evaluating queries against the index and ranking the results with BM25
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use libimagstore::storeid::StoreId;
+
+use index::Index;
+use index::Positions;
+use index::term_key;
+use query::Query;
+use error::Result;
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+
+/// BM25 document length normalization
+const B: f64 = 0.75;
+
+/// A search result
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub id: StoreId,
+    pub score: f64,
+}
+
+impl Index {
+
+    /// Find all entries matching `query`, best matches first
+    pub fn search(&self, query: &Query) -> Result<Vec<SearchHit>> {
+        let matching = self.evaluate(query);
+
+        let mut keys : Vec<String> = query
+            .positive_terms()
+            .into_iter()
+            .map(|(field, term)| term_key(field.as_ref().map(String::as_ref), &term))
+            .collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut scored : Vec<(String, f64)> = matching
+            .into_iter()
+            .map(|doc| {
+                let score = self.score(&doc, &keys);
+                (doc, score)
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        scored
+            .into_iter()
+            .map(|(doc, score)| {
+                StoreId::new_baseless(PathBuf::from(doc))
+                    .map(|id| SearchHit { id: id, score: score })
+                    .map_err(From::from)
+            })
+            .collect()
+    }
+
+    /// Get the set of documents matching a query, without ranking them
+    fn evaluate(&self, query: &Query) -> BTreeSet<String> {
+        match *query {
+            Query::Term { ref field, ref term } => {
+                self.postings(&term_key(field.as_ref().map(String::as_ref), term))
+                    .map(|docs| docs.keys().cloned().collect())
+                    .unwrap_or_else(BTreeSet::new)
+            },
+
+            Query::Phrase { ref field, ref terms } => {
+                let postings : Option<Vec<&BTreeMap<String, Positions>>> = terms
+                    .iter()
+                    .map(|t| self.postings(&term_key(field.as_ref().map(String::as_ref), t)))
+                    .collect();
+
+                match postings {
+                    None           => BTreeSet::new(),
+                    Some(postings) => postings[0]
+                        .keys()
+                        .filter(|doc| {
+                            let positions : Option<Vec<&Positions>> = postings
+                                .iter()
+                                .map(|p| p.get(*doc))
+                                .collect();
+
+                            positions.map(|p| is_phrase(&p)).unwrap_or(false)
+                        })
+                        .cloned()
+                        .collect(),
+                }
+            },
+
+            Query::And(ref a, ref b) => {
+                let a = self.evaluate(a);
+                let b = self.evaluate(b);
+                a.intersection(&b).cloned().collect()
+            },
+
+            Query::Or(ref a, ref b) => {
+                let a = self.evaluate(a);
+                let b = self.evaluate(b);
+                a.union(&b).cloned().collect()
+            },
+
+            Query::Not(ref q) => {
+                let excluded = self.evaluate(q);
+                self.documents()
+                    .keys()
+                    .filter(|doc| !excluded.contains(*doc))
+                    .cloned()
+                    .collect()
+            },
+        }
+    }
+
+    /// BM25 score of a document for a set of index keys
+    fn score(&self, doc: &str, keys: &[String]) -> f64 {
+        let documents = self.documents();
+        let n_docs    = documents.len() as f64;
+        let total_len = documents.values().map(|d| d.length).sum::<usize>() as f64;
+        let avg_len   = if total_len > 0.0 { total_len / n_docs } else { 1.0 };
+        let doc_len   = documents.get(doc).map(|d| d.length as f64).unwrap_or(0.0);
+
+        keys.iter()
+            .filter_map(|key| self.postings(key))
+            .filter_map(|docs| docs.get(doc).map(|positions| (docs.len() as f64, positions.len() as f64)))
+            .map(|(n_containing, tf)| {
+                let idf = (1.0 + (n_docs - n_containing + 0.5) / (n_containing + 0.5)).ln();
+                idf * (tf * (K1 + 1.0)) / (tf + K1 * (1.0 - B + B * doc_len / avg_len))
+            })
+            .sum()
+    }
+
+}
+
+/// Check whether the terms occur in consecutive positions somewhere
+fn is_phrase(positions: &[&Positions]) -> bool {
+    positions[0].iter().any(|start| {
+        positions
+            .iter()
+            .enumerate()
+            .skip(1)
+            .all(|(offset, p)| p.binary_search(&(start + offset)).is_ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use libimagstore::store::Store;
+    use libimagstore::file_abstraction::InMemoryFileAbstraction;
+
+    use index::Index;
+    use query::Query;
+
+    fn get_store() -> Store {
+        let backend = Arc::new(InMemoryFileAbstraction::default());
+        Store::new_with_backend(PathBuf::from("/"), &None, backend).unwrap()
+    }
+
+    fn setup() -> Index {
+        let store = get_store();
+        for &(name, content) in [
+            ("fox",    "The quick brown fox jumps over the lazy dog"),
+            ("dog",    "A dog is a dog is a dog"),
+            ("brown",  "Brown bears and brown foxes"),
+            ("empty",  ""),
+        ].iter() {
+            let mut entry = store.create(PathBuf::from(name)).unwrap();
+            *entry.get_content_mut() = String::from(content);
+        }
+
+        let mut index = Index::new();
+        index.refresh(&store).unwrap();
+        index
+    }
+
+    fn search(index: &Index, q: &str) -> Vec<String> {
+        index.search(&Query::parse(q).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|hit| format!("{}", hit.id))
+            .collect()
+    }
+
+    #[test]
+    fn test_term_and_ranking() {
+        let index = setup();
+        assert_eq!(search(&index, "dog"), vec!["dog", "fox"]);
+    }
+
+    #[test]
+    fn test_stemmed_match() {
+        let index = setup();
+        assert_eq!(search(&index, "foxes"), vec!["brown", "fox"]);
+    }
+
+    #[test]
+    fn test_boolean() {
+        let index = setup();
+        assert_eq!(search(&index, "brown dog"), vec!["fox"]);
+        assert_eq!(search(&index, "bears OR lazy").len(), 2);
+        assert_eq!(search(&index, "fox -dog"), vec!["brown"]);
+        assert_eq!(search(&index, "NOT (fox OR dog)"), vec!["empty"]);
+    }
+
+    #[test]
+    fn test_phrase() {
+        let index = setup();
+        assert_eq!(search(&index, "\"brown fox\""), vec!["fox"]);
+        assert_eq!(search(&index, "\"brown bears\""), vec!["brown"]);
+        assert!(search(&index, "\"fox brown\"").is_empty());
+    }
+
+}
+
@@ -0,0 +1,147 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+//! Extracting the relevant part of an entry for showing it in search results
+
+use analyzer::Analyzer;
+
+/// An excerpt of a text with the positions of the matched terms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    text: String,
+    matches: Vec<(usize, usize)>,
+    truncated_start: bool,
+    truncated_end: bool,
+}
+
+impl Snippet {
+
+    /// Build a snippet of about `width` bytes from `content`, around the first occurence of one of
+    /// the (normalized) `terms`
+    ///
+    /// If none of the terms occur in the content, the beginning of the content is used.
+    /// Returns None if the content is empty.
+    pub fn new(analyzer: &Analyzer, content: &str, terms: &[String], width: usize) -> Option<Snippet> {
+        if content.trim().is_empty() {
+            return None;
+        }
+
+        let tokens = analyzer.tokens(content);
+        let found  : Vec<_> = tokens.iter().filter(|t| terms.contains(&t.term)).collect();
+
+        let mut start = found.first().map(|t| t.start.saturating_sub(width / 4)).unwrap_or(0);
+        while !content.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = ::std::cmp::min(content.len(), start + width);
+        while !content.is_char_boundary(end) {
+            end += 1;
+        }
+
+        let matches = found
+            .into_iter()
+            .filter(|t| t.start >= start && t.end <= end)
+            .map(|t| (t.start - start, t.end - start))
+            .collect();
+
+        Some(Snippet {
+            text: String::from(&content[start..end]),
+            matches: matches,
+            truncated_start: start > 0,
+            truncated_end: end < content.len(),
+        })
+    }
+
+    /// Render the snippet, wrapping every match in `pre` and `post`
+    ///
+    /// Whitespace (including newlines) is collapsed, so the snippet fits on one line.
+    pub fn highlight(&self, pre: &str, post: &str) -> String {
+        let mut out  = String::new();
+        let mut last = 0;
+
+        if self.truncated_start {
+            out.push_str("...");
+        }
+
+        for &(start, end) in self.matches.iter() {
+            out.push_str(&collapse_whitespace(&self.text[last..start]));
+            out.push_str(pre);
+            out.push_str(&self.text[start..end]);
+            out.push_str(post);
+            last = end;
+        }
+        out.push_str(&collapse_whitespace(&self.text[last..]));
+
+        if self.truncated_end {
+            out.push_str("...");
+        }
+
+        out
+    }
+
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    let mut out        = String::with_capacity(s.len());
+    let mut last_blank = false;
+
+    for c in s.chars() {
+        if c.is_whitespace() {
+            if !last_blank {
+                out.push(' ');
+            }
+            last_blank = true;
+        } else {
+            out.push(c);
+            last_blank = false;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        let a = Analyzer::new();
+        let s = Snippet::new(&a, "Foxes are\nquick. A fox!", &[a.normalize("fox")], 100).unwrap();
+        assert_eq!(s.highlight("[", "]"), "[Foxes] are quick. A [fox]!");
+    }
+
+    #[test]
+    fn test_truncation() {
+        let a       = Analyzer::new();
+        let content = "aaaa bbbb cccc dddd eeee ffff gggg";
+        let s       = Snippet::new(&a, content, &[a.normalize("eeee")], 12).unwrap();
+        assert_eq!(s.highlight("[", "]"), "...dd [eeee] ffff...");
+    }
+
+    #[test]
+    fn test_no_match_uses_beginning() {
+        let a = Analyzer::new();
+        let s = Snippet::new(&a, "hello world", &[String::from("nothing")], 5).unwrap();
+        assert_eq!(s.highlight("[", "]"), "hello...");
+        assert!(Snippet::new(&a, "  \n", &[], 5).is_none());
+    }
+
+}
+
@@ -42,6 +42,7 @@ extern crate regex;
 pub mod cli;
 pub mod date;
 pub mod datetime;
+pub mod natural;
 pub mod parse;
 pub mod time;
 pub mod ui;
@@ -0,0 +1,407 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Natural language date parsing
+//!
+//! Parses expressions like "tomorrow 9am", "next friday", "in 3 days", "last monday 14:00" or
+//! "end of month" relative to a reference time. An expression consists of an optional date part
+//! and an optional time part:
+//!
+//! * Date part: "now", "today", "tomorrow", "yesterday", a weekday ("friday", the next friday
+//!   including today), "next <weekday>", "last <weekday>", "next week|month|year",
+//!   "last week|month|year", "start of week|month|year", "end of week|month|year",
+//!   "in <n> <unit>", "<n> <unit> ago" (unit being minutes, hours, days, weeks, months or years)
+//!   or a date in the form "YYYY-MM-DD".
+//! * Time part: "9am", "9:30pm", "14:00", "14:00:30", "noon" or "midnight", optionally prefixed
+//!   with "at".
+//!
+//! If only a time is passed, the date of the reference is used. If only a date is passed, the time
+//! is midnight, except for "now" and relative offsets, which keep the time of the reference.
+
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::Weekday;
+use chrono::naive::NaiveDate;
+use chrono::naive::NaiveDateTime;
+use chrono::naive::NaiveTime;
+use regex::Regex;
+
+use datetime::DateTime;
+use parse::Parse;
+
+/// Parse a datetime, either in the fixed format (see `ui::time_ui_fmtstr()`) or in natural
+/// language, relative to `reference`
+///
+/// Strings in the fixed format with values out of range (like "2018-02-30T10") do not parse.
+pub fn parse_datetime(s: &str, reference: &NaiveDateTime) -> Option<NaiveDateTime> {
+    match DateTime::parse(s) {
+        Some(dt) => fixed_to_naive(&dt),
+        None     => parse_natural(s, reference),
+    }
+}
+
+fn fixed_to_naive(dt: &DateTime) -> Option<NaiveDateTime> {
+    let (d, t) = (dt.date(), dt.time());
+    let date   = NaiveDate::from_ymd_opt(d.year(), d.month(), d.day())?;
+    let time   = NaiveTime::from_hms_opt(t.hour(), t.minute(), t.second())?;
+
+    Some(date.and_time(time))
+}
+
+/// Parse a natural language datetime expression, relative to `reference`
+pub fn parse_natural(s: &str, reference: &NaiveDateTime) -> Option<NaiveDateTime> {
+    let lower  = s.trim().to_lowercase();
+    let tokens = tokenize(&lower);
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let (date_tokens, time) = match tokens.split_last() {
+        Some((last, rest)) => match parse_time(last) {
+            Some(t) => (rest, Some(t)),
+            None    => (&tokens[..], None),
+        },
+        None => return None,
+    };
+
+    match (parse_date_part(date_tokens, reference)?, time) {
+        (DatePart::Date(d), Some(t))      => Some(d.and_time(t)),
+        (DatePart::Date(d), None)         => Some(d.and_hms(0, 0, 0)),
+        (DatePart::DateTime(dt), Some(t)) => Some(dt.date().and_time(t)),
+        (DatePart::DateTime(dt), None)    => Some(dt),
+    }
+}
+
+/// Parse a natural language date expression, relative to `reference`
+///
+/// Same as `parse_natural()`, but drops the time.
+pub fn parse_natural_date(s: &str, reference: &NaiveDate) -> Option<NaiveDate> {
+    parse_natural(s, &reference.and_hms(0, 0, 0)).map(|dt| dt.date())
+}
+
+enum DatePart {
+    /// A calendar day, the time is taken from the time part of the expression
+    Date(NaiveDate),
+
+    /// A point in time, which keeps its time if the expression has no time part
+    DateTime(NaiveDateTime),
+}
+
+/// Split into words, dropping "at" and joining "9 am" into "9am"
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens : Vec<String> = Vec::new();
+
+    for word in s.split_whitespace() {
+        match word {
+            "at" => continue,
+            "am" | "pm" if tokens.last().map(|t| is_clock_time(t)).unwrap_or(false) => {
+                tokens.last_mut().unwrap().push_str(word); // checked above
+            },
+            other => tokens.push(String::from(other)),
+        }
+    }
+
+    tokens
+}
+
+/// Whether `s` looks like "9" or "9:15", which can be followed by "am" or "pm"
+fn is_clock_time(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_digit(10) || c == ':')
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    lazy_static! {
+        static ref R: Regex = Regex::new(r"^(?P<h>\d{1,2})(:(?P<m>\d{2}))?(:(?P<s>\d{2}))?(?P<ampm>am|pm)?$").unwrap();
+    }
+
+    match s {
+        "noon"     => return Some(NaiveTime::from_hms(12, 0, 0)),
+        "midnight" => return Some(NaiveTime::from_hms(0, 0, 0)),
+        _          => {},
+    }
+
+    let caps = R.captures(s)?;
+    let ampm = caps.name("ampm").map(|m| m.as_str());
+
+    // A plain number is not a time, it has to have minutes or am/pm
+    if caps.name("m").is_none() && ampm.is_none() {
+        return None;
+    }
+
+    let hour   = caps.name("h").and_then(|m| m.as_str().parse::<u32>().ok())?;
+    let minute = caps.name("m").and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
+    let second = caps.name("s").and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
+
+    let hour = match ampm {
+        Some(_) if hour == 0 || hour > 12 => return None,
+        Some("am") => hour % 12,
+        Some(_)    => hour % 12 + 12,
+        None       => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+fn parse_date_part(tokens: &[String], reference: &NaiveDateTime) -> Option<DatePart> {
+    let today  = reference.date();
+    let words  = tokens.iter().map(String::as_str).collect::<Vec<&str>>();
+    let phrase = words.join(" ");
+
+    let date = match phrase.as_str() {
+        ""          => today,
+        "now"       => return Some(DatePart::DateTime(*reference)),
+        "today"     => today,
+        "tomorrow"  => today.succ_opt()?,
+        "yesterday" => today.pred_opt()?,
+
+        "next week"  => today.checked_add_signed(Duration::weeks(1))?,
+        "last week"  => today.checked_sub_signed(Duration::weeks(1))?,
+        "next month" => add_months(today, 1)?,
+        "last month" => add_months(today, -1)?,
+        "next year"  => add_months(today, 12)?,
+        "last year"  => add_months(today, -12)?,
+
+        "start of week"  | "beginning of week"  => start_of_week(today),
+        "start of month" | "beginning of month" => today.with_day(1)?,
+        "start of year"  | "beginning of year"  => NaiveDate::from_ymd_opt(today.year(), 1, 1)?,
+        "end of week"  => start_of_week(today).checked_add_signed(Duration::days(6))?,
+        "end of month" => add_months(today.with_day(1)?, 1)?.pred_opt()?,
+        "end of year"  => NaiveDate::from_ymd_opt(today.year(), 12, 31)?,
+
+        _ => match words.len() {
+            1 => match parse_weekday(words[0]) {
+                Some(wd) => next_weekday(today, wd, true),
+                None     => NaiveDate::parse_from_str(words[0], "%Y-%m-%d").ok()?,
+            },
+
+            2 => match (words[0], parse_weekday(words[1])) {
+                ("next", Some(wd)) => next_weekday(today, wd, false),
+                ("last", Some(wd)) => last_weekday(today, wd),
+                _ => return None,
+            },
+
+            3 => return match (words[0], words[2]) {
+                ("in", _)  => offset(reference, words[1], words[2], 1).map(DatePart::DateTime),
+                (_, "ago") => offset(reference, words[0], words[1], -1).map(DatePart::DateTime),
+                _ => None,
+            },
+
+            _ => return None,
+        },
+    };
+
+    Some(DatePart::Date(date))
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "monday"    | "mon" => Some(Weekday::Mon),
+        "tuesday"   | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday"  | "thu" => Some(Weekday::Thu),
+        "friday"    | "fri" => Some(Weekday::Fri),
+        "saturday"  | "sat" => Some(Weekday::Sat),
+        "sunday"    | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The next date which is a `wd`, today included if `include_today` is true
+fn next_weekday(today: NaiveDate, wd: Weekday, include_today: bool) -> NaiveDate {
+    let current = today.weekday().num_days_from_monday() as i64;
+    let wanted  = wd.num_days_from_monday() as i64;
+    let mut diff = (wanted - current + 7) % 7;
+    if diff == 0 && !include_today {
+        diff = 7;
+    }
+    today + Duration::days(diff)
+}
+
+/// The last date before today which is a `wd`
+fn last_weekday(today: NaiveDate, wd: Weekday) -> NaiveDate {
+    let current = today.weekday().num_days_from_monday() as i64;
+    let wanted  = wd.num_days_from_monday() as i64;
+    let mut diff = (current - wanted + 7) % 7;
+    if diff == 0 {
+        diff = 7;
+    }
+    today - Duration::days(diff)
+}
+
+/// Monday of the week of `d`
+fn start_of_week(d: NaiveDate) -> NaiveDate {
+    d - Duration::days(d.weekday().num_days_from_monday() as i64)
+}
+
+/// Add `months` months to `d`, clamping the day to the length of the resulting month
+fn add_months(d: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let total = d.year().checked_mul(12)?.checked_add(d.month0() as i32)?.checked_add(months)?;
+    let year  = if total >= 0 { total / 12 } else { (total - 11) / 12 };
+    let month = (total - year * 12) as u32 + 1;
+
+    let first_of_next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    let days_in_month = first_of_next.pred_opt()?.day();
+
+    NaiveDate::from_ymd_opt(year, month, ::std::cmp::min(d.day(), days_in_month))
+}
+
+/// Move `reference` by `amount` units, None if the result is out of range
+fn offset(reference: &NaiveDateTime, amount: &str, unit: &str, sign: i64) -> Option<NaiveDateTime> {
+    let amount = amount.parse::<i64>().ok()?.checked_mul(sign)?;
+    let unit   = unit.trim_right_matches('s');
+
+    let seconds = |factor: i64| -> Option<NaiveDateTime> {
+        let secs = amount.checked_mul(factor)?;
+
+        // Duration::seconds() panics for values which do not fit into milliseconds
+        if secs.abs() > ::std::i64::MAX / 1000 {
+            return None;
+        }
+
+        reference.checked_add_signed(Duration::seconds(secs))
+    };
+
+    let months = |factor: i64| -> Option<NaiveDateTime> {
+        let months = amount.checked_mul(factor)?;
+        if months.abs() > ::std::i32::MAX as i64 {
+            return None;
+        }
+
+        add_months(reference.date(), months as i32).map(|d| d.and_time(reference.time()))
+    };
+
+    match unit {
+        "min" | "minute" => seconds(60),
+        "hour"           => seconds(60 * 60),
+        "day"            => seconds(24 * 60 * 60),
+        "week"           => seconds(7 * 24 * 60 * 60),
+        "month"          => months(1),
+        "year"           => months(12),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::naive::NaiveDate;
+    use chrono::naive::NaiveDateTime;
+
+    use super::*;
+
+    // Wednesday
+    fn reference() -> NaiveDateTime {
+        NaiveDate::from_ymd(2018, 1, 31).and_hms(10, 30, 0)
+    }
+
+    fn parse(s: &str) -> Option<NaiveDateTime> {
+        parse_natural(s, &reference())
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> Option<NaiveDateTime> {
+        Some(NaiveDate::from_ymd(y, m, d).and_hms(h, min, 0))
+    }
+
+    #[test]
+    fn test_simple_days() {
+        assert_eq!(at(2018, 1, 31, 10, 30), parse("now"));
+        assert_eq!(at(2018, 1, 31, 0, 0), parse("today"));
+        assert_eq!(at(2018, 2, 1, 0, 0), parse("tomorrow"));
+        assert_eq!(at(2018, 1, 30, 0, 0), parse("Yesterday"));
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(at(2018, 2, 1, 9, 0), parse("tomorrow 9am"));
+        assert_eq!(at(2018, 2, 1, 21, 15), parse("tomorrow at 9:15 pm"));
+        assert_eq!(at(2018, 1, 31, 14, 0), parse("14:00"));
+        assert_eq!(at(2018, 1, 31, 12, 0), parse("noon"));
+        assert_eq!(at(2018, 1, 31, 0, 0), parse("12am"));
+        assert!(parse("tomorrow 13pm").is_none());
+        assert!(parse("tomorrow 25:00").is_none());
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(at(2018, 2, 2, 0, 0), parse("friday"));
+        assert_eq!(at(2018, 2, 2, 0, 0), parse("next friday"));
+        assert_eq!(at(2018, 1, 31, 0, 0), parse("wednesday"));
+        assert_eq!(at(2018, 2, 7, 0, 0), parse("next wednesday"));
+        assert_eq!(at(2018, 1, 29, 14, 0), parse("last monday 14:00"));
+        assert_eq!(at(2018, 1, 24, 0, 0), parse("last wednesday"));
+    }
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(at(2018, 2, 3, 10, 30), parse("in 3 days"));
+        assert_eq!(at(2018, 1, 31, 12, 30), parse("in 2 hours"));
+        assert_eq!(at(2018, 1, 17, 10, 30), parse("2 weeks ago"));
+        assert_eq!(at(2018, 2, 28, 10, 30), parse("in 1 month"));
+        assert_eq!(at(2018, 2, 3, 9, 0), parse("in 3 days 9am"));
+        assert!(parse("in three days").is_none());
+    }
+
+    #[test]
+    fn test_offsets_out_of_range() {
+        assert!(parse("in 100000000 days").is_none());
+        assert!(parse("in 9223372036854775807 days").is_none());
+        assert!(parse("9223372036854775807 minutes ago").is_none());
+        assert!(parse("in 9223372036854775807 years").is_none());
+        assert!(parse("in 1000000000 months").is_none());
+    }
+
+    #[test]
+    fn test_periods() {
+        assert_eq!(at(2018, 1, 31, 0, 0), parse("end of month"));
+        assert_eq!(at(2018, 1, 1, 0, 0), parse("start of month"));
+        assert_eq!(at(2018, 2, 4, 0, 0), parse("end of week"));
+        assert_eq!(at(2018, 1, 29, 0, 0), parse("beginning of week"));
+        assert_eq!(at(2018, 12, 31, 0, 0), parse("end of year"));
+        assert_eq!(at(2018, 2, 28, 0, 0), parse("next month"));
+    }
+
+    #[test]
+    fn test_iso_date() {
+        assert_eq!(at(2018, 3, 4, 9, 0), parse("2018-03-04 9am"));
+        assert_eq!(at(2018, 3, 4, 0, 0), parse("2018-03-04"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse("").is_none());
+        assert!(parse("someday").is_none());
+        assert!(parse("next someday").is_none());
+    }
+
+    #[test]
+    fn test_parse_datetime_prefers_fixed_format() {
+        let expected = at(2018, 3, 4, 9, 10);
+        assert_eq!(expected, parse_datetime("2018-03-04T09:10:00", &reference()));
+    }
+
+    #[test]
+    fn test_parse_datetime_out_of_range() {
+        assert!(parse_datetime("2018-02-30T10", &reference()).is_none());
+        assert!(parse_datetime("2018-01-01T25:00", &reference()).is_none());
+    }
+
+}
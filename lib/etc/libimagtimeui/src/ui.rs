@@ -25,6 +25,8 @@ pub fn time_ui_fmtstr_expl() -> &'static str {
     r#"In the UI, the format for Time is always YEAR-MONTH-DAY.
     Optionally, Time can be specified by seperating it from the date with 'T'.
     Minutes and Seconds are optional.
+    Where a natural language expression is accepted, things like "tomorrow 9am", "next friday",
+    "in 3 days", "last monday 14:00" or "end of month" can be used as well.
     "#
 }
 
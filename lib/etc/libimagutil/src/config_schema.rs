@@ -0,0 +1,116 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Declarations of the configuration keys a crate reads
+//!
+//! Every crate which reads configuration declares the keys it reads as a constant slice of
+//! `ConfigKey`s, so the configuration can be checked for typos and wrong types and the documented
+//! schema can be printed (`imag config schema`). The checking itself lives in `libimagrt`.
+//!
+//! ```
+//! use libimagutil::config_schema::{ConfigKey, ValueType};
+//!
+//! pub const CONFIG_KEYS : &'static [ConfigKey] = &[
+//!     ConfigKey {
+//!         path: "foo.*.enabled",
+//!         kind: ValueType::Boolean,
+//!         default: Some("true"),
+//!         doc: "Whether the foo with this name is enabled",
+//!     },
+//! ];
+//! ```
+
+/// The type of a configuration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Datetime,
+    Array,
+
+    /// A table with arbitrary content, which is not checked any further
+    Table,
+
+    /// Any value, which is not checked at all
+    Any,
+}
+
+impl ValueType {
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ValueType::String   => "string",
+            ValueType::Integer  => "integer",
+            ValueType::Float    => "float",
+            ValueType::Boolean  => "boolean",
+            ValueType::Datetime => "datetime",
+            ValueType::Array    => "array",
+            ValueType::Table    => "table",
+            ValueType::Any      => "any",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ValueType> {
+        match name {
+            "string"   => Some(ValueType::String),
+            "integer"  => Some(ValueType::Integer),
+            "float"    => Some(ValueType::Float),
+            "boolean"  => Some(ValueType::Boolean),
+            "datetime" => Some(ValueType::Datetime),
+            "array"    => Some(ValueType::Array),
+            "table"    => Some(ValueType::Table),
+            "any"      => Some(ValueType::Any),
+            _          => None,
+        }
+    }
+
+}
+
+/// A configuration key a crate reads
+///
+/// The `path` is the path of the value in the configuration, where a `*` segment stands for any
+/// key (for example the name of a diary). The `default` is the value which is used if the key is
+/// not set, written as TOML value, or `None` if there is no default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigKey {
+    pub path: &'static str,
+    pub kind: ValueType,
+    pub default: Option<&'static str>,
+    pub doc: &'static str,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_value_type_names() {
+        let all = [
+            ValueType::String, ValueType::Integer, ValueType::Float, ValueType::Boolean,
+            ValueType::Datetime, ValueType::Array, ValueType::Table, ValueType::Any,
+        ];
+
+        for kind in all.iter() {
+            assert_eq!(ValueType::from_name(kind.name()), Some(*kind));
+        }
+        assert_eq!(ValueType::from_name("str"), None);
+    }
+}
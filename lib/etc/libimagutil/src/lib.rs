@@ -43,11 +43,14 @@ extern crate chrono;
 
 #[macro_use] mod log_result;
 pub mod cli_validators;
+pub mod config_schema;
 pub mod date;
 pub mod debug_result;
 pub mod edit;
 pub mod info_result;
 pub mod key_value_split;
+pub mod slug;
+pub mod tag;
 pub mod variants;
 pub mod warn_exit;
 pub mod warn_result;
@@ -0,0 +1,48 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+/// Turn a title into a slug, usable as anchor or as name of an entry
+///
+/// The text is lowercased, whitespace is replaced by `-` and all other characters which are not
+/// alphanumeric, `-` or `_` are removed. This is the way GitHub builds anchors from headings.
+pub fn slugify(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| if c.is_whitespace() {
+            Some('-')
+        } else if c.is_alphanumeric() || c == '-' || c == '_' {
+            Some(c)
+        } else {
+            None
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::slugify;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!("hello-world", slugify("Hello World!"));
+        assert_eq!("a_b-c", slugify("  A_b-C  "));
+    }
+}
@@ -0,0 +1,59 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2018 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Turning text from other programs (like categories or front matter tags) into imag tags
+
+/// Turn `s` into a valid imag tag
+///
+/// imag tags consist of lowercase ASCII letters only, so `s` is lowercased and all other
+/// characters are removed. A warning is logged if characters are removed, and None is returned
+/// if no letter is left, so the user knows that a tag was altered or dropped.
+pub fn normalize_tag(s: &str) -> Option<String> {
+    let lower = s.trim().to_lowercase();
+    if lower.is_empty() {
+        return None;
+    }
+
+    let tag = lower.chars().filter(|c| c.is_ascii_lowercase()).collect::<String>();
+
+    if tag.is_empty() {
+        warn!("'{}' cannot be a tag, it contains no letters a-z, dropping it", s);
+        None
+    } else {
+        if tag != lower {
+            warn!("'{}' is not a valid tag, using '{}' instead", s, tag);
+        }
+
+        Some(tag)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::normalize_tag;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(Some(String::from("work")), normalize_tag("Work"));
+        assert_eq!(Some(String::from("c")), normalize_tag("c++"));
+        assert_eq!(Some(String::from("strae")), normalize_tag("Straße"));
+        assert_eq!(None, normalize_tag("2018"));
+        assert_eq!(None, normalize_tag("  "));
+    }
+}
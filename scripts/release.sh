@@ -13,6 +13,7 @@ CRATES=(
     ./lib/etc/libimagtimeui
     ./lib/core/libimagerror
     ./lib/core/libimagstore
+    ./lib/etc/libimagsearch
     ./lib/etc/libimagnotification
     ./lib/etc/libimaginteraction
     ./lib/core/libimagrt
@@ -28,6 +29,7 @@ CRATES=(
     ./lib/entry/libimagentryref
     ./lib/entry/libimagentrymarkdown
     ./lib/entry/libimagentryannotation
+    ./lib/entry/libimagentryfrontmatter
     ./lib/domain/libimagbookmark
     ./lib/domain/libimaghabit
     ./lib/domain/libimagnotes
@@ -38,6 +40,7 @@ CRATES=(
     ./lib/domain/libimagtodo
     ./lib/domain/libimagmail
     ./lib/domain/libimagwiki
+    ./lib/domain/libimagagenda
     ./bin/domain/imag-habit
     ./bin/domain/imag-diary
     ./bin/domain/imag-contact
@@ -48,6 +51,7 @@ CRATES=(
     ./bin/domain/imag-todo
     ./bin/domain/imag-log
     ./bin/domain/imag-wiki
+    ./bin/domain/imag-agenda
     ./bin/core/imag-ref
     ./bin/core/imag-gps
     ./bin/core/imag-diagnostics
@@ -55,6 +59,7 @@ CRATES=(
     ./bin/core/imag-store
     ./bin/core/imag-tag
     ./bin/core/imag-grep
+    ./bin/core/imag-search
     ./bin/core/imag-annotate
     ./bin/core/imag-link
     ./bin/core/imag-view
@@ -63,6 +68,9 @@ CRATES=(
     ./bin/core/imag-ids
     ./bin/core/imag-git
     ./bin/core/imag-category
+    ./bin/core/imag-shell
+    ./bin/core/imag-browse
+    ./bin/core/imag-config
     ./bin/core/imag
 )
 